@@ -0,0 +1,222 @@
+//! `crux alias` — shell functions that route commands through `crux run`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// The shell named by `$SHELL`, defaulting to bash.
+    pub(crate) fn detect() -> Shell {
+        let shell = std::env::var("SHELL").unwrap_or_default();
+        match shell.rsplit('/').next() {
+            Some("zsh") => Shell::Zsh,
+            Some("fish") => Shell::Fish,
+            _ => Shell::Bash,
+        }
+    }
+
+    pub(crate) fn rc_file(self) -> &'static str {
+        match self {
+            Shell::Bash => "~/.bashrc",
+            Shell::Zsh => "~/.zshrc",
+            Shell::Fish => "~/.config/fish/config.fish",
+        }
+    }
+}
+
+/// Which invocations of a wrapped program go through crux.
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    /// The program itself has a filter: every invocation.
+    All,
+    /// Only these subcommands have filters; the rest run the program directly,
+    /// so interactive ones (`git rebase -i`, `docker compose up`) keep working.
+    Subcommands(BTreeSet<String>),
+}
+
+pub fn cmd_alias(shell: Option<Shell>, prefix: &str, wrap: &[String]) -> Result<()> {
+    let shell = shell.unwrap_or_else(Shell::detect);
+    if !is_shell_word(prefix) {
+        bail!("invalid function name '{prefix}'");
+    }
+
+    let filters: Vec<String> = crate::commands::filter_entries()
+        .into_iter()
+        .map(|(_, command)| command)
+        .collect();
+    let mut routes = BTreeMap::new();
+    for program in wrap {
+        if !is_shell_word(program) {
+            bail!("invalid program name '{program}'");
+        }
+        match route_for(program, &filters) {
+            Some(route) => {
+                routes.insert(program.as_str(), route);
+            }
+            None => eprintln!("crux: no filter for '{program}', not wrapping it"),
+        }
+    }
+
+    let eval = match shell {
+        Shell::Fish => format!(
+            "crux alias --shell fish{} | source",
+            wrap_args(prefix, wrap)
+        ),
+        _ => format!(
+            "eval \"$(crux alias --shell {}{})\"",
+            if shell == Shell::Zsh { "zsh" } else { "bash" },
+            wrap_args(prefix, wrap)
+        ),
+    };
+    println!("# crux shell functions. Add to {}:", shell.rc_file());
+    println!("#   {eval}");
+    print!("{}", render(shell, prefix, &routes));
+    Ok(())
+}
+
+/// The flags to reproduce this invocation in the rc-file hint.
+fn wrap_args(prefix: &str, wrap: &[String]) -> String {
+    let mut args = String::new();
+    if prefix != "c" {
+        args.push_str(&format!(" --prefix {prefix}"));
+    }
+    if !wrap.is_empty() {
+        args.push_str(&format!(" --wrap {}", wrap.join(",")));
+    }
+    args
+}
+
+/// How to route `program`, given every filter command; `None` if no filter
+/// covers it.
+fn route_for(program: &str, filters: &[String]) -> Option<Route> {
+    let mut subcommands = BTreeSet::new();
+    for filter in filters {
+        let words: Vec<&str> = filter.split_whitespace().collect();
+        match words.as_slice() {
+            [p] if *p == program => return Some(Route::All),
+            [p, sub] if *p == program && is_shell_word(sub) => {
+                subcommands.insert(sub.to_string());
+            }
+            _ => {}
+        }
+    }
+    (!subcommands.is_empty()).then_some(Route::Subcommands(subcommands))
+}
+
+/// Safe to use unquoted as a function name or `case` pattern.
+pub(crate) fn is_shell_word(word: &str) -> bool {
+    !word.is_empty()
+        && !word.starts_with('-')
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+}
+
+fn render(shell: Shell, prefix: &str, routes: &BTreeMap<&str, Route>) -> String {
+    let mut out = String::new();
+    match shell {
+        Shell::Bash | Shell::Zsh => {
+            out.push_str(&format!("{prefix}() {{ crux run \"$@\"; }}\n"));
+            for (program, route) in routes {
+                match route {
+                    Route::All => {
+                        out.push_str(&format!("{program}() {{ crux run {program} \"$@\"; }}\n"));
+                    }
+                    Route::Subcommands(subs) => {
+                        let subs: Vec<&str> = subs.iter().map(String::as_str).collect();
+                        out.push_str(&format!(
+                            "{program}() {{\n  case \"$1\" in\n    {}) crux run {program} \"$@\" ;;\n    *) command {program} \"$@\" ;;\n  esac\n}}\n",
+                            subs.join("|")
+                        ));
+                    }
+                }
+            }
+        }
+        Shell::Fish => {
+            out.push_str(&format!("function {prefix}\n  crux run $argv\nend\n"));
+            for (program, route) in routes {
+                match route {
+                    Route::All => out.push_str(&format!(
+                        "function {program} --wraps {program}\n  crux run {program} $argv\nend\n"
+                    )),
+                    Route::Subcommands(subs) => {
+                        let subs: Vec<&str> = subs.iter().map(String::as_str).collect();
+                        out.push_str(&format!(
+                            "function {program} --wraps {program}\n  switch \"$argv[1]\"\n    case {}\n      crux run {program} $argv\n    case '*'\n      command {program} $argv\n  end\nend\n",
+                            subs.join(" ")
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn routes_only_filtered_subcommands() {
+        let all = filters(&["git status", "git diff", "docker compose logs", "make"]);
+        assert_eq!(route_for("make", &all), Some(Route::All));
+        assert_eq!(
+            route_for("git", &all),
+            Some(Route::Subcommands(
+                ["diff", "status"].iter().map(|s| s.to_string()).collect()
+            ))
+        );
+        // Three-word filters would route every `docker compose` call
+        assert_eq!(route_for("docker", &all), None);
+        assert_eq!(route_for("vim", &all), None);
+    }
+
+    #[test]
+    fn renders_posix_functions() {
+        let mut routes = BTreeMap::new();
+        routes.insert("make", Route::All);
+        routes.insert(
+            "git",
+            Route::Subcommands(["diff", "status"].iter().map(|s| s.to_string()).collect()),
+        );
+        let out = render(Shell::Zsh, "c", &routes);
+        assert!(out.starts_with("c() { crux run \"$@\"; }\n"));
+        assert!(out.contains("    diff|status) crux run git \"$@\" ;;\n"));
+        assert!(out.contains("    *) command git \"$@\" ;;\n"));
+        assert!(out.contains("make() { crux run make \"$@\"; }\n"));
+    }
+
+    #[test]
+    fn renders_fish_functions() {
+        let mut routes = BTreeMap::new();
+        routes.insert(
+            "cargo",
+            Route::Subcommands(["test"].iter().map(|s| s.to_string()).collect()),
+        );
+        let out = render(Shell::Fish, "cx", &routes);
+        assert!(out.starts_with("function cx\n  crux run $argv\nend\n"));
+        assert!(out.contains("function cargo --wraps cargo\n"));
+        assert!(out.contains("    case test\n      crux run cargo $argv\n"));
+    }
+
+    #[test]
+    fn rejects_unsafe_names() {
+        assert!(is_shell_word("git"));
+        assert!(is_shell_word("migrate:status"));
+        assert!(!is_shell_word("rm -rf"));
+        assert!(!is_shell_word("--prod"));
+        assert!(!is_shell_word("$(x)"));
+    }
+}
@@ -0,0 +1,231 @@
+//! `crux bench` — time every filter over the stored corpus of real inputs.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use crux_core::config::FilterConfig;
+
+/// How many recent history entries are added to the corpus.
+#[cfg(feature = "tracking")]
+const HISTORY_SAMPLES: usize = 500;
+
+/// One input to run a filter on.
+struct Sample {
+    config: FilterConfig,
+    input: String,
+    exit_code: i32,
+}
+
+pub fn cmd_bench(filter_name: Option<&str>, iterations: usize) -> Result<()> {
+    let iterations = iterations.max(1);
+    let mut corpus: BTreeMap<String, Vec<Sample>> = BTreeMap::new();
+    let mut add = |sample: Sample| {
+        let wanted = match filter_name {
+            Some(name) => name == sample.config.command,
+            None => true,
+        };
+        if wanted {
+            corpus
+                .entry(sample.config.command.clone())
+                .or_default()
+                .push(sample);
+        }
+    };
+
+    // _test/ inputs of the embedded stdlib and of local and global filters
+    let mut cases = crux_core::verify::embedded_test_cases();
+    cases.extend(crux_core::verify::test_cases_in_dir(Path::new(
+        ".crux/filters",
+    )));
+    if let Some(home) = crate::commands::home_dir() {
+        cases.extend(crux_core::verify::test_cases_in_dir(
+            &home.join(".config/crux/filters"),
+        ));
+    }
+    for case in cases {
+        add(Sample {
+            config: case.config,
+            input: case.input,
+            exit_code: 0,
+        });
+    }
+
+    // Raw output of recent runs, through the filter that resolves today
+    #[cfg(feature = "tracking")]
+    for (config, input, exit_code) in history_samples() {
+        add(Sample {
+            config,
+            input,
+            exit_code,
+        });
+    }
+
+    if corpus.is_empty() {
+        match filter_name {
+            Some(name) => bail!("no corpus inputs for filter '{name}'"),
+            None => bail!("no corpus inputs found (add _test/ dirs or record runs with crux run)"),
+        }
+    }
+
+    println!(
+        "{:<24} {:>7} {:>6} {:>9} {:>9} {:>9} {:>9} {:>6}",
+        "FILTER", "SAMPLES", "RUNS", "P50", "P95", "P99", "MB/S", "SAVED"
+    );
+    for (name, samples) in &corpus {
+        let stats = bench_filter(samples, iterations);
+        println!(
+            "{:<24} {:>7} {:>6} {:>9} {:>9} {:>9} {:>9.1} {:>5.0}%",
+            crate::truncate_str(name, 24),
+            samples.len(),
+            stats.runs,
+            format_duration(stats.p50),
+            format_duration(stats.p95),
+            format_duration(stats.p99),
+            stats.throughput_mb_s,
+            stats.saved_pct
+        );
+    }
+
+    if filter_name.is_none() {
+        let missing = crux_core::filter::builtin::registry()
+            .keys()
+            .filter(|name| !corpus.contains_key(**name))
+            .count();
+        if missing > 0 {
+            println!(
+                "\n{missing} builtin filters have no corpus inputs yet; runs recorded by `crux run` add them."
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "tracking")]
+fn history_samples() -> Vec<(FilterConfig, String, i32)> {
+    let entries = crux_tracking::db::default_db_path()
+        .and_then(|path| crux_tracking::db::open_db(&path))
+        .and_then(|conn| crux_tracking::history::get_recent_history(&conn, HISTORY_SAMPLES));
+    let Ok(entries) = entries else {
+        return Vec::new();
+    };
+    entries
+        .into_iter()
+        .filter(|entry| !entry.raw_output.is_empty())
+        .filter_map(|entry| {
+            let command: Vec<String> = entry.command.split_whitespace().map(String::from).collect();
+            let exit_code = entry.exit_code.unwrap_or(0);
+            let config = crate::run::resolve_run_filter(&command)?;
+            let config = crate::run::select_variant_post(config, &entry.raw_output, exit_code);
+            Some((config, entry.raw_output, exit_code))
+        })
+        .collect()
+}
+
+struct BenchStats {
+    runs: usize,
+    p50: Duration,
+    p95: Duration,
+    p99: Duration,
+    throughput_mb_s: f64,
+    saved_pct: f64,
+}
+
+fn bench_filter(samples: &[Sample], iterations: usize) -> BenchStats {
+    let mut timings = Vec::with_capacity(samples.len() * iterations);
+    let (mut input_bytes, mut output_bytes) = (0usize, 0usize);
+    for sample in samples {
+        let filtered =
+            crux_core::filter::apply_filter(&sample.config, &sample.input, sample.exit_code);
+        input_bytes += sample.input.len();
+        output_bytes += filtered.len();
+        for _ in 0..iterations {
+            let start = Instant::now();
+            std::hint::black_box(crux_core::filter::apply_filter(
+                &sample.config,
+                &sample.input,
+                sample.exit_code,
+            ));
+            timings.push(start.elapsed());
+        }
+    }
+    timings.sort_unstable();
+
+    let total: Duration = timings.iter().sum();
+    let bytes_processed = input_bytes as f64 * iterations as f64;
+    BenchStats {
+        runs: timings.len(),
+        p50: percentile(&timings, 50.0),
+        p95: percentile(&timings, 95.0),
+        p99: percentile(&timings, 99.0),
+        throughput_mb_s: if total.is_zero() {
+            0.0
+        } else {
+            bytes_processed / total.as_secs_f64() / 1_000_000.0
+        },
+        saved_pct: if input_bytes == 0 {
+            0.0
+        } else {
+            (input_bytes as f64 - output_bytes as f64) / input_bytes as f64 * 100.0
+        },
+    }
+}
+
+/// Nearest-rank percentile of sorted `timings`.
+fn percentile(timings: &[Duration], pct: f64) -> Duration {
+    if timings.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (pct / 100.0 * timings.len() as f64).ceil() as usize;
+    timings[rank.clamp(1, timings.len()) - 1]
+}
+
+fn format_duration(d: Duration) -> String {
+    let micros = d.as_secs_f64() * 1_000_000.0;
+    if micros < 1000.0 {
+        format!("{micros:.0}µs")
+    } else {
+        format!("{:.2}ms", micros / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&v| Duration::from_millis(v)).collect()
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let timings = ms(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(percentile(&timings, 50.0), Duration::from_millis(5));
+        assert_eq!(percentile(&timings, 95.0), Duration::from_millis(10));
+        assert_eq!(percentile(&ms(&[7]), 99.0), Duration::from_millis(7));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn bench_reports_compression_of_each_sample() {
+        let config = FilterConfig {
+            skip: vec!["^noise".to_string()],
+            ..Default::default()
+        };
+        let samples = vec![Sample {
+            config,
+            input: "noise\nnoise\nkeep".to_string(),
+            exit_code: 0,
+        }];
+        let stats = bench_filter(&samples, 3);
+        assert_eq!(stats.runs, 3);
+        assert!(stats.saved_pct > 60.0);
+    }
+
+    #[test]
+    fn formats_micro_and_milliseconds() {
+        assert_eq!(format_duration(Duration::from_micros(250)), "250µs");
+        assert_eq!(format_duration(Duration::from_micros(2500)), "2.50ms");
+    }
+}
@@ -0,0 +1,498 @@
+//! Command-line arguments of `crux`.
+
+use clap::{Parser, Subcommand};
+
+#[cfg(feature = "tracking")]
+use crate::tracking;
+use crate::{alias, commands, gain, run};
+
+#[derive(Parser)]
+#[command(name = "crux", version, about = "CLI output compressor for AI agents")]
+pub struct Cli {
+    /// Filter profile: conservative, normal or aggressive (overrides CRUX_PROFILE)
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Print JSON instead of tables (gain, ls, which, show)
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Run a command through the filter pipeline
+    Run {
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+        /// Print execution timing breakdown to stderr
+        #[arg(long)]
+        time: bool,
+        /// Use this filter (by command name) instead of resolving one,
+        /// e.g. --filter "cargo test" -- ./scripts/ci.sh
+        #[arg(long)]
+        filter: Option<String>,
+        /// Show the unfiltered output, still recording the run for stats
+        #[arg(long, conflicts_with = "filter")]
+        raw: bool,
+        /// Also show estimated token counts in the summary
+        #[arg(long)]
+        tokens: bool,
+        /// Final cap on the output after filtering: 2000 (tokens) or 8000b
+        /// (bytes); the middle is cut with a marker
+        #[arg(long, value_parser = run::parse_budget)]
+        budget: Option<run::Budget>,
+        /// Output format: the filtered text, or a JSON envelope with metadata
+        /// (same as the global --json)
+        #[arg(long, value_enum, default_value = "text")]
+        output: RunOutput,
+        /// Record nothing about this run, neither savings nor history
+        /// (same as CRUX_NO_TRACK=1)
+        #[arg(long)]
+        no_track: bool,
+    },
+    /// Filter output read from stdin as if it came from a command
+    Filter {
+        /// Command whose filter to apply, e.g. "kubectl logs"
+        #[arg(long = "as", value_name = "COMMAND")]
+        as_command: String,
+        /// Exit code of the original run, for exit-code conditions and variants
+        #[arg(long, default_value = "0", allow_negative_numbers = true)]
+        exit_code: i32,
+    },
+    /// Rerun a command through the filter whenever files change
+    ///
+    /// Polls the working tree, skipping anything `.gitignore` excludes.
+    Watch {
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+        /// How often to check the tree for changes, in milliseconds
+        #[arg(long, default_value = "500")]
+        interval: u64,
+        /// Only print output when it differs from the previous run
+        #[arg(long)]
+        changed_only: bool,
+    },
+    /// Show token savings summary
+    Gain(gain::GainArgs),
+    /// Rank commands by output not saved, to find filters worth writing
+    #[cfg(feature = "tracking")]
+    Top {
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+        /// Rank by estimated tokens instead of bytes
+        #[arg(long)]
+        tokens: bool,
+        /// Only count runs since this time: 7d, 12h, 2w or a date (2026-10-01)
+        #[arg(long)]
+        since: Option<String>,
+    },
+    /// Per-filter runtime, savings and invocation counts
+    #[cfg(feature = "tracking")]
+    Stats {
+        /// Only count runs since this time: 7d, 12h, 2w or a date (2026-10-01)
+        #[arg(long)]
+        since: Option<String>,
+    },
+    /// Show recent command history
+    #[cfg(feature = "tracking")]
+    History {
+        #[command(subcommand)]
+        action: Option<HistoryCommand>,
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+        /// Only runs whose command contains this text (case-insensitive)
+        #[arg(long)]
+        command: Option<String>,
+        /// Only runs handled by this filter
+        #[arg(long)]
+        filter: Option<String>,
+        /// Only runs that exited non-zero
+        #[arg(long)]
+        failed_only: bool,
+        /// Only runs whose raw output is at least this big: 2000, 4k or 1M
+        #[arg(long, value_parser = tracking::parse_size)]
+        min_size: Option<usize>,
+        /// Only runs tagged with this label (see `crux history tag`)
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Compare the raw and filtered output of a history entry
+    #[cfg(feature = "tracking")]
+    Diff {
+        /// History entry id (see `crux history`), or `last`
+        #[arg(default_value = "last")]
+        entry: String,
+        /// Show raw and filtered output in two columns
+        #[arg(long)]
+        side_by_side: bool,
+    },
+    /// Re-apply the current filter to the raw output of a history entry
+    #[cfg(feature = "tracking")]
+    Replay {
+        /// History entry id (see `crux history`), or `last`
+        #[arg(default_value = "last")]
+        entry: String,
+        /// Use this filter (by command name) instead of resolving one
+        #[arg(long)]
+        filter: Option<String>,
+    },
+    /// Re-apply current filters to stored history and compare with what was recorded
+    #[cfg(feature = "tracking")]
+    Refilter {
+        /// Only entries recorded with this filter, replayed through its current version
+        #[arg(long)]
+        filter: Option<String>,
+        /// Number of most recent entries to replay
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+    },
+    /// Write recorded runs or history to stdout as CSV or JSON Lines
+    #[cfg(feature = "tracking")]
+    Export {
+        #[arg(long, value_enum, default_value = "csv")]
+        format: tracking::ExportFormat,
+        /// Table to export: savings events or stored command history
+        #[arg(long, value_enum, default_value = "events")]
+        table: tracking::ExportTable,
+        /// Only export runs since this time: 7d, 12h, 2w or a date (2026-10-01)
+        #[arg(long)]
+        since: Option<String>,
+    },
+    /// Push aggregated savings (no outputs) to the [sync] endpoint
+    #[cfg(feature = "tracking")]
+    Sync {
+        /// Print the payload instead of sending it
+        #[arg(long)]
+        dry_run: bool,
+        /// Resend everything, not just runs since the last sync
+        #[arg(long)]
+        all: bool,
+    },
+    /// Move tracking data between machines
+    #[cfg(feature = "tracking")]
+    Tracking {
+        #[command(subcommand)]
+        action: TrackingCommand,
+    },
+    /// Delete old tracking data and compact the database
+    #[cfg(feature = "tracking")]
+    Prune {
+        /// Delete runs older than this: 30d, 12h, 2w or a date (2026-10-01)
+        #[arg(long)]
+        older_than: Option<String>,
+        /// Keep only the newest N runs
+        #[arg(long)]
+        keep_last: Option<usize>,
+        /// Rebuild the database file to reclaim disk space
+        #[arg(long)]
+        vacuum: bool,
+    },
+    /// Print shell functions that run commands through crux
+    Alias {
+        /// Shell syntax to emit (default: from $SHELL)
+        #[arg(long, value_enum)]
+        shell: Option<alias::Shell>,
+        /// Name of the generic wrapper: `c cargo test` runs `crux run cargo test`
+        #[arg(long, default_value = "c")]
+        prefix: String,
+        /// Programs to wrap by name, e.g. --wrap git,cargo (only subcommands
+        /// with a filter go through crux)
+        #[arg(long, value_delimiter = ',')]
+        wrap: Vec<String>,
+    },
+    /// Print shell hook code that routes allowlisted commands through crux
+    ShellInit {
+        /// Shell to emit code for (default: from $SHELL)
+        #[arg(value_enum)]
+        shell: Option<alias::Shell>,
+        /// Commands to route, by word prefix, e.g. --allow "git status,make"
+        #[arg(long, value_delimiter = ',')]
+        allow: Vec<String>,
+    },
+    /// Install Claude Code hook
+    Init {
+        #[arg(long, group = "target")]
+        global: bool,
+        #[arg(long, group = "target")]
+        codex: bool,
+        /// Install the Cursor rule and hook in this project
+        #[arg(long, group = "target")]
+        cursor: bool,
+        /// Add crux rules for Zed's agent and route this project's Zed
+        /// tasks through crux
+        #[arg(long, group = "target")]
+        zed: bool,
+        /// Remove the crux hook, restoring hook entries it replaced
+        #[arg(long, conflicts_with_all = ["codex", "cursor", "zed"])]
+        uninstall: bool,
+        /// Show what would be written instead of writing it
+        #[arg(long)]
+        print: bool,
+    },
+    /// List available filters
+    Ls,
+    /// Find filters by keyword in their command or description
+    Search {
+        #[arg(required = true)]
+        term: Vec<String>,
+    },
+    /// Show which filter matches a command
+    Which {
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+        /// List every matching filter with its source, priority and why it
+        /// won or lost
+        #[arg(long)]
+        all: bool,
+    },
+    /// Run a command and trace what each filter stage did to its output
+    Explain {
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Show filter config details
+    Show {
+        filter: String,
+        /// Print the effective filter as TOML
+        #[arg(long = "toml", conflicts_with = "tests")]
+        as_toml: bool,
+        /// List the filter's test cases and whether they pass
+        #[arg(long)]
+        tests: bool,
+    },
+    /// Export builtin filter as TOML for customization
+    Eject {
+        #[arg(required_unless_present = "all")]
+        filter: Option<String>,
+        /// Write <filter>.toml and a <filter>_test/ scaffold into this directory
+        #[arg(long)]
+        out: Option<std::path::PathBuf>,
+        /// Eject every stdlib TOML filter (requires --out)
+        #[arg(long, conflicts_with = "filter", requires = "out")]
+        all: bool,
+        /// Overwrite existing filter files (test files are always kept)
+        #[arg(long)]
+        force: bool,
+    },
+    /// Scaffold a custom filter TOML with a _test/ directory
+    New {
+        /// Command the filter is for, e.g. "terraform plan"
+        command: String,
+        /// Directory to create the filter in
+        #[arg(long, default_value = ".crux/filters")]
+        dir: std::path::PathBuf,
+        /// Overwrite an existing filter file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check user filter TOMLs for typos, bad regexes and conflicts
+    Lint,
+    /// Share filter sets as .tar.gz bundles
+    Pack {
+        #[command(subcommand)]
+        command: PackCommand,
+    },
+    /// Run declarative filter tests
+    Verify,
+    /// Benchmark filters over _test inputs and recorded history
+    Bench {
+        /// Only benchmark this filter (by command name)
+        #[arg(long)]
+        filter: Option<String>,
+        /// Timed runs per corpus input
+        #[arg(long, default_value = "10")]
+        iterations: usize,
+    },
+    /// Keep only error/warning lines from command output
+    Err {
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+        /// Also keep lines matching this regex (repeatable)
+        #[arg(long = "pattern", value_name = "REGEX")]
+        patterns: Vec<String>,
+        /// Also keep warning lines
+        #[arg(long, overrides_with = "no_warnings")]
+        warnings: bool,
+        /// Drop warning lines even if the config file keeps them
+        #[arg(long, overrides_with = "warnings")]
+        no_warnings: bool,
+        /// Lines of context around each kept line
+        #[arg(long, value_name = "N")]
+        context: Option<usize>,
+    },
+    /// Extract test summary from command output.
+    ///
+    /// Auto-detects: cargo test, pytest, jest, vitest, go test, mocha,
+    /// playwright, rspec, PHPUnit, dotnet test. Falls back to extracting
+    /// lines containing pass/fail/error/warning keywords.
+    Test {
+        #[arg(trailing_var_arg = true, required_unless_present = "list_frameworks")]
+        command: Vec<String>,
+        /// Parse output as this framework instead of auto-detecting it
+        #[arg(long)]
+        framework: Option<String>,
+        /// List the supported frameworks and exit
+        #[arg(long)]
+        list_frameworks: bool,
+    },
+    /// Run command with dedup and collapse filters
+    Log {
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+        /// Also collapse a line repeated within N lines, not just consecutively
+        #[arg(long, value_name = "N")]
+        window: Option<usize>,
+        /// Only collapse lines repeated at least N times
+        #[arg(long, value_name = "N")]
+        min_repeat: Option<usize>,
+        /// Remove leading timestamps so otherwise identical lines dedup
+        #[arg(long)]
+        strip_timestamps: bool,
+    },
+    /// Run diagnostic checks on your crux installation
+    Doctor {
+        /// Repair what can be repaired: install or migrate the hook, create
+        /// the tracking database, write a config template
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Update crux to the latest GitHub release
+    Upgrade {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+    /// Serve crux's tools to agents over a protocol
+    #[cfg(feature = "mcp")]
+    Serve {
+        /// Model Context Protocol on stdin/stdout
+        #[arg(long, required = true)]
+        mcp: bool,
+    },
+    /// Agent hook management
+    Hook {
+        #[command(subcommand)]
+        command: HookCommand,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RunOutput {
+    Text,
+    Json,
+}
+
+#[cfg(feature = "tracking")]
+#[derive(Subcommand)]
+pub enum TrackingCommand {
+    /// Write the whole tracking database as a portable JSON archive
+    Dump {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Merge an archive from `crux tracking dump`, skipping runs already recorded
+    Import {
+        /// Archive file, or "-" for stdin
+        file: String,
+    },
+    /// Store a run record read from stdin (used by `crux run`)
+    #[command(hide = true)]
+    Record {
+        /// Read the record from this file instead, and delete it
+        #[arg(long)]
+        file: Option<std::path::PathBuf>,
+    },
+}
+
+#[cfg(feature = "tracking")]
+#[derive(Subcommand)]
+pub enum HistoryCommand {
+    /// Print the stored raw and filtered output of one entry
+    Show {
+        /// History entry id, or "last"
+        id: String,
+        /// Only print the raw output
+        #[arg(long, conflicts_with = "filtered")]
+        raw: bool,
+        /// Only print the filtered output
+        #[arg(long)]
+        filtered: bool,
+        /// Page the output through $PAGER (default: less)
+        #[arg(long)]
+        pager: bool,
+    },
+    /// Label an entry, e.g. as a bug reproduction or benchmark baseline
+    Tag {
+        /// History entry id, or "last"
+        id: String,
+        /// Single-word label
+        label: String,
+        /// Remove the label instead
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Attach a note to an entry, or clear it when no text is given
+    Note {
+        /// History entry id, or "last"
+        id: String,
+        text: Option<String>,
+    },
+    /// Show which filtered output lines changed between two runs
+    Diff {
+        /// Earlier history entry id
+        a: String,
+        /// Later history entry id, or "last"
+        #[arg(default_value = "last")]
+        b: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PackCommand {
+    /// Bundle filter TOMLs and their _test/ directories into a .tar.gz
+    Export {
+        /// Archive to write, e.g. team-filters.tar.gz
+        file: std::path::PathBuf,
+        /// Directory to pack filters from
+        #[arg(long, default_value = ".crux/filters")]
+        from: std::path::PathBuf,
+        /// Only pack these filters (by command), e.g. --filter "terraform plan"
+        #[arg(long = "filter", value_name = "COMMAND")]
+        filters: Vec<String>,
+    },
+    /// Install a pack from a file or URL into ~/.config/crux/filters
+    Import {
+        /// Path or https:// URL of the .tar.gz
+        source: String,
+        /// Install into .crux/filters instead
+        #[arg(long)]
+        local: bool,
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HookCommand {
+    /// Process Claude Code PreToolUse hook from stdin
+    Handle {
+        /// Agent whose hook format the event is in
+        #[arg(long, value_enum, default_value = "claude")]
+        format: commands::HookFormat,
+    },
+    /// Compact earlier tool outputs from stdin (a hook payload with
+    /// transcript_path, a JSON array of strings, or text) into a digest
+    Compact,
+    /// Show where crux is installed as an agent hook, and by which version
+    Status,
+    /// Remove the crux hook of one agent integration
+    Remove {
+        #[arg(value_enum)]
+        target: commands::HookTarget,
+    },
+}
@@ -0,0 +1,309 @@
+//! `crux doctor` — diagnostic health check.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::home_dir;
+use super::init::cmd_init;
+
+pub fn cmd_doctor(fix: bool) -> Result<()> {
+    println!("crux doctor");
+    println!("===========\n");
+
+    // Version info
+    println!("Version:  {}", crux_core::VERSION);
+    println!(
+        "Tracking: {}",
+        if cfg!(feature = "tracking") {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    println!();
+
+    // Is crux on PATH?
+    let on_path = std::process::Command::new("which")
+        .arg("crux")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    print_check("crux on PATH", on_path, "add crux to your PATH");
+
+    // Is Claude Code hook installed?
+    let settings_path = home_dir().map(|h| h.join(".claude/settings.json"));
+    let mut hook = settings_path
+        .as_deref()
+        .map_or(HookState::Missing, hook_state);
+    if fix && matches!(hook, HookState::Missing | HookState::Stale(_)) {
+        match cmd_init(true, false, false) {
+            Ok(()) => print_fixed("installed the Claude Code hook"),
+            Err(e) => println!("  [!!] could not install the hook: {e:#}"),
+        }
+        hook = settings_path
+            .as_deref()
+            .map_or(HookState::Missing, hook_state);
+    }
+    let hook_installed = hook == HookState::Installed;
+    match &hook {
+        HookState::Installed | HookState::Missing => print_check(
+            "Claude Code hook installed",
+            hook_installed,
+            "run `crux init --global` or `crux doctor --fix` to install",
+        ),
+        HookState::Stale(reason) => print_check(
+            &format!("Claude Code hook up to date ({reason})"),
+            false,
+            "run `crux doctor --fix` to reinstall it",
+        ),
+        HookState::Unreadable(reason) => print_check(
+            &format!("Claude Code settings.json readable ({reason})"),
+            false,
+            "fix the JSON by hand, then run `crux init --global`",
+        ),
+    }
+
+    // Filter counts
+    let counts = crux_core::config::count_filters();
+    let has_filters = counts.total() > 0;
+    print_check(
+        &format!(
+            "Filters available ({} builtin, {} stdlib, {} user)",
+            counts.builtin,
+            counts.stdlib_toml,
+            counts.user_local + counts.user_global
+        ),
+        has_filters,
+        "something is wrong with the installation",
+    );
+
+    // Global config file (optional)
+    if let Some(config_path) = home_dir().map(|h| h.join(".config/crux/config.toml")) {
+        if config_path.exists() {
+            println!("  [ok] Global config file ({})", config_path.display());
+        } else if fix {
+            match write_config_template(&config_path) {
+                Ok(()) => print_fixed(&format!(
+                    "wrote a commented config template to {}",
+                    config_path.display()
+                )),
+                Err(e) => println!("  [!!] could not write {}: {e:#}", config_path.display()),
+            }
+        } else {
+            println!(
+                "  [--] No global config file ({}); `crux doctor --fix` writes a commented template",
+                config_path.display()
+            );
+        }
+    }
+
+    // Tracking database
+    #[cfg(feature = "tracking")]
+    let db_ok = {
+        // Only --fix creates the directory and database
+        let existing = crux_tracking::db::data_dir().join("crux.db");
+        let mut db_ok = existing.exists() && crux_tracking::db::open_db(&existing).is_ok();
+        if fix && !db_ok {
+            match crux_tracking::db::default_db_path()
+                .and_then(|p| crux_tracking::db::open_db(&p).map(|_| ()))
+            {
+                Ok(()) => {
+                    print_fixed("created the tracking database");
+                    db_ok = true;
+                }
+                Err(e) => println!("  [!!] could not create the tracking database: {e:#}"),
+            }
+        }
+        print_check(
+            "Tracking database accessible",
+            db_ok,
+            "run `crux doctor --fix`, or check ~/.local/share/crux/ permissions",
+        );
+        db_ok
+    };
+
+    #[cfg(not(feature = "tracking"))]
+    let db_ok = {
+        println!("  [--] Tracking database (feature disabled)");
+        true
+    };
+
+    println!();
+    if on_path && hook_installed && has_filters && db_ok {
+        println!("All checks passed.");
+    } else if fix {
+        println!("Some checks still fail. See suggestions above.");
+    } else {
+        println!("Some checks failed. See suggestions above, or run `crux doctor --fix`.");
+    }
+
+    Ok(())
+}
+
+/// State of the crux hook in a Claude Code settings.json.
+#[derive(Debug, PartialEq)]
+pub(super) enum HookState {
+    Installed,
+    Missing,
+    /// Installed in a form that no longer works, e.g. an old entry format,
+    /// a tokf hook, or a hook script that was deleted.
+    Stale(String),
+    Unreadable(String),
+}
+
+pub(super) fn hook_state(settings_path: &Path) -> HookState {
+    let Ok(contents) = std::fs::read_to_string(settings_path) else {
+        return HookState::Missing;
+    };
+    let settings: serde_json::Value = match serde_json::from_str(&contents) {
+        Ok(settings) => settings,
+        Err(e) => return HookState::Unreadable(e.to_string()),
+    };
+    let entries = settings
+        .pointer("/hooks/PreToolUse")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut installed = false;
+    for entry in &entries {
+        if is_crux_command(entry.get("command")) {
+            return HookState::Stale("old hook entry format".to_string());
+        }
+        let hooks = entry.get("hooks").and_then(|h| h.as_array());
+        for command in hooks.into_iter().flatten().filter_map(|h| h.get("command")) {
+            let Some(command) = command.as_str() else {
+                continue;
+            };
+            if command.contains("tokf") {
+                return HookState::Stale("runs tokf".to_string());
+            }
+            if !command.contains("crux") {
+                continue;
+            }
+            if !command.contains(' ') && !Path::new(command).exists() {
+                return HookState::Stale(format!("{command} is missing"));
+            }
+            installed = true;
+        }
+    }
+    if installed {
+        HookState::Installed
+    } else {
+        HookState::Missing
+    }
+}
+
+/// True for a hook `command` value that runs crux or its predecessor tokf.
+pub(super) fn is_crux_command(command: Option<&serde_json::Value>) -> bool {
+    command
+        .and_then(|c| c.as_str())
+        .is_some_and(|c| c.contains("tokf") || c.contains("crux"))
+}
+
+/// Every setting of the config file, commented out.
+const CONFIG_TEMPLATE: &str = r#"# crux settings. Project values in .crux/config.toml win over these.
+# profile = "normal"             # conservative, normal or aggressive
+# tracking = true                # record savings and history
+# no_history = ["secret"]        # never store output of commands containing these
+# encrypt_history = true         # needs the `encryption` build feature
+# disabled_builtins = ["curl"]   # pass these commands through unfiltered
+# tee = "failures"               # save raw output to .crux/raw/ when a command fails
+# pipe_target = "first"          # for `a | b`, pick the filter by `a`
+# max_bytes = 16384              # default caps for filters without their own
+# max_tokens = 4000
+
+# [priority]
+# git = 50
+
+# [commands."cargo test"]
+# tail = 40
+
+# [err]
+# patterns = ["^npm ERR!"]
+# warnings = false
+# context = 0
+
+# [retention]
+# max_age = "30d"
+# max_rows = 10000
+# max_db_mb = 200
+# max_history_kb = 256
+
+# [sync]
+# endpoint = "https://dash.example.com/api/crux"
+# method = "post"
+# token_env = "CRUX_SYNC_TOKEN"
+# include_commands = false
+"#;
+
+fn write_config_template(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("creating {}", parent.display()))?;
+    }
+    std::fs::write(path, CONFIG_TEMPLATE).with_context(|| format!("writing {}", path.display()))
+}
+
+fn print_fixed(action: &str) {
+    println!("  [fixed] {action}");
+}
+
+fn print_check(label: &str, ok: bool, hint: &str) {
+    if ok {
+        println!("  [ok] {label}");
+    } else {
+        println!("  [!!] {label}");
+        println!("       hint: {hint}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(settings: &str) -> HookState {
+        let dir = std::env::temp_dir().join(format!(
+            "crux-doctor-{}-{}",
+            std::process::id(),
+            settings.len()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        std::fs::write(&path, settings).unwrap();
+        let state = hook_state(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        state
+    }
+
+    #[test]
+    fn detects_installed_missing_and_stale_hooks() {
+        let current = r#"{"hooks":{"PreToolUse":[{"matcher":"Bash","hooks":[{"type":"command","command":"crux hook handle"}]}]}}"#;
+        assert_eq!(state(current), HookState::Installed);
+        assert_eq!(state(r#"{"hooks":{}}"#), HookState::Missing);
+
+        let flat = r#"{"hooks":{"PreToolUse":[{"matcher":"Bash","command":"crux hook handle"}]}}"#;
+        assert!(matches!(state(flat), HookState::Stale(_)));
+        let tokf =
+            r#"{"hooks":{"PreToolUse":[{"matcher":"Bash","hooks":[{"command":"tokf hook"}]}]}}"#;
+        assert!(matches!(state(tokf), HookState::Stale(_)));
+        let gone = r#"{"hooks":{"PreToolUse":[{"hooks":[{"command":"/nonexistent/crux/pre-tool-use.sh"}]}]}}"#;
+        assert!(matches!(state(gone), HookState::Stale(_)));
+
+        assert!(matches!(state("{oops"), HookState::Unreadable(_)));
+    }
+
+    #[test]
+    fn config_template_parses_when_uncommented() {
+        let uncommented: String = CONFIG_TEMPLATE
+            .lines()
+            .skip(1)
+            .map(|line| line.strip_prefix("# ").unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let settings: crux_core::config::settings::Settings = toml::from_str(&uncommented).unwrap();
+        assert_eq!(settings.priority["git"], 50);
+        assert_eq!(settings.err.patterns, vec!["^npm ERR!"]);
+    }
+}
@@ -0,0 +1,122 @@
+//! `crux eject` — export a filter as TOML.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+pub fn cmd_eject(filter: Option<&str>, out: Option<&Path>, all: bool, force: bool) -> Result<()> {
+    if all {
+        let out = out.context("--all needs --out DIR")?;
+        let mut written = 0;
+        for config in crux_core::config::stdlib_filters() {
+            if write_ejected(config, out, force)? {
+                written += 1;
+            }
+        }
+        println!(
+            "Ejected {written} stdlib filters to {}. Run `crux verify` after editing.",
+            out.display()
+        );
+        return Ok(());
+    }
+
+    let filter = filter.context("name a filter to eject, or pass --all")?;
+    let tokens: Vec<String> = filter.split_whitespace().map(String::from).collect();
+    let config = crux_core::config::resolve_filter(&tokens).with_context(|| {
+        format!("no filter matches '{filter}'. Run `crux ls` to see all available filters")
+    })?;
+
+    if let Some(out) = out {
+        if write_ejected(&config, out, force)? {
+            println!(
+                "Paste real `{}` output into {}/{}_test/input.txt and the output you want into expected.txt, then run `crux verify`.",
+                config.command,
+                out.display(),
+                eject_slug(&config.command)
+            );
+        }
+        return Ok(());
+    }
+
+    let toml_str =
+        toml::to_string_pretty(&config).context("failed to serialize filter config to TOML")?;
+    println!("# Ejected filter for: {}", config.command);
+    println!(
+        "# Save to .crux/filters/{}.toml to customize",
+        eject_slug(&config.command)
+    );
+    println!();
+    print!("{toml_str}");
+    Ok(())
+}
+
+/// File name stem for an ejected filter: `git status` → `git-status`.
+pub(super) fn eject_slug(command: &str) -> String {
+    command
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .replace(['/', '\\'], "_")
+}
+
+/// Write `<slug>.toml` and a `<slug>_test/` scaffold into `dir`, seeded with
+/// the stdlib test cases for the filter if it has any. Returns false (and
+/// says so) when the TOML exists and `force` is off.
+fn write_ejected(
+    config: &crux_core::config::FilterConfig,
+    dir: &Path,
+    force: bool,
+) -> Result<bool> {
+    let slug = eject_slug(&config.command);
+    let toml_path = dir.join(format!("{slug}.toml"));
+    if toml_path.exists() && !force {
+        eprintln!(
+            "crux: {} exists, skipping (use --force to overwrite)",
+            toml_path.display()
+        );
+        return Ok(false);
+    }
+
+    let toml_str =
+        toml::to_string_pretty(config).context("failed to serialize filter config to TOML")?;
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    std::fs::write(
+        &toml_path,
+        format!("# Ejected filter for: {}\n\n{toml_str}", config.command),
+    )
+    .with_context(|| format!("writing {}", toml_path.display()))?;
+
+    let test_dir = dir.join(format!("{slug}_test"));
+    std::fs::create_dir_all(&test_dir)
+        .with_context(|| format!("creating {}", test_dir.display()))?;
+    let prefix = format!("{}::", config.command);
+    let mut seeded = false;
+    for case in crux_core::verify::embedded_test_cases() {
+        let Some(stem) = case.name.strip_prefix(&prefix) else {
+            continue;
+        };
+        let (input, expected) = match stem {
+            "default" => ("input.txt".to_string(), "expected.txt".to_string()),
+            stem => (format!("{stem}.input"), format!("{stem}.expected")),
+        };
+        write_if_missing(&test_dir.join(input), &case.input)?;
+        write_if_missing(&test_dir.join(expected), &case.expected)?;
+        seeded = true;
+    }
+    if !seeded {
+        write_if_missing(&test_dir.join("input.txt"), "")?;
+        write_if_missing(&test_dir.join("expected.txt"), "")?;
+    }
+
+    println!("Wrote {}", toml_path.display());
+    Ok(true)
+}
+
+/// Test files are never overwritten, even with `--force`: they hold the
+/// user's own samples.
+pub(super) fn write_if_missing(path: &Path, contents: &str) -> Result<()> {
+    if !path.exists() {
+        std::fs::write(path, contents).with_context(|| format!("writing {}", path.display()))?;
+    }
+    Ok(())
+}
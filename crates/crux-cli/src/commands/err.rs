@@ -0,0 +1,90 @@
+//! `crux err` — error-only filter.
+
+use anyhow::{Context, Result};
+
+/// Lines `crux err` keeps by default.
+const ERR_PATTERN: &str =
+    r"(?i)(error[:\[]|fatal[:\s]|panic[:\s]|exception[:\s]|traceback|fail(ed|ure)?[:\s])";
+
+/// Lines added by `--warnings`.
+const WARNING_PATTERN: &str = r"(?i)\bwarn(ing)?[:\[]";
+
+/// `crux err` options; unset flags fall back to `[err]` in the config file.
+pub struct ErrOptions<'a> {
+    pub patterns: &'a [String],
+    pub warnings: Option<bool>,
+    pub context: Option<usize>,
+}
+
+pub fn cmd_err(command: &[String], options: &ErrOptions) -> Result<()> {
+    let defaults = &crux_core::config::settings::settings().err;
+    let warnings = options.warnings.or(defaults.warnings).unwrap_or(false);
+    let context = options.context.or(defaults.context).unwrap_or(0);
+    let patterns = err_patterns(&defaults.patterns, options.patterns, warnings)?;
+
+    let result = crux_core::runner::run_command(command)?;
+    let filtered =
+        crux_core::filter::skip::apply_keep_context(&result.combined, &[], &patterns, context);
+
+    if filtered.is_empty() {
+        println!("(no error lines detected)");
+    } else {
+        println!("{filtered}");
+    }
+
+    if result.exit_code != 0 {
+        eprintln!("crux: exit code {}", result.exit_code);
+    }
+    Ok(())
+}
+
+/// The builtin error pattern plus configured and `--pattern` ones, each
+/// checked to compile.
+fn err_patterns(configured: &[String], extra: &[String], warnings: bool) -> Result<Vec<String>> {
+    let mut patterns = vec![ERR_PATTERN.to_string()];
+    if warnings {
+        patterns.push(WARNING_PATTERN.to_string());
+    }
+    for pattern in configured.iter().chain(extra) {
+        regex::Regex::new(pattern).with_context(|| format!("invalid pattern '{pattern}'"))?;
+        patterns.push(pattern.clone());
+    }
+    Ok(patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crux_core::filter::skip::apply_keep_context;
+
+    const OUTPUT: &str = "compiling\nwarning: unused variable\nnote: here\nerror[E0425]: bad\n  --> src/main.rs:3\nERR! custom failure\ndone";
+
+    #[test]
+    fn keeps_error_lines_by_default() {
+        let patterns = err_patterns(&[], &[], false).unwrap();
+        assert_eq!(
+            apply_keep_context(OUTPUT, &[], &patterns, 0),
+            "error[E0425]: bad"
+        );
+    }
+
+    #[test]
+    fn warnings_patterns_and_context_widen_the_match() {
+        let patterns = err_patterns(&["^ERR!".to_string()], &[], true).unwrap();
+        assert_eq!(
+            apply_keep_context(OUTPUT, &[], &patterns, 0),
+            "warning: unused variable\nerror[E0425]: bad\nERR! custom failure"
+        );
+        let patterns = err_patterns(&[], &[], false).unwrap();
+        assert_eq!(
+            apply_keep_context(OUTPUT, &[], &patterns, 1),
+            "note: here\nerror[E0425]: bad\n  --> src/main.rs:3"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let err = err_patterns(&[], &["(oops".to_string()], false).unwrap_err();
+        assert!(err.to_string().contains("(oops"));
+    }
+}
@@ -0,0 +1,325 @@
+//! `crux hook` — answer agent hook events, and manage installed agent
+//! integrations.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::doctor::{hook_state, HookState};
+use super::init::{cmd_uninstall, hook_paths, hook_script_version};
+
+/// Hook payload formats `crux hook handle` understands.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum HookFormat {
+    /// Claude Code PreToolUse
+    Claude,
+    /// Cursor beforeShellExecution
+    Cursor,
+    /// OpenCode tool.execute.before plugin call
+    Opencode,
+    /// Crush pre_tool_use
+    Crush,
+    /// Zed agent terminal tool call
+    Zed,
+}
+
+/// `crux hook handle`: answer one agent hook event.
+pub fn cmd_hook_handle(format: HookFormat) -> Result<()> {
+    // Read all of stdin
+    let mut input_str = String::new();
+    std::io::stdin().read_to_string(&mut input_str).ok();
+
+    // Parse and handle — silent on any error (never block the agent)
+    let handle_json = |handle: fn(&serde_json::Value) -> Option<serde_json::Value>| {
+        if let Ok(event) = serde_json::from_str::<serde_json::Value>(&input_str) {
+            if let Some(output) = handle(&event) {
+                println!("{output}");
+            }
+        }
+    };
+    match format {
+        HookFormat::Claude => {
+            if let Ok(input) = serde_json::from_str::<crux_hook::claude::HookInput>(&input_str) {
+                if let Some(output) = crux_hook::claude::handle_hook(&input) {
+                    if let Ok(json) = serde_json::to_string(&output) {
+                        println!("{json}");
+                    }
+                }
+            }
+        }
+        HookFormat::Cursor => {
+            // Cursor waits for an answer, so passthrough is an explicit allow
+            let output = serde_json::from_str::<crux_hook::cursor::HookInput>(&input_str)
+                .map(|input| crux_hook::cursor::handle_hook(&input))
+                .unwrap_or_else(|_| crux_hook::cursor::HookOutput::allow());
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        HookFormat::Opencode => handle_json(crux_hook::opencode::handle_hook),
+        HookFormat::Crush => handle_json(crux_hook::crush::handle_hook),
+        HookFormat::Zed => handle_json(crux_hook::zed::handle_hook),
+    }
+
+    Ok(())
+}
+
+/// Print a digest of earlier tool outputs with repeats folded, for
+/// Claude Code's PreCompact or Stop hooks.
+pub fn cmd_hook_compact() -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let outputs = crux_hook::compact::outputs_from_input(&input)?;
+    let digest = crux_hook::compact::compact_outputs(&outputs);
+    print!("{digest}");
+
+    let before: usize = outputs.iter().map(String::len).sum();
+    eprintln!(
+        "crux: compacted {} tool outputs, {before} → {} bytes",
+        outputs.len(),
+        digest.len()
+    );
+    Ok(())
+}
+
+/// An agent integration managed by `crux hook status` / `crux hook remove`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HookTarget {
+    /// Claude Code hook of this project (`crux init`)
+    Local,
+    /// Claude Code hook of all projects (`crux init --global`)
+    Global,
+    /// Codex instructions and wrapper script (`crux init --codex`)
+    Codex,
+    /// Cursor rule and hook of this project (`crux init --cursor`)
+    Cursor,
+    /// Zed agent rules and tasks of this project (`crux init --zed`)
+    Zed,
+}
+
+impl HookTarget {
+    const ALL: [HookTarget; 5] = [
+        HookTarget::Local,
+        HookTarget::Global,
+        HookTarget::Codex,
+        HookTarget::Cursor,
+        HookTarget::Zed,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            HookTarget::Local => "local",
+            HookTarget::Global => "global",
+            HookTarget::Codex => "codex",
+            HookTarget::Cursor => "cursor",
+            HookTarget::Zed => "zed",
+        }
+    }
+}
+
+/// What `crux hook status` found for one target.
+struct TargetStatus {
+    target: HookTarget,
+    state: HookState,
+    /// Settings file for Claude Code, the instructions file for Codex.
+    path: PathBuf,
+    /// crux version that wrote the hook script, if it says.
+    version: Option<String>,
+}
+
+fn target_status(target: HookTarget) -> Result<TargetStatus> {
+    if target == HookTarget::Codex {
+        // The instructions run whichever crux is on PATH; only the wrapper
+        // script can go stale.
+        let path = crux_hook::codex::instructions_path()?;
+        let wrapper_path = crux_hook::codex::wrapper_path()?;
+        let wrapper = std::fs::read_to_string(&wrapper_path).ok();
+        let (state, path, version) = match wrapper {
+            Some(script) if script != crux_hook::codex::build_wrapper_script() => (
+                HookState::Stale("wrapper script differs from this version's".to_string()),
+                wrapper_path,
+                None,
+            ),
+            Some(_) if crux_hook::codex::has_instructions() => (
+                HookState::Installed,
+                path,
+                Some(crux_core::VERSION.to_string()),
+            ),
+            None if crux_hook::codex::has_instructions() => (
+                HookState::Stale("wrapper script is missing".to_string()),
+                wrapper_path,
+                None,
+            ),
+            _ => (HookState::Missing, path, None),
+        };
+        return Ok(TargetStatus {
+            target,
+            state,
+            path,
+            version,
+        });
+    }
+
+    if target == HookTarget::Cursor {
+        let base_dir = Path::new("");
+        let path = base_dir.join(crux_hook::cursor::HOOKS_FILE);
+        let (state, version) = match crux_hook::cursor::read_hooks(&path) {
+            Err(e) => (HookState::Unreadable(format!("{e:#}")), None),
+            Ok(hooks) if !crux_hook::cursor::has_hook(&hooks) => (HookState::Missing, None),
+            // The rule carries no version; a current one means this crux
+            // wrote it
+            Ok(_) => (
+                HookState::Installed,
+                crux_hook::cursor::rule_is_current(base_dir)
+                    .then(|| crux_core::VERSION.to_string()),
+            ),
+        };
+        return Ok(TargetStatus {
+            target,
+            state,
+            path,
+            version,
+        });
+    }
+
+    if target == HookTarget::Zed {
+        // The rules block carries no version; the agent reads the current
+        // crux's behaviour either way
+        let base_dir = Path::new("");
+        let (state, version) = if crux_hook::zed::is_installed(base_dir) {
+            (HookState::Installed, Some(crux_core::VERSION.to_string()))
+        } else {
+            (HookState::Missing, None)
+        };
+        return Ok(TargetStatus {
+            target,
+            state,
+            path: crux_hook::zed::rules_path(base_dir),
+            version,
+        });
+    }
+
+    let (base_dir, settings_path) = hook_paths(target == HookTarget::Global)?;
+    let state = hook_state(&settings_path);
+    let version = match state {
+        HookState::Installed => {
+            std::fs::read_to_string(base_dir.join(".crux/hooks/pre-tool-use.sh"))
+                .ok()
+                .and_then(|script| hook_script_version(&script).map(String::from))
+        }
+        _ => None,
+    };
+    Ok(TargetStatus {
+        target,
+        state,
+        path: settings_path,
+        version,
+    })
+}
+
+pub fn cmd_hook_status(json: bool) -> Result<()> {
+    let statuses = HookTarget::ALL
+        .into_iter()
+        .map(target_status)
+        .collect::<Result<Vec<_>>>()?;
+
+    if json {
+        let targets: Vec<_> = statuses
+            .iter()
+            .map(|status| {
+                let (state, detail) = match &status.state {
+                    HookState::Installed => ("installed", None),
+                    HookState::Missing => ("missing", None),
+                    HookState::Stale(reason) => ("stale", Some(reason)),
+                    HookState::Unreadable(reason) => ("unreadable", Some(reason)),
+                };
+                serde_json::json!({
+                    "target": status.target.name(),
+                    "state": state,
+                    "detail": detail,
+                    "path": status.path,
+                    "version": status.version,
+                })
+            })
+            .collect();
+        let value = serde_json::json!({ "version": crux_core::VERSION, "targets": targets });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("crux {}", crux_core::VERSION);
+    for status in &statuses {
+        let state = match &status.state {
+            HookState::Installed => match status.version.as_deref() {
+                Some(version) if version == crux_core::VERSION => "installed".to_string(),
+                Some(version) => format!("installed by crux {version}"),
+                None => "installed by an older crux".to_string(),
+            },
+            HookState::Missing => "not installed".to_string(),
+            HookState::Stale(reason) => format!("stale ({reason})"),
+            HookState::Unreadable(reason) => format!("unreadable ({reason})"),
+        };
+        println!(
+            "  {:<7} {state}: {}",
+            status.target.name(),
+            status.path.display()
+        );
+    }
+    Ok(())
+}
+
+pub fn cmd_hook_remove(target: HookTarget) -> Result<()> {
+    match target {
+        HookTarget::Local => cmd_uninstall(false, false),
+        HookTarget::Global => cmd_uninstall(true, false),
+        HookTarget::Codex => {
+            let path = crux_hook::codex::instructions_path()?;
+            let wrapper_path = crux_hook::codex::wrapper_path()?;
+            let removed_instructions = crux_hook::codex::uninstall_codex_instructions()?;
+            let removed_wrapper = crux_hook::codex::uninstall_codex_wrapper()?;
+            if removed_instructions {
+                println!(
+                    "crux: removed crux instructions for Codex: {}",
+                    path.display()
+                );
+            }
+            if removed_wrapper {
+                println!(
+                    "crux: removed Codex wrapper script: {}",
+                    wrapper_path.display()
+                );
+            }
+            if !removed_instructions && !removed_wrapper {
+                println!("crux: no Codex integration in {}", path.display());
+            }
+            Ok(())
+        }
+        HookTarget::Cursor => {
+            let base_dir = Path::new("");
+            if crux_hook::cursor::uninstall_cursor(base_dir)? {
+                println!(
+                    "crux: removed Cursor rule and hook: {}",
+                    base_dir.join(".cursor").display()
+                );
+            } else {
+                println!(
+                    "crux: no Cursor hook in {}",
+                    base_dir.join(crux_hook::cursor::HOOKS_FILE).display()
+                );
+            }
+            Ok(())
+        }
+        HookTarget::Zed => {
+            let base_dir = Path::new("");
+            let rules_path = crux_hook::zed::rules_path(base_dir);
+            if crux_hook::zed::uninstall_zed(base_dir)? {
+                println!(
+                    "crux: removed crux rules and task rewrites for Zed: {}",
+                    rules_path.display()
+                );
+            } else {
+                println!("crux: no crux rules in {}", rules_path.display());
+            }
+            Ok(())
+        }
+    }
+}
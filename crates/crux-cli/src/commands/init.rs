@@ -0,0 +1,361 @@
+//! `crux init` — install the Claude Code hook.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::doctor::is_crux_command;
+use super::home_dir;
+
+/// The hook shim, stamped with the crux version that wrote it so
+/// `crux hook status` can tell old installs apart.
+fn hook_script() -> String {
+    format!(
+        "#!/bin/sh\n# crux {}\nexec crux hook handle\n",
+        crux_core::VERSION
+    )
+}
+
+/// Version stamped in a hook script by [`hook_script`]; `None` for scripts
+/// written before the stamp existed.
+pub(super) fn hook_script_version(script: &str) -> Option<&str> {
+    script
+        .lines()
+        .find_map(|line| line.strip_prefix("# crux "))
+        .map(str::trim)
+}
+
+/// Where the hook lives: the directory holding `.crux/hooks/` and the
+/// Claude Code settings file.
+pub(super) fn hook_paths(global: bool) -> Result<(PathBuf, PathBuf)> {
+    if global {
+        let home = home_dir().context("cannot determine home directory")?;
+        let settings = home.join(".claude/settings.json");
+        Ok((home, settings))
+    } else {
+        Ok((PathBuf::from("."), PathBuf::from(".claude/settings.json")))
+    }
+}
+
+fn read_settings(settings_path: &Path) -> Result<serde_json::Value> {
+    if !settings_path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    let contents =
+        std::fs::read_to_string(settings_path).context("reading existing settings.json")?;
+    serde_json::from_str(&contents).context("parsing settings.json")
+}
+
+pub fn cmd_init(global: bool, codex: bool, print: bool) -> Result<()> {
+    if codex {
+        return crux_hook::codex::install_codex_skill(print);
+    }
+
+    let (base_dir, settings_path) = hook_paths(global)?;
+    let hook_dir = base_dir.join(".crux/hooks");
+    let hook_script_path = hook_dir.join("pre-tool-use.sh");
+    let backup_path = hook_dir.join("replaced.json");
+
+    // 1. Build the crux hook entry
+    let hook_script_abs = std::fs::canonicalize(&base_dir)
+        .map(|dir| dir.join(".crux/hooks/pre-tool-use.sh"))
+        .unwrap_or_else(|_| hook_script_path.clone());
+    let crux_matcher_entry = serde_json::json!({
+        "matcher": "Bash",
+        "hooks": [{
+            "type": "command",
+            "command": hook_script_abs.to_string_lossy()
+        }]
+    });
+
+    // 2. Add it to settings.json, replacing older crux or tokf entries
+    let mut settings = read_settings(&settings_path)?;
+    let replaced = add_hook_entry(&mut settings, crux_matcher_entry)?;
+    let json_str = serde_json::to_string_pretty(&settings)?;
+
+    if print {
+        println!("# {}", hook_script_path.display());
+        println!("{}", hook_script());
+        println!("# {}", settings_path.display());
+        println!("{json_str}");
+        return Ok(());
+    }
+
+    // 3. Create the hook shim script
+    std::fs::create_dir_all(&hook_dir)?;
+    std::fs::write(&hook_script_path, hook_script())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook_script_path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    // 4. Keep the entries crux replaced so `--uninstall` can put them back
+    if !replaced.is_empty() {
+        let mut backup = read_backup(&backup_path)?;
+        backup.extend(replaced);
+        std::fs::write(&backup_path, serde_json::to_string_pretty(&backup)?)?;
+    }
+
+    // 5. Write settings.json
+    if let Some(parent) = settings_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&settings_path, json_str)?;
+
+    let scope = if global { "global" } else { "local" };
+    println!("crux: created hook script: {}", hook_script_path.display());
+    println!(
+        "crux: installed Claude Code hook ({scope}): {}",
+        settings_path.display()
+    );
+    Ok(())
+}
+
+pub fn cmd_uninstall(global: bool, print: bool) -> Result<()> {
+    let (base_dir, settings_path) = hook_paths(global)?;
+    let hook_dir = base_dir.join(".crux/hooks");
+    let hook_script_path = hook_dir.join("pre-tool-use.sh");
+    let backup_path = hook_dir.join("replaced.json");
+
+    let mut settings = read_settings(&settings_path)?;
+    let restored = read_backup(&backup_path)?;
+    let restored_count = restored.len();
+    let removed = remove_hook_entries(&mut settings, restored)?;
+    let json_str = serde_json::to_string_pretty(&settings)?;
+
+    if print {
+        println!("# {}", settings_path.display());
+        println!("{json_str}");
+        return Ok(());
+    }
+
+    if removed > 0 || restored_count > 0 {
+        std::fs::write(&settings_path, json_str)?;
+    }
+    for path in [&hook_script_path, &backup_path] {
+        if path.exists() {
+            std::fs::remove_file(path).with_context(|| format!("removing {}", path.display()))?;
+        }
+    }
+    // Only removes the directories if nothing else lives there
+    let _ = std::fs::remove_dir(&hook_dir);
+    let _ = std::fs::remove_dir(base_dir.join(".crux"));
+
+    let scope = if global { "global" } else { "local" };
+    if removed == 0 {
+        println!("crux: no crux hook in {}", settings_path.display());
+    } else {
+        println!(
+            "crux: removed Claude Code hook ({scope}): {}",
+            settings_path.display()
+        );
+    }
+    if restored_count > 0 {
+        println!(
+            "crux: restored {restored_count} hook entr{} it had replaced",
+            if restored_count == 1 { "y" } else { "ies" }
+        );
+    }
+    Ok(())
+}
+
+fn read_backup(path: &Path) -> Result<Vec<serde_json::Value>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// The `hooks.PreToolUse` array of `settings`, created if missing.
+fn pre_tool_use(settings: &mut serde_json::Value) -> Result<&mut Vec<serde_json::Value>> {
+    let obj = settings
+        .as_object_mut()
+        .context("settings.json is not an object")?;
+    let hooks = obj.entry("hooks").or_insert_with(|| serde_json::json!({}));
+    let hooks_obj = hooks.as_object_mut().context("hooks is not an object")?;
+    hooks_obj
+        .entry("PreToolUse")
+        .or_insert_with(|| serde_json::json!([]))
+        .as_array_mut()
+        .context("PreToolUse is not an array")
+}
+
+/// Add `entry` to `hooks.PreToolUse`, removing existing tokf or crux
+/// entries (including old flat entries that put `command` on the matcher
+/// itself) to avoid duplicates. Returns what the removed entries held
+/// besides crux itself, for [`remove_hook_entries`] to restore.
+fn add_hook_entry(
+    settings: &mut serde_json::Value,
+    entry: serde_json::Value,
+) -> Result<Vec<serde_json::Value>> {
+    let arr = pre_tool_use(settings)?;
+    let mut replaced = Vec::new();
+    arr.retain(|existing| {
+        let is_crux = is_crux_command(existing.get("command"))
+            || existing
+                .get("hooks")
+                .and_then(|h| h.as_array())
+                .is_some_and(|hooks| hooks.iter().any(|h| is_crux_command(h.get("command"))));
+        if is_crux {
+            replaced.extend(strip_own_hooks(existing));
+        }
+        !is_crux
+    });
+    arr.push(entry);
+    Ok(replaced)
+}
+
+/// Remove crux's own hooks from `hooks.PreToolUse` and append `restore`.
+/// Empty `PreToolUse` / `hooks` objects are dropped. Returns how many
+/// hooks were removed.
+fn remove_hook_entries(
+    settings: &mut serde_json::Value,
+    restore: Vec<serde_json::Value>,
+) -> Result<usize> {
+    let arr = pre_tool_use(settings)?;
+    let before: usize = arr.iter().map(count_own_hooks).sum();
+    *arr = arr
+        .iter()
+        .filter_map(strip_own_hooks)
+        .chain(restore)
+        .collect();
+    let is_empty = arr.is_empty();
+
+    let hooks = settings
+        .get_mut("hooks")
+        .and_then(|h| h.as_object_mut())
+        .context("hooks is not an object")?;
+    if is_empty {
+        hooks.remove("PreToolUse");
+    }
+    if hooks.is_empty() {
+        if let Some(obj) = settings.as_object_mut() {
+            obj.remove("hooks");
+        }
+    }
+    Ok(before)
+}
+
+/// True for a hook `command` that runs crux itself (not tokf).
+fn is_own_command(command: Option<&serde_json::Value>) -> bool {
+    command
+        .and_then(|c| c.as_str())
+        .is_some_and(|c| c.contains("crux"))
+}
+
+fn count_own_hooks(entry: &serde_json::Value) -> usize {
+    if is_own_command(entry.get("command")) {
+        return 1;
+    }
+    entry
+        .get("hooks")
+        .and_then(|h| h.as_array())
+        .map_or(0, |hooks| {
+            hooks
+                .iter()
+                .filter(|h| is_own_command(h.get("command")))
+                .count()
+        })
+}
+
+/// `entry` without crux's own hooks, or `None` if nothing else is left.
+fn strip_own_hooks(entry: &serde_json::Value) -> Option<serde_json::Value> {
+    if is_own_command(entry.get("command")) {
+        return None;
+    }
+    let mut entry = entry.clone();
+    if let Some(hooks) = entry.get_mut("hooks").and_then(|h| h.as_array_mut()) {
+        hooks.retain(|h| !is_own_command(h.get("command")));
+        if hooks.is_empty() {
+            return None;
+        }
+    }
+    Some(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crux_entry() -> serde_json::Value {
+        serde_json::json!({
+            "matcher": "Bash",
+            "hooks": [{"type": "command", "command": "/p/.crux/hooks/pre-tool-use.sh"}]
+        })
+    }
+
+    #[test]
+    fn hook_script_is_stamped_with_its_version() {
+        assert_eq!(
+            hook_script_version(&hook_script()),
+            Some(crux_core::VERSION)
+        );
+        assert_eq!(
+            hook_script_version("#!/bin/sh\nexec crux hook handle\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn install_then_uninstall_restores_replaced_entries() {
+        let original = serde_json::json!({
+            "model": "opus",
+            "hooks": {"PreToolUse": [
+                {"matcher": "Bash", "hooks": [{"command": "tokf hook handle"}]},
+                {"matcher": "Edit", "hooks": [{"command": "fmt.sh"}]},
+            ]}
+        });
+        let mut settings = original.clone();
+
+        let replaced = add_hook_entry(&mut settings, crux_entry()).unwrap();
+        assert_eq!(
+            replaced,
+            vec![
+                serde_json::json!({"matcher": "Bash", "hooks": [{"command": "tokf hook handle"}]})
+            ]
+        );
+        let entries = settings["hooks"]["PreToolUse"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1], crux_entry());
+
+        // Re-installing replaces only crux itself: nothing new to restore
+        assert!(add_hook_entry(&mut settings, crux_entry())
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(remove_hook_entries(&mut settings, replaced).unwrap(), 1);
+        let mut restored = settings["hooks"]["PreToolUse"].as_array().unwrap().clone();
+        restored.sort_by_key(|e| e["matcher"].as_str().map(String::from));
+        assert_eq!(
+            restored,
+            original["hooks"]["PreToolUse"].as_array().unwrap().clone()
+        );
+        assert_eq!(settings["model"], "opus");
+    }
+
+    #[test]
+    fn uninstall_drops_empty_hook_tables() {
+        let mut settings = serde_json::json!({"model": "opus"});
+        add_hook_entry(&mut settings, crux_entry()).unwrap();
+        assert_eq!(remove_hook_entries(&mut settings, Vec::new()).unwrap(), 1);
+        assert_eq!(settings, serde_json::json!({"model": "opus"}));
+    }
+
+    #[test]
+    fn uninstall_keeps_other_hooks_sharing_the_matcher() {
+        let mut settings = serde_json::json!({"hooks": {"PreToolUse": [
+            {"matcher": "Bash", "command": "crux hook handle"},
+            {"matcher": "Bash", "hooks": [{"command": "crux hook handle"}, {"command": "audit.sh"}]},
+        ]}});
+        assert_eq!(remove_hook_entries(&mut settings, Vec::new()).unwrap(), 2);
+        assert_eq!(
+            settings,
+            serde_json::json!({"hooks": {"PreToolUse": [
+                {"matcher": "Bash", "hooks": [{"command": "audit.sh"}]}
+            ]}})
+        );
+    }
+}
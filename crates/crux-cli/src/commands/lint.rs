@@ -0,0 +1,40 @@
+//! `crux lint` — validate user filter TOMLs.
+
+use anyhow::Result;
+
+pub fn cmd_lint(json: bool) -> Result<()> {
+    use crux_core::config::lint::Severity;
+
+    let issues = crux_core::config::lint::lint_filters();
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&issues)?);
+    } else if issues.is_empty() {
+        println!("No problems found in user filters.");
+    } else {
+        for issue in &issues {
+            let label = match issue.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            match &issue.command {
+                Some(command) => println!(
+                    "{label}: {} ({command}): {}",
+                    issue.path.display(),
+                    issue.message
+                ),
+                None => println!("{label}: {}: {}", issue.path.display(), issue.message),
+            }
+        }
+        println!("\n{errors} error(s), {} warning(s)", issues.len() - errors);
+    }
+
+    if errors > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
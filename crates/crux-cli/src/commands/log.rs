@@ -0,0 +1,48 @@
+//! `crux log` — dedup and collapse repeated log lines.
+
+use anyhow::Result;
+
+/// `crux log` options. Without `window` or `min_repeat`, only consecutive
+/// duplicates are dropped, without counts.
+pub struct LogOptions {
+    pub window: Option<usize>,
+    pub min_repeat: Option<usize>,
+    pub strip_timestamps: bool,
+}
+
+pub fn cmd_log(command: &[String], options: &LogOptions) -> Result<()> {
+    let result = crux_core::runner::run_command(command)?;
+
+    let mut output = result.combined.clone();
+    if options.strip_timestamps {
+        output = crux_core::filter::cleanup::strip_timestamps(&output);
+    }
+    let windowed = options.window.is_some() || options.min_repeat.is_some();
+    if windowed {
+        output = crux_core::filter::dedup::apply_dedup_window(
+            &output,
+            options.window.unwrap_or(1),
+            options.min_repeat.unwrap_or(2),
+        );
+    }
+
+    let config = crux_core::config::FilterConfig {
+        command: command.join(" "),
+        builtin: Some(false),
+        dedup: Some(!windowed),
+        collapse_blank_lines: Some(true),
+        trim_trailing_whitespace: Some(true),
+        ..Default::default()
+    };
+
+    let filtered = crux_core::filter::apply_filter(&config, &output, result.exit_code);
+    print!("{filtered}");
+    if !filtered.ends_with('\n') && !filtered.is_empty() {
+        println!();
+    }
+
+    if result.exit_code != 0 {
+        eprintln!("crux: exit code {}", result.exit_code);
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! `crux ls` — list available filters.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::home_dir;
+
+pub fn cmd_ls(json: bool) -> Result<()> {
+    let entries = filter_entries();
+    let stdlib_configs = crux_core::config::count_filters();
+
+    if json {
+        let filters: Vec<_> = entries
+            .iter()
+            .map(|(source, command)| serde_json::json!({ "source": source, "command": command }))
+            .collect();
+        let value = serde_json::json!({ "filters": filters, "counts": stdlib_configs });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No filters found.");
+    } else {
+        for (source, command) in &entries {
+            println!("{source}: {command}");
+        }
+        println!();
+        println!(
+            "{} builtin filters, {} TOML stdlib filters, {} user filters",
+            stdlib_configs.builtin,
+            stdlib_configs.stdlib_toml,
+            stdlib_configs.user_local + stdlib_configs.user_global,
+        );
+    }
+    Ok(())
+}
+
+/// Every available filter as `(source, command)` pairs: builtins, local and
+/// global TOML filters, and the embedded stdlib.
+pub(crate) fn filter_entries() -> BTreeSet<(String, String)> {
+    filter_configs()
+        .into_iter()
+        .map(|(source, config)| (source.to_string(), config.command))
+        .collect()
+}
+
+/// Every available filter with its source label. Builtins without a TOML
+/// are represented by a stub at the builtin fallback priority.
+pub(crate) fn filter_configs() -> Vec<(&'static str, crux_core::config::FilterConfig)> {
+    let mut configs = Vec::new();
+
+    for key in crux_core::filter::builtin::registry().keys() {
+        configs.push((
+            "builtin",
+            crux_core::config::FilterConfig {
+                command: key.to_string(),
+                priority: crux_core::config::BUILTIN_FALLBACK_PRIORITY,
+                ..Default::default()
+            },
+        ));
+    }
+
+    scan_toml_dir(Path::new(".crux/filters"), "toml/local", &mut configs);
+    if let Some(home) = home_dir() {
+        scan_toml_dir(
+            &home.join(".config/crux/filters"),
+            "toml/global",
+            &mut configs,
+        );
+    }
+
+    for config in crux_core::config::stdlib_filters() {
+        configs.push(("toml/stdlib", config.clone()));
+    }
+    configs
+}
+
+fn scan_toml_dir(
+    dir: &Path,
+    label: &'static str,
+    configs: &mut Vec<(&'static str, crux_core::config::FilterConfig)>,
+) {
+    let Ok(rd) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in rd.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            scan_toml_dir(&path, label, configs);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+            if let Ok(contents) = std::fs::read_to_string(&path) {
+                if let Ok(config) = toml::from_str::<crux_core::config::FilterConfig>(&contents) {
+                    configs.push((label, config));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Subcommand implementations for crux CLI.
+
+mod doctor;
+mod eject;
+mod err;
+mod hook;
+mod init;
+mod lint;
+mod log;
+mod ls;
+mod new;
+mod search;
+mod show;
+mod test;
+mod verify;
+mod which;
+
+use std::path::PathBuf;
+
+pub use doctor::cmd_doctor;
+pub use eject::cmd_eject;
+pub use err::{cmd_err, ErrOptions};
+pub use hook::{
+    cmd_hook_compact, cmd_hook_handle, cmd_hook_remove, cmd_hook_status, HookFormat, HookTarget,
+};
+pub use init::{cmd_init, cmd_uninstall};
+pub use lint::cmd_lint;
+pub use log::{cmd_log, LogOptions};
+pub use ls::cmd_ls;
+pub(crate) use ls::filter_entries;
+pub use new::cmd_new;
+pub use search::cmd_search;
+pub use show::cmd_show;
+pub use test::cmd_test;
+pub use verify::cmd_verify;
+pub use which::{cmd_explain, cmd_which, cmd_which_all};
+
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var("USERPROFILE").ok().map(PathBuf::from)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        std::env::var("HOME").ok().map(PathBuf::from)
+    }
+}
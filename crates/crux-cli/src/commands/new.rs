@@ -0,0 +1,139 @@
+//! `crux new` — scaffold a custom filter.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::eject::{eject_slug, write_if_missing};
+
+/// Commented template of every stage. `##` lines are prose; `#` lines are
+/// settings to uncomment.
+const NEW_FILTER_TEMPLATE: &str = r#"## Filter for `{command}`. Uncomment what you need; stages run in the order
+## below (reorder with stage_order). Check it with `crux verify`.
+command = "{command_toml}"
+# description = "What this filter keeps"
+# exclude_args = ["--json"]
+
+## Line filtering: regexes matched against each line
+# skip = ["^\\s*$", "^\\s*Compiling "]
+# keep = ["error", "warning"]
+# keep_context = 2
+
+## Cleanup
+# strip_ansi = true
+# relativize_paths = true
+# json_paths = [".items[].name", ".status"]
+# dedup = true
+# collapse_repeats = true
+# trim_trailing_whitespace = true
+# collapse_blank_lines = true
+
+## Size caps
+# max_line_width = 200
+# max_lines = 100
+# head = 20
+# tail = 20
+# max_tokens = 2000
+# max_bytes = 16384
+# min_savings_pct = 10
+
+## Keep the full raw output in .crux/raw/ ("failures" or "always")
+# tee = "failures"
+
+## Short-circuit: replace all output when it contains a marker
+# [[match_output]]
+# contains = "Already up to date"
+# template = "up to date"
+
+## Rewrite lines ({1} or $1 refer to groups)
+# [[replace]]
+# pattern = "^/home/[^/]+/"
+# replacement = "~/"
+
+## Keep only some columns of a table
+# [table]
+# keep_columns = ["NAME", "STATUS"]
+
+## Collect a section for the template
+# [[section]]
+# name = "failures"
+# start = "^failures:"
+# end = "^test result:"
+
+## Summarize with the first match
+# [[extract]]
+# pattern = "(\\d+) passed; (\\d+) failed"
+# template = "{1} passed, {2} failed"
+
+## Extra rules when the command fails
+# [on_failure]
+# tail = 50
+"#;
+
+pub fn cmd_new(command: &str, dir: &Path, force: bool) -> Result<()> {
+    let command = command.split_whitespace().collect::<Vec<_>>().join(" ");
+    if command.is_empty() {
+        anyhow::bail!("name the command to filter, e.g. crux new \"terraform plan\"");
+    }
+    let slug = eject_slug(&command);
+    let toml_path = dir.join(format!("{slug}.toml"));
+    if toml_path.exists() && !force {
+        anyhow::bail!(
+            "{} already exists (use --force to overwrite)",
+            toml_path.display()
+        );
+    }
+
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    std::fs::write(&toml_path, new_filter_toml(&command))
+        .with_context(|| format!("writing {}", toml_path.display()))?;
+
+    let test_dir = dir.join(format!("{slug}_test"));
+    std::fs::create_dir_all(&test_dir)
+        .with_context(|| format!("creating {}", test_dir.display()))?;
+    write_if_missing(&test_dir.join("input.txt"), "")?;
+    write_if_missing(&test_dir.join("expected.txt"), "")?;
+
+    println!("Created {}", toml_path.display());
+    println!("Created {}/{{input.txt,expected.txt}}", test_dir.display());
+    println!();
+    println!("Next:");
+    println!("  {command} > {}/input.txt 2>&1", test_dir.display());
+    println!("  edit {} and the expected output", toml_path.display());
+    println!("  crux verify");
+    Ok(())
+}
+
+fn new_filter_toml(command: &str) -> String {
+    let escaped = command.replace('\\', "\\\\").replace('"', "\\\"");
+    NEW_FILTER_TEMPLATE
+        .replace("{command_toml}", &escaped)
+        .replace("{command}", command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_parses_as_is() {
+        let config: crux_core::config::FilterConfig =
+            toml::from_str(&new_filter_toml("terraform \"plan\"")).unwrap();
+        assert_eq!(config.command, "terraform \"plan\"");
+        assert!(config.skip.is_empty());
+    }
+
+    #[test]
+    fn template_parses_with_every_setting_uncommented() {
+        let uncommented: String = new_filter_toml("make")
+            .lines()
+            .filter(|line| !line.starts_with("##"))
+            .map(|line| line.strip_prefix("# ").unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config: crux_core::config::FilterConfig = toml::from_str(&uncommented).unwrap();
+        assert_eq!(config.keep_context, Some(2));
+        assert_eq!(config.extract.len(), 1);
+        assert_eq!(config.on_failure.and_then(|b| b.tail), Some(50));
+    }
+}
@@ -0,0 +1,118 @@
+//! `crux search` — find filters by keyword.
+
+use anyhow::Result;
+
+use super::ls::filter_configs;
+
+pub fn cmd_search(term: &str, json: bool) -> Result<()> {
+    let mut hits: Vec<(u8, &'static str, crux_core::config::FilterConfig)> = filter_configs()
+        .into_iter()
+        .filter_map(|(source, config)| {
+            search_rank(term, &config).map(|rank| (rank, source, config))
+        })
+        .collect();
+    hits.sort_by(|a, b| (a.0, &a.2.command, a.1).cmp(&(b.0, &b.2.command, b.1)));
+
+    if json {
+        let results: Vec<_> = hits
+            .iter()
+            .map(|(_, source, config)| {
+                serde_json::json!({
+                    "source": source,
+                    "command": config.command,
+                    "priority": config.priority,
+                    "description": config.description,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
+
+    if hits.is_empty() {
+        println!("No filters match '{term}'.");
+        return Ok(());
+    }
+    let width = hits
+        .iter()
+        .map(|(_, _, config)| config.command.len())
+        .max()
+        .unwrap_or(0);
+    for (_, source, config) in &hits {
+        let line = format!(
+            "{source:<12} {:>5}  {:<width$}  {}",
+            config.priority,
+            config.command,
+            config.description.as_deref().unwrap_or("")
+        );
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+/// How well `config` matches `term` (lower is better), or `None`: every
+/// word of the term must appear in the command or description, ignoring case.
+/// Exact command matches come first, then command matches, then the rest.
+fn search_rank(term: &str, config: &crux_core::config::FilterConfig) -> Option<u8> {
+    let term = term.to_lowercase();
+    let command = config.command.to_lowercase();
+    let description = config.description.as_deref().unwrap_or("").to_lowercase();
+    let words: Vec<&str> = term.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+    if !words
+        .iter()
+        .all(|w| command.contains(w) || description.contains(w))
+    {
+        return None;
+    }
+    Some(if command == words.join(" ") {
+        0
+    } else if words.iter().all(|w| command.contains(w)) {
+        1
+    } else {
+        2
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crux_core::config::FilterConfig;
+    use std::collections::BTreeSet;
+
+    fn filter(command: &str, description: &str) -> FilterConfig {
+        FilterConfig {
+            command: command.to_string(),
+            description: Some(description.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ranks_exact_then_command_then_description() {
+        let term = "Cargo Test";
+        assert_eq!(search_rank(term, &filter("cargo test", "")), Some(0));
+        assert_eq!(search_rank(term, &filter("cargo nextest run", "")), Some(1));
+        assert_eq!(
+            search_rank(term, &filter("nextest", "Cargo test runner")),
+            Some(2)
+        );
+        assert_eq!(search_rank(term, &filter("cargo build", "")), None);
+        assert_eq!(search_rank("  ", &filter("cargo build", "")), None);
+    }
+
+    #[test]
+    fn searches_builtins_and_stdlib() {
+        let sources = |term: &str| -> BTreeSet<&str> {
+            filter_configs()
+                .into_iter()
+                .filter(|(_, config)| search_rank(term, config).is_some())
+                .map(|(source, _)| source)
+                .collect()
+        };
+        assert!(sources("git status").contains("builtin"));
+        assert!(sources("entering directory").contains("toml/stdlib"));
+    }
+}
@@ -0,0 +1,160 @@
+//! `crux show` — display filter details.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::home_dir;
+
+pub fn cmd_show(filter: &str, json: bool, as_toml: bool, tests: bool) -> Result<()> {
+    let tokens: Vec<String> = filter.split_whitespace().map(String::from).collect();
+    let config = crux_core::config::resolve_filter(&tokens).with_context(|| {
+        format!("no filter matches '{filter}'. Run `crux ls` to see all available filters")
+    })?;
+    let builtin = crux_core::filter::builtin::registry().contains_key(config.command.as_str());
+
+    if as_toml {
+        return show_toml(config, &tokens, builtin);
+    }
+    if tests {
+        return show_tests(&config, json);
+    }
+
+    if json {
+        let mut value = serde_json::to_value(&config)?;
+        if let Some(object) = value.as_object_mut() {
+            object.insert("builtin".to_string(), builtin.into());
+        }
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("Command:     {}", config.command);
+    if let Some(desc) = &config.description {
+        println!("Description: {desc}");
+    }
+    println!("Priority:    {}", config.priority);
+    println!("Builtin:     {builtin}");
+    if let Some(base) = &config.extends {
+        println!("Extends:     {base}");
+    }
+    if !config.pipeline.is_empty() {
+        println!("Pipeline:    {}", config.pipeline.join(" → "));
+    }
+    if !config.skip.is_empty() {
+        println!("Skip:        {:?}", config.skip);
+    }
+    if !config.keep.is_empty() {
+        println!("Keep:        {:?}", config.keep);
+    }
+    if !config.replace.is_empty() {
+        println!("Replace rules: {}", config.replace.len());
+        for r in &config.replace {
+            println!("  /{}/  →  {}", r.pattern, r.replacement);
+        }
+    }
+    if !config.section.is_empty() {
+        println!("Section rules: {}", config.section.len());
+    }
+    if !config.extract.is_empty() {
+        println!("Extract rules: {}", config.extract.len());
+    }
+    if !config.json_paths.is_empty() {
+        println!("JSON paths:  {:?}", config.json_paths);
+    }
+    if config.dedup == Some(true) {
+        println!("Dedup:       true");
+    }
+    if config.strip_ansi == Some(true) {
+        println!("Strip ANSI:  true");
+    }
+    if config.collapse_blank_lines == Some(true) {
+        println!("Collapse blanks: true");
+    }
+    if config.trim_trailing_whitespace == Some(true) {
+        println!("Trim trailing: true");
+    }
+    if let Some(max) = config.max_lines {
+        println!("Max lines:   {max}");
+    }
+    if config.head.is_some() || config.tail.is_some() {
+        println!(
+            "Head/tail:   {}/{}",
+            config.head.unwrap_or(0),
+            config.tail.unwrap_or(0)
+        );
+    }
+    if let Some(max) = config.max_tokens {
+        println!("Max tokens:  {max}");
+    }
+    Ok(())
+}
+
+/// Print the filter as it applies: after `extends` and config-file
+/// overrides, with `builtin` spelled out.
+fn show_toml(
+    mut config: crux_core::config::FilterConfig,
+    tokens: &[String],
+    builtin: bool,
+) -> Result<()> {
+    if config.builtin.is_none() {
+        config.builtin = Some(builtin);
+    }
+    let source = crux_core::config::match_candidates(tokens)
+        .into_iter()
+        .find(|c| c.winner)
+        .map(|c| c.source.label());
+    let toml =
+        toml::to_string_pretty(&config).context("failed to serialize filter config to TOML")?;
+    match source {
+        Some(source) => println!("# Effective filter for: {} ({source})", config.command),
+        None => println!("# Effective filter for: {}", config.command),
+    }
+    print!("{toml}");
+    Ok(())
+}
+
+/// List the filter's test cases (stdlib, local and global `_test/` dirs)
+/// with their pass/fail status, running them as `crux verify` does.
+fn show_tests(config: &crux_core::config::FilterConfig, json: bool) -> Result<()> {
+    let mut cases = crux_core::verify::embedded_test_cases();
+    cases.extend(crux_core::verify::test_cases_in_dir(Path::new(
+        ".crux/filters",
+    )));
+    if let Some(home) = home_dir() {
+        cases.extend(crux_core::verify::test_cases_in_dir(
+            &home.join(".config/crux/filters"),
+        ));
+    }
+
+    let results: Vec<(String, bool)> = cases
+        .into_iter()
+        .filter(|case| case.config.command == config.command)
+        .map(|case| {
+            let actual = crux_core::filter::apply_filter(&case.config, &case.input, 0);
+            (case.name, actual.trim() == case.expected.trim())
+        })
+        .collect();
+
+    if json {
+        let value: Vec<_> = results
+            .iter()
+            .map(|(name, passed)| serde_json::json!({ "name": name, "passed": passed }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+    if results.is_empty() {
+        println!(
+            "No test cases for {}. Add some with `crux eject \"{}\" --out .crux/filters`.",
+            config.command, config.command
+        );
+        return Ok(());
+    }
+    for (name, passed) in &results {
+        println!("  {}  {name}", if *passed { "PASS" } else { "FAIL" });
+    }
+    let passed = results.iter().filter(|(_, passed)| *passed).count();
+    println!("\n{passed}/{} tests passed", results.len());
+    Ok(())
+}
@@ -0,0 +1,555 @@
+//! `crux test` — test summary filter.
+
+use anyhow::{Context, Result};
+
+/// Every framework `crux test` knows, in detection order.
+const FRAMEWORKS: &[&str] = &[
+    "cargo test",
+    "pytest",
+    "go test",
+    "jest",
+    "vitest",
+    "mocha",
+    "playwright",
+    "rspec",
+    "phpunit",
+    "dotnet test",
+    "npm test",
+];
+
+/// Look up a `--framework` name; the first word alone (`cargo`, `go`,
+/// `dotnet`) also works.
+fn parse_framework(name: &str) -> Result<&'static str> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    FRAMEWORKS
+        .iter()
+        .copied()
+        .find(|f| *f == name || f.split(' ').next() == Some(name.as_str()))
+        .with_context(|| {
+            format!("unknown framework '{name}'. Run `crux test --list-frameworks` to see them all")
+        })
+}
+
+/// Detect which test framework produced the given output.
+/// Returns `None` when no framework signature is recognized.
+fn detect_framework(output: &str) -> Option<&'static str> {
+    // cargo test: require "test result:" with ok/FAILED, or "running N test"
+    if output.contains("test result: ok")
+        || output.contains("test result: FAILED")
+        || (output.contains("running") && output.contains("test"))
+    {
+        return Some("cargo test");
+    }
+
+    // pytest: require `=====` separator AND one of the key result words
+    if output.contains("=====")
+        && (output.contains("passed")
+            || output.contains("failed")
+            || output.contains("error")
+            || output.contains("warnings summary"))
+    {
+        return Some("pytest");
+    }
+
+    // go test: "--- PASS" or "--- FAIL" (go-specific format)
+    if output.contains("--- PASS") || output.contains("--- FAIL") {
+        return Some("go test");
+    }
+
+    // jest: "Test Suites:" is jest-specific
+    if output.contains("Test Suites:") {
+        return Some("jest");
+    }
+    // jest per-file lines (PASS /FAIL at start of line) with summary
+    let has_per_file = output
+        .lines()
+        .any(|l| l.trim_start().starts_with("PASS ") || l.trim_start().starts_with("FAIL "));
+    if has_per_file && (output.contains("Tests:") || output.contains("Time:")) {
+        return Some("jest");
+    }
+
+    // vitest: "Tests  N" (two spaces) with "Duration "
+    if output.contains("Duration ") && output.contains("Tests ") {
+        return Some("vitest");
+    }
+    if has_per_file && output.lines().any(|l| l.trim().starts_with("Tests ")) {
+        return Some("vitest");
+    }
+
+    // mocha: "N passing" with timing like "(123ms)" or "(2s)"
+    let mocha_re = regex::Regex::new(r"\d+\s+passing\s+\(\d+\w*s?\)").unwrap();
+    if mocha_re.is_match(output) {
+        return Some("mocha");
+    }
+
+    // playwright: two or more lines matching "N passed/failed/skipped"
+    let pw_re = regex::Regex::new(r"\d+\s+(passed|failed|skipped)").unwrap();
+    let pw_hits = output.lines().filter(|l| pw_re.is_match(l)).count();
+    if pw_hits >= 2 {
+        return Some("playwright");
+    }
+
+    // rspec: "N example(s), N failure(s)"
+    let rspec_re = regex::Regex::new(r"\d+\s+examples?,\s+\d+\s+failures?").unwrap();
+    if rspec_re.is_match(output) {
+        return Some("rspec");
+    }
+
+    // PHPUnit: "OK (N tests, N assertions)" or "FAILURES!" with test counts
+    let phpunit_ok_re = regex::Regex::new(r"OK\s+\(\d+\s+tests?,\s+\d+\s+assertions?\)").unwrap();
+    if phpunit_ok_re.is_match(output) {
+        return Some("phpunit");
+    }
+    if output.contains("FAILURES!") {
+        let phpunit_summary_re = regex::Regex::new(r"Tests:\s+\d+.*Assertions:\s+\d+").unwrap();
+        if phpunit_summary_re.is_match(output) {
+            return Some("phpunit");
+        }
+    }
+
+    // dotnet test: "Passed!" or "Failed!" with "Total tests:"
+    if output.contains("Total tests:") && (output.contains("Passed!") || output.contains("Failed!"))
+    {
+        return Some("dotnet test");
+    }
+
+    // npm test: very low priority — only literal "npm test" string
+    if output.contains("npm test") {
+        return Some("npm test");
+    }
+
+    None
+}
+
+/// Extract lines containing test-related keywords (case-insensitive).
+/// Falls back to last 10 lines when nothing matches.
+fn fallback_extract(output: &str) -> String {
+    let keyword_re = regex::Regex::new(r"(?i)(pass|fail|error|warning)").unwrap();
+    let relevant: Vec<&str> = output
+        .lines()
+        .filter(|line| keyword_re.is_match(line))
+        .collect();
+
+    if relevant.is_empty() {
+        let lines: Vec<&str> = output.lines().collect();
+        let start = lines.len().saturating_sub(10);
+        lines[start..].join("\n")
+    } else {
+        relevant.join("\n")
+    }
+}
+
+// -- generic filters for frameworks without a dedicated builtin handler ------
+
+fn generic_framework_filter(output: &str, exit_code: i32, framework: &str) -> String {
+    match framework {
+        "mocha" => filter_mocha(output, exit_code),
+        "playwright" => filter_playwright(output, exit_code),
+        "rspec" => filter_rspec(output, exit_code),
+        "phpunit" => filter_phpunit(output, exit_code),
+        "dotnet test" => filter_dotnet_test(output, exit_code),
+        _ => fallback_extract(output),
+    }
+}
+
+fn filter_mocha(output: &str, exit_code: i32) -> String {
+    let passing_re = regex::Regex::new(r"^\s*\d+\s+passing").unwrap();
+    let failing_re = regex::Regex::new(r"^\s*\d+\s+failing").unwrap();
+    let pending_re = regex::Regex::new(r"^\s*\d+\s+pending").unwrap();
+    let error_re =
+        regex::Regex::new(r"(?i)(AssertionError|AssertError|Error:|expected|actual)").unwrap();
+
+    let mut summary = Vec::new();
+    let mut failures = Vec::new();
+
+    for line in output.lines() {
+        let t = line.trim();
+        if passing_re.is_match(t) || failing_re.is_match(t) || pending_re.is_match(t) {
+            summary.push(t.to_string());
+        } else if exit_code != 0 && error_re.is_match(t) {
+            failures.push(format!("  {t}"));
+        }
+    }
+
+    build_test_output(&summary, &failures, exit_code)
+}
+
+fn filter_playwright(output: &str, exit_code: i32) -> String {
+    let count_re = regex::Regex::new(r"^\s*\d+\s+(passed|failed|skipped|flaky)").unwrap();
+    let numbered_re = regex::Regex::new(r"^\s*\d+\)").unwrap();
+
+    let mut summary = Vec::new();
+    let mut failures = Vec::new();
+    let mut in_error = false;
+
+    for line in output.lines() {
+        let t = line.trim();
+        if count_re.is_match(t) {
+            summary.push(t.to_string());
+            in_error = false;
+        } else if numbered_re.is_match(t) {
+            in_error = true;
+            failures.push(t.to_string());
+        } else if in_error
+            && !t.is_empty()
+            && (t.contains("Error:")
+                || t.contains("expect(")
+                || t.contains("Received")
+                || t.contains("Expected"))
+        {
+            failures.push(format!("  {t}"));
+        }
+    }
+
+    build_test_output(&summary, &failures, exit_code)
+}
+
+fn filter_rspec(output: &str, exit_code: i32) -> String {
+    let summary_re = regex::Regex::new(r"\d+\s+examples?,\s+\d+\s+failures?").unwrap();
+    let failure_re = regex::Regex::new(r"^\s*\d+\)\s+").unwrap();
+
+    let mut summary = Vec::new();
+    let mut failures = Vec::new();
+
+    for line in output.lines() {
+        let t = line.trim();
+        if summary_re.is_match(t) {
+            summary.push(t.to_string());
+        } else if exit_code != 0 && failure_re.is_match(t) {
+            failures.push(format!("  {t}"));
+        }
+    }
+
+    build_test_output(&summary, &failures, exit_code)
+}
+
+fn filter_phpunit(output: &str, exit_code: i32) -> String {
+    let ok_re = regex::Regex::new(r"OK\s+\(\d+\s+tests?,\s+\d+\s+assertions?\)").unwrap();
+    let counts_re = regex::Regex::new(r"Tests:\s+\d+.*Assertions:\s+\d+").unwrap();
+    let numbered_re = regex::Regex::new(r"^\s*\d+\)\s+").unwrap();
+
+    let mut summary = Vec::new();
+    let mut failures = Vec::new();
+
+    for line in output.lines() {
+        let t = line.trim();
+        if ok_re.is_match(t) || counts_re.is_match(t) || t == "FAILURES!" {
+            summary.push(t.to_string());
+        } else if exit_code != 0 && numbered_re.is_match(t) {
+            failures.push(format!("  {t}"));
+        }
+    }
+
+    build_test_output(&summary, &failures, exit_code)
+}
+
+fn filter_dotnet_test(output: &str, exit_code: i32) -> String {
+    let total_re = regex::Regex::new(r"Total tests:\s+\d+").unwrap();
+    let failed_detail_re = regex::Regex::new(r"(?i)^\s*Failed\s+\w").unwrap();
+
+    let mut summary = Vec::new();
+    let mut failures = Vec::new();
+
+    for line in output.lines() {
+        let t = line.trim();
+        if t.starts_with("Passed!") || t.starts_with("Failed!") || total_re.is_match(t) {
+            summary.push(t.to_string());
+        } else if exit_code != 0 && failed_detail_re.is_match(t) {
+            failures.push(format!("  {t}"));
+        }
+    }
+
+    build_test_output(&summary, &failures, exit_code)
+}
+
+/// Shared helper: compose "Failures:" block + summary lines.
+fn build_test_output(summary: &[String], failures: &[String], exit_code: i32) -> String {
+    let mut parts = Vec::new();
+
+    if exit_code != 0 && !failures.is_empty() {
+        parts.push("Failures:".to_string());
+        parts.extend(failures.iter().cloned());
+        parts.push(String::new());
+    }
+
+    if !summary.is_empty() {
+        parts.extend(summary.iter().cloned());
+    } else if exit_code == 0 {
+        parts.push("All tests passed.".to_string());
+    } else {
+        parts.push(format!("Tests failed (exit code {exit_code})."));
+    }
+
+    parts.join("\n")
+}
+
+pub fn cmd_test(command: &[String], framework: Option<&str>, list_frameworks: bool) -> Result<()> {
+    let registry = crux_core::filter::builtin::registry();
+    if list_frameworks {
+        for framework in FRAMEWORKS {
+            let kind = if registry.contains_key(framework) {
+                "builtin handler"
+            } else {
+                "generic summary"
+            };
+            println!("{framework:<12} {kind}");
+        }
+        return Ok(());
+    }
+
+    let forced = framework.map(parse_framework).transpose()?;
+    let result = crux_core::runner::run_command(command)?;
+    let output = &result.combined;
+
+    if let Some(framework) = forced.or_else(|| detect_framework(output)) {
+        // Try the builtin handler first
+        if let Some(handler) = registry.get(framework) {
+            let filtered = handler(output, result.exit_code);
+            print!("{filtered}");
+            if !filtered.ends_with('\n') && !filtered.is_empty() {
+                println!();
+            }
+            return Ok(());
+        }
+
+        // No builtin handler — use generic framework filter
+        let filtered = generic_framework_filter(output, result.exit_code, framework);
+        print!("{filtered}");
+        if !filtered.ends_with('\n') && !filtered.is_empty() {
+            println!();
+        }
+        return Ok(());
+    }
+
+    // No framework detected — smart fallback
+    let filtered = fallback_extract(output);
+    println!("{filtered}");
+
+    if result.exit_code != 0 {
+        eprintln!("crux: exit code {}", result.exit_code);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // -- --framework --
+
+    #[test]
+    fn parse_framework_accepts_full_and_short_names() {
+        assert_eq!(parse_framework("pytest").unwrap(), "pytest");
+        assert_eq!(parse_framework(" dotnet  test ").unwrap(), "dotnet test");
+        assert_eq!(parse_framework("cargo").unwrap(), "cargo test");
+        let err = parse_framework("nose").unwrap_err();
+        assert!(err.to_string().contains("--list-frameworks"));
+    }
+
+    // -- cargo test --
+
+    #[test]
+    fn detect_cargo_test_ok() {
+        let output = "running 5 tests\ntest foo ... ok\ntest result: ok. 5 passed; 0 failed;";
+        assert_eq!(detect_framework(output), Some("cargo test"));
+    }
+
+    #[test]
+    fn detect_cargo_test_failed() {
+        let output =
+            "running 3 tests\ntest bar ... FAILED\ntest result: FAILED. 1 passed; 2 failed;";
+        assert_eq!(detect_framework(output), Some("cargo test"));
+    }
+
+    // -- pytest --
+
+    #[test]
+    fn detect_pytest_passed() {
+        let output = "============================= test session starts ========\n\
+                       ============================== 5 passed in 0.12s ========";
+        assert_eq!(detect_framework(output), Some("pytest"));
+    }
+
+    #[test]
+    fn detect_pytest_failed() {
+        let output = "============================= test session starts ========\n\
+                       =============== 1 failed, 2 passed in 0.15s =============";
+        assert_eq!(detect_framework(output), Some("pytest"));
+    }
+
+    #[test]
+    fn detect_pytest_warnings_summary() {
+        let output = "============================= warnings summary ============\n\
+                       ============================== 3 passed in 0.10s ========";
+        assert_eq!(detect_framework(output), Some("pytest"));
+    }
+
+    #[test]
+    fn no_false_positive_pytest() {
+        // "passed" + "==" without "=====" should NOT match
+        let output = "Build passed\nresult == expected\nDone.";
+        assert_ne!(detect_framework(output), Some("pytest"));
+    }
+
+    // -- go test --
+
+    #[test]
+    fn detect_go_test() {
+        let output = "=== RUN TestAdd\n--- PASS: TestAdd (0.00s)\nok example.com/math 0.003s";
+        assert_eq!(detect_framework(output), Some("go test"));
+    }
+
+    // -- jest --
+
+    #[test]
+    fn detect_jest_suites() {
+        let output = "Test Suites:  1 passed, 1 total\nTests:  2 passed\nTime:  0.9 s";
+        assert_eq!(detect_framework(output), Some("jest"));
+    }
+
+    #[test]
+    fn detect_jest_per_file_pass_fail() {
+        let output = "PASS src/a.test.js\nFAIL src/b.test.js\nTests: 2 total\nTime: 1s";
+        assert_eq!(detect_framework(output), Some("jest"));
+    }
+
+    // -- vitest --
+
+    #[test]
+    fn detect_vitest() {
+        let output = " PASS  src/utils.test.ts\n Tests  6 passed (6)\n Duration  1.23s";
+        assert_eq!(detect_framework(output), Some("vitest"));
+    }
+
+    // -- mocha --
+
+    #[test]
+    fn detect_mocha() {
+        let output = "  3 passing (45ms)\n  1 failing";
+        assert_eq!(detect_framework(output), Some("mocha"));
+    }
+
+    #[test]
+    fn detect_mocha_seconds() {
+        let output = "  12 passing (2s)";
+        assert_eq!(detect_framework(output), Some("mocha"));
+    }
+
+    // -- playwright --
+
+    #[test]
+    fn detect_playwright() {
+        let output = "Running 5 tests\n\n  5 passed (3s)\n  0 failed\n  1 skipped";
+        assert_eq!(detect_framework(output), Some("playwright"));
+    }
+
+    // -- rspec --
+
+    #[test]
+    fn detect_rspec() {
+        let output = "Finished in 0.5 seconds\n3 examples, 0 failures";
+        assert_eq!(detect_framework(output), Some("rspec"));
+    }
+
+    #[test]
+    fn detect_rspec_with_failures() {
+        let output = "Finished in 1.2 seconds\n5 examples, 2 failures";
+        assert_eq!(detect_framework(output), Some("rspec"));
+    }
+
+    // -- PHPUnit --
+
+    #[test]
+    fn detect_phpunit_ok() {
+        let output = "PHPUnit 10.0.0\n...\nOK (5 tests, 10 assertions)";
+        assert_eq!(detect_framework(output), Some("phpunit"));
+    }
+
+    #[test]
+    fn detect_phpunit_failures() {
+        let output = "PHPUnit 10.0.0\nFAILURES!\nTests: 5, Assertions: 10, Failures: 2";
+        assert_eq!(detect_framework(output), Some("phpunit"));
+    }
+
+    // -- dotnet test --
+
+    #[test]
+    fn detect_dotnet_test_passed() {
+        let output = "Passed! - Failed: 0, Passed: 5\nTotal tests: 5";
+        assert_eq!(detect_framework(output), Some("dotnet test"));
+    }
+
+    #[test]
+    fn detect_dotnet_test_failed() {
+        let output = "Failed! - Failed: 2, Passed: 3\nTotal tests: 5";
+        assert_eq!(detect_framework(output), Some("dotnet test"));
+    }
+
+    // -- no match --
+
+    #[test]
+    fn detect_none_for_generic_output() {
+        let output = "Hello world\nSome output\nDone.";
+        assert_eq!(detect_framework(output), None);
+    }
+
+    // -- fallback --
+
+    #[test]
+    fn fallback_extracts_keyword_lines() {
+        let output = "line1\nAll tests passed ok\nline3\nERROR: something\nline5";
+        let result = fallback_extract(output);
+        assert!(result.contains("passed"));
+        assert!(result.contains("ERROR"));
+        assert!(!result.contains("line1"));
+        assert!(!result.contains("line5"));
+    }
+
+    #[test]
+    fn fallback_last_10_when_no_keywords() {
+        let output = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl";
+        let result = fallback_extract(output);
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(*lines.last().unwrap(), "l");
+    }
+
+    // -- generic framework filter outputs --
+
+    #[test]
+    fn mocha_filter_passing() {
+        let output = "  suite\n    ok test one\n    ok test two\n\n  2 passing (34ms)";
+        let result = filter_mocha(output, 0);
+        assert!(result.contains("2 passing"));
+    }
+
+    #[test]
+    fn playwright_filter_summary() {
+        let output = "Running 3 tests\n\n  3 passed (1.5s)\n  0 failed\n  0 skipped";
+        let result = filter_playwright(output, 0);
+        assert!(result.contains("3 passed"));
+        assert!(result.contains("0 failed"));
+    }
+
+    #[test]
+    fn rspec_filter_summary() {
+        let output = "....\n\nFinished in 0.5 seconds\n4 examples, 0 failures";
+        let result = filter_rspec(output, 0);
+        assert!(result.contains("4 examples, 0 failures"));
+    }
+
+    #[test]
+    fn phpunit_filter_ok() {
+        let output = "PHPUnit 10.0\n.....\n\nOK (5 tests, 10 assertions)";
+        let result = filter_phpunit(output, 0);
+        assert!(result.contains("OK (5 tests, 10 assertions)"));
+    }
+
+    #[test]
+    fn dotnet_filter_passed() {
+        let output = "Passed! - Failed: 0, Passed: 5\nTotal tests: 5";
+        let result = filter_dotnet_test(output, 0);
+        assert!(result.contains("Passed!"));
+        assert!(result.contains("Total tests: 5"));
+    }
+}
@@ -0,0 +1,138 @@
+//! `crux verify` — run declarative filter tests.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::home_dir;
+
+pub fn cmd_verify() -> Result<()> {
+    let mut total = 0;
+    let mut passed = 0;
+
+    // 1. Embedded stdlib test suites (compiled into the binary)
+    let embedded = crux_core::verify::verify_embedded_stdlib();
+    for tr in &embedded.results {
+        total += 1;
+        if tr.passed {
+            passed += 1;
+            println!("  PASS  {}", tr.name);
+        } else {
+            println!("  FAIL  {}", tr.name);
+            print_diff(&tr.expected, &tr.actual);
+        }
+    }
+
+    // 2. Filesystem test suites (local + global)
+    verify_dir(Path::new(".crux/filters"), &mut total, &mut passed)?;
+    if let Some(home) = home_dir() {
+        verify_dir(&home.join(".config/crux/filters"), &mut total, &mut passed)?;
+    }
+
+    if total == 0 {
+        println!("No test cases found. Add _test/ directories next to filter TOMLs.");
+        println!("Each _test/ dir should contain input.txt/expected.txt or <name>.input/<name>.expected pairs.");
+    } else {
+        println!("\n{passed}/{total} tests passed");
+        if passed < total {
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+/// Print a unified-style diff between expected and actual output.
+fn print_diff(expected: &str, actual: &str) {
+    let expected_lines: Vec<&str> = expected.trim().lines().collect();
+    let actual_lines: Vec<&str> = actual.trim().lines().collect();
+    let max_lines = expected_lines.len().max(actual_lines.len());
+    for i in 0..max_lines {
+        let exp = expected_lines.get(i).unwrap_or(&"");
+        let act = actual_lines.get(i).unwrap_or(&"");
+        if exp != act {
+            println!("    - {exp}");
+            println!("    + {act}");
+        }
+    }
+}
+
+fn verify_dir(dir: &Path, total: &mut usize, passed: &mut usize) -> Result<()> {
+    let Ok(rd) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in rd.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if name.ends_with("_test") {
+                let base_name = name.strip_suffix("_test").unwrap_or(name);
+                let toml_path = dir.join(format!("{base_name}.toml"));
+                if toml_path.exists() {
+                    run_test_suite(&toml_path, &path, total, passed)?;
+                }
+            } else {
+                verify_dir(&path, total, passed)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_test_suite(
+    toml_path: &Path,
+    test_dir: &Path,
+    total: &mut usize,
+    passed: &mut usize,
+) -> Result<()> {
+    let contents = std::fs::read_to_string(toml_path)?;
+    let mut config: crux_core::config::FilterConfig = toml::from_str(&contents)?;
+    crux_core::config::env::interpolate_config(&mut config);
+
+    // Check for input.txt / expected.txt pair (single test case)
+    let input_txt = test_dir.join("input.txt");
+    let expected_txt = test_dir.join("expected.txt");
+    if input_txt.exists() && expected_txt.exists() {
+        *total += 1;
+        let input = std::fs::read_to_string(&input_txt)?;
+        let expected = std::fs::read_to_string(&expected_txt)?;
+        let actual = crux_core::filter::apply_filter(&config, &input, 0);
+
+        let test_name = format!("{}::default", config.command);
+        if actual.trim() == expected.trim() {
+            *passed += 1;
+            println!("  PASS  {test_name}");
+        } else {
+            println!("  FAIL  {test_name}");
+            print_diff(&expected, &actual);
+        }
+    }
+
+    // Check for <name>.input / <name>.expected pairs
+    let Ok(rd) = std::fs::read_dir(test_dir) else {
+        return Ok(());
+    };
+    for entry in rd.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("input") {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            let expected_path = test_dir.join(format!("{stem}.expected"));
+            if !expected_path.exists() {
+                continue;
+            }
+            *total += 1;
+            let input = std::fs::read_to_string(&path)?;
+            let expected = std::fs::read_to_string(&expected_path)?;
+            let actual = crux_core::filter::apply_filter(&config, &input, 0);
+
+            let test_name = format!("{}::{stem}", config.command);
+            if actual.trim() == expected.trim() {
+                *passed += 1;
+                println!("  PASS  {test_name}");
+            } else {
+                println!("  FAIL  {test_name}");
+                print_diff(&expected, &actual);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+//! `crux which` and `crux explain` — which filter handles a command, and
+//! what each of its stages does.
+
+use anyhow::Result;
+
+use crate::run::{filter_output, resolve_run_filter, select_variant_post};
+use crate::truncate_str;
+
+pub fn cmd_which(command: &[String], json: bool) -> Result<()> {
+    let filter = crux_core::config::resolve_filter(command);
+    if json {
+        let value = serde_json::json!({
+            "command": command.join(" "),
+            "filter": filter.as_ref().map(|config| serde_json::json!({
+                "command": config.command,
+                "description": config.description,
+                "priority": config.priority,
+            })),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+    match filter {
+        Some(config) => {
+            println!("Filter:      {}", config.command);
+            if let Some(desc) = &config.description {
+                println!("Description: {desc}");
+            }
+            println!("Priority:    {}", config.priority);
+        }
+        None => {
+            println!("No filter matches: {}", command.join(" "));
+        }
+    }
+    Ok(())
+}
+
+pub fn cmd_which_all(command: &[String], json: bool) -> Result<()> {
+    let candidates = crux_core::config::match_candidates(command);
+    if json {
+        let value = serde_json::json!({
+            "command": command.join(" "),
+            "candidates": candidates,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+    if candidates.is_empty() {
+        println!("No filter matches: {}", command.join(" "));
+        return Ok(());
+    }
+
+    let width = candidates
+        .iter()
+        .map(|c| c.command.len())
+        .max()
+        .unwrap_or(0);
+    for candidate in &candidates {
+        let marker = if candidate.winner { "*" } else { " " };
+        let priority = if candidate.priority_override {
+            format!("{}!", candidate.priority)
+        } else {
+            candidate.priority.to_string()
+        };
+        println!(
+            "{marker} {:<width$}  {:<7}  {priority:>5}  {}",
+            candidate.command,
+            candidate.source.label(),
+            candidate.reason
+        );
+    }
+    if candidates.iter().any(|c| c.priority_override) {
+        println!("\n! priority set by [priority] in the config file");
+    }
+    Ok(())
+}
+
+pub fn cmd_explain(command: &[String]) -> Result<()> {
+    let filter = resolve_run_filter(command);
+    let result = crux_core::runner::run_command(command)?;
+    let raw_output = &result.combined;
+    let exit_code = result.exit_code;
+
+    println!("Command:     {} (exit code {exit_code})", command.join(" "));
+    println!(
+        "Raw output:  {} lines, {} bytes",
+        raw_output.lines().count(),
+        raw_output.len()
+    );
+
+    let Some(config) = filter.map(|config| select_variant_post(config, raw_output, exit_code))
+    else {
+        println!("Filter:      (none, passthrough fallback)");
+        let (output, _) = filter_output(None, raw_output, exit_code);
+        print_explained_output(raw_output, &output);
+        return Ok(());
+    };
+    println!("Filter:      {}", config.command);
+    if let Some(ref profile) = crux_core::config::profile::active_profile() {
+        println!("Profile:     {profile}");
+    }
+
+    let (filtered, steps) = crux_core::filter::explain_filter(&config, raw_output, exit_code);
+    println!();
+    println!("{:<28} {:>15} {:>19}", "STEP", "LINES", "BYTES");
+    for step in &steps {
+        let lines = format!("{} → {}", step.lines_before, step.lines_after);
+        let bytes = format!("{} → {}", step.bytes_before, step.bytes_after);
+        println!(
+            "{:<28} {:>15} {:>19}",
+            truncate_str(&step.stage, 28),
+            lines,
+            bytes
+        );
+        for rule in &step.rules {
+            println!("    {rule}");
+        }
+    }
+
+    if crux_core::filter::meets_min_savings(&config, raw_output, &filtered) {
+        print_explained_output(raw_output, &filtered);
+    } else {
+        println!(
+            "\nSaved less than min_savings_pct ({}%), so crux run shows the raw output.",
+            config.min_savings_pct.unwrap_or_default()
+        );
+        print_explained_output(raw_output, raw_output);
+    }
+    Ok(())
+}
+
+fn print_explained_output(raw_output: &str, output: &str) {
+    let saved = if raw_output.is_empty() {
+        0.0
+    } else {
+        (raw_output.len() as f64 - output.len() as f64) / raw_output.len() as f64 * 100.0
+    };
+    println!(
+        "\nResult:      {} lines, {} bytes ({saved:.0}% saved)",
+        output.lines().count(),
+        output.len()
+    );
+    println!("--- output ---");
+    print!("{output}");
+    if !output.ends_with('\n') && !output.is_empty() {
+        println!();
+    }
+}
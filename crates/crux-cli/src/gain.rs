@@ -0,0 +1,525 @@
+//! `crux gain`, `crux top` and `crux stats` — reports on recorded runs.
+
+use anyhow::Result;
+
+#[cfg(feature = "tracking")]
+use crux_tracking::events::{
+    CommandSummary, FailureSummary, FilterSummary, GainSummary, GroupBy, GroupSummary, Period,
+    PeriodSummary, RegressionSummary, StageSavingsSummary, TimeRange,
+};
+
+#[cfg(feature = "tracking")]
+use crate::truncate_str;
+
+/// Flags of `crux gain`.
+#[derive(clap::Args)]
+pub struct GainArgs {
+    #[arg(long)]
+    by_command: bool,
+    /// Savings per filter, with runs where it fell back to raw output
+    #[arg(long, conflicts_with_all = ["by_command", "group_by", "by_session", "timeline"])]
+    by_filter: bool,
+    /// Filters whose output was no smaller than their input, with example history ids
+    #[arg(long, conflicts_with_all = ["by_command", "by_filter", "group_by", "by_session", "timeline"])]
+    regressions: bool,
+    /// Bytes each pipeline stage removed, per filter
+    #[arg(long, conflicts_with_all = ["by_command", "by_filter", "regressions", "group_by", "by_session", "timeline"])]
+    by_stage: bool,
+    /// Failure rate per command, with average output size on failure vs success
+    #[arg(long, conflicts_with_all = ["by_command", "by_filter", "regressions", "by_stage", "group_by", "by_session", "timeline"])]
+    failures: bool,
+    /// Only count runs since this time: 7d, 12h, 2w or a date (2026-10-01)
+    #[arg(long)]
+    since: Option<String>,
+    /// Only count runs before this time (a date includes that whole day)
+    #[arg(long)]
+    until: Option<String>,
+    /// Break savings down by day, week, filter, agent session or source (hook, codex, cli)
+    #[arg(long, value_enum, conflicts_with = "by_command")]
+    group_by: Option<GainGroup>,
+    /// Break savings down by agent session (same as --group-by session)
+    #[arg(long, conflicts_with_all = ["by_command", "group_by"])]
+    by_session: bool,
+    /// Chart savings per day (or --timeline week), with empty periods shown
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "day",
+        conflicts_with_all = ["by_command", "group_by", "by_session"]
+    )]
+    timeline: Option<GainPeriod>,
+}
+
+#[cfg(feature = "tracking")]
+impl GainArgs {
+    fn view(&self) -> GainView {
+        // The flags conflict with each other, so at most one is set
+        if self.by_command {
+            GainView::ByCommand
+        } else if self.by_filter {
+            GainView::ByFilter
+        } else if self.regressions {
+            GainView::Regressions
+        } else if self.by_stage {
+            GainView::ByStage
+        } else if self.failures {
+            GainView::Failures
+        } else if self.by_session {
+            GainView::Grouped(GainGroup::Session)
+        } else if let Some(group_by) = self.group_by {
+            GainView::Grouped(group_by)
+        } else if let Some(period) = self.timeline {
+            GainView::Timeline(period)
+        } else {
+            GainView::Summary
+        }
+    }
+}
+
+#[cfg(feature = "tracking")]
+/// Which report `crux gain` prints.
+#[derive(Clone, Copy)]
+enum GainView {
+    Summary,
+    ByCommand,
+    ByFilter,
+    ByStage,
+    Failures,
+    Regressions,
+    Grouped(GainGroup),
+    Timeline(GainPeriod),
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum GainPeriod {
+    Day,
+    Week,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum GainGroup {
+    Day,
+    Week,
+    Filter,
+    Session,
+    Source,
+}
+
+#[cfg(feature = "tracking")]
+impl GainPeriod {
+    /// The period to query, and the heading of its column.
+    fn query(self) -> (Period, &'static str) {
+        match self {
+            GainPeriod::Day => (Period::Day, "DAY"),
+            GainPeriod::Week => (Period::Week, "WEEK OF"),
+        }
+    }
+}
+
+#[cfg(feature = "tracking")]
+impl GainGroup {
+    /// The grouping to query, and the heading of its column.
+    fn query(self) -> (GroupBy, &'static str) {
+        match self {
+            GainGroup::Day => (GroupBy::Day, "DAY"),
+            GainGroup::Week => (GroupBy::Week, "WEEK"),
+            GainGroup::Filter => (GroupBy::Filter, "FILTER"),
+            GainGroup::Session => (GroupBy::Session, "SESSION"),
+            GainGroup::Source => (GroupBy::Source, "SOURCE"),
+        }
+    }
+}
+
+#[cfg(feature = "tracking")]
+impl GainView {
+    /// What to print when the view has no rows.
+    fn empty_message(self) -> &'static str {
+        match self {
+            GainView::Summary | GainView::ByCommand => {
+                "No filter events recorded yet. Run some commands through crux first!"
+            }
+            GainView::ByFilter => {
+                "No filtered runs recorded yet. Run some commands through crux first!"
+            }
+            GainView::ByStage => {
+                "No stage breakdowns recorded yet. Run some filtered commands first!"
+            }
+            GainView::Failures => "No commands recorded yet. Run some commands through crux first!",
+            GainView::Regressions => {
+                "No filter produced output as large as its input. Nothing to fix!"
+            }
+            GainView::Grouped(_) | GainView::Timeline(_) => {
+                "No filter events recorded in this period."
+            }
+        }
+    }
+}
+
+pub fn cmd_gain(args: &GainArgs, json: bool) -> Result<()> {
+    #[cfg(feature = "tracking")]
+    {
+        use crux_tracking::events as ev;
+
+        let db_path = crux_tracking::db::default_db_path()?;
+        let conn = crux_tracking::db::open_db(&db_path)?;
+        let range = TimeRange::parse(&conn, args.since.as_deref(), args.until.as_deref())?;
+        let view = args.view();
+        let out = Report {
+            json,
+            empty: view.empty_message(),
+        };
+
+        match view {
+            GainView::Summary => print_summary(&ev::get_gain_summary(&conn, &range)?, &range, &out),
+            GainView::ByCommand => out.table(
+                &ev::get_per_command_summary(&conn, &range)?,
+                print_by_command,
+            ),
+            GainView::ByFilter => {
+                out.table(&ev::get_per_filter_summary(&conn, &range)?, print_by_filter)
+            }
+            GainView::ByStage => out.table(&ev::get_stage_savings(&conn, &range)?, print_by_stage),
+            GainView::Failures => {
+                out.table(&ev::get_failure_summary(&conn, &range)?, print_failures)
+            }
+            GainView::Regressions => {
+                out.table(&ev::get_regressions(&conn, &range)?, print_regressions)
+            }
+            GainView::Grouped(group) => {
+                let (group_by, label) = group.query();
+                let groups = ev::get_grouped_summary(&conn, &range, group_by)?;
+                out.table(&groups, |groups| print_grouped(groups, label))
+            }
+            GainView::Timeline(period) => {
+                let (period, label) = period.query();
+                let series = ev::get_gain_by_period(&conn, &range, period)?;
+                out.table(&series, |series| print_timeline(series, label))
+            }
+        }
+    }
+
+    #[cfg(not(feature = "tracking"))]
+    {
+        let _ = (args, json);
+        eprintln!("crux: tracking feature is not enabled");
+        Ok(())
+    }
+}
+
+/// How `crux gain` prints a view: as JSON, or as a table with a message
+/// instead when it has no rows.
+#[cfg(feature = "tracking")]
+struct Report {
+    json: bool,
+    empty: &'static str,
+}
+
+#[cfg(feature = "tracking")]
+impl Report {
+    fn table<T: serde::Serialize>(&self, rows: &[T], table: impl FnOnce(&[T])) -> Result<()> {
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(rows)?);
+        } else if rows.is_empty() {
+            println!("{}", self.empty);
+        } else {
+            table(rows);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tracking")]
+fn print_summary(summary: &GainSummary, range: &TimeRange, out: &Report) -> Result<()> {
+    if out.json {
+        println!("{}", serde_json::to_string_pretty(summary)?);
+        return Ok(());
+    }
+    if summary.total_events == 0 {
+        println!("{}", out.empty);
+        return Ok(());
+    }
+    println!("crux token savings summary");
+    println!("──────────────────────────");
+    if let Some(ref since) = range.since {
+        println!("Since:         {since} UTC");
+    }
+    if let Some(ref until) = range.until {
+        println!("Until:         {until} UTC");
+    }
+    println!("Total events:  {}", summary.total_events);
+    println!("Total input:   {} bytes", summary.total_input_bytes);
+    println!("Total output:  {} bytes", summary.total_output_bytes);
+    println!("Total saved:   {} bytes", summary.total_savings_bytes);
+    println!("Avg savings:   {:.1}%", summary.avg_savings_pct);
+    if summary.tokenized_events > 0 {
+        let saved = summary.total_input_tokens - summary.total_output_tokens;
+        let pct = if summary.total_input_tokens > 0 {
+            saved as f64 / summary.total_input_tokens as f64 * 100.0
+        } else {
+            0.0
+        };
+        println!(
+            "Tokens saved:  {saved} of {} ({pct:.1}%, counted on {} of {} runs)",
+            summary.total_input_tokens, summary.tokenized_events, summary.total_events
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "tracking")]
+fn print_by_command(summaries: &[CommandSummary]) {
+    println!(
+        "{:<30} {:>5} {:>12} {:>12} {:>6}",
+        "COMMAND", "RUNS", "INPUT", "SAVED", "AVG%"
+    );
+    println!("{}", "─".repeat(69));
+    for s in summaries {
+        println!(
+            "{:<30} {:>5} {:>10} B {:>10} B {:>5.1}%",
+            truncate_str(&s.command, 30),
+            s.events,
+            s.total_input_bytes,
+            s.total_savings_bytes,
+            s.avg_savings_pct,
+        );
+    }
+}
+
+#[cfg(feature = "tracking")]
+fn print_by_filter(filters: &[FilterSummary]) {
+    println!(
+        "{:<30} {:>5} {:>5} {:>12} {:>12} {:>6} {:>8}",
+        "FILTER", "RUNS", "CMDS", "INPUT", "SAVED", "AVG%", "FALLBACK"
+    );
+    println!("{}", "─".repeat(84));
+    for f in filters {
+        println!(
+            "{:<30} {:>5} {:>5} {:>10} B {:>10} B {:>5.1}% {:>8}",
+            truncate_str(&f.filter_name, 30),
+            f.events,
+            f.commands,
+            f.total_input_bytes,
+            f.total_savings_bytes,
+            f.avg_savings_pct,
+            f.raw_fallbacks,
+        );
+    }
+}
+
+#[cfg(feature = "tracking")]
+fn print_by_stage(stages: &[StageSavingsSummary]) {
+    println!("{:<30} {:>5} {:>12}", "FILTER / STAGE", "RUNS", "REMOVED");
+    println!("{}", "─".repeat(50));
+    let mut current = None;
+    for s in stages {
+        if current != Some(&s.filter_name) {
+            println!("{}", truncate_str(&s.filter_name, 50));
+            current = Some(&s.filter_name);
+        }
+        println!(
+            "  {:<28} {:>5} {:>10} B",
+            truncate_str(&s.stage, 28),
+            s.events,
+            s.total_bytes_removed
+        );
+    }
+}
+
+#[cfg(feature = "tracking")]
+fn print_failures(rows: &[FailureSummary]) {
+    println!(
+        "{:<30} {:>5} {:>6} {:>6} {:>12} {:>12}",
+        "COMMAND", "RUNS", "FAILED", "RATE", "AVG FAIL", "AVG OK"
+    );
+    println!("{}", "─".repeat(78));
+    let avg = |bytes: Option<f64>| bytes.map_or_else(|| "-".to_string(), |b| format!("{b:.0} B"));
+    for r in rows {
+        println!(
+            "{:<30} {:>5} {:>6} {:>5.1}% {:>12} {:>12}",
+            truncate_str(&r.command, 30),
+            r.events,
+            r.failures,
+            r.failure_pct,
+            avg(r.avg_output_bytes_failed),
+            avg(r.avg_output_bytes_succeeded),
+        );
+    }
+}
+
+#[cfg(feature = "tracking")]
+fn print_regressions(regressions: &[RegressionSummary]) {
+    println!("{:<30} {:>5} {:>12}  EXAMPLES", "FILTER", "RUNS", "EXTRA");
+    println!("{}", "─".repeat(69));
+    for r in regressions {
+        let examples: Vec<String> = r.history_ids.iter().map(|id| format!("#{id}")).collect();
+        let row = format!(
+            "{:<30} {:>5} {:>10} B  {}",
+            truncate_str(&r.filter_name, 30),
+            r.events,
+            r.total_extra_bytes,
+            examples.join(" ")
+        );
+        println!("{}", row.trim_end());
+    }
+    println!();
+    println!("Inspect a run with `crux diff ID` or `crux history show ID`.");
+}
+
+#[cfg(feature = "tracking")]
+fn print_grouped(groups: &[GroupSummary], label: &str) {
+    println!(
+        "{:<30} {:>5} {:>12} {:>12} {:>6}",
+        label, "RUNS", "INPUT", "SAVED", "AVG%"
+    );
+    println!("{}", "─".repeat(69));
+    for g in groups {
+        println!(
+            "{:<30} {:>5} {:>10} B {:>10} B {:>5.1}%",
+            truncate_str(&g.group, 30),
+            g.events,
+            g.total_input_bytes,
+            g.total_savings_bytes,
+            g.avg_savings_pct,
+        );
+    }
+}
+
+#[cfg(feature = "tracking")]
+fn print_timeline(series: &[PeriodSummary], label: &str) {
+    let saved: Vec<i64> = series.iter().map(|p| p.total_savings_bytes).collect();
+    let max = saved.iter().copied().max().unwrap_or(0);
+    println!("Saved bytes  {}", sparkline(&saved));
+    println!();
+    println!("{:<12} {:>5} {:>12}", label, "RUNS", "SAVED");
+    println!("{}", "─".repeat(52));
+    for p in series {
+        // Bars scale to the busiest period, 20 cells wide
+        let width = if max > 0 {
+            (p.total_savings_bytes.max(0) * 20 + max - 1) / max
+        } else {
+            0
+        };
+        let row = format!(
+            "{:<12} {:>5} {:>10} B  {}",
+            p.period_start,
+            p.events,
+            p.total_savings_bytes,
+            "█".repeat(width as usize)
+        );
+        println!("{}", row.trim_end());
+    }
+}
+
+/// One block character per value, scaled to the largest (`▁▃█`).
+#[cfg(feature = "tracking")]
+fn sparkline(values: &[i64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&v| {
+            if max <= 0 || v <= 0 {
+                ' '
+            } else {
+                BLOCKS[((v * 7 + max / 2) / max) as usize]
+            }
+        })
+        .collect()
+}
+
+#[cfg(feature = "tracking")]
+pub fn cmd_top(limit: usize, tokens: bool, since: Option<&str>, json: bool) -> Result<()> {
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    let range = crux_tracking::events::TimeRange::parse(&conn, since, None)?;
+    let mut report = crux_tracking::events::get_waste_report(&conn, &range)?;
+    report.truncate(limit);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    if report.is_empty() {
+        println!("No filter events recorded yet. Run some commands through crux first!");
+        return Ok(());
+    }
+
+    // Byte counts shown as tokens use the same 4-chars-per-token estimate
+    let unit = |bytes: i64| {
+        if tokens {
+            format!("≈{} tok", (bytes + 3) / 4)
+        } else {
+            format!("{bytes} B")
+        }
+    };
+    println!(
+        "{:<30} {:>5} {:>14} {:>6} {:>9}",
+        "COMMAND", "RUNS", "NOT SAVED", "SAVED%", "FILTERED"
+    );
+    println!("{}", "─".repeat(68));
+    for w in &report {
+        let saved_pct = if w.total_input_bytes > 0 {
+            (w.total_input_bytes - w.total_output_bytes) as f64 / w.total_input_bytes as f64 * 100.0
+        } else {
+            0.0
+        };
+        let filtered = if w.filtered_events == 0 {
+            "none".to_string()
+        } else {
+            format!("{}/{}", w.filtered_events, w.events)
+        };
+        println!(
+            "{:<30} {:>5} {:>14} {:>5.0}% {:>9}",
+            truncate_str(&w.command, 30),
+            w.events,
+            unit(w.total_output_bytes),
+            saved_pct,
+            filtered
+        );
+    }
+    if report.iter().any(|w| w.filtered_events == 0) {
+        println!("\nCommands with FILTERED \"none\" have no filter yet; add one in .crux/filters/ (see Custom filters in the README).");
+    }
+    Ok(())
+}
+
+#[cfg(feature = "tracking")]
+pub fn cmd_stats(since: Option<&str>, json: bool) -> Result<()> {
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    let range = crux_tracking::events::TimeRange::parse(&conn, since, None)?;
+    let stats = crux_tracking::events::get_filter_stats(&conn, &range)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    if stats.is_empty() {
+        println!("No filtered runs recorded yet. Run some commands through crux first!");
+        return Ok(());
+    }
+
+    println!(
+        "{:<30} {:>6} {:>12} {:>8} {:>12}",
+        "FILTER", "RUNS", "AVG FILTER", "SAVED%", "SAVED"
+    );
+    println!("{}", "─".repeat(72));
+    for s in &stats {
+        // Runs recorded before filter timing was stored have no duration
+        let avg_time = match s.avg_filter_us {
+            Some(us) if us >= 1000.0 => format!("{:.1}ms", us / 1000.0),
+            Some(us) => format!("{us:.0}µs"),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<30} {:>6} {:>12} {:>7.0}% {:>10} B",
+            truncate_str(&s.filter_name, 30),
+            s.invocations,
+            avg_time,
+            s.avg_savings_pct,
+            s.total_savings_bytes
+        );
+    }
+    Ok(())
+}
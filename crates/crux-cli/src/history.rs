@@ -0,0 +1,618 @@
+//! Subcommands that work on stored history entries.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use crux_core::config::FilterConfig;
+use crux_tracking::history::HistoryEntry;
+
+/// Load a history entry by id, or the most recent one for `last`.
+fn load_entry(selector: &str) -> Result<HistoryEntry> {
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    if selector == "last" {
+        return crux_tracking::history::get_recent_history(&conn, 1)?
+            .into_iter()
+            .next()
+            .context("no history entries yet");
+    }
+    let id: i64 = selector.parse().with_context(|| {
+        format!("invalid history id '{selector}' (expected a number or 'last')")
+    })?;
+    match crux_tracking::history::get_history_entry(&conn, id)? {
+        Some(entry) => Ok(entry),
+        None => bail!("no history entry with id {id}"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// List — recent entries, optionally narrowed down
+// ---------------------------------------------------------------------------
+
+/// `crux history`: list recent runs matching `query`.
+pub fn cmd_list(limit: usize, query: &crux_tracking::history::HistoryQuery) -> Result<()> {
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    let entries = crux_tracking::history::search_history(&conn, query, limit)?;
+
+    if entries.is_empty() {
+        let filtered = query.command.is_some()
+            || query.filter.is_some()
+            || query.failed_only
+            || query.min_size.is_some()
+            || query.tag.is_some();
+        if filtered {
+            println!("No history entries match.");
+        } else {
+            println!("No history entries yet. Run some commands through crux first!");
+        }
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let raw_len = entry.raw_output.len();
+        let filtered_len = entry.filtered_output.len();
+        let savings_pct = if raw_len > 0 {
+            ((raw_len - filtered_len) as f64 / raw_len as f64) * 100.0
+        } else {
+            0.0
+        };
+        let filter_label = entry.filter_name.as_deref().unwrap_or("(passthrough)");
+        let tags: String = entry.tags.iter().map(|tag| format!(" [{tag}]")).collect();
+        println!(
+            "#{} [{}] {} | filter: {} | {:.0}% saved{}",
+            entry.id, entry.timestamp, entry.command, filter_label, savings_pct, tags
+        );
+        if let Some(notes) = &entry.notes {
+            println!("    {notes}");
+        }
+    }
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Show — stored outputs of a single entry
+// ---------------------------------------------------------------------------
+
+/// Which stored outputs `crux history show` prints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShowPart {
+    Both,
+    Raw,
+    Filtered,
+}
+
+impl ShowPart {
+    pub fn from_flags(raw: bool, filtered: bool) -> Self {
+        if raw {
+            ShowPart::Raw
+        } else if filtered {
+            ShowPart::Filtered
+        } else {
+            ShowPart::Both
+        }
+    }
+}
+
+pub fn cmd_show(selector: &str, part: ShowPart, pager: bool) -> Result<()> {
+    let entry = load_entry(selector)?;
+    let text = render_entry(&entry, part);
+    if pager {
+        page(&text)
+    } else {
+        print!("{text}");
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tag / Note — label entries worth keeping track of
+// ---------------------------------------------------------------------------
+
+pub fn cmd_tag(selector: &str, label: &str, remove: bool) -> Result<()> {
+    let entry = load_entry(selector)?;
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    if remove {
+        if !crux_tracking::history::untag_history(&conn, entry.id, label)? {
+            bail!("#{} is not tagged '{label}'", entry.id);
+        }
+        println!("Removed tag '{label}' from #{}", entry.id);
+    } else {
+        crux_tracking::history::tag_history(&conn, entry.id, label)?;
+        println!("Tagged #{} '{label}'", entry.id);
+    }
+    Ok(())
+}
+
+pub fn cmd_note(selector: &str, text: Option<&str>) -> Result<()> {
+    let entry = load_entry(selector)?;
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    crux_tracking::history::set_history_notes(&conn, entry.id, text)?;
+    if text.is_some() {
+        println!("Saved note on #{}", entry.id);
+    } else {
+        println!("Cleared note on #{}", entry.id);
+    }
+    Ok(())
+}
+
+/// `crux history diff`: filtered output lines that changed between runs.
+pub fn cmd_compare(selector_a: &str, selector_b: &str, json: bool) -> Result<()> {
+    let (a, b) = (load_entry(selector_a)?, load_entry(selector_b)?);
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    let diff = crux_tracking::history::compare(&conn, a.id, b.id)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+
+    let exit_label =
+        |code: Option<i32>| code.map_or_else(|| "unknown".to_string(), |c| c.to_string());
+    println!(
+        "--- #{} [{}] {} | exit: {}",
+        a.id,
+        a.timestamp,
+        a.command,
+        exit_label(a.exit_code)
+    );
+    println!(
+        "+++ #{} [{}] {} | exit: {}",
+        b.id,
+        b.timestamp,
+        b.command,
+        exit_label(b.exit_code)
+    );
+    if a.command != b.command {
+        println!("(different commands)");
+    }
+    for line in &diff.removed {
+        println!("- {line}");
+    }
+    for line in &diff.added {
+        println!("+ {line}");
+    }
+    println!(
+        "{} lines unchanged, {} removed, {} added",
+        diff.unchanged_lines,
+        diff.removed.len(),
+        diff.added.len()
+    );
+    Ok(())
+}
+
+/// A single output on its own is printed verbatim so it can be piped;
+/// both together get a header and a section per output.
+fn render_entry(entry: &HistoryEntry, part: ShowPart) -> String {
+    let with_newline = |s: &str| {
+        if s.is_empty() || s.ends_with('\n') {
+            s.to_string()
+        } else {
+            format!("{s}\n")
+        }
+    };
+    match part {
+        ShowPart::Raw => with_newline(&entry.raw_output),
+        ShowPart::Filtered => with_newline(&entry.filtered_output),
+        ShowPart::Both => {
+            let filter_label = entry.filter_name.as_deref().unwrap_or("(passthrough)");
+            let exit_label = entry
+                .exit_code
+                .map_or_else(|| "unknown".to_string(), |code| code.to_string());
+            let mut out = format!(
+                "#{} [{}] {} | filter: {} | exit: {}\n",
+                entry.id, entry.timestamp, entry.command, filter_label, exit_label
+            );
+            if !entry.tags.is_empty() {
+                out.push_str(&format!("tags: {}\n", entry.tags.join(", ")));
+            }
+            if let Some(notes) = &entry.notes {
+                out.push_str(&format!("note: {notes}\n"));
+            }
+            if entry.truncated {
+                out.push_str("truncated: output over the history size cap, head and tail kept\n");
+            }
+            for (name, body) in [
+                ("raw", &entry.raw_output),
+                ("filtered", &entry.filtered_output),
+            ] {
+                out.push_str(&format!(
+                    "\n=== {name} ({} lines, {} bytes)\n",
+                    body.lines().count(),
+                    body.len()
+                ));
+                out.push_str(&with_newline(body));
+            }
+            out
+        }
+    }
+}
+
+/// Pipe `text` through `$PAGER`, falling back to `less -R`.
+fn page(text: &str) -> Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less -R".to_string());
+    let mut parts = pager.split_whitespace();
+    let program = parts.next().context("empty $PAGER")?;
+    let mut child = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("running pager '{pager}'"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user quitting the pager early closes the pipe; that's fine.
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Diff — raw vs filtered output of a history entry
+// ---------------------------------------------------------------------------
+
+pub fn cmd_diff(selector: &str, side_by_side: bool) -> Result<()> {
+    let entry = load_entry(selector)?;
+    let filter_label = entry.filter_name.as_deref().unwrap_or("(passthrough)");
+    println!(
+        "#{} [{}] {} | filter: {}",
+        entry.id, entry.timestamp, entry.command, filter_label
+    );
+
+    let ops = diff_lines(&entry.raw_output, &entry.filtered_output);
+    if side_by_side {
+        print_side_by_side(&ops, terminal_width());
+    } else {
+        println!(
+            "--- raw ({} lines, {} bytes)",
+            entry.raw_output.lines().count(),
+            entry.raw_output.len()
+        );
+        println!(
+            "+++ filtered ({} lines, {} bytes)",
+            entry.filtered_output.lines().count(),
+            entry.filtered_output.len()
+        );
+        for op in &ops {
+            match op {
+                DiffOp::Keep(line) => println!("  {line}"),
+                DiffOp::Remove(line) => println!("- {line}"),
+                DiffOp::Add(line) => println!("+ {line}"),
+            }
+        }
+    }
+
+    let removed = ops
+        .iter()
+        .filter(|op| matches!(op, DiffOp::Remove(_)))
+        .count();
+    let added = ops.iter().filter(|op| matches!(op, DiffOp::Add(_))).count();
+    println!("{removed} lines removed, {added} lines added");
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Replay — re-run the current filter on stored raw output
+// ---------------------------------------------------------------------------
+
+/// Filter an entry's raw output as `crux run` would today: with the named
+/// filter if given, else the one resolved from its command. Returns the
+/// filter used, the output and whether it fell back to raw output.
+fn refilter_entry(
+    entry: &HistoryEntry,
+    filter_name: Option<&str>,
+) -> Result<(Option<FilterConfig>, String, bool)> {
+    let command: Vec<String> = entry.command.split_whitespace().map(String::from).collect();
+    // Entries recorded before exit codes were stored replay as successes.
+    let exit_code = entry.exit_code.unwrap_or(0);
+
+    let filter = match filter_name {
+        Some(name) => Some(
+            crux_core::config::find_filter_by_name(name)
+                .with_context(|| format!("no filter named '{name}'"))?,
+        ),
+        None => crate::run::resolve_run_filter(&command),
+    };
+    let filter =
+        filter.map(|config| crate::run::select_variant_post(config, &entry.raw_output, exit_code));
+    let (filtered, raw_fallback) =
+        crate::run::filter_output(filter.as_ref(), &entry.raw_output, exit_code);
+    Ok((filter, filtered, raw_fallback))
+}
+
+pub fn cmd_replay(selector: &str, filter_name: Option<&str>) -> Result<()> {
+    let entry = load_entry(selector)?;
+    let (filter, filtered, raw_fallback) = refilter_entry(&entry, filter_name)?;
+
+    print!("{filtered}");
+    if !filtered.ends_with('\n') && !filtered.is_empty() {
+        println!();
+    }
+
+    let filter_label = filter
+        .as_ref()
+        .map_or("(passthrough)", |config| config.command.as_str());
+    let raw_bytes = entry.raw_output.len();
+    eprintln!(
+        "crux: replayed #{} with {}: {} → {} bytes ({} saved; recorded run: {} bytes)",
+        entry.id,
+        filter_label,
+        raw_bytes,
+        filtered.len(),
+        savings_label(raw_bytes, filtered.len()),
+        entry.filtered_output.len()
+    );
+    if raw_fallback {
+        eprintln!("crux: below min_savings_pct, showing raw output");
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Refilter — replay many entries to catch filter regressions
+// ---------------------------------------------------------------------------
+
+/// Result of re-filtering one history entry.
+struct Refiltered {
+    id: i64,
+    command: String,
+    filter: Option<String>,
+    raw_bytes: usize,
+    recorded_bytes: usize,
+    refiltered_bytes: usize,
+    /// `refiltered_bytes - recorded_bytes`: negative means the current
+    /// filters save more than they did when the run was recorded.
+    delta_bytes: i64,
+}
+
+/// Re-apply current filters to the newest `limit` history entries and
+/// compare with the recorded output. With `filter_name`, only entries that
+/// filter handled are replayed, all through its current version.
+pub fn cmd_refilter(filter_name: Option<&str>, limit: usize, json: bool) -> Result<()> {
+    if let Some(name) = filter_name {
+        if crux_core::config::find_filter_by_name(name).is_none() {
+            bail!("no filter named '{name}'");
+        }
+    }
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    let query = crux_tracking::history::HistoryQuery {
+        filter: filter_name.map(String::from),
+        ..Default::default()
+    };
+    let entries = crux_tracking::history::search_history(&conn, &query, limit)?;
+
+    let mut results = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        let (filter, filtered, _) = refilter_entry(entry, filter_name)?;
+        results.push(Refiltered {
+            id: entry.id,
+            command: entry.command.clone(),
+            filter: filter.map(|config| config.command),
+            raw_bytes: entry.raw_output.len(),
+            recorded_bytes: entry.filtered_output.len(),
+            refiltered_bytes: filtered.len(),
+            delta_bytes: filtered.len() as i64 - entry.filtered_output.len() as i64,
+        });
+    }
+
+    if json {
+        let rows: Vec<serde_json::Value> = results
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "id": r.id,
+                    "command": r.command,
+                    "filter": r.filter,
+                    "raw_bytes": r.raw_bytes,
+                    "recorded_bytes": r.recorded_bytes,
+                    "refiltered_bytes": r.refiltered_bytes,
+                    "delta_bytes": r.delta_bytes,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+    if results.is_empty() {
+        println!("No history entries to refilter.");
+        return Ok(());
+    }
+
+    println!(
+        "{:>6} {:<30} {:>10} {:>10} {:>10}",
+        "ID", "COMMAND", "RECORDED", "NOW", "DELTA"
+    );
+    println!("{}", "─".repeat(70));
+    for r in &results {
+        println!(
+            "{:>6} {:<30} {:>8} B {:>8} B {:>+10}",
+            format!("#{}", r.id),
+            clip(&r.command, 30),
+            r.recorded_bytes,
+            r.refiltered_bytes,
+            r.delta_bytes
+        );
+    }
+    let larger = results.iter().filter(|r| r.delta_bytes > 0).count();
+    let smaller = results.iter().filter(|r| r.delta_bytes < 0).count();
+    let total: i64 = results.iter().map(|r| r.delta_bytes).sum();
+    println!();
+    println!(
+        "{} entr{}: {} smaller, {} larger, {} unchanged ({:+} bytes in total)",
+        results.len(),
+        if results.len() == 1 { "y" } else { "ies" },
+        smaller,
+        larger,
+        results.len() - smaller - larger,
+        total
+    );
+    Ok(())
+}
+
+fn savings_label(raw: usize, filtered: usize) -> String {
+    if raw == 0 {
+        return "0%".to_string();
+    }
+    let pct = (raw as f64 - filtered as f64) / raw as f64 * 100.0;
+    format!("{pct:.0}%")
+}
+
+#[derive(Debug, PartialEq)]
+enum DiffOp<'a> {
+    Keep(&'a str),
+    Remove(&'a str),
+    Add(&'a str),
+}
+
+/// Line diff of raw vs filtered output.
+///
+/// Filters mostly delete lines, so each filtered line is aligned greedily
+/// with its next occurrence in the raw output; raw lines skipped over are
+/// removals, and filtered lines with no later match (markers, rewritten
+/// lines) are additions. Runs in O(n log n) even for huge outputs.
+fn diff_lines<'a>(raw: &'a str, filtered: &'a str) -> Vec<DiffOp<'a>> {
+    let raw_lines: Vec<&str> = raw.lines().collect();
+    let mut positions: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, line) in raw_lines.iter().enumerate() {
+        positions.entry(line).or_default().push(i);
+    }
+
+    let mut ops = Vec::new();
+    let mut next = 0;
+    for line in filtered.lines() {
+        let found = positions.get(line).and_then(|idxs| {
+            let k = idxs.partition_point(|&i| i < next);
+            idxs.get(k).copied()
+        });
+        match found {
+            Some(pos) => {
+                ops.extend(raw_lines[next..pos].iter().map(|l| DiffOp::Remove(l)));
+                ops.push(DiffOp::Keep(line));
+                next = pos + 1;
+            }
+            None => ops.push(DiffOp::Add(line)),
+        }
+    }
+    ops.extend(raw_lines[next..].iter().map(|l| DiffOp::Remove(l)));
+    ops
+}
+
+/// Print raw on the left and filtered on the right, `width` columns in total.
+fn print_side_by_side(ops: &[DiffOp], width: usize) {
+    let col = width.saturating_sub(5) / 2;
+    println!("{:<col$} │ filtered", "raw");
+    println!("{}", "─".repeat(width.min(col * 2 + 5)));
+    for op in ops {
+        let (left, mark, right) = match op {
+            DiffOp::Keep(line) => (*line, ' ', *line),
+            DiffOp::Remove(line) => (*line, '<', ""),
+            DiffOp::Add(line) => ("", '>', *line),
+        };
+        println!("{:<col$} {mark} {}", clip(left, col), clip(right, col));
+    }
+}
+
+fn clip(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let kept: String = s.chars().take(max.saturating_sub(1)).collect();
+        format!("{kept}…")
+    }
+}
+
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .filter(|&w: &usize| w >= 40)
+        .unwrap_or(160)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_marks_removed_and_added_lines() {
+        let raw = "Compiling a\nCompiling b\nerror: boom\n  --> src/lib.rs:1\nwarning: x";
+        let filtered = "error: boom\n  --> src/lib.rs:1\n… 1 line omitted";
+        assert_eq!(
+            diff_lines(raw, filtered),
+            vec![
+                DiffOp::Remove("Compiling a"),
+                DiffOp::Remove("Compiling b"),
+                DiffOp::Keep("error: boom"),
+                DiffOp::Keep("  --> src/lib.rs:1"),
+                DiffOp::Add("… 1 line omitted"),
+                DiffOp::Remove("warning: x"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_aligns_repeated_lines_in_order() {
+        let ops = diff_lines("a\nb\na\nc", "a\nc");
+        assert_eq!(
+            ops,
+            vec![
+                DiffOp::Keep("a"),
+                DiffOp::Remove("b"),
+                DiffOp::Remove("a"),
+                DiffOp::Keep("c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_output_is_all_kept() {
+        let ops = diff_lines("x\ny", "x\ny");
+        assert!(ops.iter().all(|op| matches!(op, DiffOp::Keep(_))));
+    }
+
+    fn entry() -> HistoryEntry {
+        HistoryEntry {
+            id: 7,
+            timestamp: "2026-10-01 12:00:00".to_string(),
+            command: "cargo test".to_string(),
+            raw_output: "a\nb\nc".to_string(),
+            filtered_output: "c\n".to_string(),
+            filter_name: Some("cargo test".to_string()),
+            exit_code: Some(101),
+            tags: vec!["repro".to_string()],
+            notes: Some("only fails on CI".to_string()),
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn show_single_part_is_verbatim() {
+        assert_eq!(render_entry(&entry(), ShowPart::Raw), "a\nb\nc\n");
+        assert_eq!(render_entry(&entry(), ShowPart::Filtered), "c\n");
+    }
+
+    #[test]
+    fn show_both_has_header_and_sections() {
+        let out = render_entry(&entry(), ShowPart::Both);
+        assert!(out
+            .starts_with("#7 [2026-10-01 12:00:00] cargo test | filter: cargo test | exit: 101\n"));
+        assert!(out.contains("=== raw (3 lines, 5 bytes)\na\nb\nc\n"));
+        assert!(out.contains("=== filtered (1 lines, 2 bytes)\nc\n"));
+        assert!(out.contains("\ntags: repro\nnote: only fails on CI\n"));
+    }
+
+    #[test]
+    fn clip_respects_char_boundaries() {
+        assert_eq!(clip("héllo", 10), "héllo");
+        assert_eq!(clip("héllo", 3), "hé…");
+    }
+}
@@ -1,356 +1,315 @@
+mod alias;
+mod bench;
+mod cli;
 mod commands;
+mod gain;
+#[cfg(feature = "tracking")]
+mod history;
+#[cfg(feature = "mcp")]
+mod mcp;
+mod pack;
+mod run;
+mod shell_init;
+#[cfg(feature = "tracking")]
+mod sync;
+#[cfg(feature = "tracking")]
+mod tracking;
+mod upgrade;
+mod watch;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use std::io::Read;
-use std::time::Instant;
+use clap::Parser;
 
-#[derive(Parser)]
-#[command(name = "crux", version, about = "CLI output compressor for AI agents")]
-struct Cli {
-    #[command(subcommand)]
-    command: Commands,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    /// Run a command through the filter pipeline
-    Run {
-        #[arg(trailing_var_arg = true, required = true)]
-        command: Vec<String>,
-        /// Print execution timing breakdown to stderr
-        #[arg(long)]
-        time: bool,
-    },
-    /// Show token savings summary
-    Gain {
-        #[arg(long)]
-        by_command: bool,
-    },
-    /// Show recent command history
-    #[cfg(feature = "tracking")]
-    History {
-        #[arg(short, long, default_value = "20")]
-        limit: usize,
-    },
-    /// Install Claude Code hook
-    Init {
-        #[arg(long, group = "target")]
-        global: bool,
-        #[arg(long, group = "target")]
-        codex: bool,
-    },
-    /// List available filters
-    Ls,
-    /// Show which filter matches a command
-    Which {
-        #[arg(trailing_var_arg = true, required = true)]
-        command: Vec<String>,
-    },
-    /// Show filter config details
-    Show { filter: String },
-    /// Export builtin filter as TOML for customization
-    Eject { filter: String },
-    /// Run declarative filter tests
-    Verify,
-    /// Keep only error/warning lines from command output
-    Err {
-        #[arg(trailing_var_arg = true, required = true)]
-        command: Vec<String>,
-    },
-    /// Extract test summary from command output.
-    ///
-    /// Auto-detects: cargo test, pytest, jest, vitest, go test, mocha,
-    /// playwright, rspec, PHPUnit, dotnet test. Falls back to extracting
-    /// lines containing pass/fail/error/warning keywords.
-    Test {
-        #[arg(trailing_var_arg = true, required = true)]
-        command: Vec<String>,
-    },
-    /// Run command with dedup and collapse filters
-    Log {
-        #[arg(trailing_var_arg = true, required = true)]
-        command: Vec<String>,
-    },
-    /// Run diagnostic checks on your crux installation
-    Doctor,
-    /// Agent hook management
-    Hook {
-        #[command(subcommand)]
-        command: HookCommand,
-    },
-}
-
-#[derive(Subcommand)]
-enum HookCommand {
-    /// Process Claude Code PreToolUse hook from stdin
-    Handle,
-}
+use cli::{Cli, Commands, HookCommand, PackCommand, RunOutput};
+#[cfg(feature = "tracking")]
+use cli::{HistoryCommand, TrackingCommand};
 
 fn main() {
     let cli = Cli::parse();
 
-    let result = match cli.command {
-        Commands::Run { command, time } => cmd_run(&command, time),
-        Commands::Gain { by_command } => cmd_gain(by_command),
-        #[cfg(feature = "tracking")]
-        Commands::History { limit } => cmd_history(limit),
-        Commands::Init { global, codex } => commands::cmd_init(global, codex),
-        Commands::Ls => commands::cmd_ls(),
-        Commands::Which { command } => cmd_which(&command),
-        Commands::Show { filter } => commands::cmd_show(&filter),
-        Commands::Eject { filter } => commands::cmd_eject(&filter),
-        Commands::Verify => commands::cmd_verify(),
-        Commands::Err { command } => commands::cmd_err(&command),
-        Commands::Test { command } => commands::cmd_test(&command),
-        Commands::Log { command } => commands::cmd_log(&command),
-        Commands::Doctor => commands::cmd_doctor(),
-        Commands::Hook { command } => match command {
-            HookCommand::Handle => cmd_hook_handle(),
-        },
-    };
-
-    if let Err(e) = result {
-        eprintln!("crux: error: {e:#}");
-        std::process::exit(1);
-    }
-}
-
-// ---------------------------------------------------------------------------
-// Run
-// ---------------------------------------------------------------------------
-
-fn cmd_run(command: &[String], show_time: bool) -> Result<()> {
-    let wall_start = Instant::now();
-
-    let filter = crux_core::config::resolve_filter(command);
-
-    let exec_start = Instant::now();
-    let result = crux_core::runner::run_command(command)?;
-    let exec_elapsed = exec_start.elapsed();
-
-    let raw_output = &result.combined;
-    let input_bytes = raw_output.len();
-
-    let filter_start = Instant::now();
-    let filtered = if let Some(ref config) = filter {
-        crux_core::filter::apply_filter(config, raw_output, result.exit_code)
-    } else {
-        raw_output.clone()
-    };
-    let filter_elapsed = filter_start.elapsed();
-    let output_bytes = filtered.len();
-
-    print!("{filtered}");
-    if !filtered.ends_with('\n') && !filtered.is_empty() {
-        println!();
-    }
-
-    if result.exit_code != 0 {
-        eprintln!("crux: exit code {}", result.exit_code);
+    if let Some(ref profile) = cli.profile {
+        std::env::set_var(crux_core::config::profile::PROFILE_ENV, profile);
     }
 
     #[cfg(feature = "tracking")]
-    {
-        let duration_ms = wall_start.elapsed().as_millis() as u64;
-        if let Err(e) = record_tracking_and_history(
-            command,
-            &filter,
-            input_bytes,
-            output_bytes,
-            result.exit_code,
-            duration_ms,
-            raw_output,
-            &filtered,
-        ) {
-            eprintln!("crux: tracking error: {e}");
-        }
+    if let Err(e) = run::init_history_encryption() {
+        eprintln!("crux: {e:#}");
     }
 
-    #[cfg(not(feature = "tracking"))]
-    let _ = wall_start;
-
-    if input_bytes > 0 && input_bytes != output_bytes {
-        let saved_pct = ((input_bytes - output_bytes) as f64 / input_bytes as f64) * 100.0;
-        eprintln!("crux: {input_bytes} → {output_bytes} bytes ({saved_pct:.0}% saved)");
+    if let Err(e) = dispatch(cli.command, cli.json) {
+        eprintln!("crux: error: {e:#}");
+        std::process::exit(1);
     }
+}
 
-    if show_time {
-        let wall_elapsed = wall_start.elapsed();
-        eprintln!("crux: timing breakdown:");
-        eprintln!(
-            "  command execution: {:.3}ms",
-            exec_elapsed.as_secs_f64() * 1000.0
-        );
-        eprintln!(
-            "  filter pipeline:  {:.3}ms",
-            filter_elapsed.as_secs_f64() * 1000.0
-        );
-        eprintln!(
-            "  total wall time:  {:.3}ms",
-            wall_elapsed.as_secs_f64() * 1000.0
-        );
-        eprintln!("  input size:       {} bytes", input_bytes);
-        eprintln!("  output size:      {} bytes", output_bytes);
+fn dispatch(command: Commands, json: bool) -> Result<()> {
+    match command {
+        Commands::Run {
+            command,
+            time,
+            filter,
+            raw,
+            tokens,
+            budget,
+            output,
+            no_track,
+        } => run::cmd_run(
+            &command,
+            &run::RunOptions {
+                show_time: time,
+                filter_name: filter.as_deref(),
+                raw,
+                show_tokens: tokens,
+                budget,
+                json: output == RunOutput::Json || json,
+                no_track,
+            },
+        ),
+        Commands::Filter {
+            as_command,
+            exit_code,
+        } => run::cmd_filter(&as_command, exit_code),
+        Commands::Gain(args) => gain::cmd_gain(&args, json),
+        #[cfg(feature = "tracking")]
+        command @ (Commands::History { .. }
+        | Commands::Diff { .. }
+        | Commands::Replay { .. }
+        | Commands::Refilter { .. }) => dispatch_history(command, json),
+        #[cfg(feature = "tracking")]
+        command @ (Commands::Top { .. }
+        | Commands::Stats { .. }
+        | Commands::Export { .. }
+        | Commands::Sync { .. }
+        | Commands::Tracking { .. }
+        | Commands::Prune { .. }) => dispatch_tracking(command, json),
+        command @ (Commands::Watch { .. }
+        | Commands::Err { .. }
+        | Commands::Test { .. }
+        | Commands::Log { .. }) => dispatch_shortcuts(command),
+        command @ (Commands::Alias { .. }
+        | Commands::ShellInit { .. }
+        | Commands::Init { .. }
+        | Commands::Doctor { .. }
+        | Commands::Upgrade { .. }
+        | Commands::Hook { .. }) => dispatch_setup(command, json),
+        #[cfg(feature = "mcp")]
+        Commands::Serve { mcp: _ } => mcp::cmd_serve_mcp(),
+        command => dispatch_filters(command, json),
     }
-
-    Ok(())
 }
 
-// ---------------------------------------------------------------------------
-// Tracking helpers
-// ---------------------------------------------------------------------------
-
+/// `crux history` and the commands that replay stored entries.
 #[cfg(feature = "tracking")]
-#[allow(clippy::too_many_arguments)]
-fn record_tracking_and_history(
-    command: &[String],
-    filter: &Option<crux_core::config::FilterConfig>,
-    input_bytes: usize,
-    output_bytes: usize,
-    exit_code: i32,
-    duration_ms: u64,
-    raw_output: &str,
-    filtered_output: &str,
-) -> Result<()> {
-    let db_path = crux_tracking::db::default_db_path()?;
-    let conn = crux_tracking::db::open_db(&db_path)?;
-    let cmd_str = command.join(" ");
-    let filter_name = filter.as_ref().map(|f| f.command.clone());
-
-    let event = crux_tracking::events::FilterEvent {
-        command: cmd_str.clone(),
-        filter_name: filter_name.clone(),
-        input_bytes,
-        output_bytes,
-        exit_code,
-        duration_ms: Some(duration_ms),
-    };
-    crux_tracking::events::record_event(&conn, &event)?;
-
-    crux_tracking::history::store_history(
-        &conn,
-        &cmd_str,
-        raw_output,
-        filtered_output,
-        filter_name.as_deref(),
-    )?;
-
-    Ok(())
-}
-
-// ---------------------------------------------------------------------------
-// Gain
-// ---------------------------------------------------------------------------
-
-fn cmd_gain(by_command: bool) -> Result<()> {
-    #[cfg(feature = "tracking")]
-    {
-        let db_path = crux_tracking::db::default_db_path()?;
-        let conn = crux_tracking::db::open_db(&db_path)?;
-
-        if by_command {
-            let summaries = crux_tracking::events::get_per_command_summary(&conn)?;
-            if summaries.is_empty() {
-                println!("No filter events recorded yet. Run some commands through crux first!");
-                return Ok(());
-            }
-            println!(
-                "{:<30} {:>5} {:>12} {:>12} {:>6}",
-                "COMMAND", "RUNS", "INPUT", "SAVED", "AVG%"
-            );
-            println!("{}", "─".repeat(69));
-            for s in &summaries {
-                println!(
-                    "{:<30} {:>5} {:>10} B {:>10} B {:>5.1}%",
-                    truncate_str(&s.command, 30),
-                    s.events,
-                    s.total_input_bytes,
-                    s.total_savings_bytes,
-                    s.avg_savings_pct,
-                );
-            }
-        } else {
-            let summary = crux_tracking::events::get_gain_summary(&conn)?;
-            if summary.total_events == 0 {
-                println!("No filter events recorded yet. Run some commands through crux first!");
-                return Ok(());
-            }
-            println!("crux token savings summary");
-            println!("──────────────────────────");
-            println!("Total events:  {}", summary.total_events);
-            println!("Total input:   {} bytes", summary.total_input_bytes);
-            println!("Total output:  {} bytes", summary.total_output_bytes);
-            println!("Total saved:   {} bytes", summary.total_savings_bytes);
-            println!("Avg savings:   {:.1}%", summary.avg_savings_pct);
+fn dispatch_history(command: Commands, json: bool) -> Result<()> {
+    match command {
+        Commands::History {
+            action: Some(action),
+            ..
+        } => match action {
+            HistoryCommand::Show {
+                id,
+                raw,
+                filtered,
+                pager,
+            } => history::cmd_show(&id, history::ShowPart::from_flags(raw, filtered), pager),
+            HistoryCommand::Tag { id, label, remove } => history::cmd_tag(&id, &label, remove),
+            HistoryCommand::Note { id, text } => history::cmd_note(&id, text.as_deref()),
+            HistoryCommand::Diff { a, b } => history::cmd_compare(&a, &b, json),
+        },
+        Commands::History {
+            action: None,
+            limit,
+            command,
+            filter,
+            failed_only,
+            min_size,
+            tag,
+        } => history::cmd_list(
+            limit,
+            &crux_tracking::history::HistoryQuery {
+                command,
+                filter,
+                failed_only,
+                min_size,
+                tag,
+            },
+        ),
+        Commands::Diff {
+            entry,
+            side_by_side,
+        } => history::cmd_diff(&entry, side_by_side),
+        Commands::Replay { entry, filter } => history::cmd_replay(&entry, filter.as_deref()),
+        Commands::Refilter { filter, limit } => {
+            history::cmd_refilter(filter.as_deref(), limit, json)
         }
-        Ok(())
-    }
-
-    #[cfg(not(feature = "tracking"))]
-    {
-        let _ = by_command;
-        eprintln!("crux: tracking feature is not enabled");
-        Ok(())
+        _ => unreachable!("not a history command"),
     }
 }
 
-// ---------------------------------------------------------------------------
-// History
-// ---------------------------------------------------------------------------
-
+/// Reports on the tracking database, and commands that move or trim it.
 #[cfg(feature = "tracking")]
-fn cmd_history(limit: usize) -> Result<()> {
-    let db_path = crux_tracking::db::default_db_path()?;
-    let conn = crux_tracking::db::open_db(&db_path)?;
-    let entries = crux_tracking::history::get_recent_history(&conn, limit)?;
-
-    if entries.is_empty() {
-        println!("No history entries yet. Run some commands through crux first!");
-        return Ok(());
+fn dispatch_tracking(command: Commands, json: bool) -> Result<()> {
+    match command {
+        Commands::Top {
+            limit,
+            tokens,
+            since,
+        } => gain::cmd_top(limit, tokens, since.as_deref(), json),
+        Commands::Stats { since } => gain::cmd_stats(since.as_deref(), json),
+        Commands::Export {
+            format,
+            table,
+            since,
+        } => tracking::cmd_export(format, table, since.as_deref()),
+        Commands::Sync { dry_run, all } => sync::cmd_sync(dry_run, all),
+        Commands::Tracking { action } => match action {
+            TrackingCommand::Dump { output } => tracking::cmd_tracking_dump(output.as_deref()),
+            TrackingCommand::Import { file } => tracking::cmd_tracking_import(&file),
+            TrackingCommand::Record { file } => run::cmd_tracking_record(file.as_deref()),
+        },
+        Commands::Prune {
+            older_than,
+            keep_last,
+            vacuum,
+        } => tracking::cmd_prune(older_than.as_deref(), keep_last, vacuum),
+        _ => unreachable!("not a tracking command"),
     }
+}
 
-    for entry in &entries {
-        let raw_len = entry.raw_output.len();
-        let filtered_len = entry.filtered_output.len();
-        let savings_pct = if raw_len > 0 {
-            ((raw_len - filtered_len) as f64 / raw_len as f64) * 100.0
-        } else {
-            0.0
-        };
-        let filter_label = entry.filter_name.as_deref().unwrap_or("(passthrough)");
-        println!(
-            "[{}] {} | filter: {} | {:.0}% saved",
-            entry.timestamp, entry.command, filter_label, savings_pct
-        );
+/// Commands that run something and reduce its output a fixed way.
+fn dispatch_shortcuts(command: Commands) -> Result<()> {
+    match command {
+        Commands::Watch {
+            command,
+            interval,
+            changed_only,
+        } => watch::cmd_watch(&command, interval, changed_only),
+        Commands::Err {
+            command,
+            patterns,
+            warnings,
+            no_warnings,
+            context,
+        } => commands::cmd_err(
+            &command,
+            &commands::ErrOptions {
+                patterns: &patterns,
+                warnings: match (warnings, no_warnings) {
+                    (true, _) => Some(true),
+                    (_, true) => Some(false),
+                    _ => None,
+                },
+                context,
+            },
+        ),
+        Commands::Test {
+            command,
+            framework,
+            list_frameworks,
+        } => commands::cmd_test(&command, framework.as_deref(), list_frameworks),
+        Commands::Log {
+            command,
+            window,
+            min_repeat,
+            strip_timestamps,
+        } => commands::cmd_log(
+            &command,
+            &commands::LogOptions {
+                window,
+                min_repeat,
+                strip_timestamps,
+            },
+        ),
+        _ => unreachable!("not a shortcut command"),
     }
-
-    Ok(())
 }
 
-// ---------------------------------------------------------------------------
-// Which
-// ---------------------------------------------------------------------------
-
-fn cmd_which(command: &[String]) -> Result<()> {
-    match crux_core::config::resolve_filter(command) {
-        Some(config) => {
-            println!("Filter:      {}", config.command);
-            if let Some(desc) = &config.description {
-                println!("Description: {desc}");
+/// Installing crux into shells and agents, and keeping it up to date.
+fn dispatch_setup(command: Commands, json: bool) -> Result<()> {
+    match command {
+        Commands::Alias {
+            shell,
+            prefix,
+            wrap,
+        } => alias::cmd_alias(shell, &prefix, &wrap),
+        Commands::ShellInit { shell, allow } => shell_init::cmd_shell_init(shell, &allow),
+        Commands::Init {
+            global,
+            codex,
+            cursor,
+            zed,
+            uninstall,
+            print,
+        } => {
+            if uninstall {
+                commands::cmd_uninstall(global, print)
+            } else if cursor {
+                crux_hook::cursor::install_cursor(std::path::Path::new(""), print)
+            } else if zed {
+                crux_hook::zed::install_zed(std::path::Path::new(""), print)
+            } else {
+                commands::cmd_init(global, codex, print)
             }
-            println!("Priority:    {}", config.priority);
-        }
-        None => {
-            println!("No filter matches: {}", command.join(" "));
         }
+        Commands::Doctor { fix } => commands::cmd_doctor(fix),
+        Commands::Upgrade { check } => upgrade::cmd_upgrade(check),
+        Commands::Hook { command } => match command {
+            HookCommand::Handle { format } => commands::cmd_hook_handle(format),
+            HookCommand::Compact => commands::cmd_hook_compact(),
+            HookCommand::Status => commands::cmd_hook_status(json),
+            HookCommand::Remove { target } => commands::cmd_hook_remove(target),
+        },
+        _ => unreachable!("not a setup command"),
     }
-    Ok(())
 }
 
-// ---------------------------------------------------------------------------
-// Helpers
-// ---------------------------------------------------------------------------
+/// Finding, inspecting, writing and sharing filters.
+fn dispatch_filters(command: Commands, json: bool) -> Result<()> {
+    match command {
+        Commands::Ls => commands::cmd_ls(json),
+        Commands::Search { term } => commands::cmd_search(&term.join(" "), json),
+        Commands::Which { command, all } => {
+            if all {
+                commands::cmd_which_all(&command, json)
+            } else {
+                commands::cmd_which(&command, json)
+            }
+        }
+        Commands::Explain { command } => commands::cmd_explain(&command),
+        Commands::Show {
+            filter,
+            as_toml,
+            tests,
+        } => commands::cmd_show(&filter, json, as_toml, tests),
+        Commands::Eject {
+            filter,
+            out,
+            all,
+            force,
+        } => commands::cmd_eject(filter.as_deref(), out.as_deref(), all, force),
+        Commands::New {
+            command,
+            dir,
+            force,
+        } => commands::cmd_new(&command, &dir, force),
+        Commands::Lint => commands::cmd_lint(json),
+        Commands::Pack { command } => match command {
+            PackCommand::Export {
+                file,
+                from,
+                filters,
+            } => pack::cmd_export(&file, &from, &filters),
+            PackCommand::Import {
+                source,
+                local,
+                force,
+            } => pack::cmd_import(&source, local, force),
+        },
+        Commands::Verify => commands::cmd_verify(),
+        Commands::Bench { filter, iterations } => bench::cmd_bench(filter.as_deref(), iterations),
+        _ => unreachable!("routed by dispatch"),
+    }
+}
 
 fn truncate_str(s: &str, max: usize) -> String {
     if s.len() <= max {
@@ -359,24 +318,3 @@ fn truncate_str(s: &str, max: usize) -> String {
         format!("{}…", &s[..max - 1])
     }
 }
-
-// ---------------------------------------------------------------------------
-// Hook handle — process Claude Code PreToolUse hook from stdin
-// ---------------------------------------------------------------------------
-
-fn cmd_hook_handle() -> Result<()> {
-    // Read all of stdin
-    let mut input_str = String::new();
-    std::io::stdin().read_to_string(&mut input_str).ok();
-
-    // Parse and handle — silent on any error (never block Claude Code)
-    if let Ok(input) = serde_json::from_str::<crux_hook::claude::HookInput>(&input_str) {
-        if let Some(output) = crux_hook::claude::handle_hook(&input) {
-            if let Ok(json) = serde_json::to_string(&output) {
-                println!("{json}");
-            }
-        }
-    }
-
-    Ok(())
-}
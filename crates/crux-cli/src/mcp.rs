@@ -0,0 +1,306 @@
+//! `crux serve --mcp` — a Model Context Protocol server on stdio, so agents
+//! that speak MCP can use crux without a shell hook (`mcp` feature).
+//!
+//! Messages are JSON-RPC 2.0, one per line. Tools:
+//!
+//! - `run_filtered(command)`: run a shell command, return its filtered output
+//! - `filter_text(text, as_command)`: filter text as `as_command`'s output
+//! - `gain_summary(since)`: token savings so far (`tracking` feature)
+
+use std::io::{BufRead, Write};
+use std::time::Instant;
+
+use anyhow::{ensure, Context, Result};
+use serde_json::{json, Value};
+
+/// Protocol revision answered when the client doesn't ask for one, or asks
+/// for one not in [`SUPPORTED_VERSIONS`].
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Revisions whose `tools/list` and `tools/call` match what this server
+/// speaks, so a client asking for one of them gets it back.
+const SUPPORTED_VERSIONS: &[&str] = &[PROTOCOL_VERSION, "2025-03-26", "2025-06-18"];
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+pub fn cmd_serve_mcp() -> Result<()> {
+    let stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    for line in stdin.lines() {
+        let line = line.context("reading stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(&message),
+            Err(e) => Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Answer one JSON-RPC message. Notifications and responses (no `id` or no
+/// `method`) get no answer.
+fn handle_message(message: &Value) -> Option<Value> {
+    let id = message.get("id")?.clone();
+    let method = message.get("method")?.as_str().unwrap_or_default();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => call_tool(&params),
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method {method:?}"))),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error_response(id, code, &message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn initialize(params: &Value) -> Value {
+    let version = params
+        .get("protocolVersion")
+        .and_then(Value::as_str)
+        .filter(|version| SUPPORTED_VERSIONS.contains(version))
+        .unwrap_or(PROTOCOL_VERSION);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "crux", "version": crux_core::VERSION },
+    })
+}
+
+fn tools() -> Vec<Value> {
+    let mut tools = vec![
+        json!({
+            "name": "run_filtered",
+            "description": "Run a shell command and return its output filtered by crux: \
+                            noise removed, failures and summaries kept.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string", "description": "Shell command line" },
+                },
+                "required": ["command"],
+            },
+        }),
+        json!({
+            "name": "filter_text",
+            "description": "Filter text as if it were the output of a command.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string", "description": "Output to filter" },
+                    "as_command": {
+                        "type": "string",
+                        "description": "Command that produced it, e.g. \"cargo test\"",
+                    },
+                    "exit_code": { "type": "integer", "description": "Its exit code (default 0)" },
+                },
+                "required": ["text", "as_command"],
+            },
+        }),
+    ];
+    if cfg!(feature = "tracking") {
+        tools.push(json!({
+            "name": "gain_summary",
+            "description": "Bytes and tokens crux has saved so far.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "since": {
+                        "type": "string",
+                        "description": "Only count runs since e.g. 7d, 12h or 2026-10-01",
+                    },
+                },
+            },
+        }));
+    }
+    tools
+}
+
+/// Run a tool. Unknown tools and missing arguments are protocol errors;
+/// a tool that fails reports it in its result, for the agent to read.
+fn call_tool(params: &Value) -> Result<Value, (i64, String)> {
+    let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+    let args = params.get("arguments").cloned().unwrap_or(json!({}));
+    let arg = |key: &str| {
+        args.get(key).and_then(Value::as_str).ok_or_else(|| {
+            (
+                INVALID_PARAMS,
+                format!("{name}: missing string argument {key:?}"),
+            )
+        })
+    };
+
+    let outcome = match name {
+        "run_filtered" => run_filtered(arg("command")?),
+        "filter_text" => {
+            let exit_code = args.get("exit_code").and_then(Value::as_i64).unwrap_or(0);
+            filter_text(arg("text")?, arg("as_command")?, exit_code as i32)
+        }
+        #[cfg(feature = "tracking")]
+        "gain_summary" => gain_summary(args.get("since").and_then(Value::as_str)),
+        _ => return Err((INVALID_PARAMS, format!("unknown tool {name:?}"))),
+    };
+    Ok(match outcome {
+        Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+        Err(e) => {
+            json!({ "content": [{ "type": "text", "text": format!("{e:#}") }], "isError": true })
+        }
+    })
+}
+
+/// Run `command` through `sh -c` with the filter its words pick, recording
+/// it like `crux run` does.
+fn run_filtered(command: &str) -> Result<String> {
+    let words: Vec<String> = command.split_whitespace().map(String::from).collect();
+    ensure!(!words.is_empty(), "command is empty");
+    let start = Instant::now();
+
+    let filter = crate::run::resolve_run_filter(&words);
+    let shell = ["sh", "-c", command].map(String::from);
+    let result = crux_core::runner::run_command(&shell)?;
+    let raw_output = &result.combined;
+    let filter =
+        filter.map(|config| crate::run::select_variant_post(config, raw_output, result.exit_code));
+
+    let filter_start = Instant::now();
+    let (filtered, raw_fallback, stage_savings) =
+        crate::run::filter_output_with_savings(filter.as_ref(), raw_output, result.exit_code);
+    let filter_elapsed = filter_start.elapsed();
+
+    #[cfg(feature = "tracking")]
+    if crux_core::config::settings::settings().tracking_enabled() && !crate::run::no_track_env() {
+        let source = std::env::var("CRUX_SOURCE")
+            .ok()
+            .filter(|source| !source.trim().is_empty())
+            .unwrap_or_else(|| "mcp".to_string());
+        crate::run::record_in_background(&crate::run::RunRecord {
+            command: command.to_string(),
+            filter_name: filter.as_ref().map(|f| f.command.clone()),
+            input_bytes: raw_output.len(),
+            output_bytes: filtered.len(),
+            exit_code: result.exit_code,
+            duration_ms: start.elapsed().as_millis() as u64,
+            filter_duration_us: filter_elapsed.as_micros() as u64,
+            raw_output: raw_output.to_string(),
+            filtered_output: filtered.clone(),
+            raw_fallback,
+            stage_savings,
+            source,
+        });
+    }
+    #[cfg(not(feature = "tracking"))]
+    let _ = (start, filter_elapsed, raw_fallback, stage_savings);
+
+    Ok(with_exit_code(filtered, result.exit_code))
+}
+
+fn with_exit_code(mut output: String, exit_code: i32) -> String {
+    if exit_code != 0 {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str(&format!("(exit code {exit_code})"));
+    }
+    output
+}
+
+/// Filter `text` like `crux filter --as <as_command>`.
+fn filter_text(text: &str, as_command: &str, exit_code: i32) -> Result<String> {
+    let command: Vec<String> = as_command.split_whitespace().map(String::from).collect();
+    ensure!(!command.is_empty(), "as_command is empty");
+    let filter = crate::run::resolve_run_filter(&command)
+        .map(|config| crate::run::select_variant_post(config, text, exit_code));
+    Ok(crate::run::filter_output(filter.as_ref(), text, exit_code).0)
+}
+
+#[cfg(feature = "tracking")]
+fn gain_summary(since: Option<&str>) -> Result<String> {
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    let range = crux_tracking::events::TimeRange::parse(&conn, since, None)?;
+    let summary = crux_tracking::get_gain_summary(&conn, &range)?;
+    Ok(serde_json::to_string_pretty(&summary)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, params: Value) -> Value {
+        handle_message(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .expect("requests are answered")
+    }
+
+    #[test]
+    fn initialize_and_list_tools() {
+        let init = request("initialize", json!({ "protocolVersion": "2025-03-26" }));
+        assert_eq!(init["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(init["result"]["serverInfo"]["name"], "crux");
+        let init = request("initialize", json!({ "protocolVersion": "1999-01-01" }));
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+        let list = request("tools/list", json!({}));
+        let names: Vec<_> = list["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert!(names.starts_with(&["run_filtered", "filter_text"]));
+    }
+
+    #[test]
+    fn notifications_get_no_answer_and_unknown_methods_an_error() {
+        let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert_eq!(handle_message(&initialized), None);
+        assert_eq!(
+            request("resources/list", json!({}))["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn filter_text_tool_filters_like_the_command() {
+        let text = "   Compiling foo v0.1.0 (/p)\n    Finished `dev` profile [unoptimized] target(s) in 1.2s\n";
+        let response = request(
+            "tools/call",
+            json!({ "name": "filter_text", "arguments": { "text": text, "as_command": "cargo build" } }),
+        );
+        assert_eq!(response["result"]["isError"], false);
+        let filtered = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(!filtered.contains("Compiling"), "got: {filtered}");
+
+        let missing = request(
+            "tools/call",
+            json!({ "name": "filter_text", "arguments": {} }),
+        );
+        assert_eq!(missing["error"]["code"], INVALID_PARAMS);
+        let unknown = request("tools/call", json!({ "name": "rm_rf", "arguments": {} }));
+        assert_eq!(unknown["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn failing_commands_report_their_exit_code() {
+        assert_eq!(with_exit_code("boom".to_string(), 2), "boom\n(exit code 2)");
+        assert_eq!(with_exit_code("ok\n".to_string(), 0), "ok\n");
+    }
+}
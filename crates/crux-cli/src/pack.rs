@@ -0,0 +1,303 @@
+//! `crux pack` — share filter sets as `.tar.gz` bundles of TOMLs and their
+//! `_test/` directories. Archiving and downloads go through the system `tar`
+//! and `curl`.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crux_core::config::FilterConfig;
+
+use crate::commands::home_dir;
+
+pub fn cmd_export(file: &Path, from: &Path, filters: &[String]) -> Result<()> {
+    let entries = pack_entries(from, filters)?;
+    let toml_count = entries
+        .iter()
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("toml"))
+        .count();
+
+    // The archive path is opened after `-C`, so make it absolute first
+    let file = std::env::current_dir()?.join(file);
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&file)
+        .arg("-C")
+        .arg(from)
+        .args(&entries)
+        .status()
+        .context("running tar")?;
+    if !status.success() {
+        bail!("tar failed to write {}", file.display());
+    }
+    println!(
+        "Packed {toml_count} filter(s) from {} into {}",
+        from.display(),
+        file.display()
+    );
+    Ok(())
+}
+
+pub fn cmd_import(source: &str, local: bool, force: bool) -> Result<()> {
+    let dest = if local {
+        PathBuf::from(".crux/filters")
+    } else {
+        home_dir()
+            .context("cannot determine home directory")?
+            .join(".config/crux/filters")
+    };
+
+    // A fresh, private directory (removed on drop), so nothing else can
+    // plant files or links where the pack is downloaded and unpacked.
+    let work = tempfile::Builder::new()
+        .prefix("crux-pack-")
+        .tempdir()
+        .context("creating a temporary directory")?;
+    std::fs::create_dir(work.path().join("contents"))?;
+    let commands = import_into(source, work.path(), &dest, force)?;
+    println!(
+        "Installed {} filter(s) into {}: {}",
+        commands.len(),
+        dest.display(),
+        commands.into_iter().collect::<Vec<_>>().join(", ")
+    );
+    Ok(())
+}
+
+/// Fetch and unpack `source` under `work`, then install it into `dest`.
+/// Returns the commands of the installed filters.
+fn import_into(source: &str, work: &Path, dest: &Path, force: bool) -> Result<BTreeSet<String>> {
+    if source.starts_with("http://") {
+        bail!("{source}: packs are only downloaded over https://");
+    }
+    let archive = if source.starts_with("https://") {
+        let archive = work.join("pack.tar.gz");
+        let status = Command::new("curl")
+            .args(["-fsSL", "--proto", "=https", "-o"])
+            .arg(&archive)
+            .arg(source)
+            .status()
+            .context("running curl")?;
+        if !status.success() {
+            bail!("failed to download {source}");
+        }
+        archive
+    } else {
+        let archive = PathBuf::from(source);
+        if !archive.is_file() {
+            bail!("{source}: no such file");
+        }
+        archive
+    };
+
+    let contents = work.join("contents");
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&contents)
+        .status()
+        .context("running tar")?;
+    if !status.success() {
+        bail!("{source} is not a valid .tar.gz pack");
+    }
+    install(&contents, dest, force)
+}
+
+/// The paths (relative to `from`) to pack: each filter TOML, or only those
+/// whose `command` is in `filters`, plus its `<name>_test/` directory.
+fn pack_entries(from: &Path, filters: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_files(from, from, &mut files)?;
+
+    let wanted: BTreeSet<String> = filters
+        .iter()
+        .map(|f| f.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    let mut found = BTreeSet::new();
+    let mut entries = Vec::new();
+    for rel in files {
+        if rel.extension().and_then(|e| e.to_str()) != Some("toml") || in_test_dir(&rel) {
+            continue;
+        }
+        let config = parse_filter(&from.join(&rel))?;
+        if !wanted.is_empty() && !wanted.contains(&config.command) {
+            continue;
+        }
+        found.insert(config.command);
+        let test_dir = rel.with_file_name(format!(
+            "{}_test",
+            rel.file_stem().unwrap_or_default().to_string_lossy()
+        ));
+        entries.push(rel);
+        if from.join(&test_dir).is_dir() {
+            entries.push(test_dir);
+        }
+    }
+
+    let missing: Vec<&String> = wanted.difference(&found).collect();
+    if !missing.is_empty() {
+        bail!(
+            "no filter for {} in {}",
+            missing
+                .iter()
+                .map(|m| format!("'{m}'"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            from.display()
+        );
+    }
+    if entries.is_empty() {
+        bail!("no filter TOMLs found in {}", from.display());
+    }
+    Ok(entries)
+}
+
+/// Copy an unpacked pack into `dest`. Every filter TOML must parse, and
+/// nothing is written if a file would be overwritten without `force`.
+fn install(contents: &Path, dest: &Path, force: bool) -> Result<BTreeSet<String>> {
+    let mut files = Vec::new();
+    collect_files(contents, contents, &mut files)?;
+
+    let mut commands = BTreeSet::new();
+    for rel in &files {
+        if rel.extension().and_then(|e| e.to_str()) == Some("toml") && !in_test_dir(rel) {
+            commands.insert(parse_filter(&contents.join(rel))?.command);
+        }
+    }
+    if commands.is_empty() {
+        bail!("the pack contains no filter TOMLs");
+    }
+
+    if !force {
+        let existing: Vec<String> = files
+            .iter()
+            .map(|rel| dest.join(rel))
+            .filter(|path| path.exists())
+            .map(|path| path.display().to_string())
+            .collect();
+        if !existing.is_empty() {
+            bail!("would overwrite {} (use --force)", existing.join(", "));
+        }
+    }
+
+    for rel in &files {
+        let target = dest.join(rel);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("creating {}", parent.display()))?;
+        }
+        std::fs::copy(contents.join(rel), &target)
+            .with_context(|| format!("writing {}", target.display()))?;
+    }
+    Ok(commands)
+}
+
+/// Regular files under `dir`, relative to `root`. Symlinks are skipped so a
+/// pack can't point outside the filter directory.
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("reading directory {}", dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        let meta = std::fs::symlink_metadata(&path)?;
+        if meta.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if meta.is_file() {
+            out.push(path.strip_prefix(root)?.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+fn in_test_dir(rel: &Path) -> bool {
+    rel.parent().is_some_and(|parent| {
+        parent
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().ends_with("_test"))
+    })
+}
+
+fn parse_filter(path: &Path) -> Result<FilterConfig> {
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    toml::from_str(&contents).with_context(|| format!("parsing {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crux-pack-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("make_test")).unwrap();
+        std::fs::write(dir.join("make.toml"), "command = \"make\"\ntail = 5\n").unwrap();
+        std::fs::write(dir.join("make_test/input.txt"), "x\n").unwrap();
+        std::fs::write(dir.join("ls.toml"), "command = \"ls\"\nhead = 5\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn packs_selected_filters_with_their_tests() {
+        let dir = scratch("entries");
+        let all = pack_entries(&dir, &[]).unwrap();
+        assert_eq!(
+            all,
+            vec![
+                PathBuf::from("ls.toml"),
+                PathBuf::from("make.toml"),
+                PathBuf::from("make_test")
+            ]
+        );
+        let some = pack_entries(&dir, &["make".to_string()]).unwrap();
+        assert_eq!(some.len(), 2);
+        let err = pack_entries(&dir, &["cargo test".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("'cargo test'"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn install_refuses_to_overwrite_without_force() {
+        let contents = scratch("contents");
+        let dest = std::env::temp_dir().join(format!("crux-pack-dest-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dest);
+
+        let commands = install(&contents, &dest, false).unwrap();
+        assert_eq!(
+            commands.into_iter().collect::<Vec<_>>(),
+            vec!["ls".to_string(), "make".to_string()]
+        );
+        assert!(dest.join("make_test/input.txt").is_file());
+
+        let err = install(&contents, &dest, false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        install(&contents, &dest, true).unwrap();
+
+        std::fs::remove_dir_all(&contents).unwrap();
+        std::fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn import_refuses_plain_http() {
+        let work = tempfile::tempdir().unwrap();
+        let dest = work.path().join("dest");
+        let err =
+            import_into("http://example.com/pack.tar.gz", work.path(), &dest, false).unwrap_err();
+        assert!(err.to_string().contains("https://"));
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn install_rejects_invalid_filters() {
+        let contents = scratch("invalid");
+        std::fs::write(contents.join("bad.toml"), "command = 5\n").unwrap();
+        let dest = contents.join("dest-never-written");
+        assert!(install(&contents, &dest, false).is_err());
+        assert!(!dest.exists());
+        std::fs::remove_dir_all(&contents).unwrap();
+    }
+}
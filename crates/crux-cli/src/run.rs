@@ -0,0 +1,543 @@
+//! `crux run` and `crux filter` — run a command (or read stdin) through the
+//! filter pipeline, and record the run for `crux gain` and `crux history`.
+
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use crux_core::config::settings::Settings;
+use crux_core::config::FilterConfig;
+
+/// Flags of `crux run`.
+pub struct RunOptions<'a> {
+    pub show_time: bool,
+    pub filter_name: Option<&'a str>,
+    pub raw: bool,
+    pub show_tokens: bool,
+    pub budget: Option<Budget>,
+    pub json: bool,
+    pub no_track: bool,
+}
+
+/// A hard cap on `crux run` output, applied after every filter.
+#[derive(Debug, Clone, Copy)]
+pub enum Budget {
+    Tokens(usize),
+    Bytes(usize),
+}
+
+impl Budget {
+    fn apply(self, output: &str) -> String {
+        match self {
+            Budget::Tokens(n) => crux_core::filter::truncate::apply_max_tokens(output, n),
+            Budget::Bytes(n) => crux_core::filter::truncate::apply_max_bytes(output, n),
+        }
+    }
+}
+
+/// `2000` is a token budget; a `b` suffix (`8000b`) makes it bytes.
+pub fn parse_budget(s: &str) -> Result<Budget, String> {
+    let s = s.trim();
+    let parsed = match s.strip_suffix(['b', 'B']) {
+        Some(bytes) => bytes.parse().map(Budget::Bytes),
+        None => s.parse().map(Budget::Tokens),
+    };
+    parsed.map_err(|_| format!("invalid budget '{s}' (expected e.g. 2000 tokens or 8000b)"))
+}
+
+/// Filtered output of one run, with what the filter did to it.
+struct Filtered {
+    output: String,
+    raw_fallback: bool,
+    stage_savings: Vec<(String, i64)>,
+}
+
+pub fn cmd_run(command: &[String], options: &RunOptions) -> Result<()> {
+    let wall_start = Instant::now();
+    let settings = crux_core::config::settings::settings();
+
+    let filter = run_filter(command, options)?;
+
+    let exec_start = Instant::now();
+    let result = crux_core::runner::run_command(command)?;
+    let exec_elapsed = exec_start.elapsed();
+    let raw_output = &result.combined;
+    let filter = filter.map(|config| select_variant_post(config, raw_output, result.exit_code));
+
+    let filter_start = Instant::now();
+    let filtered = filter_run_output(filter.as_ref(), raw_output, result.exit_code, options);
+    let filter_elapsed = filter_start.elapsed();
+
+    if !filtered.raw_fallback && !options.raw {
+        save_raw_output(&filter, settings, command, raw_output, result.exit_code);
+    }
+    if options.json {
+        let duration = wall_start.elapsed();
+        print_envelope(
+            command,
+            result.exit_code,
+            raw_output,
+            &filtered.output,
+            filter.as_ref(),
+            duration,
+        )?;
+    } else {
+        print_output(&filtered.output, result.exit_code);
+        print_savings(raw_output, &filtered.output, options.show_tokens, settings);
+    }
+    if options.show_time {
+        let sizes = (raw_output.len(), filtered.output.len());
+        print_timing(exec_elapsed, filter_elapsed, wall_start.elapsed(), sizes);
+    }
+
+    // Recorded last, once everything the caller reads has been written
+    #[cfg(feature = "tracking")]
+    if settings.tracking_enabled() && !options.no_track && !no_track_env() {
+        record_in_background(&RunRecord {
+            command: command.join(" "),
+            filter_name: filter.as_ref().map(|f| f.command.clone()),
+            input_bytes: raw_output.len(),
+            output_bytes: filtered.output.len(),
+            exit_code: result.exit_code,
+            duration_ms: wall_start.elapsed().as_millis() as u64,
+            filter_duration_us: filter_elapsed.as_micros() as u64,
+            raw_output: raw_output.to_string(),
+            filtered_output: filtered.output,
+            raw_fallback: filtered.raw_fallback,
+            stage_savings: filtered.stage_savings,
+            source: run_source(),
+        });
+    }
+    #[cfg(not(feature = "tracking"))]
+    let _ = (options.no_track, filtered.stage_savings);
+    Ok(())
+}
+
+/// The filter `--filter` names, none for `--raw`, or else the resolved one.
+fn run_filter(command: &[String], options: &RunOptions) -> Result<Option<FilterConfig>> {
+    Ok(match options.filter_name {
+        _ if options.raw => None,
+        Some(name) => Some(
+            crux_core::config::find_filter_by_name(name)
+                .with_context(|| format!("no filter named '{name}'"))?,
+        ),
+        None => resolve_run_filter(command),
+    })
+}
+
+/// Filter the output of `crux run`, then cap it at the `--budget`.
+fn filter_run_output(
+    filter: Option<&FilterConfig>,
+    raw_output: &str,
+    exit_code: i32,
+    options: &RunOptions,
+) -> Filtered {
+    // --raw skips even the passthrough fallback, but the run is still
+    // recorded (unfiltered).
+    let (output, raw_fallback, mut stage_savings) = if options.raw {
+        (raw_output.to_string(), false, Vec::new())
+    } else {
+        filter_output_with_savings(filter, raw_output, exit_code)
+    };
+    let output = match options.budget {
+        Some(budget) => {
+            let capped = budget.apply(&output);
+            let removed = output.len() as i64 - capped.len() as i64;
+            if removed != 0 {
+                stage_savings.push(("budget".to_string(), removed));
+            }
+            capped
+        }
+        None => output,
+    };
+    Filtered {
+        output,
+        raw_fallback,
+        stage_savings,
+    }
+}
+
+/// `crux run --json`: everything the text mode reports on stderr, in one
+/// envelope.
+fn print_envelope(
+    command: &[String],
+    exit_code: i32,
+    raw_output: &str,
+    output: &str,
+    filter: Option<&FilterConfig>,
+    duration: Duration,
+) -> Result<()> {
+    let envelope = serde_json::json!({
+        "command": command.join(" "),
+        "exit_code": exit_code,
+        "filtered_output": output,
+        "input_bytes": raw_output.len(),
+        "output_bytes": output.len(),
+        "filter": filter.map(|config| config.command.as_str()),
+        "duration_ms": duration.as_millis() as u64,
+    });
+    println!("{}", serde_json::to_string(&envelope)?);
+    Ok(())
+}
+
+/// Print filtered output, ending it with a newline, and report a failure.
+fn print_output(output: &str, exit_code: i32) {
+    print!("{output}");
+    if !output.ends_with('\n') && !output.is_empty() {
+        println!();
+    }
+    if exit_code != 0 {
+        eprintln!("crux: exit code {exit_code}");
+    }
+}
+
+/// Report on stderr how much filtering saved, in tokens too when asked.
+fn print_savings(raw_output: &str, filtered: &str, show_tokens: bool, settings: &Settings) {
+    let (input_bytes, output_bytes) = (raw_output.len(), filtered.len());
+    if input_bytes == 0 || input_bytes == output_bytes {
+        return;
+    }
+    let saved_pct = ((input_bytes as f64 - output_bytes as f64) / input_bytes as f64) * 100.0;
+    if show_tokens || settings.show_tokens == Some(true) {
+        let tokenizer = settings.tokenizer.unwrap_or_default();
+        eprintln!(
+            "crux: {input_bytes} → {output_bytes} bytes, ≈{} → ≈{} tokens ({saved_pct:.0}% saved)",
+            tokenizer.count(raw_output),
+            tokenizer.count(filtered)
+        );
+    } else {
+        eprintln!("crux: {input_bytes} → {output_bytes} bytes ({saved_pct:.0}% saved)");
+    }
+}
+
+/// `crux run --time`: where the time went, and the input and output sizes.
+fn print_timing(exec: Duration, filter: Duration, wall: Duration, sizes: (usize, usize)) {
+    eprintln!("crux: timing breakdown:");
+    eprintln!("  command execution: {:.3}ms", exec.as_secs_f64() * 1000.0);
+    eprintln!("  filter pipeline:  {:.3}ms", filter.as_secs_f64() * 1000.0);
+    eprintln!("  total wall time:  {:.3}ms", wall.as_secs_f64() * 1000.0);
+    eprintln!("  input size:       {} bytes", sizes.0);
+    eprintln!("  output size:      {} bytes", sizes.1);
+}
+
+pub fn cmd_filter(as_command: &str, exit_code: i32) -> Result<()> {
+    let command: Vec<String> = as_command.split_whitespace().map(String::from).collect();
+    if command.is_empty() {
+        bail!("--as needs a command, e.g. --as \"cargo test\"");
+    }
+
+    let mut raw_output = String::new();
+    std::io::stdin()
+        .read_to_string(&mut raw_output)
+        .context("reading stdin")?;
+
+    let filter = resolve_run_filter(&command)
+        .map(|config| select_variant_post(config, &raw_output, exit_code));
+    let (filtered, _) = filter_output(filter.as_ref(), &raw_output, exit_code);
+
+    print!("{filtered}");
+    if !filtered.ends_with('\n') && !filtered.is_empty() {
+        println!();
+    }
+
+    let (input_bytes, output_bytes) = (raw_output.len(), filtered.len());
+    if input_bytes > 0 && input_bytes != output_bytes {
+        let saved_pct = ((input_bytes as f64 - output_bytes as f64) / input_bytes as f64) * 100.0;
+        eprintln!("crux: {input_bytes} → {output_bytes} bytes ({saved_pct:.0}% saved)");
+    }
+    Ok(())
+}
+
+/// Resolve the filter for `command`, switching to an argument or file variant.
+pub(crate) fn resolve_run_filter(command: &[String]) -> Option<FilterConfig> {
+    crux_core::config::resolve_filter(command).map(|config| {
+        crux_core::filter::variant::select_variant_pre(&config, command).unwrap_or(config)
+    })
+}
+
+/// Switch to the variant filter picked by the output and exit code, if any.
+pub(crate) fn select_variant_post(
+    config: FilterConfig,
+    raw_output: &str,
+    exit_code: i32,
+) -> FilterConfig {
+    crux_core::filter::variant::detect_variant_post(&config, raw_output, exit_code)
+        .and_then(|name| crux_core::config::find_filter_by_name(&name))
+        .unwrap_or(config)
+}
+
+/// Filter raw output with `filter`, or the passthrough fallback without one.
+///
+/// Returns the output to show and whether the raw output was kept because
+/// the filter saved less than its `min_savings_pct`.
+pub(crate) fn filter_output(
+    filter: Option<&FilterConfig>,
+    raw_output: &str,
+    exit_code: i32,
+) -> (String, bool) {
+    let Some(config) = filter else {
+        return (passthrough_output(raw_output, exit_code), false);
+    };
+    let filtered = crux_core::filter::apply_filter(config, raw_output, exit_code);
+    if crux_core::filter::meets_min_savings(config, raw_output, &filtered) {
+        (filtered, false)
+    } else {
+        (raw_output.to_string(), true)
+    }
+}
+
+/// Like [`filter_output`], also returning the bytes each pipeline stage
+/// removed (nothing for the passthrough fallback or a raw fallback).
+pub(crate) fn filter_output_with_savings(
+    filter: Option<&FilterConfig>,
+    raw_output: &str,
+    exit_code: i32,
+) -> (String, bool, Vec<(String, i64)>) {
+    let Some(config) = filter else {
+        return (passthrough_output(raw_output, exit_code), false, Vec::new());
+    };
+    let (filtered, savings) =
+        crux_core::filter::apply_filter_with_savings(config, raw_output, exit_code);
+    if crux_core::filter::meets_min_savings(config, raw_output, &filtered) {
+        (filtered, false, savings)
+    } else {
+        (raw_output.to_string(), true, Vec::new())
+    }
+}
+
+/// Output shown when no filter matched: the fallback, capped by `max_bytes`.
+fn passthrough_output(raw_output: &str, exit_code: i32) -> String {
+    let output = crux_core::filter::apply_fallback(raw_output, exit_code);
+    match crux_core::config::settings::settings().max_bytes {
+        Some(max) => crux_core::filter::truncate::apply_max_bytes(&output, max),
+        None => output,
+    }
+}
+
+/// Tee the raw output to a file when the filter (or the config file) asks
+/// for it, so nothing the filter dropped is lost.
+fn save_raw_output(
+    filter: &Option<FilterConfig>,
+    settings: &Settings,
+    command: &[String],
+    raw_output: &str,
+    exit_code: i32,
+) {
+    let (mode, dir) = match filter {
+        Some(config) => (config.tee.as_ref(), config.tee_dir.as_deref()),
+        None => (settings.tee.as_ref(), settings.tee_dir.as_deref()),
+    };
+    let Some(mode) = mode else {
+        return;
+    };
+    let dir = crux_core::filter::tee::tee_dir(dir);
+    if let Some(path) = crux_core::filter::tee::maybe_save_tee(
+        mode,
+        &dir,
+        &command.join(" "),
+        raw_output,
+        exit_code,
+    ) {
+        eprintln!("crux: raw output saved to {}", path.display());
+    }
+}
+
+/// `CRUX_NO_TRACK` set to anything but empty or `0`.
+#[cfg(feature = "tracking")]
+pub(crate) fn no_track_env() -> bool {
+    std::env::var_os("CRUX_NO_TRACK").is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Load the history key (`history.key` in the data directory) so encrypted
+/// outputs can be read, sealing new ones when `encrypt_history` is set.
+#[cfg(feature = "tracking")]
+pub(crate) fn init_history_encryption() -> Result<()> {
+    let key_file = crux_tracking::db::data_dir().join("history.key");
+    let encrypt = crux_core::config::settings::settings().encrypt_history == Some(true);
+    crux_tracking::crypto::init(&key_file, encrypt)
+}
+
+/// What invoked this run: `CRUX_SOURCE` when set (the Codex wrapper sets
+/// `codex`), `claude-hook` inside Claude Code, whose hook is what routes
+/// its commands through crux, and `cli` otherwise.
+#[cfg(feature = "tracking")]
+fn run_source() -> String {
+    match std::env::var("CRUX_SOURCE") {
+        Ok(source) if !source.trim().is_empty() => source.trim().to_string(),
+        _ if std::env::var_os("CLAUDECODE").is_some() => "claude-hook".to_string(),
+        _ => "cli".to_string(),
+    }
+}
+
+/// Everything recorded about one `crux run`, passed to the background
+/// `crux tracking record` process as JSON.
+#[cfg(feature = "tracking")]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct RunRecord {
+    pub(crate) command: String,
+    pub(crate) filter_name: Option<String>,
+    pub(crate) input_bytes: usize,
+    pub(crate) output_bytes: usize,
+    pub(crate) exit_code: i32,
+    pub(crate) duration_ms: u64,
+    pub(crate) filter_duration_us: u64,
+    pub(crate) raw_output: String,
+    pub(crate) filtered_output: String,
+    pub(crate) raw_fallback: bool,
+    pub(crate) stage_savings: Vec<(String, i64)>,
+    pub(crate) source: String,
+}
+
+/// Hand `record` to a detached `crux tracking record` so the database
+/// write (and token counting) doesn't delay the caller, who waits for this
+/// process to exit. Records inline if the child can't be started, or when
+/// `CRUX_TRACK_SYNC` is set, for scripts that read history right after a run.
+/// With `CRUX_DEBUG` set, failures of the background write show on stderr.
+#[cfg(feature = "tracking")]
+pub(crate) fn record_in_background(record: &RunRecord) {
+    if !env_flag("CRUX_TRACK_SYNC") {
+        match spawn_recorder(record) {
+            Ok(()) => return,
+            Err(e) if env_flag("CRUX_DEBUG") => {
+                eprintln!("crux: background tracking failed, recording inline: {e:#}");
+            }
+            Err(_) => {}
+        }
+    }
+    if let Err(e) = record_tracking_and_history(record) {
+        eprintln!("crux: tracking error: {e}");
+    }
+}
+
+/// Write `record` to a private temp file and start `crux tracking record
+/// --file` on it, so a large output never waits on a pipe. A detached thread
+/// reaps the child, which matters for long-lived callers like `crux serve`.
+#[cfg(feature = "tracking")]
+fn spawn_recorder(record: &RunRecord) -> Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut file = tempfile::Builder::new()
+        .prefix("crux-record-")
+        .suffix(".json")
+        .tempfile()
+        .context("creating a temporary file")?;
+    file.write_all(&serde_json::to_vec(record)?)?;
+    let path = file.into_temp_path();
+
+    // The child must not hold our stdout/stderr open, or a caller reading
+    // them to EOF would wait for it anyway; CRUX_DEBUG trades that for
+    // seeing its errors.
+    let stderr = if env_flag("CRUX_DEBUG") {
+        Stdio::inherit()
+    } else {
+        Stdio::null()
+    };
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(["tracking", "record", "--file"])
+        .arg(&path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(stderr)
+        .spawn()
+        .context("starting crux tracking record")?;
+    // From here on the child owns the file and deletes it
+    path.keep()?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// True if the environment variable `name` is set to something other than
+/// empty or `0`.
+#[cfg(feature = "tracking")]
+fn env_flag(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// `crux tracking record`: store a [`RunRecord`] read from `file` (which is
+/// then deleted) or from stdin.
+#[cfg(feature = "tracking")]
+pub fn cmd_tracking_record(file: Option<&std::path::Path>) -> Result<()> {
+    let record: RunRecord = match file {
+        Some(path) => {
+            let contents =
+                std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+            let _ = std::fs::remove_file(path);
+            serde_json::from_slice(&contents)
+        }
+        None => serde_json::from_reader(std::io::stdin().lock()),
+    }
+    .context("reading run record")?;
+    record_tracking_and_history(&record)
+}
+
+#[cfg(feature = "tracking")]
+fn record_tracking_and_history(record: &RunRecord) -> Result<()> {
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    let encoding = crux_core::config::settings::settings()
+        .token_encoding
+        .as_deref()
+        .unwrap_or(crux_tracking::tokens::DEFAULT_ENCODING);
+    let count_tokens =
+        |text: &str| crux_tracking::tokens::count_tokens(text, encoding).map(|n| n as u64);
+
+    let event = crux_tracking::events::FilterEvent {
+        command: record.command.clone(),
+        filter_name: record.filter_name.clone(),
+        input_bytes: record.input_bytes,
+        output_bytes: record.output_bytes,
+        exit_code: record.exit_code,
+        duration_ms: Some(record.duration_ms),
+        filter_duration_us: Some(record.filter_duration_us),
+        input_tokens: count_tokens(&record.raw_output),
+        output_tokens: count_tokens(&record.filtered_output),
+        raw_fallback: record.raw_fallback,
+        session_id: std::env::var("CRUX_SESSION_ID")
+            .ok()
+            .filter(|id| !id.is_empty()),
+        stage_savings: record.stage_savings.clone(),
+        source: Some(record.source.clone()),
+    };
+    crux_tracking::events::record_event(&conn, &event)?;
+    if let Some(max_output_bytes) = history_limit(&record.command) {
+        crux_tracking::history::store_history_with_limit(
+            &conn,
+            &record.command,
+            &record.raw_output,
+            &record.filtered_output,
+            record.filter_name.as_deref(),
+            record.exit_code,
+            max_output_bytes,
+        )?;
+    }
+
+    let limits = &crux_core::config::settings::settings().retention;
+    let retention = crux_tracking::db::Retention {
+        max_age: limits.max_age.clone(),
+        max_rows: limits.max_rows,
+        max_db_bytes: limits.max_db_mb.map(|mb| mb * 1024 * 1024),
+    };
+    crux_tracking::db::maybe_apply_retention(&conn, &retention)?;
+
+    Ok(())
+}
+
+/// How many bytes of each output history keeps for `command`, or `None`
+/// when its outputs are not stored.
+#[cfg(feature = "tracking")]
+fn history_limit(command: &str) -> Option<usize> {
+    // Asked for encryption that isn't available: keep outputs out of the
+    // database rather than store them in the clear.
+    let settings = crux_core::config::settings::settings();
+    let can_store =
+        settings.encrypt_history != Some(true) || crux_tracking::crypto::is_encrypting();
+    if !can_store || !settings.stores_history(command) {
+        return None;
+    }
+    Some(match settings.retention.max_history_kb {
+        Some(0) => usize::MAX,
+        Some(kb) => (kb * 1024) as usize,
+        None => crux_tracking::history::DEFAULT_MAX_OUTPUT_BYTES,
+    })
+}
@@ -0,0 +1,157 @@
+//! `crux shell-init` — route allowlisted commands typed in an interactive
+//! shell through `crux run`, without wrapper functions.
+
+use anyhow::{bail, Result};
+
+use crate::alias::{is_shell_word, Shell};
+
+/// Set to any non-empty value to run commands directly for a while.
+const BYPASS_ENV: &str = "CRUX_BYPASS";
+
+/// Read-only commands that never prompt, routed when `--allow` is not given.
+const DEFAULT_ALLOW: &[&str] = &[
+    "git status",
+    "git diff",
+    "git log",
+    "git show",
+    "cargo build",
+    "cargo check",
+    "cargo clippy",
+    "cargo test",
+    "go build",
+    "go test",
+    "npm test",
+    "pytest",
+    "make",
+    "tsc",
+    "eslint",
+    "ruff check",
+    "mypy",
+];
+
+pub fn cmd_shell_init(shell: Option<Shell>, allow: &[String]) -> Result<()> {
+    let shell = shell.unwrap_or_else(Shell::detect);
+    let allow: Vec<String> = if allow.is_empty() {
+        DEFAULT_ALLOW.iter().map(|s| s.to_string()).collect()
+    } else {
+        allow.iter().map(|s| s.trim().to_string()).collect()
+    };
+    for entry in &allow {
+        if entry.is_empty() || !entry.split_whitespace().all(is_shell_word) {
+            bail!("invalid allowlist entry '{entry}'");
+        }
+    }
+    print!("{}", render(shell, &allow));
+    Ok(())
+}
+
+fn render(shell: Shell, allow: &[String]) -> String {
+    let quoted: Vec<String> = allow.iter().map(|entry| format!("'{entry}'")).collect();
+    let quoted = quoted.join(" ");
+    match shell {
+        Shell::Zsh => format!(
+            r#"# crux shell integration. Add to ~/.zshrc:
+#   eval "$(crux shell-init zsh)"
+# Set {BYPASS_ENV}=1 to run commands directly.
+_crux_allow=({quoted})
+{posix_should_route}
+_crux_accept_line() {{
+  if [[ -z "${BYPASS_ENV}" ]] && _crux_should_route "$BUFFER"; then
+    BUFFER="crux run $BUFFER"
+  fi
+  zle .accept-line
+}}
+zle -N accept-line _crux_accept_line
+"#,
+            posix_should_route = POSIX_SHOULD_ROUTE
+        ),
+        Shell::Bash => format!(
+            r#"# crux shell integration. Add to ~/.bashrc:
+#   eval "$(crux shell-init bash)"
+# Set {BYPASS_ENV}=1 to run commands directly. Uses `shopt -s extdebug`.
+_crux_allow=({quoted})
+{posix_should_route}
+_crux_debug_trap() {{
+  [[ -n "${BYPASS_ENV}" || -n "$COMP_LINE" || $BASH_SUBSHELL -ne 0 ]] && return 0
+  # Only a typed line that is exactly this one simple command
+  local line
+  line="$(HISTTIMEFORMAT= builtin history 1)"
+  line="${{line#"${{line%%[^ 0-9]*}}"}}"
+  [[ "$line" == "$BASH_COMMAND" ]] || return 0
+  _crux_should_route "$BASH_COMMAND" || return 0
+  eval "crux run $BASH_COMMAND"
+  return 1
+}}
+shopt -s extdebug
+trap '_crux_debug_trap' DEBUG
+"#,
+            posix_should_route = POSIX_SHOULD_ROUTE
+        ),
+        Shell::Fish => format!(
+            r#"# crux shell integration. Add to ~/.config/fish/config.fish:
+#   crux shell-init fish | source
+# Set {BYPASS_ENV}=1 to run commands directly.
+set -g __crux_allow {quoted}
+function __crux_should_route
+  set -l line (string trim -- $argv[1])
+  string match -qr '[|;&<>`]|\$\(' -- $line; and return 1
+  for entry in $__crux_allow
+    if test "$line" = "$entry"; or string match -q -- "$entry *" $line
+      return 0
+    end
+  end
+  return 1
+end
+function __crux_accept_line
+  set -l cmd (commandline)
+  if not set -q {BYPASS_ENV}; and __crux_should_route "$cmd"
+    commandline -r "crux run $cmd"
+  end
+  commandline -f execute
+end
+bind \r __crux_accept_line
+bind \n __crux_accept_line
+"#
+        ),
+    }
+}
+
+/// Shared by bash and zsh: is `$1` a single allowlisted command?
+const POSIX_SHOULD_ROUTE: &str = r#"_crux_should_route() {
+  local line="$1" entry
+  line="${line#"${line%%[![:space:]]*}"}"
+  case "$line" in
+    *[\|\;\&\<\>\`]*|*'$('*|'crux '*) return 1 ;;
+  esac
+  for entry in "${_crux_allow[@]}"; do
+    [[ "$line" == "$entry" || "$line" == "$entry "* ]] && return 0
+  done
+  return 1
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allow(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn embeds_allowlist_and_bypass_toggle() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = render(shell, &allow(&["git status", "make"]));
+            assert!(script.contains("'git status' 'make'"), "{shell:?}");
+            assert!(script.contains(BYPASS_ENV), "{shell:?}");
+        }
+    }
+
+    #[test]
+    fn zsh_rewrites_buffer_and_bash_uses_debug_trap() {
+        let zsh = render(Shell::Zsh, &allow(&["make"]));
+        assert!(zsh.contains("zle -N accept-line _crux_accept_line"));
+        let bash = render(Shell::Bash, &allow(&["make"]));
+        assert!(bash.contains("trap '_crux_debug_trap' DEBUG"));
+        assert!(bash.contains("shopt -s extdebug"));
+    }
+}
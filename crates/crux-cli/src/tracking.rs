@@ -0,0 +1,130 @@
+//! `crux export`, `crux tracking` and `crux prune` — moving and trimming
+//! the tracking database.
+
+use std::io::Read;
+
+use anyhow::{bail, Context, Result};
+use crux_tracking::export;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum ExportTable {
+    Events,
+    History,
+}
+
+pub fn cmd_export(format: ExportFormat, table: ExportTable, since: Option<&str>) -> Result<()> {
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    let range = crux_tracking::events::TimeRange::parse(&conn, since, None)?;
+    let format = match format {
+        ExportFormat::Csv => export::ExportFormat::Csv,
+        ExportFormat::Jsonl => export::ExportFormat::Jsonl,
+    };
+    let table = match table {
+        ExportTable::Events => export::ExportTable::Events,
+        ExportTable::History => export::ExportTable::History,
+    };
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    export::export(&conn, table, format, &range, &mut out)?;
+    std::io::Write::flush(&mut out)?;
+    Ok(())
+}
+
+pub fn cmd_tracking_dump(output: Option<&std::path::Path>) -> Result<()> {
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+    let archive = crux_tracking::archive::dump(&conn)?;
+    let json = serde_json::to_string(&archive)?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, json + "\n")
+                .with_context(|| format!("writing {}", path.display()))?;
+            let count = |key: &str| archive[key].as_array().map_or(0, Vec::len);
+            eprintln!(
+                "Wrote {} event(s) and {} history entr{} to {}",
+                count("events"),
+                count("history"),
+                if count("history") == 1 { "y" } else { "ies" },
+                path.display()
+            );
+        }
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+pub fn cmd_tracking_import(file: &str) -> Result<()> {
+    let text = if file == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        std::fs::read_to_string(file).with_context(|| format!("reading {file}"))?
+    };
+    let archive: serde_json::Value =
+        serde_json::from_str(&text).with_context(|| format!("parsing {file}"))?;
+
+    let db_path = crux_tracking::db::default_db_path()?;
+    let mut conn = crux_tracking::db::open_db(&db_path)?;
+    let stats = crux_tracking::archive::import(&mut conn, &archive)?;
+    println!(
+        "Imported {} event(s) and {} history entr{} ({} already present)",
+        stats.events,
+        stats.history,
+        if stats.history == 1 { "y" } else { "ies" },
+        stats.skipped
+    );
+    Ok(())
+}
+
+pub fn cmd_prune(older_than: Option<&str>, keep_last: Option<usize>, vacuum: bool) -> Result<()> {
+    if older_than.is_none() && keep_last.is_none() && !vacuum {
+        bail!("nothing to do: pass --older-than, --keep-last and/or --vacuum");
+    }
+    let db_path = crux_tracking::db::default_db_path()?;
+    let conn = crux_tracking::db::open_db(&db_path)?;
+
+    if older_than.is_some() || keep_last.is_some() {
+        let before = crux_tracking::events::TimeRange::parse(&conn, older_than, None)?.since;
+        let stats = crux_tracking::db::prune(&conn, before.as_deref(), keep_last)?;
+        println!(
+            "Deleted {} event(s) and {} history entr{}",
+            stats.events,
+            stats.history,
+            if stats.history == 1 { "y" } else { "ies" }
+        );
+    }
+    if vacuum {
+        let size = || std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0);
+        let before = size();
+        crux_tracking::db::vacuum(&conn)?;
+        println!(
+            "Compacted {}: {} → {} bytes",
+            db_path.display(),
+            before,
+            size()
+        );
+    }
+    Ok(())
+}
+
+/// Parse a byte count with an optional `k` or `M` suffix (1024-based).
+pub(crate) fn parse_size(s: &str) -> Result<usize, String> {
+    let (digits, multiplier) = match s.trim().strip_suffix(['k', 'K']) {
+        Some(rest) => (rest, 1024),
+        None => match s.trim().strip_suffix(['m', 'M']) {
+            Some(rest) => (rest, 1024 * 1024),
+            None => (s.trim(), 1),
+        },
+    };
+    digits
+        .parse::<usize>()
+        .map(|n| n * multiplier)
+        .map_err(|_| format!("invalid size '{s}' (expected e.g. 2000, 4k or 1M)"))
+}
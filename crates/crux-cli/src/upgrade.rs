@@ -0,0 +1,301 @@
+//! `crux upgrade` — replace the running binary with the latest GitHub
+//! release. Like `crux pack`, downloads and archives go through the system
+//! `curl` and `tar`; checksums through `sha256sum` (or `shasum -a 256`).
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/polaminggkub-debug/crux/releases/latest";
+
+pub fn cmd_upgrade(check: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = fetch_json(LATEST_RELEASE_URL)?;
+    let tag = release["tag_name"]
+        .as_str()
+        .context("release has no tag_name")?;
+    let latest = tag.trim_start_matches('v');
+
+    if !is_newer(latest, current)? {
+        println!("crux {current} is up to date");
+        return Ok(());
+    }
+    if check {
+        println!("Update available: {current} → {latest} (run `crux upgrade`)");
+        return Ok(());
+    }
+
+    let target = platform_target()?;
+    let (asset_name, asset_url) = find_asset(&release, |name| {
+        name.contains(target) && name.ends_with(".tar.gz")
+    })
+    .with_context(|| format!("release {tag} has no .tar.gz for {target}"))?;
+
+    // A fresh, private directory (removed on drop), so the binary that gets
+    // installed can't be swapped out between download and install.
+    let work = tempfile::Builder::new()
+        .prefix("crux-upgrade-")
+        .tempdir()
+        .context("creating a temporary directory")?;
+    std::fs::create_dir(work.path().join("contents"))?;
+    let exe = install_release(&release, &asset_name, &asset_url, work.path())?;
+
+    println!("Upgraded crux {current} → {latest} ({})", exe.display());
+    Ok(())
+}
+
+/// Download, verify and unpack `asset_name`, then swap it in for the
+/// running binary. Returns the path of the replaced binary.
+fn install_release(
+    release: &serde_json::Value,
+    asset_name: &str,
+    asset_url: &str,
+    work: &Path,
+) -> Result<PathBuf> {
+    let archive = work.join(asset_name);
+    download(asset_url, &archive)?;
+
+    let expected = expected_checksum(release, asset_name, work)?;
+    let actual = sha256_file(&archive)?;
+    if !actual.eq_ignore_ascii_case(&expected) {
+        bail!("checksum mismatch for {asset_name}: expected {expected}, got {actual}");
+    }
+
+    let contents = work.join("contents");
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&contents)
+        .status()
+        .context("running tar")?;
+    if !status.success() {
+        bail!("{asset_name} is not a valid .tar.gz");
+    }
+    let binary = find_binary(&contents)?.context("the release archive contains no crux binary")?;
+
+    let exe = std::env::current_exe()
+        .and_then(|p| p.canonicalize())
+        .context("locating the running crux binary")?;
+    replace_binary(&binary, &exe)?;
+    Ok(exe)
+}
+
+/// The checksum published for `asset_name`: either `<asset>.sha256` or a
+/// combined `SHA256SUMS` / `checksums.txt`.
+fn expected_checksum(release: &serde_json::Value, asset_name: &str, work: &Path) -> Result<String> {
+    let sidecar = format!("{asset_name}.sha256");
+    let (name, url) = find_asset(release, |name| {
+        name == sidecar || name == "SHA256SUMS" || name == "checksums.txt"
+    })
+    .with_context(|| format!("release publishes no checksum for {asset_name}"))?;
+    let path = work.join(&name);
+    download(&url, &path)?;
+    let listing =
+        std::fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    parse_checksum(&listing, asset_name)
+        .with_context(|| format!("{name} has no entry for {asset_name}"))
+}
+
+/// Find `asset_name`'s hash in `sha256sum` output. A single bare hash (a
+/// `.sha256` sidecar) is accepted as is.
+fn parse_checksum(listing: &str, asset_name: &str) -> Option<String> {
+    let is_hash = |s: &str| s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit());
+    let mut found = None;
+    for line in listing.lines() {
+        let mut words = line.split_whitespace();
+        let (Some(hash), name) = (words.next(), words.next()) else {
+            continue;
+        };
+        match name {
+            // `*` marks binary mode in sha256sum output
+            Some(name) if name.trim_start_matches('*') == asset_name && is_hash(hash) => {
+                return Some(hash.to_string())
+            }
+            None if is_hash(hash) => found = Some(hash.to_string()),
+            _ => {}
+        }
+    }
+    found
+}
+
+/// `(name, browser_download_url)` of the first release asset whose name
+/// satisfies `pred`.
+fn find_asset(
+    release: &serde_json::Value,
+    pred: impl Fn(&str) -> bool,
+) -> Option<(String, String)> {
+    release["assets"].as_array()?.iter().find_map(|asset| {
+        let name = asset["name"].as_str()?;
+        let url = asset["browser_download_url"].as_str()?;
+        pred(name).then(|| (name.to_string(), url.to_string()))
+    })
+}
+
+/// Whether `latest` is a higher `major.minor.patch` than `current`.
+fn is_newer(latest: &str, current: &str) -> Result<bool> {
+    let parse = |v: &str| -> Result<Vec<u64>> {
+        // Ignore pre-release and build suffixes (`1.2.3-rc.1`)
+        let core = v.split(['-', '+']).next().unwrap_or(v);
+        core.split('.')
+            .map(|part| part.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("invalid version '{v}'"))
+    };
+    Ok(parse(latest)? > parse(current)?)
+}
+
+/// The Rust target triple release archives are named after.
+fn platform_target() -> Result<&'static str> {
+    Ok(match (std::env::consts::ARCH, std::env::consts::OS) {
+        ("x86_64", "linux") => "x86_64-unknown-linux",
+        ("aarch64", "linux") => "aarch64-unknown-linux",
+        ("x86_64", "macos") => "x86_64-apple-darwin",
+        ("aarch64", "macos") => "aarch64-apple-darwin",
+        (arch, os) => bail!("no prebuilt release for {arch}-{os}; use `cargo install cruxai-cli`"),
+    })
+}
+
+/// A file named `crux` anywhere under `dir`.
+fn find_binary(dir: &Path) -> Result<Option<PathBuf>> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if let Some(found) = find_binary(&path)? {
+                return Ok(Some(found));
+            }
+        } else if path.file_name().and_then(|n| n.to_str()) == Some("crux") {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+/// Copy `binary` next to `exe`, then rename it over `exe` so the swap is
+/// atomic and a failed copy leaves the old binary in place.
+fn replace_binary(binary: &Path, exe: &Path) -> Result<()> {
+    let staged = exe.with_extension("new");
+    std::fs::copy(binary, &staged).with_context(|| format!("writing {}", staged.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+    if let Err(e) = std::fs::rename(&staged, exe) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e).with_context(|| format!("replacing {}", exe.display()));
+    }
+    Ok(())
+}
+
+fn fetch_json(url: &str) -> Result<serde_json::Value> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "-H", "Accept: application/vnd.github+json"])
+        .arg(url)
+        .output()
+        .context("running curl")?;
+    if !output.status.success() {
+        bail!("failed to fetch {url}");
+    }
+    serde_json::from_slice(&output.stdout).with_context(|| format!("parsing {url}"))
+}
+
+fn download(url: &str, dest: &Path) -> Result<()> {
+    let status = Command::new("curl")
+        .args(["-fsSL", "-o"])
+        .arg(dest)
+        .arg(url)
+        .status()
+        .context("running curl")?;
+    if !status.success() {
+        bail!("failed to download {url}");
+    }
+    Ok(())
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let output = match Command::new("sha256sum").arg(path).output() {
+        Ok(output) => output,
+        Err(_) => Command::new("shasum")
+            .args(["-a", "256"])
+            .arg(path)
+            .output()
+            .context("running sha256sum or shasum")?,
+    };
+    if !output.status.success() {
+        bail!("failed to hash {}", path.display());
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+        .context("empty checksum output")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(is_newer("0.3.10", "0.3.9").unwrap());
+        assert!(is_newer("1.0.0", "0.9.9").unwrap());
+        assert!(!is_newer("0.3.3", "0.3.3").unwrap());
+        assert!(!is_newer("0.3.3-rc.1", "0.3.3").unwrap());
+        assert!(is_newer("latest", "0.3.3").is_err());
+    }
+
+    #[test]
+    fn parses_sums_files_and_sidecars() {
+        let sums = format!("{HASH}  crux-x86_64-apple-darwin.tar.gz\n{HASH}  *crux-x86_64-unknown-linux-gnu.tar.gz\n");
+        assert_eq!(
+            parse_checksum(&sums, "crux-x86_64-unknown-linux-gnu.tar.gz").as_deref(),
+            Some(HASH)
+        );
+        assert_eq!(parse_checksum(&sums, "crux-other.tar.gz"), None);
+        assert_eq!(
+            parse_checksum(&format!("{HASH}\n"), "x.tar.gz").as_deref(),
+            Some(HASH)
+        );
+        assert_eq!(parse_checksum("not a hash\n", "x.tar.gz"), None);
+    }
+
+    #[test]
+    fn finds_assets_by_name() {
+        let release = serde_json::json!({
+            "tag_name": "v0.4.0",
+            "assets": [
+                {"name": "crux-x86_64-apple-darwin.tar.gz", "browser_download_url": "https://a"},
+                {"name": "crux-x86_64-unknown-linux-gnu.tar.gz", "browser_download_url": "https://b"},
+            ]
+        });
+        let found = find_asset(&release, |n| n.contains("x86_64-unknown-linux"));
+        assert_eq!(
+            found,
+            Some((
+                "crux-x86_64-unknown-linux-gnu.tar.gz".to_string(),
+                "https://b".to_string()
+            ))
+        );
+        assert_eq!(find_asset(&release, |n| n.contains("windows")), None);
+    }
+
+    #[test]
+    fn replaces_binary_in_place() {
+        let dir = std::env::temp_dir().join(format!("crux-upgrade-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("pkg/bin")).unwrap();
+        std::fs::write(dir.join("pkg/bin/crux"), "new").unwrap();
+        std::fs::write(dir.join("crux"), "old").unwrap();
+
+        let binary = find_binary(&dir.join("pkg")).unwrap().unwrap();
+        replace_binary(&binary, &dir.join("crux")).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("crux")).unwrap(), "new");
+        assert!(!dir.join("crux.new").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
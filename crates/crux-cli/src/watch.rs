@@ -0,0 +1,130 @@
+//! `crux watch` — rerun a command whenever the working tree changes.
+//!
+//! Changes are found by polling: every `--interval` milliseconds (500 by
+//! default) the tree is walked and each file's path, size and modification
+//! time hashed. The walk honours `.gitignore`, `.ignore` and the global git
+//! excludes, even outside a git repository, so build output listed there
+//! doesn't retrigger the command. Hidden files are watched; only `.git` is
+//! always skipped.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use ignore::WalkBuilder;
+
+pub fn cmd_watch(command: &[String], interval_ms: u64, changed_only: bool) -> Result<()> {
+    let interval = Duration::from_millis(interval_ms.max(50));
+    let root = Path::new(".");
+    let label = command.join(" ");
+    let mut last_output: Option<String> = None;
+
+    for run in 1.. {
+        let snapshot = fingerprint(root);
+        let filter = crate::run::resolve_run_filter(command);
+        let result = crux_core::runner::run_command(command)?;
+        let filter = filter.map(|config| {
+            crate::run::select_variant_post(config, &result.combined, result.exit_code)
+        });
+        let (filtered, _) =
+            crate::run::filter_output(filter.as_ref(), &result.combined, result.exit_code);
+
+        if changed_only && last_output.as_deref() == Some(filtered.as_str()) {
+            eprintln!("crux: run #{run} of {label}: output unchanged");
+        } else {
+            eprintln!(
+                "crux: run #{run} of {label} (exit code {})",
+                result.exit_code
+            );
+            print!("{filtered}");
+            if !filtered.ends_with('\n') && !filtered.is_empty() {
+                println!();
+            }
+            last_output = Some(filtered);
+        }
+
+        wait_for_change(root, snapshot, interval);
+    }
+    Ok(())
+}
+
+/// Poll until the tree differs from `snapshot`, then until it stops changing
+/// so that a burst of saves triggers a single rerun.
+fn wait_for_change(root: &Path, snapshot: u64, interval: Duration) {
+    let mut current = snapshot;
+    while current == snapshot {
+        std::thread::sleep(interval);
+        current = fingerprint(root);
+    }
+    loop {
+        std::thread::sleep(interval);
+        let next = fingerprint(root);
+        if next == current {
+            return;
+        }
+        current = next;
+    }
+}
+
+/// Hash of every watched file's path, size and modification time.
+fn fingerprint(root: &Path) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+    for entry in walker.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            continue;
+        }
+        entry.path().hash(&mut hasher);
+        meta.len().hash(&mut hasher);
+        if let Ok(modified) = meta.modified() {
+            modified.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_respects_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::create_dir_all(dir.join("out")).unwrap();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join(".gitignore"), "out/\n").unwrap();
+        std::fs::write(dir.join("src/lib.rs"), "fn a() {}").unwrap();
+
+        let before = fingerprint(dir);
+        std::fs::write(dir.join("out/app.js"), "junk").unwrap();
+        std::fs::write(dir.join(".git/index"), "junk").unwrap();
+        assert_eq!(fingerprint(dir), before);
+
+        std::fs::write(dir.join("src/lib.rs"), "fn a() { b() }").unwrap();
+        assert_ne!(fingerprint(dir), before);
+    }
+
+    #[test]
+    fn fingerprint_watches_hidden_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        std::fs::create_dir_all(dir.join(".config")).unwrap();
+        std::fs::write(dir.join(".config/app.toml"), "a = 1").unwrap();
+
+        let before = fingerprint(dir);
+        std::fs::write(dir.join(".config/app.toml"), "a = 2").unwrap();
+        assert_ne!(fingerprint(dir), before);
+    }
+}
@@ -1,4 +1,5 @@
-use std::process::Command;
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 fn crux_bin() -> Command {
     Command::new(env!("CARGO_BIN_EXE_crux"))
@@ -110,6 +111,34 @@ fn eject_git_status_outputs_toml() {
     );
 }
 
+#[test]
+fn eject_out_writes_toml_and_seeded_tests() {
+    let dir = std::env::temp_dir().join(format!("crux-eject-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let output = crux_bin()
+        .args(["eject", "make", "--out"])
+        .arg(&dir)
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+    let toml = std::fs::read_to_string(dir.join("make.toml")).unwrap();
+    assert!(toml.contains("command = \"make\""), "got: {toml}");
+    // The stdlib make filter ships a test case, copied into the scaffold
+    let input = std::fs::read_to_string(dir.join("make_test/input.txt")).unwrap();
+    assert!(!input.is_empty());
+
+    // A second eject leaves the existing file alone
+    let again = crux_bin()
+        .args(["eject", "make", "--out"])
+        .arg(&dir)
+        .output()
+        .expect("failed to execute crux");
+    assert!(String::from_utf8_lossy(&again.stderr).contains("--force"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 #[test]
 fn err_filters_error_lines() {
     let output = crux_bin()
@@ -174,6 +203,371 @@ fn run_with_builtin_git_status_compresses() {
     );
 }
 
+#[test]
+fn run_filter_flag_overrides_resolution() {
+    let raw = "On branch main\nChanges not staged for commit:\n  (use \"git restore <file>...\" to discard changes in working directory)\n\tmodified:   src/lib.rs\n";
+    let output = crux_bin()
+        .args(["run", "--filter", "git status", "--", "printf", "%s", raw])
+        .output()
+        .expect("failed to execute crux");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(
+        stdout.contains("src/lib.rs"),
+        "Expected modified file, got: {stdout}"
+    );
+    assert!(
+        !stdout.contains("(use \"git restore"),
+        "Hint lines should be stripped, got: {stdout}"
+    );
+
+    let output = crux_bin()
+        .args(["run", "--filter", "no such filter", "--", "true"])
+        .output()
+        .expect("failed to execute crux");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no filter named"));
+}
+
+#[test]
+fn run_raw_skips_filtering_and_records_history() {
+    let data = std::env::temp_dir().join(format!("crux-raw-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data);
+    let raw = "On branch main\n  (use \"git restore <file>...\" to discard changes in working directory)\n";
+    let output = crux_bin()
+        .args(["run", "--raw", "--", "printf", "%s", raw])
+        .env("XDG_DATA_HOME", &data)
+        .env("CRUX_TRACK_SYNC", "1")
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), raw);
+
+    let output = crux_bin()
+        .args(["history"])
+        .env("XDG_DATA_HOME", &data)
+        .output()
+        .expect("failed to execute crux");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("printf") && stdout.contains("(passthrough)"),
+        "Expected the raw run in history, got: {stdout}"
+    );
+    std::fs::remove_dir_all(&data).unwrap();
+}
+
+#[test]
+fn refilter_reports_deltas_against_recorded_output() {
+    let data = std::env::temp_dir().join(format!("crux-refilter-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data);
+    let output = crux_bin()
+        .args(["run", "--raw", "--", "seq", "1", "5"])
+        .env("XDG_DATA_HOME", &data)
+        .env("CRUX_TRACK_SYNC", "1")
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+
+    let output = crux_bin()
+        .args(["--json", "refilter"])
+        .env("XDG_DATA_HOME", &data)
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(rows[0]["command"], "seq 1 5");
+    assert_eq!(rows[0]["recorded_bytes"], 10);
+    assert_eq!(rows[0]["delta_bytes"], 0);
+
+    let output = crux_bin()
+        .args(["refilter", "--filter", "no such filter"])
+        .env("XDG_DATA_HOME", &data)
+        .output()
+        .expect("failed to execute crux");
+    assert!(!output.status.success());
+    std::fs::remove_dir_all(&data).unwrap();
+}
+
+#[test]
+fn gain_by_stage_reports_bytes_removed_per_stage() {
+    let data = std::env::temp_dir().join(format!("crux-by-stage-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data);
+    let raw =
+        "On branch main\n  (use \"git restore <file>...\" to discard changes)\n\tmodified: a.rs\n";
+    let output = crux_bin()
+        .args(["run", "--filter", "git status", "--", "printf", "%s", raw])
+        .env("XDG_DATA_HOME", &data)
+        .env("CRUX_TRACK_SYNC", "1")
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+
+    let output = crux_bin()
+        .args(["--json", "gain", "--by-stage"])
+        .env("XDG_DATA_HOME", &data)
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(rows[0]["filter_name"], "git status");
+    assert_eq!(rows[0]["stage"], "builtin git status");
+    assert!(rows[0]["total_bytes_removed"].as_i64().unwrap() > 0);
+    std::fs::remove_dir_all(&data).unwrap();
+}
+
+#[test]
+fn gain_groups_runs_by_source() {
+    let data = std::env::temp_dir().join(format!("crux-source-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data);
+    for source in ["test", ""] {
+        let output = crux_bin()
+            .args(["run", "--", "echo", "hi"])
+            .env("XDG_DATA_HOME", &data)
+            .env("CRUX_TRACK_SYNC", "1")
+            .env("CRUX_SOURCE", source)
+            .env_remove("CLAUDECODE")
+            .output()
+            .expect("failed to execute crux");
+        assert!(output.status.success());
+    }
+
+    let output = crux_bin()
+        .args(["--json", "gain", "--group-by", "source"])
+        .env("XDG_DATA_HOME", &data)
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+    let groups: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut names: Vec<&str> = groups
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|g| g["group"].as_str().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["cli", "test"]);
+    std::fs::remove_dir_all(&data).unwrap();
+}
+
+#[test]
+fn sync_is_opt_in_and_sends_no_outputs() {
+    let data = std::env::temp_dir().join(format!("crux-sync-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data);
+    let output = crux_bin()
+        .args(["run", "--", "echo", "private output"])
+        .env("XDG_DATA_HOME", &data)
+        .env("CRUX_TRACK_SYNC", "1")
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+
+    let output = crux_bin()
+        .args(["sync"])
+        .env("XDG_DATA_HOME", &data)
+        .output()
+        .expect("failed to execute crux");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("sync is not configured"));
+
+    let output = crux_bin()
+        .args(["sync", "--dry-run"])
+        .env("XDG_DATA_HOME", &data)
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+    let payload: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["rows"][0]["events"], 1);
+    let text = String::from_utf8_lossy(&output.stdout);
+    assert!(!text.contains("private output") && !text.contains("echo"));
+    std::fs::remove_dir_all(&data).unwrap();
+}
+
+#[test]
+fn run_no_track_records_nothing() {
+    let data = std::env::temp_dir().join(format!("crux-no-track-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data);
+    let output = crux_bin()
+        .args(["run", "--no-track", "--", "echo", "flag"])
+        .env("XDG_DATA_HOME", &data)
+        .env("CRUX_TRACK_SYNC", "1")
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+    let output = crux_bin()
+        .args(["run", "--", "echo", "env"])
+        .env("XDG_DATA_HOME", &data)
+        .env("CRUX_TRACK_SYNC", "1")
+        .env("CRUX_NO_TRACK", "1")
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+
+    let output = crux_bin()
+        .args(["history"])
+        .env("XDG_DATA_HOME", &data)
+        .output()
+        .expect("failed to execute crux");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No history entries yet"), "got: {stdout}");
+    let _ = std::fs::remove_dir_all(&data);
+}
+
+#[test]
+fn run_records_in_background() {
+    let data = std::env::temp_dir().join(format!("crux-async-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data);
+    let output = crux_bin()
+        .args(["run", "--", "echo", "background"])
+        .env("XDG_DATA_HOME", &data)
+        .output()
+        .expect("failed to execute crux");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "background\n");
+
+    // The detached recorder finishes shortly after `crux run` exits
+    let mut stdout = String::new();
+    for _ in 0..50 {
+        let output = crux_bin()
+            .args(["history"])
+            .env("XDG_DATA_HOME", &data)
+            .output()
+            .expect("failed to execute crux");
+        stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        if stdout.contains("echo background") {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert!(stdout.contains("echo background"), "got: {stdout}");
+    let _ = std::fs::remove_dir_all(&data);
+}
+
+#[test]
+fn run_budget_caps_output() {
+    let output = crux_bin()
+        .args(["run", "--raw", "--budget", "50", "--", "seq", "1", "2000"])
+        .output()
+        .expect("failed to execute crux");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("tokens omitted"), "got: {stdout}");
+    assert!(stdout.starts_with("1\n") && stdout.trim_end().ends_with("2000"));
+    assert!(stdout.len() < 300, "got {} bytes", stdout.len());
+
+    let output = crux_bin()
+        .args(["run", "--budget", "100b", "--", "seq", "1", "2000"])
+        .output()
+        .expect("failed to execute crux");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("bytes omitted"), "got: {stdout}");
+    assert!(stdout.len() < 150, "got {} bytes", stdout.len());
+
+    let output = crux_bin()
+        .args(["run", "--budget", "lots", "--", "true"])
+        .output()
+        .expect("failed to execute crux");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn run_output_json_envelope() {
+    let output = crux_bin()
+        .args([
+            "run",
+            "--output",
+            "json",
+            "--",
+            "sh",
+            "-c",
+            "echo hello; exit 3",
+        ])
+        .output()
+        .expect("failed to execute crux");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let envelope: serde_json::Value =
+        serde_json::from_str(&stdout).unwrap_or_else(|e| panic!("{e}: {stdout}"));
+    assert_eq!(envelope["command"], "sh -c echo hello; exit 3");
+    assert_eq!(envelope["exit_code"], 3);
+    assert_eq!(envelope["filtered_output"], "hello\n");
+    assert_eq!(envelope["input_bytes"], 6);
+    assert_eq!(envelope["output_bytes"], 6);
+    assert!(envelope["filter"].is_null());
+    assert!(envelope["duration_ms"].is_u64());
+}
+
+#[test]
+fn filter_reads_stdin_as_command() {
+    let raw = "On branch main\nChanges not staged for commit:\n  (use \"git add <file>...\" to update what will be committed)\n  (use \"git restore <file>...\" to discard changes in working directory)\n\tmodified:   src/lib.rs\n";
+    let mut child = crux_bin()
+        .args(["filter", "--as", "git status"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to execute crux");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(raw.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(
+        stdout.contains("src/lib.rs"),
+        "Expected modified file, got: {stdout}"
+    );
+    assert!(
+        !stdout.contains("(use \"git restore"),
+        "Hint lines should be stripped, got: {stdout}"
+    );
+}
+
+#[test]
+fn explain_traces_builtin_stage() {
+    let output = crux_bin()
+        .args(["explain", "git", "status"])
+        .output()
+        .expect("failed to execute crux");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Filter:      git status"),
+        "Expected filter name, got: {stdout}"
+    );
+    assert!(
+        stdout.contains("builtin git status"),
+        "Expected builtin step, got: {stdout}"
+    );
+}
+
+#[test]
+fn which_json_reports_filter() {
+    let output = crux_bin()
+        .args(["which", "--json", "git", "status"])
+        .output()
+        .expect("failed to execute crux");
+
+    let value: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("which --json prints JSON");
+    assert_eq!(value["command"], "git status");
+    assert_eq!(value["filter"]["command"], "git status");
+}
+
+#[test]
+fn show_json_includes_builtin_flag() {
+    let output = crux_bin()
+        .args(["--json", "show", "git status"])
+        .output()
+        .expect("failed to execute crux");
+
+    let value: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("show --json prints JSON");
+    assert_eq!(value["command"], "git status");
+    assert_eq!(value["builtin"], true);
+}
+
 #[test]
 fn version_flag_works() {
     let output = crux_bin()
@@ -187,3 +581,147 @@ fn version_flag_works() {
         "Expected version output, got: {stdout}"
     );
 }
+
+#[test]
+fn pack_export_then_import_installs_filters() {
+    let dir = std::env::temp_dir().join(format!("crux-pack-it-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let filters = dir.join("filters");
+    std::fs::create_dir_all(filters.join("deploy_test")).unwrap();
+    std::fs::write(
+        filters.join("deploy.toml"),
+        "command = \"deploy\"\ntail = 3\n",
+    )
+    .unwrap();
+    std::fs::write(filters.join("deploy_test/input.txt"), "a\n").unwrap();
+    let archive = dir.join("team.tar.gz");
+
+    let export = crux_bin()
+        .args(["pack", "export"])
+        .arg(&archive)
+        .arg("--from")
+        .arg(&filters)
+        .output()
+        .expect("failed to execute crux");
+    assert!(export.status.success(), "{:?}", export);
+
+    let home = dir.join("home");
+    let import = crux_bin()
+        .args(["pack", "import"])
+        .arg(&archive)
+        .env("HOME", &home)
+        .output()
+        .expect("failed to execute crux");
+    let stdout = String::from_utf8_lossy(&import.stdout);
+    assert!(stdout.contains("Installed 1 filter(s)"), "got: {stdout}");
+    let installed = home.join(".config/crux/filters");
+    assert!(installed.join("deploy.toml").is_file());
+    assert!(installed.join("deploy_test/input.txt").is_file());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn show_toml_and_tests_for_stdlib_filter() {
+    let output = crux_bin()
+        .args(["show", "make", "--toml"])
+        .output()
+        .expect("failed to execute crux");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("# Effective filter for: make (stdlib)"),
+        "got: {stdout}"
+    );
+    let config: toml::Value = toml::from_str(&stdout).expect("--toml output should parse");
+    assert_eq!(config["command"].as_str(), Some("make"));
+
+    let output = crux_bin()
+        .args(["show", "make", "--tests"])
+        .output()
+        .expect("failed to execute crux");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("PASS  make::default"), "got: {stdout}");
+}
+
+#[test]
+fn hook_status_and_remove_manage_installed_hooks() {
+    let dir = std::env::temp_dir().join(format!("crux-hook-it-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let home = dir.join("home");
+    let project = dir.join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let crux = |args: &[&str]| {
+        crux_bin()
+            .args(args)
+            .current_dir(&project)
+            .env("HOME", &home)
+            .env_remove("CODEX_HOME")
+            .output()
+            .expect("failed to execute crux")
+    };
+    let targets = |output: std::process::Output| {
+        let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        value["targets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| (t["target"].as_str().unwrap().to_string(), t.clone()))
+            .collect::<std::collections::HashMap<_, _>>()
+    };
+
+    assert!(crux(&["init", "--global"]).status.success());
+    assert!(crux(&["init", "--codex"]).status.success());
+    let status = targets(crux(&["--json", "hook", "status"]));
+    assert_eq!(status["local"]["state"], "missing");
+    assert_eq!(status["global"]["state"], "installed");
+    assert_eq!(status["global"]["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(status["codex"]["state"], "installed");
+    let agents_md = home.join(".codex/AGENTS.md");
+    assert!(std::fs::read_to_string(&agents_md)
+        .unwrap()
+        .contains("crux-codex-wrapper cargo test"));
+
+    assert!(crux(&["hook", "remove", "global"]).status.success());
+    assert!(crux(&["hook", "remove", "codex"]).status.success());
+    let status = targets(crux(&["--json", "hook", "status"]));
+    assert_eq!(status["global"]["state"], "missing");
+    assert_eq!(status["codex"]["state"], "missing");
+    assert!(!home.join(".local/bin/crux-codex-wrapper").exists());
+    assert!(!agents_md.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "mcp")]
+#[test]
+fn serve_mcp_answers_requests_line_by_line() {
+    let mut child = crux_bin()
+        .args(["serve", "--mcp"])
+        .env("CRUX_NO_TRACK", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to execute crux");
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05"}}"#,
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"run_filtered","arguments":{"command":"echo hello; exit 3"}}}"#,
+    ];
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        writeln!(stdin, "{}", requests.join("\n")).unwrap();
+    }
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let responses: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 2, "notifications get no answer");
+    assert_eq!(responses[0]["result"]["serverInfo"]["name"], "crux");
+    assert_eq!(
+        responses[1]["result"]["content"][0]["text"],
+        "hello\n(exit code 3)"
+    );
+}
@@ -0,0 +1,135 @@
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+
+use super::rule_block::RuleBlock;
+use super::types::FilterConfig;
+
+/// `${VAR}` or `${VAR:-default}`. Names must start with a letter or `_`, so
+/// numeric capture references like `${1}` never match.
+static ENV_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?::-([^}]*))?\}").unwrap());
+
+/// Expand `${VAR}` / `${VAR:-default}` from the environment.
+///
+/// A variable that is unset and has no default is left as written, so
+/// named capture references in replacements (`${name}`) keep working.
+pub fn expand_env_vars(input: &str) -> String {
+    expand_with(input, |name| std::env::var(name).ok())
+}
+
+fn expand_with(input: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    if !input.contains("${") {
+        return input.to_string();
+    }
+    ENV_RE
+        .replace_all(input, |caps: &Captures| {
+            lookup(&caps[1])
+                .or_else(|| caps.get(2).map(|d| d.as_str().to_string()))
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+/// Interpolate environment variables into a loaded filter's skip, keep,
+/// replace and template strings (including exit-code and profile blocks).
+pub fn interpolate_config(config: &mut FilterConfig) {
+    expand_list(&mut config.skip);
+    expand_list(&mut config.keep);
+    for rule in &mut config.replace {
+        rule.pattern = expand_env_vars(&rule.pattern);
+        rule.replacement = expand_env_vars(&rule.replacement);
+    }
+    if let Some(ref mut template) = config.template {
+        *template = expand_env_vars(template);
+    }
+    for block in config
+        .on_success
+        .iter_mut()
+        .chain(config.on_failure.iter_mut())
+        .chain(config.profile.values_mut())
+    {
+        interpolate_block(block);
+    }
+}
+
+fn interpolate_block(block: &mut RuleBlock) {
+    expand_list(&mut block.skip);
+    expand_list(&mut block.keep);
+    for rule in &mut block.replace {
+        rule.pattern = expand_env_vars(&rule.pattern);
+        rule.replacement = expand_env_vars(&rule.replacement);
+    }
+    if let Some(ref mut template) = block.template {
+        *template = expand_env_vars(template);
+    }
+}
+
+fn expand_list(list: &mut [String]) {
+    for item in list {
+        *item = expand_env_vars(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "PROJECT" => Some("acme".to_string()),
+            "HOST" => Some("db.internal".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_set_variables() {
+        assert_eq!(
+            expand_with("^${PROJECT}/src on ${HOST}", lookup),
+            "^acme/src on db.internal"
+        );
+    }
+
+    #[test]
+    fn default_used_when_unset() {
+        assert_eq!(expand_with("${MISSING:-fallback}", lookup), "fallback");
+        assert_eq!(expand_with("${PROJECT:-fallback}", lookup), "acme");
+    }
+
+    #[test]
+    fn unset_and_capture_refs_left_alone() {
+        assert_eq!(
+            expand_with("${MISSING} ${1} ${name}", lookup),
+            "${MISSING} ${1} ${name}"
+        );
+        assert_eq!(expand_with("end$", lookup), "end$");
+    }
+
+    #[test]
+    fn interpolates_config_fields() {
+        std::env::set_var("CRUX_TEST_ENV_PROJECT", "acme");
+        let mut config: FilterConfig = toml::from_str(
+            r#"
+command = "make"
+skip = ["^/home/${CRUX_TEST_ENV_PROJECT}/"]
+template = "built ${CRUX_TEST_ENV_PROJECT}"
+
+[[replace]]
+pattern = "${CRUX_TEST_ENV_PROJECT}-(?P<ver>\\d+)"
+replacement = "v${ver}"
+
+[on_failure]
+keep = ["${CRUX_TEST_ENV_PROJECT}: error"]
+"#,
+        )
+        .unwrap();
+        interpolate_config(&mut config);
+
+        assert_eq!(config.skip, vec!["^/home/acme/"]);
+        assert_eq!(config.template.as_deref(), Some("built acme"));
+        assert_eq!(config.replace[0].pattern, r"acme-(?P<ver>\d+)");
+        assert_eq!(config.replace[0].replacement, "v${ver}");
+        assert_eq!(config.on_failure.unwrap().keep, vec!["acme: error"]);
+    }
+}
@@ -0,0 +1,577 @@
+//! Static checks for user filter TOMLs (`crux lint`).
+//!
+//! Loading is lenient — unknown keys are ignored and bad regexes are dropped
+//! at run time — so a typo can silently turn a filter into a no-op. Linting
+//! parses each file strictly and reports what the loader would paper over.
+
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::Serialize;
+
+use super::resolve::{home_dir, stdlib_filters};
+use super::rule_block::RuleBlock;
+use super::types::{ExtractRule, FilterConfig, ReplaceRule, SectionRule};
+use crate::filter::stage::{Stage, DEFAULT_ORDER};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// One problem found in a filter file.
+#[derive(Debug, Clone, Serialize)]
+pub struct LintIssue {
+    pub path: PathBuf,
+    /// The filter's `command`, when the file parsed far enough to have one.
+    pub command: Option<String>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// A parsed user filter and where it came from.
+struct UserFilter {
+    path: PathBuf,
+    /// Index of the source directory; lower wins ties at resolve time.
+    source: usize,
+    config: FilterConfig,
+}
+
+/// Lint the local (`.crux/filters/`) and global (`~/.config/crux/filters/`)
+/// filters.
+pub fn lint_filters() -> Vec<LintIssue> {
+    let mut dirs = vec![PathBuf::from(".crux/filters")];
+    if let Some(home) = home_dir() {
+        dirs.push(home.join(".config/crux/filters"));
+    }
+    lint_dirs(&dirs)
+}
+
+/// Lint every filter TOML under `dirs`, given in resolve precedence order.
+pub fn lint_dirs(dirs: &[PathBuf]) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut filters = Vec::new();
+
+    for (source, dir) in dirs.iter().enumerate() {
+        let mut paths = Vec::new();
+        toml_files(dir, &mut paths);
+        paths.sort();
+        for path in paths {
+            let mut issue = |command: Option<&str>, severity, message: String| {
+                issues.push(LintIssue {
+                    path: path.clone(),
+                    command: command.map(str::to_string),
+                    severity,
+                    message,
+                });
+            };
+            let contents = match std::fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    issue(None, Severity::Error, format!("unreadable: {e}"));
+                    continue;
+                }
+            };
+            match lint_source(&contents) {
+                Ok((config, problems)) => {
+                    for (severity, message) in problems {
+                        issue(Some(&config.command), severity, message);
+                    }
+                    filters.push(UserFilter {
+                        path: path.clone(),
+                        source,
+                        config,
+                    });
+                }
+                Err(message) => issue(None, Severity::Error, message),
+            }
+        }
+    }
+
+    check_overlaps(&filters, &mut issues);
+    check_extends(&filters, &mut issues);
+    issues
+}
+
+/// Parse one filter file and run the per-file checks.
+///
+/// Returns the config with its issues, or the parse error.
+fn lint_source(contents: &str) -> Result<(FilterConfig, Vec<(Severity, String)>), String> {
+    let raw: toml::Value = toml::from_str(contents).map_err(|e| format!("invalid TOML: {e}"))?;
+    let config: FilterConfig =
+        toml::from_str(contents).map_err(|e| format!("invalid filter: {e}"))?;
+
+    let mut problems = Vec::new();
+    if config.command.trim().is_empty() {
+        problems.push((Severity::Error, "`command` is empty".to_string()));
+    }
+
+    // Every field the config knows about survives a round trip (unset options
+    // as null), so keys missing from it are ones serde ignored.
+    let known = serde_json::to_value(&config).unwrap_or_default();
+    let mut unknown = Vec::new();
+    unknown_keys(&raw, &known, "", &mut unknown);
+    for key in unknown {
+        problems.push((Severity::Error, format!("unknown field `{key}`")));
+    }
+
+    for (field, pattern) in regexes(&config) {
+        if let Err(e) = Regex::new(pattern) {
+            let reason = e.to_string();
+            let reason = reason.lines().last().unwrap_or_default().trim();
+            problems.push((
+                Severity::Error,
+                format!("invalid regex in `{field}`: {pattern:?} ({reason})"),
+            ));
+        }
+    }
+
+    for name in &config.stage_order {
+        if Stage::parse(name).is_none() {
+            problems.push((
+                Severity::Warning,
+                format!("unknown stage `{name}` in `stage_order`"),
+            ));
+        }
+    }
+
+    if !has_effect(&config) {
+        problems.push((
+            Severity::Warning,
+            "no effective stages; output passes through unchanged".to_string(),
+        ));
+    }
+
+    Ok((config, problems))
+}
+
+/// Collect dotted paths of keys in `raw` that have no counterpart in `known`.
+fn unknown_keys(raw: &toml::Value, known: &serde_json::Value, at: &str, out: &mut Vec<String>) {
+    match (raw, known) {
+        (toml::Value::Table(table), serde_json::Value::Object(object)) => {
+            for (key, value) in table {
+                let path = if at.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{at}.{key}")
+                };
+                match object.get(key) {
+                    Some(known) => unknown_keys(value, known, &path, out),
+                    None => out.push(path),
+                }
+            }
+        }
+        (toml::Value::Array(items), serde_json::Value::Array(known)) => {
+            for (i, (item, known)) in items.iter().zip(known).enumerate() {
+                unknown_keys(item, known, &format!("{at}[{i}]"), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Every regex in the config, labelled with the field it came from.
+fn regexes(config: &FilterConfig) -> Vec<(String, &str)> {
+    let mut out = Vec::new();
+    if let Some(pattern) = config
+        .command_match
+        .as_ref()
+        .and_then(|m| m.regex.as_deref())
+    {
+        out.push(("match.regex".to_string(), pattern));
+    }
+    push_lists("", &config.skip, &config.keep, &mut out);
+    push_rules(
+        "",
+        &config.replace,
+        &config.section,
+        &config.extract,
+        &mut out,
+    );
+    for (i, rule) in config.match_output.iter().enumerate() {
+        if let Some(pattern) = rule.regex.as_deref() {
+            out.push((format!("match_output[{i}].regex"), pattern));
+        }
+    }
+    for (i, rule) in config.variant.iter().enumerate() {
+        if let Some(pattern) = rule.detect_output.as_deref() {
+            out.push((format!("variant[{i}].detect_output"), pattern));
+        }
+    }
+    let blocks = [
+        ("on_success", &config.on_success),
+        ("on_failure", &config.on_failure),
+    ];
+    for (name, block) in blocks {
+        if let Some(block) = block {
+            rule_block_regexes(name, block, &mut out);
+        }
+    }
+    for (name, block) in &config.profile {
+        rule_block_regexes(&format!("profile.{name}"), block, &mut out);
+    }
+    out
+}
+
+fn rule_block_regexes<'a>(prefix: &str, block: &'a RuleBlock, out: &mut Vec<(String, &'a str)>) {
+    let prefix = format!("{prefix}.");
+    push_lists(&prefix, &block.skip, &block.keep, out);
+    push_rules(&prefix, &block.replace, &block.section, &block.extract, out);
+}
+
+fn push_lists<'a>(
+    prefix: &str,
+    skip: &'a [String],
+    keep: &'a [String],
+    out: &mut Vec<(String, &'a str)>,
+) {
+    for (name, list) in [("skip", skip), ("keep", keep)] {
+        for (i, pattern) in list.iter().enumerate() {
+            out.push((format!("{prefix}{name}[{i}]"), pattern.as_str()));
+        }
+    }
+}
+
+fn push_rules<'a>(
+    prefix: &str,
+    replace: &'a [ReplaceRule],
+    section: &'a [SectionRule],
+    extract: &'a [ExtractRule],
+    out: &mut Vec<(String, &'a str)>,
+) {
+    for (i, rule) in replace.iter().enumerate() {
+        out.push((
+            format!("{prefix}replace[{i}].pattern"),
+            rule.pattern.as_str(),
+        ));
+    }
+    for (i, rule) in section.iter().enumerate() {
+        out.push((format!("{prefix}section[{i}].start"), rule.start.as_str()));
+        if let Some(end) = rule.end.as_deref() {
+            out.push((format!("{prefix}section[{i}].end"), end));
+        }
+    }
+    for (i, rule) in extract.iter().enumerate() {
+        out.push((
+            format!("{prefix}extract[{i}].pattern"),
+            rule.pattern.as_str(),
+        ));
+    }
+}
+
+/// Does the filter change output at all?
+fn has_effect(config: &FilterConfig) -> bool {
+    #[cfg(feature = "lua")]
+    if config.lua.is_some() {
+        return true;
+    }
+    DEFAULT_ORDER
+        .iter()
+        .any(|stage| stage.is_configured(config))
+        || config.builtin == Some(true)
+        || config.extends.is_some()
+        || !config.pipeline.is_empty()
+        || !config.match_output.is_empty()
+        || !config.variant.is_empty()
+        || config.on_success.is_some()
+        || config.on_failure.is_some()
+        || !config.profile.is_empty()
+        || config.max_bytes.is_some()
+}
+
+/// Cross-file checks: ties between user filters, user filters that never win,
+/// and user filters that replace stdlib filters or builtin handlers.
+fn check_overlaps(filters: &[UserFilter], issues: &mut Vec<LintIssue>) {
+    for (i, filter) in filters.iter().enumerate() {
+        let config = &filter.config;
+        if config.command.trim().is_empty() {
+            continue;
+        }
+        let mut issue = |severity, message: String| {
+            issues.push(LintIssue {
+                path: filter.path.clone(),
+                command: Some(config.command.clone()),
+                severity,
+                message,
+            });
+        };
+
+        // Earlier user filters for the same command (same dir first, then the
+        // higher-precedence dirs) have already been compared against this one.
+        for other in &filters[i + 1..] {
+            let theirs = &other.config;
+            if theirs.command != config.command || !same_conditions(config, theirs) {
+                continue;
+            }
+            if theirs.priority > config.priority {
+                issue(
+                    Severity::Error,
+                    format!(
+                        "never used: {} has higher priority ({} > {})",
+                        other.path.display(),
+                        theirs.priority,
+                        config.priority
+                    ),
+                );
+            } else if theirs.priority == config.priority && other.source == filter.source {
+                issue(
+                    Severity::Warning,
+                    format!(
+                        "same command and priority ({}) as {}; which one wins is unspecified",
+                        config.priority,
+                        other.path.display()
+                    ),
+                );
+            }
+        }
+
+        check_shadowing(config, &mut issue);
+    }
+}
+
+/// True if two filters apply under the same project and env conditions.
+fn same_conditions(a: &FilterConfig, b: &FilterConfig) -> bool {
+    a.requires_project == b.requires_project && a.when_env == b.when_env
+}
+
+/// Compare a user filter with the stdlib filter and builtin handler for its
+/// command.
+fn check_shadowing(config: &FilterConfig, issue: &mut impl FnMut(Severity, String)) {
+    if let Some(stdlib) = stdlib_filters()
+        .iter()
+        .filter(|s| s.command == config.command && same_conditions(config, s))
+        .max_by_key(|s| s.priority)
+    {
+        if stdlib.priority > config.priority {
+            issue(
+                Severity::Error,
+                format!(
+                    "never used: the stdlib filter has higher priority ({} > {})",
+                    stdlib.priority, config.priority
+                ),
+            );
+        } else {
+            issue(
+                Severity::Warning,
+                "shadows the stdlib filter for this command".to_string(),
+            );
+        }
+    }
+
+    if crate::filter::builtin::registry().contains_key(config.command.as_str())
+        && config.builtin != Some(true)
+        && config.extends.as_deref() != Some(config.command.as_str())
+    {
+        issue(
+            Severity::Warning,
+            "replaces the builtin handler (set `builtin = true` to keep it)".to_string(),
+        );
+    }
+}
+
+/// Report `extends` names that resolve to no base.
+///
+/// A base is looked up among lower-precedence filters only (later user
+/// files, the stdlib, builtin handlers); otherwise the key is ignored.
+fn check_extends(filters: &[UserFilter], issues: &mut Vec<LintIssue>) {
+    for (i, filter) in filters.iter().enumerate() {
+        let Some(name) = filter.config.extends.as_deref() else {
+            continue;
+        };
+        let found = filters[i + 1..].iter().any(|f| f.config.command == name)
+            || stdlib_filters().iter().any(|s| s.command == name)
+            || crate::filter::builtin::registry().contains_key(name);
+        if !found {
+            issues.push(LintIssue {
+                path: filter.path.clone(),
+                command: Some(filter.config.command.clone()),
+                severity: Severity::Error,
+                message: format!("extends unknown filter `{name}`"),
+            });
+        }
+    }
+}
+
+/// Recursively collect `.toml` files, skipping `_test` directories.
+fn toml_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let is_test = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with("_test"));
+            if !is_test {
+                toml_files(&path, out);
+            }
+        } else if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+            out.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(contents: &str) -> Vec<String> {
+        let (_, problems) = lint_source(contents).unwrap();
+        problems.into_iter().map(|(_, message)| message).collect()
+    }
+
+    #[test]
+    fn reports_unknown_fields_at_any_depth() {
+        let found = messages(
+            r#"
+command = "make"
+skp = ["^make"]
+[[replace]]
+pattern = "a"
+replacement = "b"
+replace_all = true
+[on_failure]
+tial = 5
+"#,
+        );
+        assert!(found.contains(&"unknown field `skp`".to_string()));
+        assert!(found.contains(&"unknown field `replace[0].replace_all`".to_string()));
+        assert!(found.contains(&"unknown field `on_failure.tial`".to_string()));
+    }
+
+    #[test]
+    fn known_fields_are_not_reported() {
+        let found = messages(
+            r#"
+command = "make"
+match = { regex = "^make" }
+when_env = { CI = "true" }
+[profile.aggressive]
+max_lines = 10
+[[variant]]
+name = "x"
+when_args = ["--json"]
+"#,
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn reports_invalid_regexes_with_field() {
+        let found = messages(
+            r#"
+command = "make"
+keep = ["ok", "(unclosed"]
+[[section]]
+start = "^a"
+end = "[z-a]"
+[on_failure]
+skip = ["*"]
+"#,
+        );
+        assert_eq!(found.len(), 3, "{found:?}");
+        assert!(found[0].starts_with("invalid regex in `keep[1]`: \"(unclosed\""));
+        assert!(found[1].starts_with("invalid regex in `section[0].end`"));
+        assert!(found[2].starts_with("invalid regex in `on_failure.skip[0]`"));
+    }
+
+    #[test]
+    fn reports_filters_without_effect() {
+        let found = messages("command = \"make\"\ndescription = \"nothing\"\n");
+        assert_eq!(
+            found,
+            vec!["no effective stages; output passes through unchanged"]
+        );
+        assert!(messages("command = \"make\"\ntail = 5\n").is_empty());
+        assert!(messages("command = \"make\"\nextends = \"make\"\n").is_empty());
+    }
+
+    #[test]
+    fn parse_errors_are_reported() {
+        assert!(lint_source("command = ")
+            .unwrap_err()
+            .starts_with("invalid TOML"));
+        assert!(lint_source("command = 5")
+            .unwrap_err()
+            .starts_with("invalid filter"));
+    }
+
+    #[test]
+    fn reports_ties_and_unreachable_filters() {
+        let dir = std::env::temp_dir().join(format!("crux-lint-{}", std::process::id()));
+        let (local, global) = (dir.join("local"), dir.join("global"));
+        std::fs::create_dir_all(&local).unwrap();
+        std::fs::create_dir_all(&global).unwrap();
+        let filter =
+            |priority: i32| format!("command = \"my tool\"\npriority = {priority}\ntail = 5\n");
+        std::fs::write(local.join("a.toml"), filter(0)).unwrap();
+        std::fs::write(local.join("b.toml"), filter(0)).unwrap();
+        std::fs::write(global.join("c.toml"), filter(10)).unwrap();
+
+        let issues = lint_dirs(&[local.clone(), global.clone()]);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let about = |name: &str| -> Vec<&str> {
+            issues
+                .iter()
+                .filter(|i| i.path.ends_with(name))
+                .map(|i| i.message.as_str())
+                .collect()
+        };
+        let a = about("a.toml");
+        assert_eq!(a.len(), 2, "{a:?}");
+        assert!(a[0].starts_with("same command and priority (0)"));
+        assert!(a[1].starts_with("never used:"));
+        assert!(about("c.toml").is_empty());
+    }
+
+    #[test]
+    fn reports_unknown_extends_base() {
+        let dir = std::env::temp_dir().join(format!("crux-lint-extends-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let filter = |command: &str, base: &str| {
+            format!("command = \"{command}\"\nextends = \"{base}\"\ntail = 5\n")
+        };
+        std::fs::write(dir.join("a.toml"), filter("my tool", "my base")).unwrap();
+        std::fs::write(dir.join("b.toml"), filter("my base", "make")).unwrap();
+        std::fs::write(dir.join("c.toml"), filter("my build", "cargo build")).unwrap();
+        std::fs::write(dir.join("d.toml"), filter("my test", "no such filter")).unwrap();
+
+        let issues = lint_dirs(std::slice::from_ref(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let unknown: Vec<_> = issues
+            .iter()
+            .filter(|i| i.message.starts_with("extends unknown"))
+            .collect();
+        assert_eq!(unknown.len(), 1, "{issues:?}");
+        assert!(unknown[0].path.ends_with("d.toml"));
+        assert_eq!(
+            unknown[0].message,
+            "extends unknown filter `no such filter`"
+        );
+    }
+
+    #[test]
+    fn stdlib_filters_lint_clean() {
+        let mut paths = Vec::new();
+        toml_files(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("filters"),
+            &mut paths,
+        );
+        assert!(!paths.is_empty());
+        for path in paths {
+            let contents = std::fs::read_to_string(&path).unwrap();
+            let (_, problems) = lint_source(&contents).unwrap();
+            let errors: Vec<_> = problems
+                .iter()
+                .filter(|(severity, _)| *severity == Severity::Error)
+                .collect();
+            assert!(errors.is_empty(), "{}: {errors:?}", path.display());
+        }
+    }
+}
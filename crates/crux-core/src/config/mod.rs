@@ -1,7 +1,17 @@
 #[cfg(feature = "cache")]
 pub mod cache;
+pub mod env;
+pub mod lint;
+pub mod pipe;
+pub mod profile;
+pub mod project;
 pub mod resolve;
+pub mod rule_block;
+pub mod settings;
 pub mod types;
 
-pub use resolve::{count_filters, resolve_filter, FilterCounts, BUILTIN_FALLBACK_PRIORITY};
+pub use resolve::{
+    count_filters, find_filter_by_name, match_candidates, resolve_filter, stdlib_filters,
+    Candidate, FilterCounts, FilterSource, BUILTIN_FALLBACK_PRIORITY,
+};
 pub use types::FilterConfig;
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+/// Which command of a shell pipeline (`cargo test 2>&1 | tee log`) drives
+/// filter selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PipeTarget {
+    /// The producing command (`cargo test`).
+    #[default]
+    First,
+    /// The final consumer (`tee log`).
+    Last,
+}
+
+/// Split command tokens into pipeline segments on `|` / `|&`, dropping
+/// redirections (`2>&1`, `> out.log`, `<in`) from each segment.
+///
+/// `||` is a logical operator, not a pipe, and is left alone. Empty
+/// segments are dropped.
+pub fn split_pipeline(command: &[String]) -> Vec<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut tokens = command.iter();
+    while let Some(token) = tokens.next() {
+        match token.as_str() {
+            "|" | "|&" => segments.push(std::mem::take(&mut current)),
+            t if is_redirection_operator(t) => {
+                tokens.next();
+            }
+            t if is_redirection(t) => {}
+            _ => current.push(token.clone()),
+        }
+    }
+    segments.push(current);
+    segments.retain(|s| !s.is_empty());
+    segments
+}
+
+/// Segments of `command` in the order they should be tried for `target`.
+pub fn segments_for(command: &[String], target: PipeTarget) -> Vec<Vec<String>> {
+    let mut segments = split_pipeline(command);
+    if target == PipeTarget::Last {
+        segments.reverse();
+    }
+    segments
+}
+
+/// A bare operator whose target is the next token (`>`, `2>>`, `<`).
+fn is_redirection_operator(token: &str) -> bool {
+    let op = token.trim_start_matches(|c: char| c.is_ascii_digit());
+    matches!(op, ">" | ">>" | "<" | "&>" | "&>>" | ">|")
+}
+
+/// An operator with its target attached (`2>&1`, `>/dev/null`, `<input`).
+fn is_redirection(token: &str) -> bool {
+    let op = token.trim_start_matches(|c: char| c.is_ascii_digit());
+    op.len() > 1 && (op.starts_with('>') || op.starts_with('<') || op.starts_with("&>"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(cmd: &str) -> Vec<String> {
+        cmd.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn splits_on_pipes_and_drops_redirections() {
+        assert_eq!(
+            split_pipeline(&tokens("cargo test 2>&1 | tee test.log")),
+            vec![tokens("cargo test"), tokens("tee test.log")]
+        );
+        assert_eq!(
+            split_pipeline(&tokens("make > build.log 2> /dev/null")),
+            vec![tokens("make")]
+        );
+        assert_eq!(
+            split_pipeline(&tokens("go test ./... |& grep FAIL")),
+            vec![tokens("go test ./..."), tokens("grep FAIL")]
+        );
+    }
+
+    #[test]
+    fn logical_or_is_not_a_pipe() {
+        assert_eq!(
+            split_pipeline(&tokens("make || true")),
+            vec![tokens("make || true")]
+        );
+    }
+
+    #[test]
+    fn last_target_reverses_order() {
+        let cmd = tokens("cargo test | tee log");
+        assert_eq!(
+            segments_for(&cmd, PipeTarget::First)[0],
+            tokens("cargo test")
+        );
+        assert_eq!(segments_for(&cmd, PipeTarget::Last)[0], tokens("tee log"));
+    }
+}
@@ -0,0 +1,36 @@
+/// Environment variable selecting the active profile (`crux --profile` sets it).
+pub const PROFILE_ENV: &str = "CRUX_PROFILE";
+
+/// The active aggressiveness profile, e.g. `conservative` or `aggressive`.
+///
+/// `normal` (the default) and an unset or empty variable mean no profile:
+/// filters run with their top-level rules only. When the variable is unset,
+/// the `profile` from the crux config file is used.
+pub fn active_profile() -> Option<String> {
+    match std::env::var(PROFILE_ENV) {
+        Ok(value) => parse_profile(&value),
+        Err(_) => parse_profile(super::settings::settings().profile.as_deref()?),
+    }
+}
+
+fn parse_profile(value: &str) -> Option<String> {
+    match value.trim() {
+        "" | "normal" => None,
+        name => Some(name.to_ascii_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_and_empty_mean_no_profile() {
+        assert_eq!(parse_profile(""), None);
+        assert_eq!(parse_profile("normal"), None);
+        assert_eq!(
+            parse_profile(" Aggressive "),
+            Some("aggressive".to_string())
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::path::Path;
+
+/// Marker files that identify a project type, checked in the working directory.
+const PROJECT_MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "rust"),
+    ("package.json", "node"),
+    ("go.mod", "go"),
+    ("pyproject.toml", "python"),
+    ("setup.py", "python"),
+    ("requirements.txt", "python"),
+];
+
+/// Detect the project types of `dir` from its marker files.
+///
+/// A directory can be several types at once (e.g. a Rust crate with a
+/// `package.json` for its docs site). Each type is listed once.
+pub fn detect_project_types(dir: &Path) -> Vec<&'static str> {
+    let mut types: Vec<&'static str> = Vec::new();
+    for &(marker, kind) in PROJECT_MARKERS {
+        if !types.contains(&kind) && dir.join(marker).is_file() {
+            types.push(kind);
+        }
+    }
+    types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_marker_files() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(detect_project_types(dir.path()).is_empty());
+
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        std::fs::write(dir.path().join("requirements.txt"), "").unwrap();
+        assert_eq!(detect_project_types(dir.path()), vec!["node", "python"]);
+    }
+
+    #[test]
+    fn marker_directory_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("go.mod")).unwrap();
+        assert!(detect_project_types(dir.path()).is_empty());
+    }
+}
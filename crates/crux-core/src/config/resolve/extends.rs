@@ -0,0 +1,63 @@
+//! Merging each filter's `extends` base into it.
+
+use crate::config::types::FilterConfig;
+
+/// Merge each candidate's `extends` base into it.
+///
+/// A base is looked up among the candidates *after* it (lower-precedence
+/// sources), so a local `cargo test` can extend the stdlib `cargo test`.
+/// Candidates are processed back to front so a base's own `extends` is
+/// already applied; a builtin handler with no TOML acts as an empty base.
+/// An unknown base is ignored here; `crux lint` reports it.
+pub(super) fn resolve_extends(candidates: &mut [FilterConfig]) {
+    for i in (0..candidates.len()).rev() {
+        let Some(name) = candidates[i].extends.clone() else {
+            continue;
+        };
+        let base = candidates[i + 1..]
+            .iter()
+            .filter(|c| c.command == name)
+            .rev()
+            .max_by_key(|c| c.priority)
+            .cloned();
+        if let Some(base) = base {
+            candidates[i].inherit_from(&base);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::resolve::tests::make_config;
+
+    #[test]
+    fn extends_merges_lower_precedence_base() {
+        let mut local = make_config("make", 0);
+        local.extends = Some("make".to_string());
+        local.skip = vec!["^local".to_string()];
+        let mut stdlib = make_config("make", 0);
+        stdlib.skip = vec!["^stdlib".to_string()];
+        stdlib.dedup = Some(true);
+
+        let mut candidates = vec![local, stdlib];
+        resolve_extends(&mut candidates);
+        assert_eq!(candidates[0].skip, vec!["^stdlib", "^local"]);
+        assert_eq!(candidates[0].dedup, Some(true));
+    }
+
+    #[test]
+    fn extends_chain_resolves_transitively() {
+        let mut a = make_config("a", 0);
+        a.extends = Some("b".to_string());
+        let mut b = make_config("b", 0);
+        b.extends = Some("c".to_string());
+        b.keep = vec!["^b".to_string()];
+        let mut c = make_config("c", 0);
+        c.keep = vec!["^c".to_string()];
+
+        let mut candidates = vec![a, b, c];
+        resolve_extends(&mut candidates);
+        assert_eq!(candidates[0].keep, vec!["^c", "^b"]);
+    }
+}
@@ -0,0 +1,159 @@
+//! Loading filter configs from disk and from the embedded stdlib.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+
+use crate::config::types::FilterConfig;
+
+/// Recursively scan a directory for `.toml` files and parse them.
+pub(super) fn load_configs_from_dir(dir: &Path) -> Result<Vec<FilterConfig>> {
+    let mut configs = Vec::new();
+    if !dir.is_dir() {
+        return Ok(configs);
+    }
+    collect_toml_files(dir, &mut configs)?;
+    Ok(configs)
+}
+
+fn collect_toml_files(dir: &Path, configs: &mut Vec<FilterConfig>) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("reading directory {}", dir.display()))?;
+
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            // Skip directories whose name ends with `_test` (declarative test suites).
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                if name.ends_with("_test") {
+                    continue;
+                }
+            }
+            collect_toml_files(&path, configs)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+            match parse_toml_file(&path) {
+                Ok(config) => configs.push(config),
+                Err(e) => {
+                    eprintln!("crux: skipping {}: {e}", path.display());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_toml_file(path: &Path) -> Result<FilterConfig> {
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let mut config: FilterConfig =
+        toml::from_str(&contents).with_context(|| format!("parsing {}", path.display()))?;
+    crate::config::env::interpolate_config(&mut config);
+    Ok(config)
+}
+
+/// The TOML filters of the embedded stdlib (not builtins or user filters).
+pub fn stdlib_filters() -> &'static [FilterConfig] {
+    cached_embedded_stdlib()
+}
+
+/// Return a cached reference to parsed embedded stdlib filters.
+///
+/// The embedded TOML files are parsed once on first access and then reused
+/// for every subsequent `resolve_filter` call, avoiding repeated
+/// deserialization overhead on the hot path.
+pub(super) fn cached_embedded_stdlib() -> &'static [FilterConfig] {
+    static CACHE: OnceLock<Vec<FilterConfig>> = OnceLock::new();
+    CACHE.get_or_init(load_embedded_stdlib)
+}
+
+/// Load embedded stdlib filters compiled into the binary via `include_dir`.
+fn load_embedded_stdlib() -> Vec<FilterConfig> {
+    use include_dir::{include_dir, Dir};
+
+    static STDLIB_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/filters");
+
+    parse_embedded_dir(&STDLIB_DIR)
+}
+
+fn parse_embedded_dir(dir: &include_dir::Dir<'_>) -> Vec<FilterConfig> {
+    let mut configs = Vec::new();
+
+    for file in dir.files() {
+        if file.path().extension().and_then(|e| e.to_str()) == Some("toml") {
+            if let Some(contents) = file.contents_utf8() {
+                match toml::from_str::<FilterConfig>(contents) {
+                    Ok(mut config) => {
+                        crate::config::env::interpolate_config(&mut config);
+                        configs.push(config);
+                    }
+                    Err(e) => {
+                        eprintln!("crux: skipping embedded {}: {e}", file.path().display());
+                    }
+                }
+            }
+        }
+    }
+
+    for subdir in dir.dirs() {
+        // Skip _test directories
+        if let Some(name) = subdir.path().file_name().and_then(|n| n.to_str()) {
+            if name.ends_with("_test") {
+                continue;
+            }
+        }
+        configs.extend(parse_embedded_dir(subdir));
+    }
+
+    configs
+}
+
+/// Counts of filters broken down by source category.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct FilterCounts {
+    pub builtin: usize,
+    pub stdlib_toml: usize,
+    pub user_local: usize,
+    pub user_global: usize,
+}
+
+impl FilterCounts {
+    pub fn total(&self) -> usize {
+        self.builtin + self.stdlib_toml + self.user_local + self.user_global
+    }
+}
+
+/// Count all available filters by source category.
+pub fn count_filters() -> FilterCounts {
+    let builtin = crate::filter::builtin::registry().len();
+    let stdlib_toml = cached_embedded_stdlib().len();
+
+    let user_local = load_configs_from_dir(Path::new(".crux/filters"))
+        .map(|c| c.len())
+        .unwrap_or(0);
+
+    let user_global = home_dir()
+        .and_then(|h| load_configs_from_dir(&h.join(".config/crux/filters")).ok())
+        .map(|c| c.len())
+        .unwrap_or(0);
+
+    FilterCounts {
+        builtin,
+        stdlib_toml,
+        user_local,
+        user_global,
+    }
+}
+
+/// Platform-aware home directory lookup.
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var("USERPROFILE").ok().map(PathBuf::from)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        std::env::var("HOME").ok().map(PathBuf::from)
+    }
+}
@@ -0,0 +1,207 @@
+//! Scoring a filter's `command` and `match` patterns against a command line.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use regex::Regex;
+
+use crate::config::types::{CommandMatch, FilterConfig};
+
+/// Strip shell noise from a command string passed to `bash -c` / `sh -c`.
+///
+/// Removes surrounding quotes and trailing shell redirections/pipes that
+/// prevent filter matching (e.g. `2>&1`, `| head -200`).
+pub(super) fn strip_shell_noise(cmd: &str) -> String {
+    let mut s = cmd.trim();
+
+    // Strip surrounding quotes
+    if (s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')) {
+        s = &s[1..s.len() - 1];
+        s = s.trim();
+    }
+
+    let mut result = s.to_string();
+
+    // Repeatedly strip trailing pipe expressions and redirections
+    loop {
+        let before = result.clone();
+
+        // Remove trailing pipe segments: | head -200, | tail -n 100, | grep ...
+        if let Some(pipe_pos) = result.rfind('|') {
+            let after_pipe = result[pipe_pos + 1..].trim();
+            let pipe_cmd = after_pipe.split_whitespace().next().unwrap_or("");
+            if matches!(
+                pipe_cmd,
+                "head" | "tail" | "grep" | "sort" | "wc" | "less" | "more"
+            ) {
+                result = result[..pipe_pos].trim_end().to_string();
+            }
+        }
+
+        // Remove trailing redirections: 2>&1, 2>/dev/null, >/dev/null, etc.
+        for pattern in &["2>&1", "2>/dev/null", ">/dev/null", "&>/dev/null"] {
+            if result.ends_with(pattern) {
+                result = result[..result.len() - pattern.len()]
+                    .trim_end()
+                    .to_string();
+            }
+        }
+
+        if result == before {
+            break;
+        }
+    }
+
+    result
+}
+
+/// Build the full command string from tokens for matching.
+pub(super) fn command_string(command: &[String]) -> String {
+    command.join(" ")
+}
+
+/// Score how well a filter's command pattern matches the input command.
+///
+/// Returns `None` if there is no match, or `Some(specificity)` where higher
+/// values indicate a more specific match.
+fn match_score(filter_command: &str, input_command: &str) -> Option<usize> {
+    let filter_cmd = filter_command.trim();
+    let input_cmd = input_command.trim();
+
+    if input_cmd == filter_cmd {
+        // Exact match — highest specificity = number of words
+        return Some(filter_cmd.split_whitespace().count() * 100);
+    }
+
+    // Prefix match: "git" matches "git status", "git diff", etc.
+    // Also match colon separators for npm scripts: "npm run test" matches "npm run test:unit"
+    if let Some(rest) = input_cmd.strip_prefix(filter_cmd) {
+        if rest.starts_with(char::is_whitespace) || rest.starts_with(':') {
+            return Some(filter_cmd.split_whitespace().count() * 100);
+        }
+    }
+
+    None
+}
+
+/// Score a candidate against the input command.
+///
+/// Filters with a `match` table match only through their patterns, scoring
+/// like a prefix match on their `command`; others use [`match_score`].
+pub(super) fn config_score(config: &FilterConfig, input_command: &str) -> Option<usize> {
+    match &config.command_match {
+        Some(patterns) => pattern_matches(patterns, input_command.trim())
+            .then(|| config.command.split_whitespace().count() * 100),
+        None => match_score(&config.command, input_command),
+    }
+}
+
+/// True if the regex or glob in `patterns` matches the command line.
+/// Invalid patterns never match.
+fn pattern_matches(patterns: &CommandMatch, input_command: &str) -> bool {
+    let regex_hit = patterns
+        .regex
+        .as_deref()
+        .and_then(cached_regex)
+        .is_some_and(|re| re.is_match(input_command));
+    let glob_hit = patterns
+        .glob
+        .as_deref()
+        .and_then(|glob| cached_regex(&glob_to_regex(glob)))
+        .is_some_and(|re| re.is_match(input_command));
+    regex_hit || glob_hit
+}
+
+/// Compile `pattern`, or `None` if it is invalid.
+///
+/// Every candidate with a `match` table is scored on each resolution, so
+/// compiled patterns (and failures) are kept for the life of the process.
+fn cached_regex(pattern: &str) -> Option<Regex> {
+    static PATTERNS: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+    PATTERNS
+        .get_or_init(Default::default)
+        .lock()
+        .ok()?
+        .entry(pattern.to_string())
+        .or_insert_with(|| Regex::new(pattern).ok())
+        .clone()
+}
+
+/// Translate a shell-style glob into an anchored regex.
+fn glob_to_regex(glob: &str) -> String {
+    let mut re = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            _ => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    re
+}
+
+/// True if `flag` appears in `args`, either alone or as `flag=value`.
+pub(crate) fn args_contain_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|arg| {
+        arg == flag
+            || arg
+                .strip_prefix(flag)
+                .is_some_and(|rest| rest.starts_with('='))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_score_no_partial_word_match() {
+        // "git" should NOT match "gitk"
+        assert!(match_score("git", "gitk").is_none());
+    }
+
+    #[test]
+    fn glob_escapes_regex_metacharacters() {
+        assert_eq!(glob_to_regex("make.?"), r"^make\..$");
+    }
+
+    #[test]
+    fn match_score_exact() {
+        assert_eq!(match_score("git status", "git status"), Some(200));
+    }
+
+    #[test]
+    fn match_score_prefix() {
+        assert_eq!(match_score("git", "git status"), Some(100));
+    }
+
+    #[test]
+    fn match_score_colon_separator() {
+        // "npm run test" should match "npm run test:unit" via colon prefix
+        assert_eq!(match_score("npm run test", "npm run test:unit"), Some(300));
+        assert_eq!(match_score("npm run test", "npm run test:e2e"), Some(300));
+    }
+
+    #[test]
+    fn match_score_no_colon_partial() {
+        // "npm run tes" should NOT match "npm run test:unit"
+        assert!(match_score("npm run tes", "npm run test:unit").is_none());
+    }
+
+    #[test]
+    fn strip_shell_noise_removes_quotes_and_redirections() {
+        assert_eq!(strip_shell_noise("\"git status\""), "git status");
+        assert_eq!(strip_shell_noise("'git status'"), "git status");
+        assert_eq!(strip_shell_noise("docker ps 2>&1"), "docker ps");
+        assert_eq!(
+            strip_shell_noise("\"npm ls --depth=0 2>&1\""),
+            "npm ls --depth=0"
+        );
+        assert_eq!(
+            strip_shell_noise("\"npx vitest run 2>&1 | head -200\""),
+            "npx vitest run"
+        );
+        assert_eq!(strip_shell_noise("git status"), "git status");
+    }
+}
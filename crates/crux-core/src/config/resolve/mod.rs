@@ -0,0 +1,466 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::types::FilterConfig;
+
+mod extends;
+mod load;
+mod matching;
+mod overlays;
+mod rank;
+
+pub(crate) use load::home_dir;
+pub use load::{count_filters, stdlib_filters, FilterCounts};
+pub(crate) use matching::args_contain_flag;
+pub use rank::{match_candidates, Candidate};
+
+use extends::resolve_extends;
+use load::{cached_embedded_stdlib, load_configs_from_dir};
+use matching::{command_string, config_score, strip_shell_noise};
+use overlays::{for_env, for_project};
+use rank::candidate_rank;
+
+/// Priority assigned to builtin filter stubs when no TOML config exists.
+///
+/// This is the lowest priority so that any user or stdlib TOML filter always
+/// wins over the auto-generated builtin stub. The value -100 was chosen to
+/// leave plenty of room for negative-priority TOML overrides while ensuring
+/// builtins never accidentally shadow user config.
+pub const BUILTIN_FALLBACK_PRIORITY: i32 = -100;
+
+/// Directories searched for filter configs, in priority order:
+/// 1. `.crux/filters/` — local project overrides
+/// 2. `~/.config/crux/filters/` — global user filters
+/// 3. Embedded stdlib (via `include_dir`)
+///
+/// First match wins. Most specific command match wins, then highest priority.
+///
+/// Resolve a filter for the given command tokens.
+///
+/// Returns `None` when no filter matches (passthrough behavior), or when the
+/// match is listed in `disabled_builtins`. Defaults and `[commands]`
+/// overrides from the crux config file are applied to the match.
+pub fn resolve_filter(command: &[String]) -> Option<FilterConfig> {
+    let settings = super::settings::settings();
+    let mut config = find_filter(command)?;
+    if settings.is_disabled(&config.command) {
+        return None;
+    }
+    settings.apply_to(&mut config, command);
+    Some(config)
+}
+
+fn find_filter(command: &[String]) -> Option<FilterConfig> {
+    if command.is_empty() {
+        return None;
+    }
+
+    // Shell pipelines and redirections: try each command of the pipeline,
+    // starting with the one `pipe_target` selects.
+    let target = super::settings::settings().pipe_target.unwrap_or_default();
+    let segments = super::pipe::segments_for(command, target);
+    if segments.len() != 1 || segments[0] != command {
+        return segments.iter().find_map(|segment| find_filter(segment));
+    }
+
+    let project_types = super::project::detect_project_types(Path::new("."));
+    let candidates = for_env(for_project(collect_candidates(), &project_types), |name| {
+        std::env::var(name).ok()
+    });
+
+    // Try original command first
+    if let Some(result) = find_best_match(&candidates, command) {
+        return Some(result);
+    }
+
+    // Strip runner prefixes (npx, bunx, pnpx) and retry
+    if command.len() >= 2 {
+        let runner = command[0].as_str();
+        if matches!(runner, "npx" | "bunx" | "pnpx") {
+            return find_best_match(&candidates, &command[1..]);
+        }
+    }
+
+    // Strip shell wrapper (bash -c, sh -c) and retry
+    if command.len() >= 3 {
+        let shell = command[0].as_str();
+        if matches!(shell, "bash" | "sh") && command[1] == "-c" {
+            let inner_cmd = if command.len() == 3 {
+                command[2].clone()
+            } else {
+                command[2..].join(" ")
+            };
+            let cleaned = strip_shell_noise(&inner_cmd);
+            let inner_tokens: Vec<String> =
+                cleaned.split_whitespace().map(|s| s.to_string()).collect();
+            if !inner_tokens.is_empty() {
+                return find_filter(&inner_tokens);
+            }
+        }
+    }
+
+    None
+}
+
+/// Where a filter candidate was loaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterSource {
+    Local,
+    Global,
+    Stdlib,
+    Builtin,
+}
+
+impl FilterSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            FilterSource::Local => "local",
+            FilterSource::Global => "global",
+            FilterSource::Stdlib => "stdlib",
+            FilterSource::Builtin => "builtin",
+        }
+    }
+}
+
+/// Gather every filter config candidate, in priority order of source:
+/// local, global, embedded stdlib, then builtin stubs.
+fn collect_candidates() -> Vec<FilterConfig> {
+    collect_sourced_candidates()
+        .into_iter()
+        .map(|(_, config)| config)
+        .collect()
+}
+
+/// [`collect_candidates`], with the source of each candidate.
+fn collect_sourced_candidates() -> Vec<(FilterSource, FilterConfig)> {
+    let mut sources: Vec<FilterSource> = Vec::new();
+    let mut candidates: Vec<FilterConfig> = Vec::new();
+
+    // 1. Local project filters
+    if let Ok(configs) = load_configs_from_dir(Path::new(".crux/filters")) {
+        sources.extend(configs.iter().map(|_| FilterSource::Local));
+        candidates.extend(configs);
+    }
+
+    // 2. Global user filters
+    if let Some(home) = home_dir() {
+        let global_dir = home.join(".config/crux/filters");
+        if let Ok(configs) = load_configs_from_dir(&global_dir) {
+            sources.extend(configs.iter().map(|_| FilterSource::Global));
+            candidates.extend(configs);
+        }
+    }
+
+    // 3. Embedded stdlib (cached after first parse)
+    let stdlib = cached_embedded_stdlib();
+    sources.extend(stdlib.iter().map(|_| FilterSource::Stdlib));
+    candidates.extend_from_slice(stdlib);
+
+    // 4. Builtin registry stubs (lowest priority fallback)
+    // Ensures builtin handlers fire even when no TOML filters exist.
+    for key in crate::filter::builtin::registry().keys() {
+        if !candidates.iter().any(|c| c.command == *key) {
+            sources.push(FilterSource::Builtin);
+            candidates.push(FilterConfig {
+                command: key.to_string(),
+                priority: BUILTIN_FALLBACK_PRIORITY,
+                ..Default::default()
+            });
+        }
+    }
+
+    resolve_extends(&mut candidates);
+    sources.into_iter().zip(candidates).collect()
+}
+
+/// Look up a filter by its exact `command` name, e.g. a building-block
+/// filter referenced from another filter's `pipeline`.
+///
+/// Searches the same sources as [`resolve_filter`]; the highest priority wins,
+/// and on a tie the earlier source (local before global before stdlib).
+pub fn find_filter_by_name(name: &str) -> Option<FilterConfig> {
+    collect_candidates()
+        .into_iter()
+        .filter(|c| c.command == name)
+        .rev()
+        .max_by_key(|c| c.priority)
+}
+
+/// Among all candidates, pick the best match for the given command.
+fn find_best_match(candidates: &[FilterConfig], command: &[String]) -> Option<FilterConfig> {
+    find_best_match_with(candidates, command, &super::settings::settings().priority)
+}
+
+/// Like [`find_best_match`], with `priorities` overriding the priority of
+/// filters by `command`.
+///
+/// An overridden priority ranks ahead of command specificity: a positive
+/// override beats every match without one, a negative one loses to them.
+fn find_best_match_with(
+    candidates: &[FilterConfig],
+    command: &[String],
+    priorities: &BTreeMap<String, i32>,
+) -> Option<FilterConfig> {
+    let input = command_string(command);
+
+    let mut best: Option<((i32, usize, i32), &FilterConfig)> = None;
+
+    for config in candidates {
+        if excluded_by_args(config, command) {
+            continue;
+        }
+        if let Some(score) = config_score(config, &input) {
+            let rank = candidate_rank(config, score, priorities);
+            let better = match &best {
+                Some((best_rank, _)) => rank > *best_rank,
+                None => true,
+            };
+            if better {
+                best = Some((rank, config));
+            }
+        }
+    }
+
+    best.map(|(_, config)| {
+        let mut config = config.clone();
+        if let Some(&priority) = priorities.get(&config.command) {
+            config.priority = priority;
+        }
+        config
+    })
+}
+
+fn excluded_by_args(config: &FilterConfig, command: &[String]) -> bool {
+    config
+        .exclude_args
+        .iter()
+        .any(|flag| args_contain_flag(command, flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::CommandMatch;
+
+    pub(super) fn make_config(command: &str, priority: i32) -> FilterConfig {
+        FilterConfig {
+            command: command.to_string(),
+            priority,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exact_match_wins_over_prefix() {
+        let candidates = vec![make_config("git", 0), make_config("git status", 0)];
+        let cmd = vec!["git".to_string(), "status".to_string()];
+        let result = find_best_match(&candidates, &cmd).unwrap();
+        assert_eq!(result.command, "git status");
+    }
+
+    #[test]
+    fn prefix_match_works() {
+        let candidates = vec![make_config("git", 0)];
+        let cmd = vec!["git".to_string(), "log".to_string()];
+        let result = find_best_match(&candidates, &cmd).unwrap();
+        assert_eq!(result.command, "git");
+    }
+
+    #[test]
+    fn no_match_returns_none() {
+        let candidates = vec![make_config("cargo test", 0)];
+        let cmd = vec!["git".to_string(), "status".to_string()];
+        let result = find_best_match(&candidates, &cmd);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn higher_priority_wins_when_same_specificity() {
+        let candidates = vec![make_config("git status", 5), make_config("git status", 10)];
+        let cmd = vec!["git".to_string(), "status".to_string()];
+        let result = find_best_match(&candidates, &cmd).unwrap();
+        assert_eq!(result.priority, 10);
+    }
+
+    #[test]
+    fn empty_command_returns_none() {
+        let result = resolve_filter(&[]);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn builtin_stubs_provide_fallback_match() {
+        // Even with no TOML files, builtin commands should resolve
+        let cmd = vec!["git".to_string(), "status".to_string()];
+        let result = resolve_filter(&cmd);
+        assert!(result.is_some(), "git status should match via builtin stub");
+        assert_eq!(result.unwrap().command, "git status");
+    }
+
+    #[test]
+    fn builtin_stubs_for_cargo_test() {
+        let cmd = vec!["cargo".to_string(), "test".to_string()];
+        let result = resolve_filter(&cmd);
+        assert!(result.is_some(), "cargo test should match via builtin stub");
+        assert_eq!(result.unwrap().command, "cargo test");
+    }
+
+    pub(super) fn with_match(
+        command: &str,
+        regex: Option<&str>,
+        glob: Option<&str>,
+    ) -> FilterConfig {
+        FilterConfig {
+            command_match: Some(CommandMatch {
+                regex: regex.map(String::from),
+                glob: glob.map(String::from),
+            }),
+            ..make_config(command, 0)
+        }
+    }
+
+    pub(super) fn tokens(cmd: &str) -> Vec<String> {
+        cmd.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn regex_match_covers_script_family() {
+        let candidates = vec![with_match("npm run", Some("^npm run (build|dev)"), None)];
+        assert!(find_best_match(&candidates, &tokens("npm run build")).is_some());
+        assert!(find_best_match(&candidates, &tokens("npm run dev -- --port 3000")).is_some());
+        // The pattern replaces prefix matching.
+        assert!(find_best_match(&candidates, &tokens("npm run lint")).is_none());
+    }
+
+    #[test]
+    fn glob_match_whole_command() {
+        let candidates = vec![with_match("terraform", None, Some("terraform *plan*"))];
+        assert!(find_best_match(&candidates, &tokens("terraform plan -out x")).is_some());
+        assert!(find_best_match(&candidates, &tokens("terraform apply")).is_none());
+    }
+
+    #[test]
+    fn invalid_regex_never_matches() {
+        let candidates = vec![with_match("npm", Some("("), None)];
+        assert!(find_best_match(&candidates, &tokens("npm test")).is_none());
+    }
+
+    #[test]
+    fn exclude_args_skips_filter() {
+        let mut status = make_config("git status", 0);
+        status.exclude_args = vec!["--porcelain".to_string(), "--format".to_string()];
+        let candidates = vec![status];
+        assert!(find_best_match(&candidates, &tokens("git status -sb")).is_some());
+        assert!(find_best_match(&candidates, &tokens("git status --porcelain")).is_none());
+        assert!(find_best_match(&candidates, &tokens("git status --format=v2")).is_none());
+    }
+
+    #[test]
+    fn exclude_args_falls_back_to_other_candidates() {
+        let mut json_free = make_config("gh pr list", 10);
+        json_free.exclude_args = vec!["--json".to_string()];
+        let candidates = vec![json_free, make_config("gh", 0)];
+        let result = find_best_match(&candidates, &tokens("gh pr list --json number")).unwrap();
+        assert_eq!(result.command, "gh");
+    }
+
+    #[test]
+    fn priority_override_outranks_specificity() {
+        let candidates = vec![make_config("git", 0), make_config("git status", 0)];
+        let cmd = tokens("git status -sb");
+        let mut priorities = BTreeMap::new();
+        assert_eq!(
+            find_best_match_with(&candidates, &cmd, &priorities)
+                .unwrap()
+                .command,
+            "git status"
+        );
+
+        priorities.insert("git".to_string(), 50);
+        let best = find_best_match_with(&candidates, &cmd, &priorities).unwrap();
+        assert_eq!(best.command, "git");
+        assert_eq!(best.priority, 50);
+
+        priorities.clear();
+        priorities.insert("git status".to_string(), -1);
+        let best = find_best_match_with(&candidates, &cmd, &priorities).unwrap();
+        assert_eq!(best.command, "git");
+    }
+
+    #[test]
+    fn find_filter_by_name_exact_only() {
+        let found = find_filter_by_name("make").expect("stdlib make filter");
+        assert_eq!(found.command, "make");
+        assert!(find_filter_by_name("mak").is_none());
+    }
+
+    #[test]
+    fn piped_command_resolves_producer() {
+        let cmd = tokens("git status 2>&1 | tee status.log");
+        assert_eq!(resolve_filter(&cmd).unwrap().command, "git status");
+
+        let cmd: Vec<String> = vec!["bash", "-c", "docker ps | cat -n"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(resolve_filter(&cmd).unwrap().command, "docker ps");
+    }
+
+    #[test]
+    fn bash_c_git_status_resolves() {
+        let cmd: Vec<String> = vec!["bash", "-c", "git status"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let result = resolve_filter(&cmd);
+        assert!(result.is_some(), "bash -c 'git status' should resolve");
+        assert_eq!(result.unwrap().command, "git status");
+    }
+
+    #[test]
+    fn bash_c_with_quotes_and_redirection() {
+        let cmd: Vec<String> = vec!["bash", "-c", "\"docker ps 2>&1\""]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let result = resolve_filter(&cmd);
+        assert!(result.is_some(), "bash -c 'docker ps 2>&1' should resolve");
+    }
+
+    #[test]
+    fn bash_c_npx_stripping() {
+        // bash -c "npx vite build 2>&1" should strip bash -c, then npx
+        let cmd: Vec<String> = vec!["bash", "-c", "'npx vite build 2>&1'"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let result = resolve_filter(&cmd);
+        assert!(
+            result.is_some(),
+            "bash -c 'npx vite build 2>&1' should resolve via npx stripping"
+        );
+    }
+
+    #[test]
+    fn sh_c_resolves() {
+        let cmd: Vec<String> = vec!["sh", "-c", "ls -la"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let result = resolve_filter(&cmd);
+        assert!(result.is_some(), "sh -c 'ls -la' should resolve");
+    }
+
+    #[test]
+    fn bash_c_no_filter_returns_none() {
+        let cmd: Vec<String> = vec!["bash", "-c", "echo hello"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let result = resolve_filter(&cmd);
+        assert!(result.is_none(), "echo has no filter, should return None");
+    }
+}
@@ -0,0 +1,96 @@
+//! Narrowing candidates by project type and `when_env` conditions.
+
+use crate::config::types::FilterConfig;
+
+/// Drop candidates whose `requires_project` is not among `project_types`.
+///
+/// Project-specific candidates are moved ahead of generic ones (keeping
+/// their relative order), so they win ties on specificity and priority.
+pub(super) fn for_project(
+    candidates: Vec<FilterConfig>,
+    project_types: &[&str],
+) -> Vec<FilterConfig> {
+    let (specific, generic): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .filter(|c| match c.requires_project.as_deref() {
+            Some(project) => project_types.contains(&project),
+            None => true,
+        })
+        .partition(|c| c.requires_project.is_some());
+    specific.into_iter().chain(generic).collect()
+}
+
+/// Drop candidates whose `when_env` conditions don't hold, looking variables
+/// up with `lookup`.
+///
+/// Like [`for_project`], candidates with conditions move ahead of those
+/// without, so a CI-specific filter beats the generic one for its command.
+pub(super) fn for_env(
+    candidates: Vec<FilterConfig>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Vec<FilterConfig> {
+    let (specific, generic): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .filter(|c| {
+            c.when_env
+                .iter()
+                .all(|(name, condition)| condition.matches(lookup(name).as_deref()))
+        })
+        .partition(|c| !c.when_env.is_empty());
+    specific.into_iter().chain(generic).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::resolve::find_best_match;
+    use crate::config::resolve::tests::{make_config, tokens};
+
+    #[test]
+    fn requires_project_filters_and_prefers_specific() {
+        let generic = make_config("npm test", 0);
+        let mut node = make_config("npm test", 0);
+        node.requires_project = Some("node".to_string());
+        node.description = Some("node".to_string());
+        let mut go = make_config("npm test", 0);
+        go.requires_project = Some("go".to_string());
+
+        let candidates = for_project(vec![generic, node, go], &["node"]);
+        assert_eq!(candidates.len(), 2);
+        let best = find_best_match(&candidates, &tokens("npm test")).unwrap();
+        assert_eq!(best.description.as_deref(), Some("node"));
+
+        let candidates = for_project(candidates, &[]);
+        assert_eq!(candidates.len(), 1);
+        assert!(candidates[0].requires_project.is_none());
+    }
+
+    #[test]
+    fn when_env_filters_and_prefers_conditional() {
+        use crate::config::types::EnvCondition;
+
+        let generic = make_config("cargo test", 0);
+        let mut ci = make_config("cargo test", 0);
+        ci.when_env
+            .insert("CI".to_string(), EnvCondition::Value("true".to_string()));
+        ci.description = Some("ci".to_string());
+        let mut local = make_config("cargo test", 0);
+        local
+            .when_env
+            .insert("CI".to_string(), EnvCondition::Set(false));
+        local.description = Some("local".to_string());
+        let candidates = vec![generic, ci, local];
+
+        let in_ci = for_env(candidates.clone(), |name| {
+            (name == "CI").then(|| "true".to_string())
+        });
+        assert_eq!(in_ci.len(), 2);
+        let best = find_best_match(&in_ci, &tokens("cargo test")).unwrap();
+        assert_eq!(best.description.as_deref(), Some("ci"));
+
+        let interactive = for_env(candidates, |_| None);
+        assert_eq!(interactive.len(), 2);
+        let best = find_best_match(&interactive, &tokens("cargo test")).unwrap();
+        assert_eq!(best.description.as_deref(), Some("local"));
+    }
+}
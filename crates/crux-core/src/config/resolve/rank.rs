@@ -0,0 +1,256 @@
+//! Ranking every matching filter for `crux which --all`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::{
+    collect_sourced_candidates, command_string, config_score, excluded_by_args, FilterSource,
+};
+use crate::config::types::FilterConfig;
+
+/// The ranking key of a matching candidate: priority override, match
+/// specificity, then priority. Higher wins; on a tie the earlier candidate.
+pub(super) fn candidate_rank(
+    config: &FilterConfig,
+    score: usize,
+    priorities: &BTreeMap<String, i32>,
+) -> (i32, usize, i32) {
+    let (pin, priority) = match priorities.get(&config.command) {
+        Some(&priority) => (priority, priority),
+        None => (0, config.priority),
+    };
+    (pin, score, priority)
+}
+
+/// A filter whose command matches, as listed by `crux which --all`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+    pub command: String,
+    pub source: FilterSource,
+    /// Effective priority, after `[priority]` overrides.
+    pub priority: i32,
+    /// Set when the priority comes from `[priority]` in the config file.
+    pub priority_override: bool,
+    /// Words of the filter command that matched.
+    pub matched_words: usize,
+    /// True for the filter that applies.
+    pub winner: bool,
+    /// Why this filter applies, or why it doesn't.
+    pub reason: String,
+}
+
+/// Every filter whose command matches `command`, in resolution order, with
+/// the one that applies marked and a reason for each.
+///
+/// Only the command as given is matched; pipelines and `bash -c` / `npx`
+/// wrappers are not unwrapped.
+pub fn match_candidates(command: &[String]) -> Vec<Candidate> {
+    let settings = crate::config::settings::settings();
+    let project_types = crate::config::project::detect_project_types(Path::new("."));
+    rank_candidates(
+        collect_sourced_candidates(),
+        command,
+        &settings.priority,
+        |config| settings.is_disabled(&config.command),
+        &project_types,
+        |name| std::env::var(name).ok(),
+    )
+}
+
+/// A matching candidate: order key, rank, eligibility, the listing entry and
+/// its config.
+type Ranked = (
+    (bool, bool, usize),
+    (i32, usize, i32),
+    bool,
+    Candidate,
+    FilterConfig,
+);
+
+fn rank_candidates(
+    candidates: Vec<(FilterSource, FilterConfig)>,
+    command: &[String],
+    priorities: &BTreeMap<String, i32>,
+    is_disabled: impl Fn(&FilterConfig) -> bool,
+    project_types: &[&str],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Vec<Candidate> {
+    let input = command_string(command);
+    let mut matched: Vec<Ranked> = Vec::new();
+    for (index, (source, config)) in candidates.into_iter().enumerate() {
+        let Some(score) = config_score(&config, &input) else {
+            continue;
+        };
+        let rejected = rejection(&config, command, project_types, &lookup);
+        // Conditional filters move ahead of generic ones (see `for_env`,
+        // `for_project`), so they win ties.
+        let order = (
+            config.when_env.is_empty(),
+            config.requires_project.is_none(),
+            index,
+        );
+        let rank = candidate_rank(&config, score, priorities);
+        let candidate = Candidate {
+            priority: rank.2,
+            priority_override: priorities.contains_key(&config.command),
+            matched_words: score / 100,
+            command: config.command.clone(),
+            source,
+            winner: false,
+            reason: rejected.clone().unwrap_or_default(),
+        };
+        matched.push((order, rank, rejected.is_none(), candidate, config));
+    }
+    matched.sort_by_key(|(order, ..)| *order);
+
+    mark_winner(matched, is_disabled)
+}
+
+/// Pick the highest-ranked eligible candidate and give every eligible one
+/// its reason, in relation to the winner.
+fn mark_winner(
+    matched: Vec<Ranked>,
+    is_disabled: impl Fn(&FilterConfig) -> bool,
+) -> Vec<Candidate> {
+    let mut best: Option<usize> = None;
+    for (i, (_, rank, eligible, ..)) in matched.iter().enumerate() {
+        let better = match best {
+            Some(b) => rank > &matched[b].1,
+            None => true,
+        };
+        if *eligible && better {
+            best = Some(i);
+        }
+    }
+
+    // The strongest of the rest, to say why the winner beat it.
+    let runner_up = matched
+        .iter()
+        .enumerate()
+        .filter(|(i, m)| m.2 && Some(*i) != best)
+        .max_by(|(i, a), (j, b)| a.1.cmp(&b.1).then(j.cmp(i)))
+        .map(|(_, m)| (m.1, m.3.command.clone()));
+
+    let winner = best.map(|b| (matched[b].1, matched[b].3.command.clone()));
+    let mut out = Vec::with_capacity(matched.len());
+    for (i, (_, rank, eligible, mut candidate, config)) in matched.into_iter().enumerate() {
+        if let Some((best_rank, ref best_command)) = winner {
+            if Some(i) == best {
+                candidate.reason = match &runner_up {
+                    Some((other_rank, other)) => compare_ranks(rank, *other_rank, other, true),
+                    None => "the only eligible match".to_string(),
+                };
+                if is_disabled(&config) {
+                    candidate.reason =
+                        "wins, but is in disabled_builtins: output passes through".to_string();
+                } else {
+                    candidate.winner = true;
+                }
+            } else if eligible {
+                candidate.reason = compare_ranks(rank, best_rank, best_command, false);
+            }
+        }
+        out.push(candidate);
+    }
+    out
+}
+
+/// Why a matching candidate can't apply, or `None` if it is eligible.
+fn rejection(
+    config: &FilterConfig,
+    command: &[String],
+    project_types: &[&str],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    if excluded_by_args(config, command) {
+        return Some(format!(
+            "excluded by exclude_args ({})",
+            config.exclude_args.join(", ")
+        ));
+    }
+    if let Some(project) = config
+        .requires_project
+        .as_deref()
+        .filter(|p| !project_types.contains(p))
+    {
+        return Some(format!("requires a {project} project"));
+    }
+    config
+        .when_env
+        .iter()
+        .find(|(name, condition)| !condition.matches(lookup(name).as_deref()))
+        .map(|(name, _)| format!("when_env condition on {name} not met"))
+}
+
+/// Explain the ranking between a candidate and `other`, from the point of
+/// view of the winner (`won`) or a loser.
+fn compare_ranks(
+    rank: (i32, usize, i32),
+    other_rank: (i32, usize, i32),
+    other: &str,
+    won: bool,
+) -> String {
+    let (better, worse) = if won {
+        ("higher", "more")
+    } else {
+        ("lower", "less")
+    };
+    if rank.0 != other_rank.0 {
+        format!("{better} [priority] override than {other}")
+    } else if rank.1 != other_rank.1 {
+        format!("{worse} specific match than {other}")
+    } else if rank.2 != other_rank.2 {
+        format!("{better} priority than {other}")
+    } else if won {
+        format!("ties with {other}, found first")
+    } else {
+        format!("ties with {other}, found later")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::resolve::tests::{make_config, tokens};
+
+    #[test]
+    fn rank_candidates_marks_winner_and_reasons() {
+        let mut local = make_config("git status", 0);
+        local.exclude_args = vec!["--porcelain".to_string()];
+        let candidates = vec![
+            (FilterSource::Local, local),
+            (FilterSource::Stdlib, make_config("git", 5)),
+            (FilterSource::Builtin, make_config("git status", -100)),
+            (FilterSource::Stdlib, make_config("cargo test", 0)),
+        ];
+        let rank = |cmd: &str, priorities: &BTreeMap<String, i32>| {
+            rank_candidates(
+                candidates.clone(),
+                &tokens(cmd),
+                priorities,
+                |_| false,
+                &[],
+                |_| None,
+            )
+        };
+
+        let plain = rank("git status -sb", &BTreeMap::new());
+        assert_eq!(plain.len(), 3);
+        assert!(plain[0].winner);
+        assert_eq!(plain[0].source, FilterSource::Local);
+        assert_eq!(plain[0].reason, "higher priority than git status");
+        assert_eq!(plain[1].reason, "less specific match than git status");
+        assert_eq!(plain[2].reason, "lower priority than git status");
+
+        let porcelain = rank("git status --porcelain", &BTreeMap::new());
+        assert!(!porcelain[0].winner);
+        assert!(porcelain[0].reason.starts_with("excluded by exclude_args"));
+        assert!(porcelain[2].winner);
+
+        let pinned = rank("git status", &BTreeMap::from([("git".to_string(), 50)]));
+        assert!(pinned[1].winner && pinned[1].priority_override);
+        assert_eq!(pinned[0].reason, "lower [priority] override than git");
+    }
+}
@@ -0,0 +1,290 @@
+//! Merging filter configs: a filter's `extends` base, and the conditional
+//! `[profile.<name>]` / `[on_success]` / `[on_failure]` rule blocks.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{ExtractRule, FilterConfig, ReplaceRule, SectionRule};
+
+impl FilterConfig {
+    /// Fill this config in from the filter it `extends`.
+    ///
+    /// Rule lists are appended to the base's (base rules first); options left
+    /// unset here take the base's value. `command` and `priority` stay as-is.
+    pub fn inherit_from(&mut self, base: &FilterConfig) {
+        fn prepend<T: Clone>(list: &mut Vec<T>, base: &[T]) {
+            let own = std::mem::take(list);
+            list.extend(base.iter().cloned());
+            list.extend(own);
+        }
+        fn or<T: Clone>(value: &mut Option<T>, base: &Option<T>) {
+            if value.is_none() {
+                value.clone_from(base);
+            }
+        }
+
+        or(&mut self.description, &base.description);
+        or(&mut self.command_match, &base.command_match);
+        prepend(&mut self.exclude_args, &base.exclude_args);
+        or(&mut self.requires_project, &base.requires_project);
+        for (name, condition) in &base.when_env {
+            self.when_env
+                .entry(name.clone())
+                .or_insert_with(|| condition.clone());
+        }
+        or(&mut self.builtin, &base.builtin);
+        or(&mut self.post_process, &base.post_process);
+        prepend(&mut self.pipeline, &base.pipeline);
+        if self.stage_order.is_empty() {
+            self.stage_order.clone_from(&base.stage_order);
+        }
+        prepend(&mut self.skip, &base.skip);
+        prepend(&mut self.keep, &base.keep);
+        or(&mut self.keep_context, &base.keep_context);
+        or(&mut self.relativize_paths, &base.relativize_paths);
+        prepend(&mut self.replace, &base.replace);
+        or(&mut self.table, &base.table);
+        prepend(&mut self.section, &base.section);
+        prepend(&mut self.json_paths, &base.json_paths);
+        prepend(&mut self.extract, &base.extract);
+        or(&mut self.dedup, &base.dedup);
+        or(&mut self.collapse_repeats, &base.collapse_repeats);
+        or(&mut self.template, &base.template);
+        or(&mut self.strip_ansi, &base.strip_ansi);
+        or(
+            &mut self.trim_trailing_whitespace,
+            &base.trim_trailing_whitespace,
+        );
+        or(&mut self.collapse_blank_lines, &base.collapse_blank_lines);
+        or(&mut self.max_lines, &base.max_lines);
+        or(&mut self.head, &base.head);
+        or(&mut self.tail, &base.tail);
+        or(&mut self.max_tokens, &base.max_tokens);
+        or(&mut self.max_bytes, &base.max_bytes);
+        or(&mut self.max_line_width, &base.max_line_width);
+        or(&mut self.min_savings_pct, &base.min_savings_pct);
+        or(&mut self.on_success, &base.on_success);
+        or(&mut self.on_failure, &base.on_failure);
+        for (name, block) in &base.profile {
+            self.profile
+                .entry(name.clone())
+                .or_insert_with(|| block.clone());
+        }
+        prepend(&mut self.match_output, &base.match_output);
+        prepend(&mut self.variant, &base.variant);
+        or(&mut self.tee, &base.tee);
+        or(&mut self.tee_dir, &base.tee_dir);
+        #[cfg(feature = "lua")]
+        or(&mut self.lua, &base.lua);
+    }
+
+    /// Return the config with the `[profile.<name>]` block merged in.
+    /// Borrows when there is no active profile or the filter has no block for it.
+    pub fn for_profile(&self, profile: Option<&str>) -> Cow<'_, FilterConfig> {
+        match profile.and_then(|name| self.profile.get(name)) {
+            Some(block) => {
+                let mut merged = self.clone();
+                block.merge_into(&mut merged);
+                Cow::Owned(merged)
+            }
+            None => Cow::Borrowed(self),
+        }
+    }
+
+    /// Return the config with the `[on_success]` (exit code 0) or
+    /// `[on_failure]` (non-zero) block merged in. Borrows when no block applies.
+    pub fn for_exit_code(&self, exit_code: i32) -> Cow<'_, FilterConfig> {
+        let block = if exit_code == 0 {
+            &self.on_success
+        } else {
+            &self.on_failure
+        };
+        match block {
+            Some(block) => {
+                let mut merged = self.clone();
+                block.merge_into(&mut merged);
+                Cow::Owned(merged)
+            }
+            None => Cow::Borrowed(self),
+        }
+    }
+}
+
+/// Pipeline rules merged in conditionally: for one exit-code outcome
+/// (`[on_success]` / `[on_failure]`) or one profile (`[profile.aggressive]`).
+///
+/// Rule lists are appended to the top-level ones; scalar options override them.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RuleBlock {
+    #[serde(default)]
+    pub skip: Vec<String>,
+    #[serde(default)]
+    pub keep: Vec<String>,
+    #[serde(default)]
+    pub keep_context: Option<usize>,
+    #[serde(default)]
+    pub replace: Vec<ReplaceRule>,
+    #[serde(default)]
+    pub section: Vec<SectionRule>,
+    #[serde(default)]
+    pub extract: Vec<ExtractRule>,
+    #[serde(default)]
+    pub dedup: Option<bool>,
+    #[serde(default)]
+    pub collapse_repeats: Option<bool>,
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub trim_trailing_whitespace: Option<bool>,
+    #[serde(default)]
+    pub collapse_blank_lines: Option<bool>,
+    #[serde(default)]
+    pub max_line_width: Option<usize>,
+    #[serde(default)]
+    pub max_lines: Option<usize>,
+    #[serde(default)]
+    pub head: Option<usize>,
+    #[serde(default)]
+    pub tail: Option<usize>,
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+}
+
+impl RuleBlock {
+    pub(crate) fn merge_into(&self, config: &mut FilterConfig) {
+        config.skip.extend(self.skip.iter().cloned());
+        config.keep.extend(self.keep.iter().cloned());
+        config.replace.extend(self.replace.iter().cloned());
+        config.section.extend(self.section.iter().cloned());
+        config.extract.extend(self.extract.iter().cloned());
+        if self.keep_context.is_some() {
+            config.keep_context = self.keep_context;
+        }
+        if self.dedup.is_some() {
+            config.dedup = self.dedup;
+        }
+        if self.collapse_repeats.is_some() {
+            config.collapse_repeats = self.collapse_repeats;
+        }
+        if self.template.is_some() {
+            config.template.clone_from(&self.template);
+        }
+        if self.trim_trailing_whitespace.is_some() {
+            config.trim_trailing_whitespace = self.trim_trailing_whitespace;
+        }
+        if self.collapse_blank_lines.is_some() {
+            config.collapse_blank_lines = self.collapse_blank_lines;
+        }
+        if self.max_line_width.is_some() {
+            config.max_line_width = self.max_line_width;
+        }
+        if self.max_lines.is_some() {
+            config.max_lines = self.max_lines;
+        }
+        if self.head.is_some() {
+            config.head = self.head;
+        }
+        if self.tail.is_some() {
+            config.tail = self.tail;
+        }
+        if self.max_tokens.is_some() {
+            config.max_tokens = self.max_tokens;
+        }
+        if self.max_bytes.is_some() {
+            config.max_bytes = self.max_bytes;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inherit_from_appends_lists_and_fills_unset_options() {
+        let base: FilterConfig = toml::from_str(
+            r#"
+command = "cargo test"
+description = "base"
+priority = 5
+skip = ["^Compiling"]
+dedup = true
+max_lines = 50
+"#,
+        )
+        .unwrap();
+        let mut child: FilterConfig = toml::from_str(
+            r#"
+command = "cargo test"
+extends = "cargo test"
+skip = ["^Downloaded"]
+max_lines = 20
+"#,
+        )
+        .unwrap();
+        child.inherit_from(&base);
+
+        assert_eq!(child.extends.as_deref(), Some("cargo test"));
+        assert_eq!(child.skip, vec!["^Compiling", "^Downloaded"]);
+        assert_eq!(child.description.as_deref(), Some("base"));
+        assert_eq!(child.dedup, Some(true));
+        assert_eq!(child.max_lines, Some(20));
+        assert_eq!(child.priority, 0);
+    }
+
+    #[test]
+    fn profile_block_merges_for_active_profile() {
+        let toml_str = r#"
+command = "cargo build"
+skip = ["^\\s+Compiling"]
+
+[profile.aggressive]
+skip = ["^warning"]
+collapse_repeats = true
+max_tokens = 500
+
+[profile.conservative]
+max_lines = 1000
+"#;
+        let config: FilterConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.profile.len(), 2);
+
+        let aggressive = config.for_profile(Some("aggressive"));
+        assert_eq!(aggressive.skip.len(), 2);
+        assert_eq!(aggressive.collapse_repeats, Some(true));
+        assert_eq!(aggressive.max_tokens, Some(500));
+
+        assert!(matches!(config.for_profile(None), Cow::Borrowed(_)));
+        assert!(matches!(
+            config.for_profile(Some("normal")),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn for_exit_code_merges_matching_block() {
+        let config = FilterConfig {
+            skip: vec!["^noise".to_string()],
+            on_failure: Some(RuleBlock {
+                skip: vec!["^debug".to_string()],
+                keep: vec!["error".to_string()],
+                tail: Some(5),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let failed = config.for_exit_code(2);
+        assert_eq!(failed.skip, vec!["^noise", "^debug"]);
+        assert_eq!(failed.keep, vec!["error"]);
+        assert_eq!(failed.tail, Some(5));
+
+        let ok = config.for_exit_code(0);
+        assert!(matches!(ok, Cow::Borrowed(_)));
+        assert_eq!(ok.skip, vec!["^noise"]);
+        assert!(ok.keep.is_empty());
+    }
+}
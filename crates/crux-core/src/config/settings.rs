@@ -0,0 +1,415 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::pipe::PipeTarget;
+use super::rule_block::RuleBlock;
+use super::types::{FilterConfig, TeeMode};
+use crate::tokens::Tokenizer;
+
+/// Project-wide crux settings from `.crux/config.toml` (local) and
+/// `~/.config/crux/config.toml` (global). Local values win.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Settings {
+    /// Profile used when `CRUX_PROFILE` / `--profile` is not given.
+    #[serde(default)]
+    pub profile: Option<String>,
+
+    /// Set to `false` to stop recording savings and history.
+    #[serde(default)]
+    pub tracking: Option<bool>,
+
+    /// Commands whose output is never stored in history, as text matched
+    /// anywhere in the command line ignoring case (`"secret"`, `"vault"`).
+    /// Their savings are still recorded.
+    #[serde(default)]
+    pub no_history: Vec<String>,
+
+    /// Encrypt history outputs at rest with a key file next to the
+    /// database (needs crux built with the `encryption` feature).
+    #[serde(default)]
+    pub encrypt_history: Option<bool>,
+
+    /// Default raw-output tee mode and directory for filters that don't set
+    /// their own; also applies to commands without a filter.
+    #[serde(default)]
+    pub tee: Option<TeeMode>,
+    #[serde(default)]
+    pub tee_dir: Option<String>,
+
+    /// Default output caps for filters that don't set their own.
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+
+    /// Show estimated token counts in the `crux run` summary, as `--tokens`.
+    #[serde(default)]
+    pub show_tokens: Option<bool>,
+
+    /// How displayed token counts are estimated (`"chars"` or `"words"`).
+    #[serde(default)]
+    pub tokenizer: Option<Tokenizer>,
+
+    /// tiktoken encoding for the exact token counts recorded by builds with
+    /// the `tokenizer` feature (default `"cl100k_base"`).
+    #[serde(default)]
+    pub token_encoding: Option<String>,
+
+    /// Builtin commands (`"curl"`, `"git status"`) whose output passes
+    /// through unfiltered.
+    #[serde(default)]
+    pub disabled_builtins: Vec<String>,
+
+    /// Which command of a piped command line picks the filter: `"first"`
+    /// (the producer, default) or `"last"` (the final consumer).
+    #[serde(default)]
+    pub pipe_target: Option<PipeTarget>,
+
+    /// Priority overrides by filter `command` (`[priority] git = 50`). An
+    /// overridden priority ranks ahead of command specificity.
+    #[serde(default)]
+    pub priority: BTreeMap<String, i32>,
+
+    /// Per-command rule overrides (`[commands."cargo test"]`), matched by word
+    /// prefix against the command being run; the most specific key wins.
+    #[serde(default)]
+    pub commands: BTreeMap<String, RuleBlock>,
+
+    /// Project defaults for `crux err` (`[err]`).
+    #[serde(default)]
+    pub err: ErrSettings,
+
+    /// Limits on stored savings and history (`[retention]`).
+    #[serde(default)]
+    pub retention: RetentionSettings,
+
+    /// Where `crux sync` pushes aggregated savings (`[sync]`).
+    #[serde(default)]
+    pub sync: SyncSettings,
+}
+
+/// Limits on the tracking database, enforced at most hourly by `crux run`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RetentionSettings {
+    /// Drop runs older than this: `30d`, `12h`, `2w`.
+    #[serde(default)]
+    pub max_age: Option<String>,
+    /// Keep at most this many recorded runs.
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+    /// Drop the oldest history while stored data exceeds this many MB.
+    #[serde(default)]
+    pub max_db_mb: Option<u64>,
+    /// Store at most this many KB of each history output (head and tail
+    /// are kept); `0` stores outputs whole. Defaults to 256.
+    #[serde(default)]
+    pub max_history_kb: Option<u64>,
+}
+
+/// Defaults for `crux err`; command-line flags win.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ErrSettings {
+    /// Extra regexes for lines to keep, on top of the builtin error pattern.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Also keep warning lines.
+    #[serde(default)]
+    pub warnings: Option<bool>,
+    /// Lines of context around each kept line.
+    #[serde(default)]
+    pub context: Option<usize>,
+}
+
+/// Target of `crux sync`. Nothing is sent unless `endpoint` is set.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SyncSettings {
+    /// HTTP(S) URL receiving the JSON payload.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// `post` (default), or `put` for e.g. a presigned S3 URL.
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Environment variable holding a bearer token for the endpoint.
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Also send command lines (off: only filter names and totals).
+    #[serde(default)]
+    pub include_commands: Option<bool>,
+}
+
+impl Settings {
+    /// Overlay `other` (higher precedence) onto these settings.
+    fn merge(&mut self, other: Settings) {
+        if other.profile.is_some() {
+            self.profile = other.profile;
+        }
+        if other.tracking.is_some() {
+            self.tracking = other.tracking;
+        }
+        if other.tee.is_some() {
+            self.tee = other.tee;
+        }
+        if other.tee_dir.is_some() {
+            self.tee_dir = other.tee_dir;
+        }
+        if other.max_bytes.is_some() {
+            self.max_bytes = other.max_bytes;
+        }
+        if other.max_tokens.is_some() {
+            self.max_tokens = other.max_tokens;
+        }
+        if other.show_tokens.is_some() {
+            self.show_tokens = other.show_tokens;
+        }
+        if other.tokenizer.is_some() {
+            self.tokenizer = other.tokenizer;
+        }
+        if other.token_encoding.is_some() {
+            self.token_encoding = other.token_encoding;
+        }
+        if other.pipe_target.is_some() {
+            self.pipe_target = other.pipe_target;
+        }
+        self.disabled_builtins.extend(other.disabled_builtins);
+        self.no_history.extend(other.no_history);
+        if other.encrypt_history.is_some() {
+            self.encrypt_history = other.encrypt_history;
+        }
+        self.priority.extend(other.priority);
+        self.commands.extend(other.commands);
+        self.err.patterns.extend(other.err.patterns);
+        if other.err.warnings.is_some() {
+            self.err.warnings = other.err.warnings;
+        }
+        if other.err.context.is_some() {
+            self.err.context = other.err.context;
+        }
+        if other.retention.max_age.is_some() {
+            self.retention.max_age = other.retention.max_age;
+        }
+        if other.retention.max_rows.is_some() {
+            self.retention.max_rows = other.retention.max_rows;
+        }
+        if other.retention.max_db_mb.is_some() {
+            self.retention.max_db_mb = other.retention.max_db_mb;
+        }
+        if other.retention.max_history_kb.is_some() {
+            self.retention.max_history_kb = other.retention.max_history_kb;
+        }
+        if other.sync.endpoint.is_some() {
+            self.sync.endpoint = other.sync.endpoint;
+        }
+        if other.sync.method.is_some() {
+            self.sync.method = other.sync.method;
+        }
+        if other.sync.token_env.is_some() {
+            self.sync.token_env = other.sync.token_env;
+        }
+        if other.sync.include_commands.is_some() {
+            self.sync.include_commands = other.sync.include_commands;
+        }
+    }
+
+    /// Whether the filter for `command` (a filter's `command` key) is disabled.
+    pub fn is_disabled(&self, command: &str) -> bool {
+        self.disabled_builtins
+            .iter()
+            .any(|name| name.trim() == command)
+    }
+
+    /// False only when tracking is explicitly disabled.
+    pub fn tracking_enabled(&self) -> bool {
+        self.tracking != Some(false)
+    }
+
+    /// Whether the output of `command_line` may be stored in history.
+    pub fn stores_history(&self, command_line: &str) -> bool {
+        let command_line = command_line.to_lowercase();
+        !self
+            .no_history
+            .iter()
+            .any(|pattern| !pattern.is_empty() && command_line.contains(&pattern.to_lowercase()))
+    }
+
+    /// Apply default caps and the matching `[commands]` override to a
+    /// resolved filter.
+    pub fn apply_to(&self, config: &mut FilterConfig, command: &[String]) {
+        if config.tee.is_none() {
+            config.tee.clone_from(&self.tee);
+        }
+        if config.tee_dir.is_none() {
+            config.tee_dir.clone_from(&self.tee_dir);
+        }
+        if config.max_bytes.is_none() {
+            config.max_bytes = self.max_bytes;
+        }
+        if config.max_tokens.is_none() {
+            config.max_tokens = self.max_tokens;
+        }
+        if let Some(block) = self.command_override(command) {
+            block.merge_into(config);
+        }
+    }
+
+    /// The `[commands]` entry whose key is the longest word prefix of `command`.
+    fn command_override(&self, command: &[String]) -> Option<&RuleBlock> {
+        self.commands
+            .iter()
+            .filter(|(key, _)| {
+                let words: Vec<&str> = key.split_whitespace().collect();
+                !words.is_empty()
+                    && words.len() <= command.len()
+                    && words.iter().zip(command).all(|(w, c)| w == c)
+            })
+            .max_by_key(|(key, _)| key.split_whitespace().count())
+            .map(|(_, block)| block)
+    }
+}
+
+/// Settings for this process, loaded once from the local and global files.
+///
+/// Unreadable or invalid files are reported on stderr and ignored.
+pub fn settings() -> &'static Settings {
+    static SETTINGS: OnceLock<Settings> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let global = super::resolve::home_dir().map(|h| h.join(".config/crux/config.toml"));
+        load_settings(Path::new(".crux/config.toml"), global.as_deref())
+    })
+}
+
+/// Load and merge settings from `local` over `global`.
+pub fn load_settings(local: &Path, global: Option<&Path>) -> Settings {
+    let mut settings = Settings::default();
+    for path in global.into_iter().chain(std::iter::once(local)) {
+        if !path.is_file() {
+            continue;
+        }
+        match parse_settings_file(path) {
+            Ok(parsed) => settings.merge(parsed),
+            Err(e) => eprintln!("crux: skipping {}: {e:#}", path.display()),
+        }
+    }
+    settings
+}
+
+fn parse_settings_file(path: &Path) -> Result<Settings> {
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    toml::from_str(&contents).with_context(|| format!("parsing {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(cmd: &str) -> Vec<String> {
+        cmd.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn local_overrides_global() {
+        let dir = tempfile::tempdir().unwrap();
+        let global = dir.path().join("global.toml");
+        let local = dir.path().join("local.toml");
+        std::fs::write(
+            &global,
+            "profile = \"aggressive\"\nmax_bytes = 100\nshow_tokens = true\n[retention]\nmax_age = \"30d\"\nmax_rows = 500\n[commands.make]\nkeep = [\"error\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &local,
+            "profile = \"conservative\"\ntracking = false\npipe_target = \"last\"\ntokenizer = \"words\"\n[priority]\ngit = 50\n[err]\ncontext = 2\n",
+        )
+        .unwrap();
+
+        let settings = load_settings(&local, Some(&global));
+        assert_eq!(settings.profile.as_deref(), Some("conservative"));
+        assert_eq!(settings.max_bytes, Some(100));
+        assert!(!settings.tracking_enabled());
+        assert_eq!(settings.commands["make"].keep, vec!["error"]);
+        assert_eq!(settings.priority["git"], 50);
+        assert_eq!(settings.pipe_target, Some(PipeTarget::Last));
+        assert_eq!(settings.err.context, Some(2));
+        assert_eq!(settings.show_tokens, Some(true));
+        assert_eq!(settings.tokenizer, Some(Tokenizer::Words));
+        assert_eq!(settings.retention.max_age.as_deref(), Some("30d"));
+        assert_eq!(settings.retention.max_rows, Some(500));
+    }
+
+    #[test]
+    fn disabled_builtins_merge_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let global = dir.path().join("global.toml");
+        let local = dir.path().join("local.toml");
+        std::fs::write(&global, "disabled_builtins = [\"curl\"]\n").unwrap();
+        std::fs::write(&local, "disabled_builtins = [\"env\", \"git status\"]\n").unwrap();
+
+        let settings = load_settings(&local, Some(&global));
+        assert!(settings.is_disabled("curl"));
+        assert!(settings.is_disabled("env"));
+        assert!(settings.is_disabled("git status"));
+        assert!(!settings.is_disabled("git"));
+    }
+
+    #[test]
+    fn no_history_matches_anywhere_ignoring_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let global = dir.path().join("global.toml");
+        let local = dir.path().join("local.toml");
+        std::fs::write(&global, "no_history = [\"secret\"]\n").unwrap();
+        std::fs::write(&local, "no_history = [\"vault read\", \"\"]\n").unwrap();
+
+        let settings = load_settings(&local, Some(&global));
+        assert!(!settings.stores_history("kubectl get SECRETS -o yaml"));
+        assert!(!settings.stores_history("vault read kv/app"));
+        assert!(settings.stores_history("vault status"));
+        assert!(Settings::default().stores_history("cat secret.txt"));
+    }
+
+    #[test]
+    fn missing_files_give_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = load_settings(&dir.path().join("nope.toml"), None);
+        assert!(settings.profile.is_none());
+        assert!(settings.tracking_enabled());
+    }
+
+    #[test]
+    fn apply_to_fills_caps_and_most_specific_override() {
+        let settings: Settings = toml::from_str(
+            r#"
+max_bytes = 4096
+max_tokens = 800
+tee = "failures"
+
+[commands.cargo]
+skip = ["^cargo"]
+
+[commands."cargo test"]
+skip = ["^running"]
+tail = 30
+"#,
+        )
+        .unwrap();
+
+        let mut config = FilterConfig {
+            command: "cargo test".to_string(),
+            max_tokens: Some(200),
+            ..Default::default()
+        };
+        settings.apply_to(&mut config, &tokens("cargo test --workspace"));
+        assert_eq!(config.max_bytes, Some(4096));
+        assert_eq!(config.max_tokens, Some(200));
+        assert_eq!(config.tee, Some(TeeMode::Failures));
+        assert_eq!(config.skip, vec!["^running"]);
+        assert_eq!(config.tail, Some(30));
+
+        let mut other = FilterConfig::default();
+        settings.apply_to(&mut other, &tokens("cargotest"));
+        assert!(other.skip.is_empty());
+    }
+}
@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use super::rule_block::RuleBlock;
+
 /// Top-level filter configuration, backward-compatible with tokf TOML format.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FilterConfig {
@@ -12,23 +16,72 @@ pub struct FilterConfig {
     #[serde(default)]
     pub priority: i32,
 
+    /// Match commands by regex or glob instead of by `command` prefix.
+    #[serde(default, rename = "match")]
+    pub command_match: Option<CommandMatch>,
+
+    /// Flags that make this filter not apply (e.g. `--json`, `--porcelain`).
+    #[serde(default)]
+    pub exclude_args: Vec<String>,
+
+    /// Only apply in projects of this type (`rust`, `node`, `go`, `python`),
+    /// detected from marker files in the working directory.
+    #[serde(default)]
+    pub requires_project: Option<String>,
+
+    /// Only apply when these environment variables match
+    /// (`when_env = { CI = "true" }`; `false` requires the variable to be unset).
+    #[serde(default)]
+    pub when_env: BTreeMap<String, EnvCondition>,
+
     #[serde(default)]
     pub builtin: Option<bool>,
+    /// Run this filter's own stages on the builtin handler's output instead
+    /// of returning it as-is.
+    #[serde(default)]
+    pub post_process: Option<bool>,
+
+    // -- Composition --
+    /// Start from another filter (by `command`) and override/extend it.
+    #[serde(default)]
+    pub extends: Option<String>,
+    /// Named filters (by `command`) run in order before this filter's own stages.
+    #[serde(default)]
+    pub pipeline: Vec<String>,
+    /// Run these stages first, in this order; unlisted stages keep their default order.
+    #[serde(default)]
+    pub stage_order: Vec<String>,
 
     // -- Skip/keep line filtering --
     #[serde(default)]
     pub skip: Vec<String>,
     #[serde(default)]
     pub keep: Vec<String>,
+    /// Lines of context kept before and after each `keep` match.
+    #[serde(default)]
+    pub keep_context: Option<usize>,
+
+    // -- Path relativization --
+    /// Rewrite absolute paths under the working directory / git root to relative ones.
+    #[serde(default)]
+    pub relativize_paths: Option<bool>,
 
     // -- Regex replacement --
     #[serde(default)]
     pub replace: Vec<ReplaceRule>,
 
+    // -- Table column selection --
+    #[serde(default)]
+    pub table: Option<TableConfig>,
+
     // -- Section parsing --
     #[serde(default)]
     pub section: Vec<SectionRule>,
 
+    // -- JSON path selection --
+    #[serde(default)]
+    pub json_paths: Vec<String>,
+
     // -- Extract patterns --
     #[serde(default)]
     pub extract: Vec<ExtractRule>,
@@ -36,6 +89,9 @@ pub struct FilterConfig {
     // -- Dedup --
     #[serde(default)]
     pub dedup: Option<bool>,
+    /// Collapse non-consecutive repeats into `line (×N)`.
+    #[serde(default)]
+    pub collapse_repeats: Option<bool>,
 
     // -- Template --
     #[serde(default)]
@@ -49,6 +105,35 @@ pub struct FilterConfig {
     #[serde(default)]
     pub collapse_blank_lines: Option<bool>,
 
+    // -- Truncation --
+    /// Cut lines longer than this many characters (ANSI codes don't count).
+    #[serde(default)]
+    pub max_line_width: Option<usize>,
+    #[serde(default)]
+    pub max_lines: Option<usize>,
+    #[serde(default)]
+    pub head: Option<usize>,
+    #[serde(default)]
+    pub tail: Option<usize>,
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+    /// Final byte cap on the filtered output (head and tail kept).
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+    /// Show the raw output instead when filtering saves less than this percentage.
+    #[serde(default)]
+    pub min_savings_pct: Option<f64>,
+
+    // -- Exit-code conditional blocks --
+    #[serde(default)]
+    pub on_success: Option<RuleBlock>,
+    #[serde(default)]
+    pub on_failure: Option<RuleBlock>,
+
+    // -- Profile blocks (`[profile.aggressive]`), selected by `CRUX_PROFILE` --
+    #[serde(default)]
+    pub profile: BTreeMap<String, RuleBlock>,
+
     // -- Match output --
     #[serde(default)]
     pub match_output: Vec<MatchOutputRule>,
@@ -58,8 +143,12 @@ pub struct FilterConfig {
     pub variant: Vec<VariantRule>,
 
     // -- Tee mode --
+    /// Save the full raw output to a file (`never`, `failures`, `always`).
     #[serde(default)]
     pub tee: Option<TeeMode>,
+    /// Directory for tee files (default `.crux/raw`).
+    #[serde(default)]
+    pub tee_dir: Option<String>,
 
     // -- Lua escape hatch (only available with "lua" feature) --
     #[cfg(feature = "lua")]
@@ -67,6 +156,39 @@ pub struct FilterConfig {
     pub lua: Option<LuaConfig>,
 }
 
+/// Pattern-based command matching (`match = { regex = "^npm run (build|dev)" }`).
+///
+/// Patterns are tested against the whole command line; the filter matches if
+/// either pattern does.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CommandMatch {
+    #[serde(default)]
+    pub regex: Option<String>,
+    /// Shell-style glob: `*` matches any run of characters, `?` a single one.
+    #[serde(default)]
+    pub glob: Option<String>,
+}
+
+/// Environment-variable condition in `when_env`: an exact value
+/// (`CI = "true"`), or `true` / `false` for set / unset.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum EnvCondition {
+    Set(bool),
+    Value(String),
+}
+
+impl EnvCondition {
+    /// True if `value` (the variable's value, `None` when unset) satisfies
+    /// the condition.
+    pub fn matches(&self, value: Option<&str>) -> bool {
+        match self {
+            EnvCondition::Set(set) => value.is_some() == *set,
+            EnvCondition::Value(expected) => value == Some(expected.as_str()),
+        }
+    }
+}
+
 /// Tee mode: save raw output for debugging/recovery.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -92,8 +214,19 @@ pub struct ReplaceRule {
     pub replacement: String,
 }
 
+/// Column selection for fixed-width table output (`[table]`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TableConfig {
+    /// Header names of the columns to keep (case-insensitive).
+    #[serde(default)]
+    pub keep_columns: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SectionRule {
+    /// Name used to reference the section from templates (default `section_N`).
+    #[serde(default)]
+    pub name: Option<String>,
     pub start: String,
     #[serde(default)]
     pub end: Option<String>,
@@ -106,22 +239,92 @@ pub struct ExtractRule {
     pub pattern: String,
     #[serde(default)]
     pub template: Option<String>,
+    /// Collect every matching line instead of stopping at the first.
+    #[serde(default)]
+    pub all: Option<bool>,
+    /// With `all`, keep at most this many matches.
+    #[serde(default)]
+    pub max: Option<usize>,
 }
 
+/// Short-circuit rule: when the output matches, emit `template` and stop.
+///
+/// A rule needs `contains` or `regex` (both must match if both are set), and
+/// only applies when `exit_code` (if set) matches.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchOutputRule {
-    pub contains: String,
+    #[serde(default)]
+    pub contains: Option<String>,
+    #[serde(default)]
+    pub regex: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<ExitCodeCondition>,
     #[serde(default)]
     pub template: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Exit-code condition: an exact code (`exit_code = 2`), `"nonzero"` /
+/// `"zero"`, an inclusive range (`"1-127"`) or a comparison (`">=128"`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ExitCodeCondition {
+    Code(i32),
+    Named(String),
+}
+
+impl ExitCodeCondition {
+    /// True if `exit_code` satisfies the condition. Unknown names never match.
+    pub fn matches(&self, exit_code: i32) -> bool {
+        match self {
+            ExitCodeCondition::Code(code) => *code == exit_code,
+            ExitCodeCondition::Named(name) => match name.trim() {
+                "nonzero" | "failure" => exit_code != 0,
+                "zero" | "success" => exit_code == 0,
+                spec => matches_code_spec(spec, exit_code).unwrap_or(false),
+            },
+        }
+    }
+}
+
+/// Evaluate a range (`"1-127"`) or comparison (`">=128"`) spec; `None` if
+/// the spec doesn't parse.
+fn matches_code_spec(spec: &str, exit_code: i32) -> Option<bool> {
+    let num = |s: &str| s.trim().parse::<i32>().ok();
+    if let Some(n) = spec.strip_prefix(">=") {
+        return Some(exit_code >= num(n)?);
+    }
+    if let Some(n) = spec.strip_prefix("<=") {
+        return Some(exit_code <= num(n)?);
+    }
+    if let Some(n) = spec.strip_prefix('>') {
+        return Some(exit_code > num(n)?);
+    }
+    if let Some(n) = spec.strip_prefix('<') {
+        return Some(exit_code < num(n)?);
+    }
+    // Split on the first '-' after position 0 so negative starts still parse.
+    let dash = spec.get(1..)?.find('-')? + 1;
+    let (lo, hi) = (num(&spec[..dash])?, num(&spec[dash + 1..])?);
+    Some((lo..=hi).contains(&exit_code))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct VariantRule {
     pub name: String,
     #[serde(default)]
     pub detect_file: Option<String>,
     #[serde(default)]
     pub detect_output: Option<String>,
+    /// Flags that must all be present in the command's arguments
+    /// (`--stat` also matches `--stat=20`).
+    #[serde(default)]
+    pub when_args: Vec<String>,
+    /// Exit-code condition checked after the run (`when_exit = "1-127"`).
+    #[serde(default)]
+    pub when_exit: Option<ExitCodeCondition>,
+    /// Minimum raw output size in bytes, checked after the run.
+    #[serde(default)]
+    pub min_output_bytes: Option<usize>,
     #[serde(default)]
     pub filter: Option<String>,
 }
@@ -191,7 +394,7 @@ filter = "cargo/test-nextest"
         assert_eq!(config.section[0].keep, Some(true));
         assert_eq!(config.extract.len(), 1);
         assert_eq!(config.match_output.len(), 1);
-        assert_eq!(config.match_output[0].contains, "FAILED");
+        assert_eq!(config.match_output[0].contains.as_deref(), Some("FAILED"));
         assert_eq!(config.variant.len(), 1);
         assert_eq!(config.variant[0].name, "nextest");
         assert_eq!(
@@ -202,6 +405,110 @@ filter = "cargo/test-nextest"
         assert!(config.strip_ansi == Some(true));
     }
 
+    #[test]
+    fn parse_match_table() {
+        let toml_str = r#"
+command = "npm run"
+match = { regex = "^npm run (build|dev)", glob = "yarn *" }
+"#;
+        let config: FilterConfig = toml::from_str(toml_str).unwrap();
+        let m = config.command_match.unwrap();
+        assert_eq!(m.regex.as_deref(), Some("^npm run (build|dev)"));
+        assert_eq!(m.glob.as_deref(), Some("yarn *"));
+    }
+
+    #[test]
+    fn parse_match_output_regex_and_exit_code() {
+        let toml_str = r#"
+command = "tsc"
+
+[[match_output]]
+regex = "error TS\\d+"
+exit_code = "nonzero"
+
+[[match_output]]
+contains = "Killed"
+exit_code = 137
+"#;
+        let config: FilterConfig = toml::from_str(toml_str).unwrap();
+        let rules = &config.match_output;
+        assert_eq!(rules[0].regex.as_deref(), Some(r"error TS\d+"));
+        assert!(rules[0].contains.is_none());
+        assert!(rules[0].exit_code.as_ref().unwrap().matches(2));
+        assert!(!rules[0].exit_code.as_ref().unwrap().matches(0));
+        assert_eq!(rules[1].exit_code, Some(ExitCodeCondition::Code(137)));
+    }
+
+    #[test]
+    fn exit_code_condition_ranges_and_comparisons() {
+        let named = |s: &str| ExitCodeCondition::Named(s.to_string());
+        assert!(named("1-127").matches(1));
+        assert!(named("1-127").matches(127));
+        assert!(!named("1-127").matches(0));
+        assert!(!named("1-127").matches(137));
+        assert!(named(">=128").matches(137));
+        assert!(!named(">=128").matches(127));
+        assert!(named("<0").matches(-1));
+        assert!(named("-1-1").matches(0));
+        assert!(!named("bogus").matches(0));
+        assert!(!named("1-x").matches(1));
+    }
+
+    #[test]
+    fn parse_truncation_fields() {
+        let toml_str = r#"
+command = "make"
+max_lines = 40
+head = 20
+tail = 20
+max_tokens = 2000
+max_bytes = 65536
+min_savings_pct = 20.0
+json_paths = [".items[].name", ".status"]
+pipeline = ["strip-noise", "summarize-errors"]
+table.keep_columns = ["NAME", "STATUS"]
+keep_context = 2
+collapse_repeats = true
+relativize_paths = true
+"#;
+        let config: FilterConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.max_tokens, Some(2000));
+        assert_eq!(config.max_bytes, Some(65536));
+        assert_eq!(config.min_savings_pct, Some(20.0));
+        assert_eq!(config.json_paths, vec![".items[].name", ".status"]);
+        assert_eq!(config.pipeline, vec!["strip-noise", "summarize-errors"]);
+        assert_eq!(config.table.unwrap().keep_columns, vec!["NAME", "STATUS"]);
+        assert_eq!(config.keep_context, Some(2));
+        assert_eq!(config.collapse_repeats, Some(true));
+        assert_eq!(config.relativize_paths, Some(true));
+        assert_eq!(config.max_lines, Some(40));
+        assert_eq!(config.head, Some(20));
+        assert_eq!(config.tail, Some(20));
+    }
+
+    #[test]
+    fn parse_exit_code_blocks() {
+        let toml_str = r#"
+command = "make"
+skip = ["^make\\["]
+
+[on_success]
+template = "build ok"
+
+[on_failure]
+keep = ["error"]
+tail = 30
+"#;
+        let config: FilterConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.on_success.as_ref().unwrap().template.as_deref(),
+            Some("build ok")
+        );
+        let failure = config.on_failure.as_ref().unwrap();
+        assert_eq!(failure.keep, vec!["error"]);
+        assert_eq!(failure.tail, Some(30));
+    }
+
     #[test]
     fn parse_config_with_multiple_replace_rules() {
         let toml_str = r#"
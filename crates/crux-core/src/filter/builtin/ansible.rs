@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use super::BuiltinFilterFn;
+
+/// Register Ansible handlers.
+pub fn register(m: &mut HashMap<&'static str, BuiltinFilterFn>) {
+    m.insert(
+        "ansible-playbook",
+        filter_ansible_playbook as BuiltinFilterFn,
+    );
+}
+
+/// Maximum detail lines kept after a failed/unreachable task result.
+const MAX_DETAIL_LINES: usize = 15;
+
+/// Strip the trailing `*****` banner padding from PLAY/TASK headers.
+fn header_name(trimmed: &str) -> String {
+    trimmed.trim_end_matches('*').trim_end().to_string()
+}
+
+/// Per-play counters for task results that are collapsed.
+#[derive(Default)]
+struct PlayCounts {
+    ok: usize,
+    skipped: usize,
+}
+
+impl PlayCounts {
+    fn render(&self, play: &str) -> String {
+        let mut parts = Vec::new();
+        if self.ok > 0 {
+            parts.push(format!("{} ok", self.ok));
+        }
+        if self.skipped > 0 {
+            parts.push(format!("{} skipped", self.skipped));
+        }
+        if parts.is_empty() {
+            play.to_string()
+        } else {
+            format!("{play} ({})", parts.join(", "))
+        }
+    }
+}
+
+/// Scanner state for `ansible-playbook` output.
+#[derive(Default)]
+struct PlaybookScan {
+    kept: Vec<String>,
+    /// Index into `kept` of the current PLAY line (rewritten with counts on close).
+    play_idx: Option<usize>,
+    play_name: String,
+    counts: PlayCounts,
+    /// Current TASK header, emitted lazily only if the task reports something.
+    task: Option<String>,
+    detail_lines: usize,
+    in_detail: bool,
+    in_recap: bool,
+}
+
+impl PlaybookScan {
+    fn close_play(&mut self) {
+        if let Some(idx) = self.play_idx.take() {
+            self.kept[idx] = self.counts.render(&self.play_name);
+        }
+        self.counts = PlayCounts::default();
+    }
+
+    fn emit_task_result(&mut self, trimmed: &str) {
+        if let Some(task) = self.task.take() {
+            self.kept.push(task);
+        }
+        self.kept.push(format!("  {trimmed}"));
+    }
+
+    fn line(&mut self, line: &str) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            self.in_detail = false;
+            return;
+        }
+
+        if trimmed.starts_with("PLAY RECAP") {
+            self.close_play();
+            self.in_recap = true;
+            self.kept.push("PLAY RECAP".to_string());
+        } else if self.in_recap {
+            self.kept
+                .push(trimmed.split_whitespace().collect::<Vec<_>>().join(" "));
+        } else if trimmed.starts_with("PLAY [") {
+            self.close_play();
+            self.play_name = header_name(trimmed);
+            self.play_idx = Some(self.kept.len());
+            self.kept.push(self.play_name.clone());
+        } else if trimmed.starts_with("TASK [") || trimmed.starts_with("RUNNING HANDLER [") {
+            self.task = Some(header_name(trimmed));
+            self.in_detail = false;
+        } else if trimmed.starts_with("ok: [") {
+            self.counts.ok += 1;
+            self.in_detail = false;
+        } else if trimmed.starts_with("skipping: [") || trimmed.starts_with("included: ") {
+            self.counts.skipped += usize::from(trimmed.starts_with("skipping"));
+            self.in_detail = false;
+        } else if trimmed.starts_with("changed: [") {
+            self.emit_task_result(trimmed);
+            self.in_detail = false;
+        } else if trimmed.starts_with("fatal: [")
+            || trimmed.starts_with("failed: [")
+            || trimmed.contains("UNREACHABLE!")
+        {
+            self.emit_task_result(trimmed);
+            self.in_detail = true;
+            self.detail_lines = 0;
+        } else if self.in_detail {
+            if self.detail_lines < MAX_DETAIL_LINES {
+                self.kept.push(format!("    {trimmed}"));
+            }
+            self.detail_lines += 1;
+        } else if trimmed.starts_with("ERROR!") || trimmed.starts_with("[WARNING]") {
+            self.kept.push(trimmed.to_string());
+        } else if trimmed == "...ignoring" {
+            self.kept.push("    ...ignoring".to_string());
+        }
+    }
+}
+
+/// Filter `ansible-playbook` output.
+///
+/// - `ok:`/`skipping:` task results are collapsed into a count on the PLAY line.
+/// - `changed:`, `failed:`, `fatal:` and unreachable results are kept under
+///   their TASK header, with the module error details that follow them.
+/// - The PLAY RECAP table is kept with whitespace normalized.
+pub fn filter_ansible_playbook(output: &str, exit_code: i32) -> String {
+    let mut scan = PlaybookScan::default();
+    for line in output.lines() {
+        scan.line(line);
+    }
+    scan.close_play();
+
+    if scan.kept.is_empty() {
+        return if exit_code == 0 {
+            "Playbook completed.".to_string()
+        } else {
+            format!("ansible-playbook failed (exit code {exit_code}).")
+        };
+    }
+    scan.kept.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYBOOK_OUTPUT: &str = "\
+PLAY [webservers] **************************************************************
+
+TASK [Gathering Facts] *********************************************************
+ok: [web1]
+ok: [web2]
+
+TASK [Install nginx] ***********************************************************
+changed: [web1]
+ok: [web2]
+
+TASK [Skip on debian] **********************************************************
+skipping: [web1]
+skipping: [web2]
+
+TASK [Start service] ***********************************************************
+ok: [web1]
+fatal: [web2]: FAILED! => {\"changed\": false, \"msg\": \"Could not find the requested service nginx: host\"}
+
+PLAY RECAP *********************************************************************
+web1                       : ok=3    changed=1    unreachable=0    failed=0    skipped=1    rescued=0    ignored=0
+web2                       : ok=2    changed=0    unreachable=0    failed=1    skipped=1    rescued=0    ignored=0
+";
+
+    #[test]
+    fn collapses_ok_and_keeps_changed_and_failed() {
+        let result = filter_ansible_playbook(PLAYBOOK_OUTPUT, 2);
+        assert_eq!(
+            result,
+            "PLAY [webservers] (4 ok, 2 skipped)\n\
+             TASK [Install nginx]\n\
+             \x20 changed: [web1]\n\
+             TASK [Start service]\n\
+             \x20 fatal: [web2]: FAILED! => {\"changed\": false, \"msg\": \"Could not find the requested service nginx: host\"}\n\
+             PLAY RECAP\n\
+             web1 : ok=3 changed=1 unreachable=0 failed=0 skipped=1 rescued=0 ignored=0\n\
+             web2 : ok=2 changed=0 unreachable=0 failed=1 skipped=1 rescued=0 ignored=0"
+        );
+    }
+
+    #[test]
+    fn keeps_multiline_module_error_details() {
+        let input = "\
+PLAY [db] ****
+
+TASK [Run migration] ****
+fatal: [db1]: FAILED! => {
+    \"changed\": true,
+    \"msg\": \"non-zero return code\",
+    \"stderr\": \"relation users does not exist\"
+}
+...ignoring
+
+PLAY RECAP ****
+db1 : ok=1 changed=1 unreachable=0 failed=0 skipped=0 rescued=0 ignored=1";
+        let result = filter_ansible_playbook(input, 0);
+        assert!(result.contains("\"stderr\": \"relation users does not exist\""));
+        assert!(result.contains("TASK [Run migration]"));
+        assert!(result.contains("db1 : ok=1"));
+    }
+
+    #[test]
+    fn unreachable_host_kept() {
+        let input = "\
+PLAY [all] ****
+TASK [Gathering Facts] ****
+fatal: [web3]: UNREACHABLE! => {\"changed\": false, \"msg\": \"Failed to connect to the host via ssh\", \"unreachable\": true}
+ok: [web1]";
+        let result = filter_ansible_playbook(input, 4);
+        assert!(result.starts_with("PLAY [all] (1 ok)"));
+        assert!(result.contains("UNREACHABLE!"));
+    }
+
+    #[test]
+    fn syntax_error_kept() {
+        let input = "ERROR! the playbook: site.yml could not be found";
+        assert_eq!(filter_ansible_playbook(input, 1), input);
+    }
+
+    #[test]
+    fn empty_failure() {
+        assert_eq!(
+            filter_ansible_playbook("", 1),
+            "ansible-playbook failed (exit code 1)."
+        );
+    }
+}
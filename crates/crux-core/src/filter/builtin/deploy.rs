@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::BuiltinFilterFn;
+
+/// Register hosted deploy CLI handlers (Vercel, Netlify).
+pub fn register(m: &mut HashMap<&'static str, BuiltinFilterFn>) {
+    m.insert("vercel deploy", filter_vercel_deploy as BuiltinFilterFn);
+    m.insert("vercel --prod", filter_vercel_deploy as BuiltinFilterFn);
+    m.insert("netlify deploy", filter_netlify_deploy as BuiltinFilterFn);
+}
+
+/// A bare `file:line[:col]` location line.
+static LOCATION_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\S+:\d+(:\d+)?$").unwrap());
+
+/// Vercel's `Production: <url>` / `Preview: <url>` result line.
+static VERCEL_TARGET_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(Production|Preview):\s+(https?://\S+)").unwrap());
+
+/// Vercel's trailing `[58s]` step duration.
+static VERCEL_DURATION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(\d+(?:\.\d+)?m?s)\]\s*$").unwrap());
+
+/// Netlify's `... completed in 12.3s` build duration.
+static NETLIFY_DURATION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"completed in ([\d.]+m?s)").unwrap());
+
+/// Maximum number of lines kept from a build error excerpt.
+const MAX_ERROR_LINES: usize = 20;
+
+/// Returns true if a line starts or belongs to a build error block.
+fn is_error_line(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    lower.starts_with("error")
+        || lower.contains(" error ")
+        || lower.contains("error:")
+        || lower.contains("failed to compile")
+        || lower.contains("build failed")
+        || lower.contains("exited with")
+        || line.starts_with('✖')
+        || line.starts_with('❌')
+        || line.starts_with('×')
+}
+
+/// Returns true for the logs/inspect link lines reported separately.
+fn is_link_line(line: &str) -> bool {
+    line.contains("Inspect:") || line.starts_with("Logs:") || line.starts_with("Build logs:")
+}
+
+/// Collect the build error excerpt: every error line plus the lines that
+/// directly follow it (stack/location detail), capped at `MAX_ERROR_LINES`.
+/// A `file:line:col` location printed just before an error is kept too.
+fn error_excerpt(output: &str) -> Vec<String> {
+    let mut excerpt = Vec::new();
+    let mut in_block = false;
+    let mut prev: Option<&str> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || is_link_line(trimmed) {
+            in_block = false;
+            continue;
+        }
+        if !in_block && is_error_line(trimmed) {
+            in_block = true;
+            if let Some(loc) = prev.filter(|p| LOCATION_RE.is_match(p)) {
+                excerpt.push(loc.to_string());
+            }
+        }
+        prev = Some(trimmed);
+        if in_block {
+            excerpt.push(trimmed.to_string());
+            if excerpt.len() >= MAX_ERROR_LINES {
+                break;
+            }
+        }
+    }
+
+    excerpt
+}
+
+/// Filter `vercel deploy` output.
+///
+/// On success: one line with the production/preview URL and the deployment
+/// duration (e.g. `✔ Production: https://app.vercel.app (58s)`).
+/// On failure: the build error excerpt plus the Inspect URL for the full logs.
+/// Upload progress, framework detection and CLI tips are dropped.
+pub fn filter_vercel_deploy(output: &str, exit_code: i32) -> String {
+    let mut target: Option<(String, String)> = None;
+    let mut inspect: Option<String> = None;
+    let mut duration: Option<String> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(caps) = VERCEL_TARGET_RE.captures(trimmed) {
+            target = Some((caps[1].to_string(), caps[2].to_string()));
+        } else if let Some(rest) = trimmed.split("Inspect:").nth(1) {
+            inspect = rest.split_whitespace().next().map(String::from);
+        }
+        if let Some(caps) = VERCEL_DURATION_RE.captures(trimmed) {
+            duration = Some(caps[1].to_string());
+        }
+    }
+
+    if exit_code == 0 {
+        if let Some((kind, url)) = target {
+            return match duration {
+                Some(d) => format!("✔ {kind}: {url} ({d})"),
+                None => format!("✔ {kind}: {url}"),
+            };
+        }
+        return "Vercel deploy completed.".to_string();
+    }
+
+    let mut result = error_excerpt(output);
+    if result.is_empty() {
+        result.push(format!("Vercel deploy failed (exit code {exit_code})."));
+    }
+    if let Some(url) = inspect {
+        result.push(format!("Inspect: {url}"));
+    }
+    result.join("\n")
+}
+
+/// Filter `netlify deploy` output.
+///
+/// On success: the live (or draft) website URL and the build duration when
+/// `--build` reported one. On failure: the build error excerpt plus the deploy
+/// logs URL. Hashing/upload progress and path/config preamble are dropped.
+pub fn filter_netlify_deploy(output: &str, exit_code: i32) -> String {
+    let mut website: Option<String> = None;
+    let mut draft: Option<String> = None;
+    let mut logs: Option<String> = None;
+    let mut duration: Option<String> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(url) = trimmed.strip_prefix("Website URL:") {
+            website = Some(url.trim().to_string());
+        } else if let Some(url) = trimmed.strip_prefix("Website draft URL:") {
+            draft = Some(url.trim().to_string());
+        } else if let Some(url) = trimmed.strip_prefix("Logs:") {
+            logs = Some(url.trim().to_string());
+        } else if let Some(rest) = trimmed.strip_prefix("Build logs:") {
+            logs = Some(rest.trim().to_string());
+        }
+        if let Some(caps) = NETLIFY_DURATION_RE.captures(trimmed) {
+            duration = Some(caps[1].to_string());
+        }
+    }
+
+    if exit_code == 0 {
+        let line = match (website, draft) {
+            (Some(url), _) => format!("✔ Deploy is live! Website: {url}"),
+            (None, Some(url)) => format!("✔ Draft deployed: {url}"),
+            (None, None) => return "Netlify deploy completed.".to_string(),
+        };
+        return match duration {
+            Some(d) => format!("{line} ({d})"),
+            None => line,
+        };
+    }
+
+    let mut result = error_excerpt(output);
+    if result.is_empty() {
+        result.push(format!("Netlify deploy failed (exit code {exit_code})."));
+    }
+    if let Some(url) = logs {
+        result.push(format!("Logs: {url}"));
+    }
+    result.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERCEL_SUCCESS: &str = "\
+Vercel CLI 37.4.0
+Retrieving project…
+Deploying acme/web
+Uploading [--------------------] (0.0B/1.2MB)
+Uploading [==========----------] (640.0KB/1.2MB)
+Uploading [====================] (1.2MB/1.2MB)
+🔍  Inspect: https://vercel.com/acme/web/9fK2abc [3s]
+✅  Production: https://web-acme.vercel.app [58s]
+📝  Deployed to production. Run `vercel --prod` to overwrite later (https://vercel.link/2F).
+💡  To change the domain or build command, go to https://vercel.com/acme/web/settings";
+
+    const VERCEL_FAILURE: &str = "\
+Vercel CLI 37.4.0
+Retrieving project…
+Deploying acme/web
+🔍  Inspect: https://vercel.com/acme/web/Xy77 [2s]
+Running build in Washington, D.C., USA (East) – iad1
+Detected Next.js version: 14.1.0
+Running \"npm run build\"
+Creating an optimized production build ...
+Failed to compile.
+
+./src/app/page.tsx:12:5
+Type error: Property 'title' does not exist on type 'Props'.
+
+Error: Command \"npm run build\" exited with 1";
+
+    const NETLIFY_PROD: &str = "\
+Deploy path:        /home/user/site/dist
+Functions path:     /home/user/site/netlify/functions
+Configuration path: /home/user/site/netlify.toml
+Deploying to main site URL...
+✔ Finished hashing 42 files and 2 functions
+✔ CDN requesting 3 files and 1 functions
+✔ Finished uploading 4 assets
+✔ Deploy is live!
+
+(Netlify Build completed in 15.2s)
+Build logs:         https://app.netlify.com/sites/my-site/deploys/65ab
+Unique Deploy URL:  https://65ab--my-site.netlify.app
+Website URL:        https://my-site.netlify.app";
+
+    #[test]
+    fn vercel_success_keeps_url_and_duration() {
+        let result = filter_vercel_deploy(VERCEL_SUCCESS, 0);
+        assert_eq!(result, "✔ Production: https://web-acme.vercel.app (58s)");
+    }
+
+    #[test]
+    fn vercel_preview_deploy() {
+        let input = "🔍  Inspect: https://vercel.com/acme/web/1 [1s]\n\
+                     ✅  Preview: https://web-git-feat-acme.vercel.app [12s]";
+        let result = filter_vercel_deploy(input, 0);
+        assert_eq!(
+            result,
+            "✔ Preview: https://web-git-feat-acme.vercel.app (12s)"
+        );
+    }
+
+    #[test]
+    fn vercel_failure_keeps_error_excerpt() {
+        let result = filter_vercel_deploy(VERCEL_FAILURE, 1);
+        assert!(result.contains("Failed to compile."));
+        assert!(result.contains("./src/app/page.tsx:12:5\nType error:"));
+        assert!(result.contains("Error: Command \"npm run build\" exited with 1"));
+        assert!(result.contains("Inspect: https://vercel.com/acme/web/Xy77"));
+        assert!(
+            !result.contains("Detected Next.js"),
+            "drop framework chatter"
+        );
+        assert!(!result.contains("Retrieving project"));
+    }
+
+    #[test]
+    fn vercel_failure_without_details() {
+        let result = filter_vercel_deploy("Vercel CLI 37.4.0", 1);
+        assert_eq!(result, "Vercel deploy failed (exit code 1).");
+    }
+
+    #[test]
+    fn netlify_prod_keeps_website_and_duration() {
+        let result = filter_netlify_deploy(NETLIFY_PROD, 0);
+        assert_eq!(
+            result,
+            "✔ Deploy is live! Website: https://my-site.netlify.app (15.2s)"
+        );
+    }
+
+    #[test]
+    fn netlify_draft_deploy() {
+        let input = "\
+Deploying to draft URL...
+✔ Finished hashing 42 files
+✔ Deploy is live!
+
+Logs:              https://app.netlify.com/sites/my-site/deploys/65ab
+Website draft URL: https://65ab--my-site.netlify.app
+
+If everything looks good on your draft URL, deploy it to your main site URL with the --prod flag.
+netlify deploy --prod";
+        let result = filter_netlify_deploy(input, 0);
+        assert_eq!(
+            result,
+            "✔ Draft deployed: https://65ab--my-site.netlify.app"
+        );
+    }
+
+    #[test]
+    fn netlify_build_failure() {
+        let input = "\
+Netlify Build
+────────────────────────────────
+❯ Version
+  @netlify/build 29.36.1
+❯ Context
+  production
+
+build.command from netlify.toml
+$ npm run build
+src/main.ts(3,1): error TS2304: Cannot find name 'foo'.
+
+Build failed due to a user error: Build script returned non-zero exit code: 2
+Build logs: https://app.netlify.com/sites/my-site/deploys/77cd";
+        let result = filter_netlify_deploy(input, 2);
+        assert!(result.contains("error TS2304: Cannot find name 'foo'."));
+        assert!(result.contains("Build failed due to a user error"));
+        assert!(result.contains("Logs: https://app.netlify.com/sites/my-site/deploys/77cd"));
+        assert!(!result.contains("@netlify/build"), "drop version chatter");
+    }
+}
@@ -424,8 +424,8 @@ pub fn filter_docker_exec(output: &str, exit_code: i32) -> String {
 
 // -- helpers --
 
-struct ColumnDef {
-    name: String,
+pub(crate) struct ColumnDef {
+    pub(crate) name: String,
     start: usize,
     end: usize, // exclusive, or usize::MAX for last column
 }
@@ -433,7 +433,7 @@ struct ColumnDef {
 /// Parse column positions from a Docker-style header line.
 /// Docker uses fixed-width columns separated by 2+ spaces.
 /// Column names like "CONTAINER ID" or "IMAGE ID" contain single spaces.
-fn parse_column_positions(header: &str) -> Vec<ColumnDef> {
+pub(crate) fn parse_column_positions(header: &str) -> Vec<ColumnDef> {
     let mut cols = Vec::new();
 
     // Split on 2+ spaces to find column name tokens and their positions
@@ -531,7 +531,7 @@ fn strip_column(line: &str, cols: &[ColumnDef], strip_idx: Option<usize>) -> Str
 }
 
 /// Remove multiple columns from a line. Processes right-to-left to avoid index shifting.
-fn strip_columns(line: &str, cols: &[ColumnDef], strip_indices: &[usize]) -> String {
+pub(crate) fn strip_columns(line: &str, cols: &[ColumnDef], strip_indices: &[usize]) -> String {
     if strip_indices.is_empty() {
         return line.to_string();
     }
@@ -557,6 +557,9 @@ fn strip_columns(line: &str, cols: &[ColumnDef], strip_indices: &[usize]) -> Str
             result.len()
         };
         let end = end.min(result.len());
+        if !result.is_char_boundary(col.start) || !result.is_char_boundary(end) {
+            continue;
+        }
         result = format!("{}{}", &result[..col.start], &result[end..]);
     }
 
@@ -0,0 +1,125 @@
+//! `firebase emulators:start`.
+
+/// Filter `firebase emulators:start` output.
+///
+/// Keeps the "All emulators ready" notice with the Emulator UI URL, the
+/// emulator port table as `Emulator → host:port` lines, the hub/reserved-port
+/// notes and fatal errors. Per-service boot logs (`i  firestore: ...`,
+/// function initialization) and table borders are dropped.
+pub fn filter_firebase_emulators_start(output: &str, exit_code: i32) -> String {
+    let mut kept = Vec::new();
+    let mut ui_url: Option<String> = None;
+    let mut ready = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim().trim_matches('│').trim();
+
+        if trimmed.is_empty() || trimmed.starts_with(['┌', '├', '└']) {
+            continue;
+        }
+
+        if trimmed.contains("All emulators ready") {
+            ready = true;
+        } else if let Some(rest) = trimmed.split("View Emulator UI at").nth(1) {
+            ui_url = Some(rest.trim().to_string());
+        } else if trimmed.contains('│') {
+            let cols: Vec<&str> = trimmed.split('│').map(str::trim).collect();
+            if cols.len() >= 2 && cols[0] != "Emulator" {
+                kept.push(format!("  {} → {}", cols[0], cols[1]));
+            }
+        } else if trimmed.starts_with("Emulator Hub running at")
+            || trimmed.starts_with("Other reserved ports")
+        {
+            kept.push(format!("  {trimmed}"));
+        } else if is_fatal_emulator_line(trimmed) {
+            kept.push(trimmed.to_string());
+        }
+    }
+
+    if ready {
+        let header = match ui_url {
+            Some(url) => format!("✔ All emulators ready! UI: {url}"),
+            None => "✔ All emulators ready!".to_string(),
+        };
+        kept.insert(0, header);
+    }
+
+    if kept.is_empty() {
+        return if exit_code == 0 {
+            "Emulators stopped.".to_string()
+        } else {
+            format!("firebase emulators:start failed (exit code {exit_code}).")
+        };
+    }
+    kept.join("\n")
+}
+
+/// Returns true for emulator lines reporting a fatal/startup error.
+fn is_fatal_emulator_line(trimmed: &str) -> bool {
+    trimmed.starts_with("Error:")
+        || trimmed.starts_with('✖')
+        || trimmed.contains("Fatal error")
+        || trimmed.contains("Could not start")
+        || trimmed.contains("port taken")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMULATORS_OUTPUT: &str = "\
+i  emulators: Starting emulators: auth, functions, firestore
+i  firestore: Firestore Emulator logging to firestore-debug.log
+✔  firestore: Firestore Emulator UI websocket is running on 9150.
+i  ui: Emulator UI logging to ui-debug.log
+i  functions: Watching \"/home/u/my-project/functions\" for Cloud Functions...
+✔  functions: Loaded functions definitions from source: api.
+✔  functions[us-central1-api]: http function initialized (http://127.0.0.1:5001/my-project/us-central1/api).
+
+┌─────────────────────────────────────────────────────────────┐
+│ ✔  All emulators ready! It is now safe to connect your app. │
+│ i  View Emulator UI at http://127.0.0.1:4000/               │
+└─────────────────────────────────────────────────────────────┘
+
+┌────────────────┬────────────────┬─────────────────────────────────┐
+│ Emulator       │ Host:Port      │ View in Emulator UI             │
+├────────────────┼────────────────┼─────────────────────────────────┤
+│ Authentication │ 127.0.0.1:9099 │ http://127.0.0.1:4000/auth      │
+├────────────────┼────────────────┼─────────────────────────────────┤
+│ Functions      │ 127.0.0.1:5001 │ http://127.0.0.1:4000/functions │
+├────────────────┼────────────────┼─────────────────────────────────┤
+│ Firestore      │ 127.0.0.1:8080 │ http://127.0.0.1:4000/firestore │
+└────────────────┴────────────────┴─────────────────────────────────┘
+  Emulator Hub running at 127.0.0.1:4400
+  Other reserved ports: 4500, 9150
+
+Issues? Report them at https://github.com/firebase/firebase-tools/issues and attach the *-debug.log files.";
+
+    #[test]
+    fn firebase_emulators_keeps_port_table() {
+        let result = filter_firebase_emulators_start(EMULATORS_OUTPUT, 0);
+        assert_eq!(
+            result,
+            "✔ All emulators ready! UI: http://127.0.0.1:4000/\n\
+             \x20 Authentication → 127.0.0.1:9099\n\
+             \x20 Functions → 127.0.0.1:5001\n\
+             \x20 Firestore → 127.0.0.1:8080\n\
+             \x20 Emulator Hub running at 127.0.0.1:4400\n\
+             \x20 Other reserved ports: 4500, 9150"
+        );
+    }
+
+    #[test]
+    fn firebase_emulators_keeps_fatal_errors() {
+        let input = "\
+i  emulators: Starting emulators: firestore
+i  firestore: Firestore Emulator logging to firestore-debug.log
+⚠  firestore: Fatal error occurred:
+   Firestore Emulator has exited with code: 1, stopping all running emulators
+Error: Could not start Firestore Emulator, port taken.";
+        let result = filter_firebase_emulators_start(input, 1);
+        assert!(result.contains("Fatal error occurred"));
+        assert!(result.contains("Error: Could not start Firestore Emulator, port taken."));
+        assert!(!result.contains("logging to"), "should drop boot logs");
+    }
+}
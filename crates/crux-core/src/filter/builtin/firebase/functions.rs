@@ -0,0 +1,119 @@
+//! `firebase deploy --only functions`.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// A per-function result: `✔  functions[api(us-central1)] Successful update operation.`
+static RESULT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"functions\[([^\]]+)\]:?\s+(.*)$").unwrap());
+
+/// The operation in a successful result message: `Successful update operation`.
+static OP_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Successful (\w+) operation").unwrap());
+
+/// Filter `firebase deploy --only functions` output.
+///
+/// Keeps one line per function with its deploy result (`✔ api(us-central1): update`,
+/// `✖ mail(us-central1): Failed to update function ...`), the Function URL lines
+/// and `Error:` lines. Drops API enablement checks, packaging and upload progress.
+pub fn filter_firebase_deploy_functions(output: &str, exit_code: i32) -> String {
+    let mut results = Vec::new();
+    let mut urls = Vec::new();
+    let mut errors = Vec::new();
+    let mut deploy_complete = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.contains("Deploy complete") {
+            deploy_complete = true;
+        } else if let Some(rest) = trimmed.strip_prefix("Function URL") {
+            urls.push(format!("  Function URL{rest}"));
+        } else if let Some(caps) = RESULT_RE.captures(trimmed) {
+            let (name, message) = (&caps[1], &caps[2]);
+            if let Some(op) = OP_RE.captures(message) {
+                results.push(format!("✔ {name}: {}", &op[1]));
+            } else if message.contains("Failed") || message.contains("failed") {
+                results.push(format!("✖ {name}: {message}"));
+            }
+        } else if trimmed.starts_with("Error:") {
+            errors.push(trimmed.to_string());
+        }
+    }
+
+    let mut kept = results;
+    kept.extend(urls);
+    kept.extend(errors);
+    if deploy_complete && exit_code == 0 {
+        kept.push("✔ Deploy complete!".to_string());
+    }
+
+    if kept.is_empty() {
+        return if exit_code == 0 {
+            "Deploy completed.".to_string()
+        } else {
+            format!("Firebase deploy failed (exit code {exit_code}).")
+        };
+    }
+    kept.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FUNCTIONS_DEPLOY_OUTPUT: &str = "\
+=== Deploying to 'my-project'...
+
+i  deploying functions
+i  functions: preparing codebase default for deployment
+i  functions: ensuring required API cloudfunctions.googleapis.com is enabled...
+✔  functions: required API cloudbuild.googleapis.com is enabled
+i  functions: preparing functions directory for uploading...
+i  functions: packaged /home/u/my-project/functions (54.2 KB) for uploading
+✔  functions: functions folder uploaded successfully
+i  functions: updating Node.js 18 function api(us-central1)...
+i  functions: creating Node.js 18 function onUserCreate(us-central1)...
+✔  functions[api(us-central1)] Successful update operation.
+✔  functions[onUserCreate(us-central1)] Successful create operation.
+Function URL (api(us-central1)): https://us-central1-my-project.cloudfunctions.net/api
+i  functions: cleaning up build files...
+
+✔  Deploy complete!
+
+Project Console: https://console.firebase.google.com/project/my-project/overview";
+
+    #[test]
+    fn firebase_deploy_functions_keeps_per_function_results() {
+        let result = filter_firebase_deploy_functions(FUNCTIONS_DEPLOY_OUTPUT, 0);
+        assert_eq!(
+            result,
+            "✔ api(us-central1): update\n\
+             ✔ onUserCreate(us-central1): create\n\
+             \x20 Function URL (api(us-central1)): https://us-central1-my-project.cloudfunctions.net/api\n\
+             ✔ Deploy complete!"
+        );
+    }
+
+    #[test]
+    fn firebase_deploy_functions_failure() {
+        let input = "\
+i  functions: updating Node.js 18 function api(us-central1)...
+i  functions: updating Node.js 18 function sendMail(us-central1)...
+✔  functions[api(us-central1)] Successful update operation.
+⚠  functions[sendMail(us-central1)] Failed to update function sendMail in region us-central1. Build failed: npm ERR! missing script: build
+
+Functions deploy had errors with the following functions:
+\tsendMail(us-central1)
+i  functions: cleaning up build files...
+
+Error: There was an error deploying functions";
+        let result = filter_firebase_deploy_functions(input, 2);
+        assert!(result.contains("✔ api(us-central1): update"));
+        assert!(result.contains("✖ sendMail(us-central1): Failed to update function sendMail"));
+        assert!(result.ends_with("Error: There was an error deploying functions"));
+        assert!(!result.contains("cleaning up"), "should drop progress");
+        assert!(!result.contains("Deploy complete"));
+    }
+}
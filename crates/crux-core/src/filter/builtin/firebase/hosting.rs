@@ -0,0 +1,164 @@
+//! `firebase hosting:sites:list`.
+
+use super::filter_firebase_generic;
+
+/// Filter `firebase hosting:sites:list` output.
+///
+/// Extracts site names and default URLs from the table output.
+/// Firebase CLI outputs a box-drawing table with Site ID, Default URL, and App ID columns.
+/// Output: a count header plus one line per site with "site-id → url".
+pub fn filter_firebase_hosting_sites_list(output: &str, exit_code: i32) -> String {
+    if exit_code != 0 {
+        // On failure, fall back to generic filtering.
+        return filter_firebase_generic(output, exit_code);
+    }
+
+    let mut sites = Vec::new();
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        // Skip empty, decorator, header, and info lines.
+        if trimmed.is_empty()
+            || trimmed.starts_with("i  ")
+            || trimmed.starts_with("i ")
+            || trimmed.starts_with("===")
+            || trimmed.starts_with('┌')
+            || trimmed.starts_with('├')
+            || trimmed.starts_with('└')
+            || trimmed.starts_with('─')
+            || trimmed.starts_with('+')
+        {
+            continue;
+        }
+
+        // Parse table rows: │ col1 │ col2 │ col3 │
+        if trimmed.starts_with('│') || trimmed.starts_with('|') {
+            let sep = if trimmed.contains('│') { '│' } else { '|' };
+            let cols: Vec<&str> = trimmed
+                .split(sep)
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .collect();
+
+            if cols.len() >= 2 {
+                let site_id = cols[0];
+                let url = cols[1];
+
+                // Skip the header row.
+                if site_id.eq_ignore_ascii_case("Site ID")
+                    || site_id.eq_ignore_ascii_case("site")
+                    || site_id.contains("Site")
+                {
+                    continue;
+                }
+
+                // Skip separator-like rows (all dashes).
+                if site_id.chars().all(|c| c == '-' || c == '─') {
+                    continue;
+                }
+
+                if url.starts_with("http") {
+                    sites.push(format!("{site_id} → {url}"));
+                } else {
+                    sites.push(site_id.to_string());
+                }
+            }
+        }
+    }
+
+    if sites.is_empty() {
+        // Fallback: try generic filter.
+        filter_firebase_generic(output, exit_code)
+    } else {
+        let header = if sites.len() == 1 {
+            "1 hosting site:".to_string()
+        } else {
+            format!("{} hosting sites:", sites.len())
+        };
+        let mut result = header;
+        for site in &sites {
+            result.push('\n');
+            result.push_str("  ");
+            result.push_str(site);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn firebase_hosting_sites_list_table() {
+        let input = "\
+i  Preparing the list of your Firebase Hosting sites.
+┌──────────────────┬────────────────────────────────────┬────────┐
+│ Site ID          │ Default URL                        │ App ID │
+├──────────────────┼────────────────────────────────────┼────────┤
+│ my-app           │ https://my-app.web.app             │ --     │
+├──────────────────┼────────────────────────────────────┼────────┤
+│ my-app-staging   │ https://my-app-staging.web.app     │ --     │
+├──────────────────┼────────────────────────────────────┼────────┤
+│ my-app-dev       │ https://my-app-dev.web.app         │ --     │
+└──────────────────┴────────────────────────────────────┴────────┘";
+
+        let result = filter_firebase_hosting_sites_list(input, 0);
+
+        assert!(
+            result.starts_with("3 hosting sites:"),
+            "should have count header"
+        );
+        assert!(
+            result.contains("my-app → https://my-app.web.app"),
+            "should have first site"
+        );
+        assert!(
+            result.contains("my-app-staging → https://my-app-staging.web.app"),
+            "should have second site"
+        );
+        assert!(
+            result.contains("my-app-dev → https://my-app-dev.web.app"),
+            "should have third site"
+        );
+
+        // Should be compact — header + 3 sites = 4 lines.
+        let line_count = result.lines().count();
+        assert_eq!(line_count, 4, "expected 4 lines, got {line_count}");
+
+        // Should drop all the box-drawing noise.
+        assert!(!result.contains('┌'), "should drop table borders");
+        assert!(!result.contains('│'), "should drop table separators");
+        assert!(!result.contains("Preparing"), "should drop info lines");
+    }
+
+    #[test]
+    fn firebase_hosting_sites_list_single() {
+        let input = "\
+┌──────────┬──────────────────────────────┬────────┐
+│ Site ID  │ Default URL                  │ App ID │
+├──────────┼──────────────────────────────┼────────┤
+│ my-site  │ https://my-site.web.app      │ --     │
+└──────────┴──────────────────────────────┴────────┘";
+
+        let result = filter_firebase_hosting_sites_list(input, 0);
+        assert!(
+            result.starts_with("1 hosting site:"),
+            "singular form for 1 site"
+        );
+        assert!(result.contains("my-site → https://my-site.web.app"));
+    }
+
+    #[test]
+    fn firebase_hosting_sites_list_failure() {
+        let input = "\
+i  Preparing the list...
+Error: Failed to list hosting sites";
+
+        let result = filter_firebase_hosting_sites_list(input, 1);
+        // Falls back to generic filter on failure.
+        assert!(result.contains("Error:"), "should keep error on failure");
+        assert!(!result.contains("Preparing"), "should drop info lines");
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use regex::Regex;
 
+use super::locale::{self, Phrase};
 use super::BuiltinFilterFn;
 
 /// Register core git handlers.
@@ -14,17 +15,17 @@ pub fn register(m: &mut HashMap<&'static str, BuiltinFilterFn>) {
 
 /// Filter git status: keep branch line and file status lines, strip hints and boilerplate.
 /// Normalizes long-format lines to short format and compresses branch tracking info.
+/// Recognizes translated output (see [`locale`]).
 pub fn filter_git_status(output: &str, _exit_code: i32) -> String {
     let mut lines = Vec::new();
     let mut in_untracked = false;
-    let ahead_re = Regex::new(r"ahead of .+ by (\d+) commit").unwrap();
-    let behind_re = Regex::new(r"behind .+ by (\d+) commit").unwrap();
+    let count_re = Regex::new(r"(\d+) [Cc]ommit").unwrap();
 
     for line in output.lines() {
         let trimmed = line.trim();
 
         // Track when we enter/leave the "Untracked files:" section
-        if trimmed == "Untracked files:" {
+        if locale::strip_label(trimmed, Phrase::GitUntrackedFiles) == Some("") {
             in_untracked = true;
             continue;
         }
@@ -38,30 +39,30 @@ pub fn filter_git_status(output: &str, _exit_code: i32) -> String {
         }
 
         // Keep "On branch ..." line
-        if trimmed.starts_with("On branch ") {
+        if locale::starts_with(trimmed, Phrase::GitOnBranch) {
             lines.push(trimmed.to_string());
             continue;
         }
 
         // Compress branch tracking lines
-        if trimmed.starts_with("Your branch is up to date") {
+        if locale::starts_with(trimmed, Phrase::GitUpToDate) {
             // Skip entirely — up-to-date is the default assumption
             continue;
         }
-        if trimmed.starts_with("Your branch is ahead") {
-            if let Some(caps) = ahead_re.captures(trimmed) {
+        if locale::starts_with(trimmed, Phrase::GitAhead) {
+            if let Some(caps) = count_re.captures(trimmed) {
                 lines.push(format!("ahead {}", &caps[1]));
             }
             continue;
         }
-        if trimmed.starts_with("Your branch is behind") {
-            if let Some(caps) = behind_re.captures(trimmed) {
+        if locale::starts_with(trimmed, Phrase::GitBehind) {
+            if let Some(caps) = count_re.captures(trimmed) {
                 lines.push(format!("behind {}", &caps[1]));
             }
             continue;
         }
         // Keep diverged lines as-is (rare)
-        if trimmed.starts_with("Your branch and") {
+        if locale::starts_with(trimmed, Phrase::GitDiverged) {
             lines.push(trimmed.to_string());
             continue;
         }
@@ -89,7 +90,7 @@ pub fn filter_git_status(output: &str, _exit_code: i32) -> String {
         }
 
         // Keep "nothing to commit" line; skip "no changes added" (redundant)
-        if trimmed.starts_with("nothing to commit") {
+        if locale::starts_with(trimmed, Phrase::GitNothingToCommit) {
             lines.push(trimmed.to_string());
             continue;
         }
@@ -104,25 +105,23 @@ pub fn filter_git_status(output: &str, _exit_code: i32) -> String {
     }
 }
 
+/// Long-format status labels and their short-format codes.
+const STATUS_LABELS: &[(Phrase, &str)] = &[
+    (Phrase::GitModified, "M"),
+    (Phrase::GitNewFile, "A"),
+    (Phrase::GitDeleted, "D"),
+    (Phrase::GitRenamed, "R"),
+    (Phrase::GitCopied, "C"),
+    (Phrase::GitTypechange, "T"),
+];
+
 /// Normalize long-format status lines to short format.
 /// Returns `Some(short)` if the line is long-format, `None` if already short.
 fn normalize_status_line(line: &str) -> Option<String> {
-    let long_re =
-        Regex::new(r"^(modified|new file|deleted|renamed|copied|typechange):\s+(.+)$").unwrap();
-    if let Some(caps) = long_re.captures(line) {
-        let code = match &caps[1] {
-            "modified" => "M",
-            "new file" => "A",
-            "deleted" => "D",
-            "renamed" => "R",
-            "copied" => "C",
-            "typechange" => "T",
-            _ => return None,
-        };
-        Some(format!("{}  {}", code, &caps[2]))
-    } else {
-        None
-    }
+    STATUS_LABELS.iter().find_map(|&(phrase, code)| {
+        let path = locale::strip_label(line, phrase)?;
+        (!path.is_empty()).then(|| format!("{code}  {path}"))
+    })
 }
 
 fn is_status_file_line(line: &str) -> bool {
@@ -133,9 +132,7 @@ fn is_status_file_line(line: &str) -> bool {
     }
 
     // Long format: "modified:   file", "new file:   file", "deleted:   file", etc.
-    let long_re =
-        Regex::new(r"^(modified|new file|deleted|renamed|copied|typechange):\s+\S").unwrap();
-    long_re.is_match(line)
+    normalize_status_line(line).is_some()
 }
 
 /// Filter git diff: keep file headers, stats summary, collapse large hunks.
@@ -473,6 +470,27 @@ nothing to commit, working tree clean"#;
         assert!(!result.contains("up to date"), "got: {result}");
     }
 
+    #[test]
+    fn git_status_german() {
+        let input = "Auf Branch main\nIhr Branch ist 2 Commits vor 'origin/main'.\n  (benutzen Sie \"git push\", um lokale Commits zu publizieren)\n\nÄnderungen, die nicht zum Commit vorgemerkt sind:\n  (benutzen Sie \"git add <Datei>...\")\n\tgeändert:       src/lib.rs\n\nUnversionierte Dateien:\n\tneu.txt\n";
+        let result = filter_git_status(input, 0);
+        assert_eq!(
+            result,
+            "Auf Branch main\nahead 2\nM  src/lib.rs\n?? neu.txt"
+        );
+    }
+
+    #[test]
+    fn git_status_french() {
+        let input = "Sur la branche main\nVotre branche est à jour avec 'origin/main'.\n\nModifications qui seront validées :\n  (utilisez \"git restore --staged <fichier>...\")\n\tnouveau fichier : src/new.rs\n\tsupprimé :        old.rs\n";
+        let result = filter_git_status(input, 0);
+        assert_eq!(result, "Sur la branche main\nA  src/new.rs\nD  old.rs");
+
+        let clean = "Sur la branche main\nrien à valider, la copie de travail est propre";
+        let result = filter_git_status(clean, 0);
+        assert!(result.contains("rien à valider"), "got: {result}");
+    }
+
     #[test]
     fn git_status_normalizes_long_format() {
         let input = "On branch main\nChanges to be committed:\n\tnew file:   src/new.rs\n\tmodified:   src/lib.rs\n\nChanges not staged for commit:\n\tdeleted:    old.txt\n\trenamed:    a.rs -> b.rs";
@@ -0,0 +1,179 @@
+/// A user-facing phrase that builtins match on.
+///
+/// Tools like git translate their output when `LANG` is set, so builtins look
+/// phrases up here instead of hard-coding the English text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phrase {
+    GitOnBranch,
+    GitNothingToCommit,
+    GitUntrackedFiles,
+    GitUpToDate,
+    GitAhead,
+    GitBehind,
+    GitDiverged,
+    GitModified,
+    GitNewFile,
+    GitDeleted,
+    GitRenamed,
+    GitCopied,
+    GitTypechange,
+}
+
+/// English phrases. Status labels are listed without their trailing colon.
+const EN: &[(Phrase, &str)] = &[
+    (Phrase::GitOnBranch, "On branch "),
+    (Phrase::GitNothingToCommit, "nothing to commit"),
+    (Phrase::GitUntrackedFiles, "Untracked files"),
+    (Phrase::GitUpToDate, "Your branch is up to date"),
+    (Phrase::GitAhead, "Your branch is ahead"),
+    (Phrase::GitBehind, "Your branch is behind"),
+    (Phrase::GitDiverged, "Your branch and"),
+    (Phrase::GitModified, "modified"),
+    (Phrase::GitNewFile, "new file"),
+    (Phrase::GitDeleted, "deleted"),
+    (Phrase::GitRenamed, "renamed"),
+    (Phrase::GitCopied, "copied"),
+    (Phrase::GitTypechange, "typechange"),
+];
+
+/// German, from git's `de.po`.
+const DE: &[(Phrase, &str)] = &[
+    (Phrase::GitOnBranch, "Auf Branch "),
+    (Phrase::GitNothingToCommit, "nichts zu committen"),
+    (Phrase::GitUntrackedFiles, "Unversionierte Dateien"),
+    (Phrase::GitUpToDate, "Ihr Branch ist auf demselben Stand"),
+    // Ahead/behind put the count before the direction word.
+    (Phrase::GitAhead, "Ihr Branch ist * vor "),
+    (Phrase::GitBehind, "Ihr Branch ist * hinter "),
+    (Phrase::GitDiverged, "Ihr Branch und"),
+    (Phrase::GitModified, "geändert"),
+    (Phrase::GitNewFile, "neue Datei"),
+    (Phrase::GitDeleted, "gelöscht"),
+    (Phrase::GitRenamed, "umbenannt"),
+    (Phrase::GitCopied, "kopiert"),
+    (Phrase::GitTypechange, "Typänderung"),
+];
+
+/// French, from git's `fr.po`. Labels put a space before the colon.
+const FR: &[(Phrase, &str)] = &[
+    (Phrase::GitOnBranch, "Sur la branche "),
+    (Phrase::GitNothingToCommit, "rien à valider"),
+    (Phrase::GitUntrackedFiles, "Fichiers non suivis"),
+    (Phrase::GitUpToDate, "Votre branche est à jour"),
+    (Phrase::GitAhead, "Votre branche est en avance"),
+    (Phrase::GitBehind, "Votre branche est en retard"),
+    (Phrase::GitDiverged, "Votre branche et"),
+    (Phrase::GitModified, "modifié"),
+    (Phrase::GitNewFile, "nouveau fichier"),
+    (Phrase::GitDeleted, "supprimé"),
+    (Phrase::GitRenamed, "renommé"),
+    (Phrase::GitCopied, "copié"),
+    (Phrase::GitTypechange, "modif. type"),
+];
+
+/// Locale tables, English first. git ships no Japanese catalog and cargo's
+/// output is not translated, so those tools print English in every locale.
+const LOCALES: &[&[(Phrase, &str)]] = &[EN, DE, FR];
+
+/// Every translation of `phrase`, English first.
+pub fn texts(phrase: Phrase) -> impl Iterator<Item = &'static str> {
+    LOCALES
+        .iter()
+        .flat_map(|table| table.iter())
+        .filter(move |(p, _)| *p == phrase)
+        .map(|(_, text)| *text)
+}
+
+/// True if `line` starts with any translation of `phrase`.
+///
+/// A `*` in a translation stands for a count and its noun (`3 Commits`),
+/// for languages that put the count before the keyword.
+pub fn starts_with(line: &str, phrase: Phrase) -> bool {
+    strip_prefix(line, phrase).is_some()
+}
+
+/// The rest of `line` after any translation of `phrase`.
+pub fn strip_prefix(line: &str, phrase: Phrase) -> Option<&str> {
+    texts(phrase).find_map(|text| strip_pattern(line, text))
+}
+
+/// Match a header or label written as `phrase:` (or `phrase :`), returning
+/// what follows the colon, trimmed.
+pub fn strip_label(line: &str, phrase: Phrase) -> Option<&str> {
+    texts(phrase).find_map(|text| {
+        let rest = line.strip_prefix(text)?.trim_start().strip_prefix(':')?;
+        Some(rest.trim())
+    })
+}
+
+fn strip_pattern<'a>(line: &'a str, pattern: &str) -> Option<&'a str> {
+    let Some((before, after)) = pattern.split_once('*') else {
+        return line.strip_prefix(pattern);
+    };
+    let rest = line.strip_prefix(before)?;
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    let rest = rest[digits..].strip_prefix(' ')?;
+    let noun_end = rest.find(' ')?;
+    rest[noun_end..].strip_prefix(after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_phrase_has_english_text() {
+        for &(phrase, _) in DE.iter().chain(FR) {
+            assert!(EN.iter().any(|(p, _)| *p == phrase), "{phrase:?}");
+        }
+    }
+
+    #[test]
+    fn matches_translated_prefixes() {
+        assert_eq!(
+            strip_prefix("Auf Branch main", Phrase::GitOnBranch),
+            Some("main")
+        );
+        assert!(starts_with(
+            "rien à valider, la copie de travail est propre",
+            Phrase::GitNothingToCommit
+        ));
+        assert!(!starts_with("Compiling foo", Phrase::GitNothingToCommit));
+    }
+
+    #[test]
+    fn wildcard_matches_count_and_noun() {
+        assert!(starts_with(
+            "Ihr Branch ist 3 Commits vor 'origin/main'.",
+            Phrase::GitAhead
+        ));
+        assert!(starts_with(
+            "Ihr Branch ist 1 Commit hinter 'origin/main', und kann vorgespult werden.",
+            Phrase::GitBehind
+        ));
+        assert!(!starts_with(
+            "Ihr Branch ist auf demselben Stand wie 'origin/main'.",
+            Phrase::GitAhead
+        ));
+    }
+
+    #[test]
+    fn labels_allow_space_before_colon() {
+        assert_eq!(
+            strip_label("modifié :         src/lib.rs", Phrase::GitModified),
+            Some("src/lib.rs")
+        );
+        assert_eq!(
+            strip_label("neue Datei:      a.rs", Phrase::GitNewFile),
+            Some("a.rs")
+        );
+        assert_eq!(
+            strip_label("Untracked files:", Phrase::GitUntrackedFiles),
+            Some("")
+        );
+        assert_eq!(strip_label("modified files", Phrase::GitModified), None);
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::sync::LazyLock;
 
+pub mod ansible;
 pub mod cargo;
+pub mod deploy;
 pub mod docker;
 pub mod firebase;
 pub mod fs;
@@ -10,12 +12,16 @@ pub mod git;
 pub mod git_extra;
 pub mod golang;
 pub mod jsbuild;
+pub mod locale;
 pub mod npm;
 pub mod php;
+pub mod playwright;
 pub mod python;
+pub mod stacktrace;
 pub mod supabase;
 pub mod testrunners;
 pub mod util;
+pub mod wrangler;
 
 /// A builtin filter function: takes raw output + exit code, returns compressed output.
 pub type BuiltinFilterFn = fn(output: &str, exit_code: i32) -> String;
@@ -30,9 +36,13 @@ static REGISTRY: LazyLock<HashMap<&'static str, BuiltinFilterFn>> = LazyLock::ne
     gh::register(&mut m);
     fs::register(&mut m);
     testrunners::register(&mut m);
+    playwright::register(&mut m);
     jsbuild::register(&mut m);
     docker::register(&mut m);
     firebase::register(&mut m);
+    deploy::register(&mut m);
+    wrangler::register(&mut m);
+    ansible::register(&mut m);
     python::register(&mut m);
     golang::register(&mut m);
     php::register(&mut m);
@@ -75,6 +85,15 @@ mod tests {
         assert!(reg.contains_key("ls"));
         assert!(reg.contains_key("curl"));
         assert!(reg.contains_key("supabase status"));
+        assert!(reg.contains_key("vercel deploy"));
+        assert!(reg.contains_key("netlify deploy"));
+        assert!(reg.contains_key("wrangler deploy"));
+        assert!(reg.contains_key("wrangler dev"));
+        assert!(reg.contains_key("ansible-playbook"));
+        assert!(reg.contains_key("firebase deploy --only functions"));
+        assert!(reg.contains_key("firebase emulators:start"));
+        assert!(reg.contains_key("playwright test --reporter=list"));
+        assert!(reg.contains_key("playwright show-report"));
     }
 
     #[test]
@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::BuiltinFilterFn;
+
+/// Register Playwright list-reporter and report-server handlers.
+///
+/// The default `playwright test` handler lives in `testrunners.rs`; these
+/// keys are more specific so they win for the list reporter variants.
+pub fn register(m: &mut HashMap<&'static str, BuiltinFilterFn>) {
+    m.insert(
+        "playwright test --reporter=list",
+        filter_playwright_list as BuiltinFilterFn,
+    );
+    m.insert(
+        "playwright test --reporter list",
+        filter_playwright_list as BuiltinFilterFn,
+    );
+    m.insert(
+        "playwright show-report",
+        filter_playwright_show_report as BuiltinFilterFn,
+    );
+}
+
+/// A list reporter tick line:
+/// `✓  3 [chromium] › tests/cart.spec.ts:8:7 › cart › adds item (retry #1) (800ms)`.
+static TICK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(✓|✔|✘|×|ok|x|-)\s+\d+\s+(.+?)(?:\s+\(retry #(\d+)\))?(?:\s+\([\d.]+m?s\))?$")
+        .unwrap()
+});
+
+/// Failure block header: `1) [chromium] › tests/login.spec.ts:12:7 › ... ───`.
+static FAIL_HEADER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+\)\s+\[").unwrap());
+
+/// Retry annotation inside a failure block: `Retry #1 ────`.
+static RETRY_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^Retry #(\d+)").unwrap());
+
+/// Final summary counts: `1 failed`, `1 flaky`, `4 passed (12.3s)`.
+static SUMMARY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\d+ (failed|flaky|skipped|passed|did not run|interrupted)").unwrap()
+});
+
+/// The report server address: `Serving HTML report at http://localhost:9323.`
+static REPORT_URL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Serving HTML report at (\S+?)\.?(?:\s|$)").unwrap());
+
+/// Outcome of one test across all its attempts.
+struct TestOutcome {
+    title: String,
+    attempts: usize,
+    failed_attempts: usize,
+    last_passed: bool,
+    last_retry: usize,
+}
+
+/// Returns true for lines worth keeping inside a failure block.
+fn is_failure_detail(trimmed: &str) -> bool {
+    trimmed.starts_with("Error:")
+        || trimmed.starts_with("Expected")
+        || trimmed.starts_with("Received")
+        || trimmed.starts_with("Locator:")
+        || trimmed.starts_with("Timeout")
+        || trimmed.starts_with('>')
+        || (trimmed.starts_with("at ") && !trimmed.contains("node_modules"))
+}
+
+/// Collects failure blocks, folding retries that repeat the previous error.
+#[derive(Default)]
+struct FailureScan {
+    kept: Vec<String>,
+    /// Marker line for the attempt being collected (`Retry #N`), if a retry.
+    marker: Option<String>,
+    attempt: Vec<String>,
+    prev_attempt: Vec<String>,
+}
+
+impl FailureScan {
+    fn commit_attempt(&mut self) {
+        let attempt = std::mem::take(&mut self.attempt);
+        match self.marker.take() {
+            Some(marker) if attempt == self.prev_attempt => {
+                self.kept.push(format!("  {marker}: same error"));
+            }
+            Some(marker) => {
+                self.kept.push(format!("  {marker}"));
+                self.kept.extend(attempt.iter().map(|l| format!("    {l}")));
+            }
+            None => self.kept.extend(attempt.iter().map(|l| format!("  {l}"))),
+        }
+        self.prev_attempt = attempt;
+    }
+
+    fn header(&mut self, trimmed: &str) {
+        self.commit_attempt();
+        self.prev_attempt.clear();
+        self.kept
+            .push(trimmed.trim_end_matches('─').trim_end().to_string());
+    }
+
+    fn retry(&mut self, retry: &str) {
+        self.commit_attempt();
+        self.marker = Some(format!("Retry #{retry}"));
+    }
+}
+
+/// Record a tick line into the per-test outcomes, preserving first-seen order.
+fn record_tick(outcomes: &mut Vec<TestOutcome>, caps: &regex::Captures<'_>) {
+    // Skipped tests only show up in the summary count.
+    if &caps[1] == "-" {
+        return;
+    }
+    let passed = matches!(&caps[1], "✓" | "✔" | "ok");
+    let title = caps[2].to_string();
+    let retry = caps.get(3).map_or(0, |m| m.as_str().parse().unwrap_or(0));
+
+    let outcome = match outcomes.iter_mut().find(|o| o.title == title) {
+        Some(o) => o,
+        None => {
+            outcomes.push(TestOutcome {
+                title,
+                attempts: 0,
+                failed_attempts: 0,
+                last_passed: false,
+                last_retry: 0,
+            });
+            outcomes.last_mut().unwrap()
+        }
+    };
+    outcome.attempts += 1;
+    outcome.failed_attempts += usize::from(!passed);
+    outcome.last_passed = passed;
+    outcome.last_retry = retry;
+}
+
+/// Render failed and flaky tests, one line each. Passing tests are dropped.
+fn render_outcomes(outcomes: &[TestOutcome]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for o in outcomes.iter().filter(|o| o.failed_attempts > 0) {
+        if o.last_passed {
+            lines.push(format!(
+                "⚠ flaky: {} (passed on retry #{})",
+                o.title, o.last_retry
+            ));
+        } else if o.attempts > 1 {
+            lines.push(format!("✘ {} ({} attempts)", o.title, o.attempts));
+        } else {
+            lines.push(format!("✘ {}", o.title));
+        }
+    }
+    lines
+}
+
+/// Filter `playwright test --reporter=list` output.
+///
+/// - Tick lines are parsed per test; repeats from retries are deduped into one
+///   line per test, flagged `⚠ flaky` when a retry passed. Passing tests are dropped.
+/// - Failure blocks keep the error/expectation/location lines and their
+///   `Retry #N` annotations; a retry repeating the previous error is folded
+///   into `Retry #N: same error`.
+/// - The final summary counts are joined into one line.
+pub fn filter_playwright_list(output: &str, exit_code: i32) -> String {
+    let mut outcomes = Vec::new();
+    let mut failures = FailureScan::default();
+    let mut in_failure = false;
+    let mut summary = Vec::new();
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if let Some(caps) = TICK_RE.captures(trimmed) {
+            record_tick(&mut outcomes, &caps);
+        } else if FAIL_HEADER_RE.is_match(trimmed) {
+            failures.header(trimmed);
+            in_failure = true;
+        } else if SUMMARY_RE.is_match(trimmed) {
+            in_failure = false;
+            summary.push(trimmed.to_string());
+        } else if !in_failure {
+            continue;
+        } else if let Some(caps) = RETRY_RE.captures(trimmed) {
+            failures.retry(&caps[1]);
+        } else if is_failure_detail(trimmed) {
+            failures.attempt.push(trimmed.to_string());
+        }
+    }
+    failures.commit_attempt();
+
+    let mut parts = render_outcomes(&outcomes);
+    if exit_code != 0 && !failures.kept.is_empty() {
+        parts.push("Failures:".to_string());
+        parts.extend(failures.kept);
+    }
+    if !summary.is_empty() {
+        parts.push(summary.join(", "));
+    } else if exit_code == 0 {
+        parts.push("All tests passed.".to_string());
+    } else {
+        parts.push(format!("Tests failed (exit code {exit_code})."));
+    }
+    parts.join("\n")
+}
+
+/// Filter `playwright show-report` output.
+///
+/// Keeps the report server URL and any error (e.g. missing report folder);
+/// drops the "Press Ctrl+C to quit" hint.
+pub fn filter_playwright_show_report(output: &str, exit_code: i32) -> String {
+    let mut kept = Vec::new();
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(caps) = REPORT_URL_RE.captures(trimmed) {
+            kept.push(format!("Report: {}", &caps[1]));
+        } else if trimmed.starts_with("Error")
+            || trimmed.contains("No report found")
+            || trimmed.contains("EADDRINUSE")
+        {
+            kept.push(trimmed.to_string());
+        }
+    }
+
+    if kept.is_empty() {
+        return if exit_code == 0 {
+            "Report closed.".to_string()
+        } else {
+            format!("playwright show-report failed (exit code {exit_code}).")
+        };
+    }
+    kept.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST_OUTPUT: &str = "\
+Running 5 tests using 2 workers
+
+  ✓  1 [chromium] › tests/cart.spec.ts:8:7 › cart › adds item (800ms)
+  ✘  2 [chromium] › tests/login.spec.ts:12:7 › login › rejects bad password (5.0s)
+  ✘  3 [chromium] › tests/search.spec.ts:4:7 › search › finds results (2.1s)
+  ✘  4 [chromium] › tests/login.spec.ts:12:7 › login › rejects bad password (retry #1) (5.1s)
+  ✓  5 [chromium] › tests/search.spec.ts:4:7 › search › finds results (retry #1) (1.9s)
+  -  6 [chromium] › tests/cart.spec.ts:20:7 › cart › checkout
+
+  1) [chromium] › tests/login.spec.ts:12:7 › login › rejects bad password ──────────────────────
+
+    Error: Timed out 5000ms waiting for expect(locator).toBeVisible()
+
+    Locator: getByText('Invalid password')
+    Expected: visible
+    Received: <element(s) not found>
+
+      12 |   await page.fill('#password', 'nope');
+    > 13 |   await expect(page.getByText('Invalid password')).toBeVisible();
+         |                                                    ^
+        at /app/tests/login.spec.ts:13:52
+
+    Retry #1 ───────────────────────────────────────────────────────────────────────────────
+
+    Error: Timed out 5000ms waiting for expect(locator).toBeVisible()
+
+    Locator: getByText('Invalid password')
+    Expected: visible
+    Received: <element(s) not found>
+
+    > 13 |   await expect(page.getByText('Invalid password')).toBeVisible();
+        at /app/tests/login.spec.ts:13:52
+
+  2) [chromium] › tests/search.spec.ts:4:7 › search › finds results ─────────────────────────
+
+    Error: expect(received).toBe(expected)
+
+    Expected: 3
+    Received: 0
+
+  1 failed
+    [chromium] › tests/login.spec.ts:12:7 › login › rejects bad password
+  1 flaky
+    [chromium] › tests/search.spec.ts:4:7 › search › finds results
+  1 skipped
+  1 passed (14.2s)";
+
+    #[test]
+    fn list_dedupes_retries_and_flags_flaky() {
+        let result = filter_playwright_list(LIST_OUTPUT, 1);
+        assert!(result.starts_with(
+            "✘ [chromium] › tests/login.spec.ts:12:7 › login › rejects bad password (2 attempts)\n\
+             ⚠ flaky: [chromium] › tests/search.spec.ts:4:7 › search › finds results (passed on retry #1)\n"
+        ));
+        assert!(!result.contains("adds item"), "passing tests dropped");
+        assert!(result.ends_with("1 failed, 1 flaky, 1 skipped, 1 passed (14.2s)"));
+    }
+
+    #[test]
+    fn list_keeps_retry_annotations_and_folds_repeats() {
+        let result = filter_playwright_list(LIST_OUTPUT, 1);
+        assert!(result.contains(
+            "1) [chromium] › tests/login.spec.ts:12:7 › login › rejects bad password\n\
+             \x20 Error: Timed out 5000ms waiting for expect(locator).toBeVisible()\n\
+             \x20 Locator: getByText('Invalid password')"
+        ));
+        assert!(result.contains("  Retry #1: same error"));
+        assert_eq!(result.matches("Timed out 5000ms").count(), 1);
+        assert!(result.contains("  at /app/tests/login.spec.ts:13:52"));
+        assert!(result.contains("2) [chromium] › tests/search.spec.ts:4:7"));
+        assert!(!result.contains("──"));
+    }
+
+    #[test]
+    fn list_retry_with_different_error_kept() {
+        let input = "\
+  ✘  1 [firefox] › a.spec.ts:1:1 › a (1.0s)
+  ✘  2 [firefox] › a.spec.ts:1:1 › a (retry #1) (1.0s)
+
+  1) [firefox] › a.spec.ts:1:1 › a ────
+
+    Error: first failure
+
+    Retry #1 ────
+
+    Error: second failure
+
+  1 failed";
+        let result = filter_playwright_list(input, 1);
+        assert!(result.contains("  Retry #1\n    Error: second failure"));
+    }
+
+    #[test]
+    fn list_all_passed() {
+        let input = "\
+Running 2 tests using 1 worker
+
+  ✓  1 [chromium] › a.spec.ts:1:1 › a (100ms)
+  ✓  2 [chromium] › b.spec.ts:1:1 › b (120ms)
+
+  2 passed (1.1s)";
+        assert_eq!(filter_playwright_list(input, 0), "2 passed (1.1s)");
+    }
+
+    #[test]
+    fn show_report_keeps_url() {
+        let input = "\n  Serving HTML report at http://localhost:9323. Press Ctrl+C to quit.\n";
+        assert_eq!(
+            filter_playwright_show_report(input, 0),
+            "Report: http://localhost:9323"
+        );
+    }
+
+    #[test]
+    fn show_report_missing_folder() {
+        let input = "No report found at \"/app/playwright-report\"";
+        assert_eq!(filter_playwright_show_report(input, 1), input);
+    }
+}
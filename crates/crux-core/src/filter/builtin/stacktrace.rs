@@ -0,0 +1,229 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Maximum number of user (non-framework) frames kept per exception.
+const MAX_USER_FRAMES: usize = 5;
+
+/// A stack frame line: `\tat com.acme.Foo.bar(Foo.java:12)` or
+/// `    at handler (/app/src/index.js:10:5)`.
+static FRAME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s+)at\s+(\S.*(?:\)|:\d+:\d+|<anonymous>))$").unwrap());
+
+/// Java's own elision marker: `\t... 42 more`. It stands for frames shared
+/// with the enclosing trace, not framework code, so it is kept verbatim.
+static MORE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s+)\.\.\. (\d+) more$").unwrap());
+
+/// Frame locations that belong to the JDK, common JVM frameworks or the
+/// Node.js runtime / installed dependencies rather than the user's code.
+const FRAMEWORK_MARKERS: &[&str] = &[
+    "java.",
+    "javax.",
+    "jdk.",
+    "sun.",
+    "com.sun.",
+    "kotlin.",
+    "kotlinx.",
+    "scala.",
+    "org.springframework.",
+    "org.apache.",
+    "org.junit.",
+    "org.gradle.",
+    "org.hibernate.",
+    "org.eclipse.",
+    "io.netty.",
+    "reactor.",
+    "worker.org.gradle.",
+];
+
+/// Returns true if a frame location points into framework/runtime code.
+fn is_framework_frame(location: &str) -> bool {
+    let location = location.strip_prefix("async ").unwrap_or(location);
+    FRAMEWORK_MARKERS.iter().any(|m| location.starts_with(m))
+        || location.contains("node:internal")
+        || location.contains("(internal/")
+        || location.contains("node_modules/")
+        || location.contains("(native)")
+        || location == "<anonymous>"
+}
+
+/// Per-trace compaction state.
+#[derive(Default)]
+struct TraceScan {
+    kept: Vec<String>,
+    user_frames: usize,
+    /// Frames dropped since the last kept line.
+    omitted: usize,
+    /// Indentation of the last frame, reused for the collapse marker.
+    indent: String,
+    /// Set once `MAX_USER_FRAMES` is reached for the current exception.
+    capped: bool,
+    saw_frame: bool,
+}
+
+impl TraceScan {
+    fn flush(&mut self) {
+        if self.omitted > 0 {
+            let label = if self.capped { "more" } else { "framework" };
+            let plural = if self.omitted == 1 { "" } else { "s" };
+            self.kept.push(format!(
+                "{}… {} {label} frame{plural}",
+                self.indent, self.omitted
+            ));
+            self.omitted = 0;
+        }
+    }
+
+    fn frame(&mut self, line: &str, indent: &str, location: &str) {
+        self.saw_frame = true;
+        self.indent = indent.to_string();
+        if self.capped || is_framework_frame(location) {
+            self.omitted += 1;
+            return;
+        }
+        self.flush();
+        self.kept.push(line.trim_end().to_string());
+        self.user_frames += 1;
+        self.capped = self.user_frames >= MAX_USER_FRAMES;
+    }
+
+    fn other(&mut self, line: &str) {
+        self.flush();
+        // Any non-frame line (exception header, `Caused by:`, log output)
+        // starts a fresh frame budget for the next trace.
+        self.user_frames = 0;
+        self.capped = false;
+        self.kept.push(line.to_string());
+    }
+}
+
+/// Compact Java/JVM and Node.js stack traces found anywhere in the output.
+///
+/// Exception headers and messages are kept as-is. For each exception (and
+/// each `Caused by:`), the first 5 frames from the user's code are kept;
+/// runs of JDK/framework/`node_modules` frames collapse into
+/// `… N framework frames`, and frames past the budget into `… N more frames`.
+/// Java's own `... N more` lines are passed through.
+/// Output without stack frames is returned unchanged.
+pub fn filter_stack_traces(output: &str, _exit_code: i32) -> String {
+    let mut scan = TraceScan::default();
+
+    for line in output.lines() {
+        if let Some(caps) = FRAME_RE.captures(line) {
+            scan.frame(line, &caps[1], &caps[2]);
+        } else if MORE_RE.is_match(line) {
+            scan.saw_frame = true;
+            scan.flush();
+            scan.kept.push(line.to_string());
+        } else {
+            scan.other(line);
+        }
+    }
+    scan.flush();
+
+    if !scan.saw_frame {
+        return output.to_string();
+    }
+    scan.kept.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JAVA_TRACE: &str = "\
+Exception in thread \"main\" java.lang.IllegalStateException: order 42 has no items
+\tat com.acme.orders.OrderService.checkout(OrderService.java:88)
+\tat com.acme.orders.OrderController.post(OrderController.java:31)
+\tat java.base/jdk.internal.reflect.NativeMethodAccessorImpl.invoke0(Native Method)
+\tat java.base/java.lang.reflect.Method.invoke(Method.java:580)
+\tat org.springframework.web.method.support.InvocableHandlerMethod.doInvoke(InvocableHandlerMethod.java:255)
+\tat org.springframework.web.servlet.FrameworkServlet.service(FrameworkServlet.java:885)
+\tat com.acme.web.AuthFilter.doFilter(AuthFilter.java:40)
+Caused by: java.lang.NullPointerException: Cannot invoke \"Cart.items()\" because \"cart\" is null
+\tat com.acme.orders.CartRepository.load(CartRepository.java:17)
+\t... 7 more";
+
+    #[test]
+    fn java_trace_collapses_framework_frames() {
+        let result = filter_stack_traces(JAVA_TRACE, 1);
+        assert_eq!(
+            result,
+            "Exception in thread \"main\" java.lang.IllegalStateException: order 42 has no items\n\
+             \tat com.acme.orders.OrderService.checkout(OrderService.java:88)\n\
+             \tat com.acme.orders.OrderController.post(OrderController.java:31)\n\
+             \t… 4 framework frames\n\
+             \tat com.acme.web.AuthFilter.doFilter(AuthFilter.java:40)\n\
+             Caused by: java.lang.NullPointerException: Cannot invoke \"Cart.items()\" because \"cart\" is null\n\
+             \tat com.acme.orders.CartRepository.load(CartRepository.java:17)\n\
+             \t... 7 more"
+        );
+    }
+
+    #[test]
+    fn java_more_marker_follows_collapsed_frames() {
+        let input = "\
+Caused by: java.io.IOException: closed
+\tat com.acme.io.Reader.read(Reader.java:9)
+\tat java.base/java.io.InputStream.read(InputStream.java:218)
+\t... 12 more";
+        assert_eq!(
+            filter_stack_traces(input, 1),
+            "Caused by: java.io.IOException: closed\n\
+             \tat com.acme.io.Reader.read(Reader.java:9)\n\
+             \t… 1 framework frame\n\
+             \t... 12 more"
+        );
+    }
+
+    #[test]
+    fn java_trace_over_budget_collapses_remaining_frames() {
+        let mut input = String::from("java.lang.RuntimeException: boom");
+        for i in 0..8 {
+            input.push_str(&format!("\n\tat com.acme.Deep.level{i}(Deep.java:{i})"));
+        }
+        let result = filter_stack_traces(&input, 1);
+        assert_eq!(result.matches("com.acme.Deep").count(), MAX_USER_FRAMES);
+        assert!(result.ends_with("\t… 3 more frames"));
+    }
+
+    #[test]
+    fn node_trace_collapses_runtime_and_node_modules() {
+        let input = "\
+/app/src/server.js:14
+    const name = req.user.name;
+                          ^
+
+TypeError: Cannot read properties of undefined (reading 'name')
+    at handler (/app/src/server.js:14:27)
+    at Layer.handle [as handle_request] (/app/node_modules/express/lib/router/layer.js:95:5)
+    at next (/app/node_modules/express/lib/router/route.js:149:13)
+    at Module._compile (node:internal/modules/cjs/loader:1256:14)
+
+Node.js v20.11.0";
+        let result = filter_stack_traces(input, 1);
+        assert!(result.starts_with("/app/src/server.js:14\n"));
+        assert!(result.contains("TypeError: Cannot read properties of undefined (reading 'name')"));
+        assert!(
+            result.contains("    at handler (/app/src/server.js:14:27)\n    … 3 framework frames")
+        );
+        assert!(!result.contains("node_modules"));
+        assert!(result.ends_with("Node.js v20.11.0"));
+    }
+
+    #[test]
+    fn output_without_frames_unchanged() {
+        let input = "Compiled successfully\n  at most 3 warnings\n";
+        assert_eq!(filter_stack_traces(input, 0), input);
+    }
+
+    #[test]
+    fn single_framework_frame_singular() {
+        let input = "Error: x\n    at a (/app/a.js:1:1)\n    at b (node:internal/x:1:1)";
+        assert_eq!(
+            filter_stack_traces(input, 1),
+            "Error: x\n    at a (/app/a.js:1:1)\n    … 1 framework frame"
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use super::BuiltinFilterFn;
+
+/// Register Cloudflare wrangler handlers.
+pub fn register(m: &mut HashMap<&'static str, BuiltinFilterFn>) {
+    m.insert("wrangler deploy", filter_wrangler_deploy as BuiltinFilterFn);
+    m.insert(
+        "wrangler publish",
+        filter_wrangler_deploy as BuiltinFilterFn,
+    );
+    m.insert("wrangler dev", filter_wrangler_dev as BuiltinFilterFn);
+}
+
+/// Returns true for wrangler banner/decoration lines (`⛅️ wrangler 3.x`, `----`, box art).
+fn is_decoration(trimmed: &str) -> bool {
+    trimmed.contains("wrangler ") && trimmed.starts_with('⛅')
+        || trimmed.chars().all(|c| c == '-' || c == '─')
+        || trimmed.starts_with('╭')
+        || trimmed.starts_with('╰')
+        || trimmed.starts_with('│')
+}
+
+/// Returns true if the line opens an error or warning block.
+fn is_diagnostic(trimmed: &str) -> bool {
+    trimmed.contains("[ERROR]") || trimmed.contains("[WARNING]") || trimmed.starts_with("Error:")
+}
+
+/// Shared state while scanning wrangler output.
+#[derive(Default)]
+struct WranglerScan {
+    kept: Vec<String>,
+    in_bindings: bool,
+    in_diagnostic: bool,
+}
+
+impl WranglerScan {
+    /// Handle lines common to deploy and dev: bindings and diagnostics.
+    /// Returns true if the line was consumed.
+    fn common(&mut self, line: &str) -> bool {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            self.in_bindings = false;
+            self.in_diagnostic = false;
+            return true;
+        }
+        if is_decoration(trimmed) {
+            return true;
+        }
+        if is_diagnostic(trimmed) {
+            self.in_diagnostic = true;
+            self.kept.push(trimmed.to_string());
+            return true;
+        }
+        if self.in_diagnostic {
+            self.kept.push(format!("  {trimmed}"));
+            return true;
+        }
+        if trimmed.starts_with("Your worker has access to the following bindings") {
+            self.in_bindings = true;
+            self.kept.push("Bindings:".to_string());
+            return true;
+        }
+        if self.in_bindings {
+            if trimmed.starts_with("- ") || line.starts_with(' ') {
+                self.kept.push(format!("  {trimmed}"));
+                return true;
+            }
+            self.in_bindings = false;
+        }
+        false
+    }
+}
+
+/// Filter `wrangler deploy` output.
+///
+/// Keeps the bundle size, bindings, the uploaded/published summary lines with
+/// their routes, and any `[ERROR]`/`[WARNING]` blocks. Drops the banner,
+/// build progress, startup timing and deployment/version IDs.
+pub fn filter_wrangler_deploy(output: &str, exit_code: i32) -> String {
+    let mut scan = WranglerScan::default();
+    let mut in_routes = false;
+
+    for line in output.lines() {
+        if scan.common(line) {
+            in_routes = false;
+            continue;
+        }
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("Total Upload:") {
+            scan.kept.push(trimmed.to_string());
+        } else if trimmed.starts_with("Uploaded ")
+            || trimmed.starts_with("Published ")
+            || trimmed.starts_with("Deployed ")
+        {
+            scan.kept.push(trimmed.to_string());
+            in_routes = true;
+        } else if in_routes && line.starts_with(' ') && !trimmed.contains(' ') {
+            scan.kept.push(format!("  {trimmed}"));
+        } else {
+            in_routes = false;
+        }
+    }
+
+    if scan.kept.is_empty() {
+        return if exit_code == 0 {
+            "Deploy complete.".to_string()
+        } else {
+            format!("wrangler deploy failed (exit code {exit_code}).")
+        };
+    }
+    scan.kept.join("\n")
+}
+
+/// Filter `wrangler dev` output.
+///
+/// Keeps bindings, the first `Ready on http://...` line, errors/warnings and
+/// non-2xx request logs. The repeated reload/ready lines emitted on every file
+/// change and the keyboard-shortcut box are dropped.
+pub fn filter_wrangler_dev(output: &str, exit_code: i32) -> String {
+    let mut scan = WranglerScan::default();
+    let mut ready_seen = false;
+    let mut reloads = 0usize;
+
+    for line in output.lines() {
+        if scan.common(line) {
+            continue;
+        }
+        let trimmed = line.trim();
+
+        if trimmed.contains("Ready on http") {
+            if !ready_seen {
+                let ready = trimmed.trim_start_matches("[wrangler:inf]").trim();
+                scan.kept.push(ready.to_string());
+                ready_seen = true;
+            }
+        } else if trimmed.contains("Reloading local server") {
+            reloads += 1;
+        } else if let Some(request) = trimmed.strip_prefix("[wrangler:inf]") {
+            let request = request.trim();
+            if !is_success_request(request) {
+                scan.kept.push(request.to_string());
+            }
+        }
+    }
+
+    if reloads > 0 {
+        scan.kept.push(format!("(reloaded {reloads}×)"));
+    }
+    if scan.kept.is_empty() && exit_code != 0 {
+        return format!("wrangler dev failed (exit code {exit_code}).");
+    }
+    scan.kept.join("\n")
+}
+
+/// Returns true for `GET /path 2xx/3xx OK (12ms)` request log entries.
+fn is_success_request(request: &str) -> bool {
+    request
+        .split_whitespace()
+        .nth(2)
+        .and_then(|s| s.parse::<u16>().ok())
+        .is_some_and(|status| status < 400)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPLOY_SUCCESS: &str = "\
+ ⛅️ wrangler 3.57.0
+-------------------
+Total Upload: 12.34 KiB / gzip: 3.21 KiB
+Worker Startup Time: 12 ms
+Your worker has access to the following bindings:
+- KV Namespaces:
+  - CACHE: 1234abcd5678
+- Vars:
+  - ENV: \"production\"
+Uploaded my-worker (1.23 sec)
+Deployed my-worker triggers (0.45 sec)
+  https://my-worker.acme.workers.dev
+  example.com/*
+Current Version ID: 0e9f4b3c-1111-2222-3333-444455556666";
+
+    const DEV_OUTPUT: &str = "\
+ ⛅️ wrangler 3.57.0
+-------------------
+Your worker has access to the following bindings:
+- Vars:
+  - ENV: \"dev\"
+⎔ Starting local server...
+[wrangler:inf] Ready on http://localhost:8787
+╭──────────────────────────────────────────────────────────────────────╮
+│  [b] open a browser, [d] open Devtools, [c] clear console, [x] exit  │
+╰──────────────────────────────────────────────────────────────────────╯
+[wrangler:inf] GET / 200 OK (12ms)
+⎔ Reloading local server...
+[wrangler:inf] Ready on http://localhost:8787
+⎔ Reloading local server...
+[wrangler:inf] Ready on http://localhost:8787
+[wrangler:inf] GET /api 500 Internal Server Error (3ms)";
+
+    #[test]
+    fn deploy_keeps_size_bindings_and_routes() {
+        let result = filter_wrangler_deploy(DEPLOY_SUCCESS, 0);
+        assert_eq!(
+            result,
+            "Total Upload: 12.34 KiB / gzip: 3.21 KiB\n\
+             Bindings:\n\
+             \x20 - KV Namespaces:\n\
+             \x20 - CACHE: 1234abcd5678\n\
+             \x20 - Vars:\n\
+             \x20 - ENV: \"production\"\n\
+             Uploaded my-worker (1.23 sec)\n\
+             Deployed my-worker triggers (0.45 sec)\n\
+             \x20 https://my-worker.acme.workers.dev\n\
+             \x20 example.com/*"
+        );
+    }
+
+    #[test]
+    fn deploy_keeps_error_block() {
+        let input = "\
+ ⛅️ wrangler 3.57.0
+-------------------
+✘ [ERROR] Build failed with 1 error:
+  src/index.ts:4:9: ERROR: Could not resolve \"hono\"
+
+";
+        let result = filter_wrangler_deploy(input, 1);
+        assert!(result.contains("✘ [ERROR] Build failed with 1 error:"));
+        assert!(result.contains("src/index.ts:4:9: ERROR: Could not resolve"));
+        assert!(!result.contains("wrangler 3.57.0"));
+    }
+
+    #[test]
+    fn deploy_empty_failure() {
+        assert_eq!(
+            filter_wrangler_deploy("", 1),
+            "wrangler deploy failed (exit code 1)."
+        );
+    }
+
+    #[test]
+    fn dev_drops_repeated_ready_lines() {
+        let result = filter_wrangler_dev(DEV_OUTPUT, 0);
+        assert_eq!(result.matches("Ready on").count(), 1);
+        assert!(result.contains("Ready on http://localhost:8787"));
+        assert!(result.contains("ENV: \"dev\""));
+        assert!(result.contains("GET /api 500 Internal Server Error"));
+        assert!(!result.contains("GET / 200"), "2xx requests are noise");
+        assert!(!result.contains("open Devtools"));
+        assert!(result.ends_with("(reloaded 2×)"));
+    }
+}
@@ -11,6 +11,30 @@ pub fn strip_ansi(input: &str) -> String {
     ANSI_RE.replace_all(input, "").into_owned()
 }
 
+/// A timestamp at the start of a line: ISO 8601 / RFC 3339 dates with a time,
+/// syslog dates (`Jan  2 15:04:05`) or a bare time, optionally in brackets,
+/// plus the separator after it.
+static TIMESTAMP_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?x)^(\s*)\[?(?:
+            \d{4}[-/]\d{2}[-/]\d{2}[T\ ]\d{2}:\d{2}(?::\d{2}(?:[.,]\d+)?)?(?:\ ?(?:Z|UTC|[+-]\d{2}:?\d{2}))?
+          | (?:Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec)\ +\d{1,2}\ \d{2}:\d{2}:\d{2}
+          | \d{2}:\d{2}:\d{2}(?:[.,]\d+)?
+        )\]?(?:\ *[|:-]?\ +|$)",
+    )
+    .unwrap()
+});
+
+/// Remove a leading timestamp from each line, so lines that differ only in
+/// when they were logged can be deduplicated.
+pub fn strip_timestamps(input: &str) -> String {
+    input
+        .lines()
+        .map(|line| TIMESTAMP_RE.replace(line, "$1"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Collapse consecutive blank lines to a single blank line.
 pub fn collapse_blank_lines(input: &str) -> String {
     let mut result = Vec::new();
@@ -76,6 +100,27 @@ mod tests {
         assert_eq!(strip_ansi(input), "red green bg");
     }
 
+    // -- strip_timestamps tests --
+
+    #[test]
+    fn strip_timestamps_removes_common_formats() {
+        let input = "2024-05-01T12:00:03.123Z INFO ready\n\
+                     [2024-05-01 12:00:04] GET /health 200\n\
+                     May  1 12:00:05 host sshd[1]: accepted\n\
+                     12:00:06.5 - tick\n  \
+                     2024/05/01 12:00 +0200 | nested";
+        assert_eq!(
+            strip_timestamps(input),
+            "INFO ready\nGET /health 200\nhost sshd[1]: accepted\ntick\n  nested"
+        );
+    }
+
+    #[test]
+    fn strip_timestamps_leaves_other_lines_alone() {
+        let input = "v1.2.3 released\n2024 was a good year\nport 12:00";
+        assert_eq!(strip_timestamps(input), input);
+    }
+
     // -- collapse_blank_lines tests --
 
     #[test]
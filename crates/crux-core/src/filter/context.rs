@@ -8,6 +8,10 @@ pub struct FilterContext {
     pub exit_code: i32,
     /// Named sections extracted by the `section` stage.
     pub sections: HashMap<String, Vec<String>>,
+    /// Non-blank lines inside each section, without the start/end marker
+    /// lines, accumulated over every occurrence. Backs `{name.count}`,
+    /// `{name.first}`, `{name.last}` and `{name|join:", "}` in templates.
+    pub section_items: HashMap<String, Vec<String>>,
     /// Arbitrary variables for template interpolation.
     pub vars: HashMap<String, String>,
 }
@@ -17,6 +21,7 @@ impl FilterContext {
         Self {
             exit_code,
             sections: HashMap::new(),
+            section_items: HashMap::new(),
             vars: HashMap::new(),
         }
     }
@@ -31,6 +36,7 @@ mod tests {
         let ctx = FilterContext::new(0);
         assert_eq!(ctx.exit_code, 0);
         assert!(ctx.sections.is_empty());
+        assert!(ctx.section_items.is_empty());
         assert!(ctx.vars.is_empty());
     }
 
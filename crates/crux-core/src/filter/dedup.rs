@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 /// Collapse consecutive identical lines into one.
 pub fn apply_dedup(input: &str) -> String {
     let mut result = Vec::new();
@@ -11,6 +13,81 @@ pub fn apply_dedup(input: &str) -> String {
     result.join("\n")
 }
 
+/// Collapse repeated lines anywhere in the output into their first
+/// occurrence, suffixed with ` (×N)`.
+///
+/// Unlike [`apply_dedup`], repeats need not be consecutive. Blank lines are
+/// left alone.
+pub fn apply_collapse_repeats(input: &str) -> String {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line in input.lines().filter(|l| !l.trim().is_empty()) {
+        *counts.entry(line).or_default() += 1;
+    }
+
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut result = Vec::new();
+    for line in input.lines() {
+        if line.trim().is_empty() {
+            result.push(line.to_string());
+            continue;
+        }
+        if !seen.insert(line) {
+            continue;
+        }
+        match counts[line] {
+            1 => result.push(line.to_string()),
+            n => result.push(format!("{line} (×{n})")),
+        }
+    }
+    result.join("\n")
+}
+
+/// Collapse a line that recurs within `window` lines of its previous
+/// occurrence into the first one, suffixed with ` (×N)`.
+///
+/// `window = 1` only catches consecutive repeats. A run of recurrences
+/// shorter than `min_repeat` is left as is. Blank lines are left alone.
+pub fn apply_dedup_window(input: &str, window: usize, min_repeat: usize) -> String {
+    let lines: Vec<&str> = input.lines().collect();
+    // Group each line with the earlier occurrence it repeats, if any.
+    let mut group_of = vec![0; lines.len()];
+    let mut counts: Vec<usize> = Vec::new();
+    let mut last: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let group = match last.get(line) {
+            Some(&(seen, group)) if i - seen <= window.max(1) => {
+                counts[group] += 1;
+                group
+            }
+            _ => {
+                counts.push(1);
+                counts.len() - 1
+            }
+        };
+        group_of[i] = group;
+        last.insert(line, (i, group));
+    }
+
+    let mut emitted: HashSet<usize> = HashSet::new();
+    let mut result = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            result.push(line.to_string());
+            continue;
+        }
+        let count = counts[group_of[i]];
+        if count < min_repeat.max(2) {
+            result.push(line.to_string());
+        } else if emitted.insert(group_of[i]) {
+            result.push(format!("{line} (×{count})"));
+        }
+    }
+    result.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,4 +111,41 @@ mod tests {
     fn all_identical_lines() {
         assert_eq!(apply_dedup("x\nx\nx\nx"), "x");
     }
+
+    #[test]
+    fn collapse_repeats_counts_interleaved_lines() {
+        let input =
+            "warning: deprecated\nbuilding a\nwarning: deprecated\nbuilding b\nwarning: deprecated";
+        assert_eq!(
+            apply_collapse_repeats(input),
+            "warning: deprecated (×3)\nbuilding a\nbuilding b"
+        );
+    }
+
+    #[test]
+    fn dedup_window_catches_repeats_within_lookback() {
+        let input = "tick\nserving\ntick\nserving\ntick\ndone";
+        assert_eq!(apply_dedup_window(input, 1, 2), input);
+        assert_eq!(
+            apply_dedup_window(input, 2, 2),
+            "tick (×3)\nserving (×2)\ndone"
+        );
+    }
+
+    #[test]
+    fn dedup_window_starts_a_new_group_past_the_window() {
+        let input = "a\na\nb\nc\nd\na";
+        assert_eq!(apply_dedup_window(input, 1, 2), "a (×2)\nb\nc\nd\na");
+    }
+
+    #[test]
+    fn dedup_window_honours_min_repeat() {
+        let input = "x\nx\ny\ny\ny";
+        assert_eq!(apply_dedup_window(input, 1, 3), "x\nx\ny (×3)");
+    }
+
+    #[test]
+    fn collapse_repeats_keeps_blank_lines() {
+        assert_eq!(apply_collapse_repeats("a\n\nb\n\na"), "a (×2)\n\nb\n");
+    }
 }
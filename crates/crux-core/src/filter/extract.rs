@@ -2,7 +2,11 @@ use regex::Regex;
 
 use crate::config::types::ExtractRule;
 
-/// First-match regex extraction with optional template interpolation.
+/// Regex extraction with optional template interpolation.
+///
+/// By default the first matching line of the first matching rule wins. A rule
+/// with `all = true` instead renders its template once per matching line
+/// (capped at `max`, with a `… N more matches` marker).
 ///
 /// Returns `Some(result)` if any rule matches a line, `None` otherwise.
 pub fn apply_extract(input: &str, rules: &[ExtractRule]) -> Option<String> {
@@ -11,20 +15,54 @@ pub fn apply_extract(input: &str, rules: &[ExtractRule]) -> Option<String> {
             Ok(r) => r,
             Err(_) => continue,
         };
+        if rule.all == Some(true) {
+            if let Some(result) = extract_all(input, &re, rule) {
+                return Some(result);
+            }
+            continue;
+        }
         for line in input.lines() {
             if let Some(caps) = re.captures(line) {
-                return Some(match &rule.template {
-                    Some(tmpl) => interpolate(tmpl, &caps),
-                    None => line.to_string(),
-                });
+                return Some(render(line, &re, &caps, rule));
             }
         }
     }
     None
 }
 
-fn interpolate(template: &str, caps: &regex::Captures) -> String {
+/// Render every matching line for an `all = true` rule.
+fn extract_all(input: &str, re: &Regex, rule: &ExtractRule) -> Option<String> {
+    let matches: Vec<String> = input
+        .lines()
+        .filter_map(|line| re.captures(line).map(|caps| render(line, re, &caps, rule)))
+        .collect();
+    if matches.is_empty() {
+        return None;
+    }
+
+    let cap = rule.max.unwrap_or(usize::MAX);
+    let mut kept: Vec<String> = matches.iter().take(cap).cloned().collect();
+    if matches.len() > cap {
+        kept.push(format!("… {} more matches", matches.len() - cap));
+    }
+    Some(kept.join("\n"))
+}
+
+fn render(line: &str, re: &Regex, caps: &regex::Captures, rule: &ExtractRule) -> String {
+    match &rule.template {
+        Some(tmpl) => interpolate(tmpl, re, caps),
+        None => line.to_string(),
+    }
+}
+
+/// Substitute `{name}` (named groups) and `{N}` (positional groups) in a template.
+fn interpolate(template: &str, re: &Regex, caps: &regex::Captures) -> String {
     let mut result = template.to_string();
+    for name in re.capture_names().flatten() {
+        if let Some(m) = caps.name(name) {
+            result = result.replace(&format!("{{{name}}}"), m.as_str());
+        }
+    }
     // Replace in reverse order so `{10}` is replaced before `{1}`.
     for i in (1..caps.len()).rev() {
         if let Some(m) = caps.get(i) {
@@ -42,6 +80,16 @@ mod tests {
         ExtractRule {
             pattern: pattern.to_string(),
             template: template.map(String::from),
+            all: None,
+            max: None,
+        }
+    }
+
+    fn rule_all(pattern: &str, template: Option<&str>, max: Option<usize>) -> ExtractRule {
+        ExtractRule {
+            all: Some(true),
+            max,
+            ..rule(pattern, template)
         }
     }
 
@@ -83,4 +131,55 @@ mod tests {
         let rules = [rule(r"host=(\S+) req=(\S+)", Some("{1} took {2}"))];
         assert_eq!(apply_extract(input, &rules), Some("web took 42ms".into()));
     }
+
+    #[test]
+    fn all_renders_every_match() {
+        let input = "src/a.rs:3: error: x\nok\nsrc/b.rs:9: error: y";
+        let rules = [rule_all(
+            r"^(\S+):(\d+): error: (.*)",
+            Some("{1}@{2} {3}"),
+            None,
+        )];
+        assert_eq!(
+            apply_extract(input, &rules),
+            Some("src/a.rs@3 x\nsrc/b.rs@9 y".into())
+        );
+    }
+
+    #[test]
+    fn all_capped_with_marker() {
+        let input = "E1\nE2\nE3\nE4";
+        let rules = [rule_all(r"^E\d", None, Some(2))];
+        assert_eq!(
+            apply_extract(input, &rules),
+            Some("E1\nE2\n… 2 more matches".into())
+        );
+    }
+
+    #[test]
+    fn all_without_match_falls_through_to_next_rule() {
+        let input = "warning: w";
+        let rules = [
+            rule_all(r"^error", None, None),
+            rule(r"^warning", Some("W")),
+        ];
+        assert_eq!(apply_extract(input, &rules), Some("W".into()));
+    }
+
+    #[test]
+    fn template_with_named_captures() {
+        let input = "2026-02-28 host=web req=42ms";
+        let rules = [rule(
+            r"host=(?P<host>\S+) req=(?P<latency>\S+)",
+            Some("{host} took {latency}"),
+        )];
+        assert_eq!(apply_extract(input, &rules), Some("web took 42ms".into()));
+    }
+
+    #[test]
+    fn named_and_positional_mixed() {
+        let input = "user=alice id=7";
+        let rules = [rule(r"user=(?P<user>\w+) id=(\d+)", Some("{user}#{2}"))];
+        assert_eq!(apply_extract(input, &rules), Some("alice#7".into()));
+    }
 }
@@ -0,0 +1,158 @@
+use serde_json::Value;
+
+/// One step of a JSON path: `.key`, `[0]` or `[]`.
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Iter,
+}
+
+/// Parse a jq-style path (`.items[].name`, `.status`, `.[0]`, `.["odd key"]`).
+/// Returns `None` for malformed paths.
+fn parse_path(path: &str) -> Option<Vec<Segment>> {
+    let mut rest = path.trim().strip_prefix('.')?;
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let inner = after[..end].trim();
+            segments.push(if inner.is_empty() {
+                Segment::Iter
+            } else if let Some(key) = inner.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+                Segment::Key(key.to_string())
+            } else {
+                Segment::Index(inner.parse().ok()?)
+            });
+            rest = &after[end + 1..];
+        } else {
+            rest = rest.strip_prefix('.').unwrap_or(rest);
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            if end > 0 {
+                segments.push(Segment::Key(rest[..end].to_string()));
+            }
+            rest = &rest[end..];
+        }
+    }
+    Some(segments)
+}
+
+/// Collect every value reached by `segments`. Missing keys yield nothing.
+fn select<'a>(value: &'a Value, segments: &[Segment]) -> Vec<&'a Value> {
+    let Some((first, rest)) = segments.split_first() else {
+        return vec![value];
+    };
+    let next: Vec<&Value> = match (first, value) {
+        (Segment::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+        (Segment::Index(i), Value::Array(items)) => items.get(*i).into_iter().collect(),
+        (Segment::Iter, Value::Array(items)) => items.iter().collect(),
+        (Segment::Iter, Value::Object(map)) => map.values().collect(),
+        _ => Vec::new(),
+    };
+    next.into_iter().flat_map(|v| select(v, rest)).collect()
+}
+
+/// Render a selected value: strings raw (like `jq -r`), everything else as compact JSON.
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Parse the output as JSON and emit the values at `paths`, one per line, in
+/// path order (`jq -r '.a, .b[]'` semantics).
+///
+/// Output that is not valid JSON (e.g. an error message) is returned unchanged.
+/// Malformed paths are skipped.
+pub fn apply_json_paths(input: &str, paths: &[String]) -> String {
+    let Ok(root) = serde_json::from_str::<Value>(input.trim()) else {
+        return input.to_string();
+    };
+
+    paths
+        .iter()
+        .filter_map(|p| parse_path(p))
+        .flat_map(|segments| select(&root, &segments).into_iter().map(render))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    const DOC: &str = r#"{
+        "status": "ok",
+        "count": 2,
+        "items": [
+            {"name": "web", "replicas": 3, "labels": {"tier": "frontend"}},
+            {"name": "worker", "replicas": 1, "labels": {"tier": "backend"}}
+        ],
+        "meta": {"odd key": true}
+    }"#;
+
+    #[test]
+    fn parse_path_segments() {
+        assert_eq!(
+            parse_path(".items[].name"),
+            Some(vec![
+                Segment::Key("items".into()),
+                Segment::Iter,
+                Segment::Key("name".into())
+            ])
+        );
+        assert_eq!(parse_path("."), Some(vec![]));
+        assert_eq!(parse_path(".[0]"), Some(vec![Segment::Index(0)]));
+        assert_eq!(parse_path("items"), None);
+        assert_eq!(parse_path(".items[x]"), None);
+    }
+
+    #[test]
+    fn scalar_paths() {
+        assert_eq!(
+            apply_json_paths(DOC, &paths(&[".status", ".count"])),
+            "ok\n2"
+        );
+    }
+
+    #[test]
+    fn iterate_array() {
+        assert_eq!(
+            apply_json_paths(DOC, &paths(&[".items[].name"])),
+            "web\nworker"
+        );
+    }
+
+    #[test]
+    fn index_and_nested_object() {
+        assert_eq!(
+            apply_json_paths(DOC, &paths(&[".items[1].labels"])),
+            r#"{"tier":"backend"}"#
+        );
+    }
+
+    #[test]
+    fn quoted_key() {
+        assert_eq!(
+            apply_json_paths(DOC, &paths(&[r#".meta["odd key"]"#])),
+            "true"
+        );
+    }
+
+    #[test]
+    fn missing_path_emits_nothing() {
+        assert_eq!(apply_json_paths(DOC, &paths(&[".nope", ".items[9]"])), "");
+    }
+
+    #[test]
+    fn non_json_passthrough() {
+        let input = "Error: unauthorized";
+        assert_eq!(apply_json_paths(input, &paths(&[".status"])), input);
+    }
+}
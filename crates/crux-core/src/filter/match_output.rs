@@ -1,19 +1,65 @@
+use regex::Regex;
+
 use crate::config::types::MatchOutputRule;
 
-pub fn apply_match_output(input: &str, rules: &[MatchOutputRule]) -> Option<String> {
-    rules
-        .iter()
-        .find(|r| input.contains(&r.contains))
-        .map(|r| r.template.clone().unwrap_or_else(|| r.contains.clone()))
+/// Return the output of the first rule that matches, or `None`.
+///
+/// A rule matches when its `exit_code` condition (if any) holds and its
+/// `contains` substring and/or `regex` are found in the input. Without a
+/// template, the matched text is returned. Rules with neither `contains`
+/// nor a valid `regex` never match.
+pub fn apply_match_output(
+    input: &str,
+    rules: &[MatchOutputRule],
+    exit_code: i32,
+) -> Option<String> {
+    rules.iter().find_map(|r| match_rule(input, r, exit_code))
+}
+
+fn match_rule(input: &str, rule: &MatchOutputRule, exit_code: i32) -> Option<String> {
+    if rule
+        .exit_code
+        .as_ref()
+        .is_some_and(|c| !c.matches(exit_code))
+    {
+        return None;
+    }
+
+    let mut matched = None;
+    if let Some(ref needle) = rule.contains {
+        if !input.contains(needle.as_str()) {
+            return None;
+        }
+        matched = Some(needle.clone());
+    }
+    if let Some(ref pattern) = rule.regex {
+        let m = Regex::new(pattern).ok()?.find(input)?;
+        matched = Some(m.as_str().to_string());
+    }
+
+    let matched = matched?;
+    Some(rule.template.clone().unwrap_or(matched))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::types::ExitCodeCondition;
 
     fn rule(contains: &str, template: Option<&str>) -> MatchOutputRule {
         MatchOutputRule {
-            contains: contains.to_string(),
+            contains: Some(contains.to_string()),
+            regex: None,
+            exit_code: None,
+            template: template.map(String::from),
+        }
+    }
+
+    fn regex_rule(pattern: &str, template: Option<&str>) -> MatchOutputRule {
+        MatchOutputRule {
+            contains: None,
+            regex: Some(pattern.to_string()),
+            exit_code: None,
             template: template.map(String::from),
         }
     }
@@ -22,7 +68,7 @@ mod tests {
     fn match_with_template_returns_template() {
         let rules = vec![rule("error", Some("Build failed"))];
         assert_eq!(
-            apply_match_output("compile error found", &rules),
+            apply_match_output("compile error found", &rules, 1),
             Some("Build failed".into())
         );
     }
@@ -31,7 +77,7 @@ mod tests {
     fn match_without_template_returns_contains() {
         let rules = vec![rule("SUCCESS", None)];
         assert_eq!(
-            apply_match_output("BUILD SUCCESS done", &rules),
+            apply_match_output("BUILD SUCCESS done", &rules, 0),
             Some("SUCCESS".into())
         );
     }
@@ -39,15 +85,54 @@ mod tests {
     #[test]
     fn no_match_returns_none() {
         let rules = vec![rule("error", Some("bad"))];
-        assert_eq!(apply_match_output("all good", &rules), None);
+        assert_eq!(apply_match_output("all good", &rules, 0), None);
     }
 
     #[test]
     fn first_match_wins() {
         let rules = vec![rule("warn", Some("Warning")), rule("err", Some("Error"))];
         assert_eq!(
-            apply_match_output("err and warn", &rules),
+            apply_match_output("err and warn", &rules, 0),
             Some("Warning".into())
         );
     }
+
+    #[test]
+    fn regex_without_template_returns_match() {
+        let rules = vec![regex_rule(r"error TS\d+", None)];
+        assert_eq!(
+            apply_match_output("src/a.ts(3,1): error TS2304: Cannot find name", &rules, 2),
+            Some("error TS2304".into())
+        );
+    }
+
+    #[test]
+    fn contains_and_regex_must_both_match() {
+        let mut r = regex_rule(r"\d+ failed", Some("tests failed"));
+        r.contains = Some("pytest".to_string());
+        let rules = vec![r];
+        assert_eq!(apply_match_output("3 failed", &rules, 1), None);
+        assert_eq!(
+            apply_match_output("pytest: 3 failed", &rules, 1),
+            Some("tests failed".into())
+        );
+    }
+
+    #[test]
+    fn exit_code_condition_gates_rule() {
+        let mut r = regex_rule(r"error TS\d+", Some("type errors"));
+        r.exit_code = Some(ExitCodeCondition::Named("nonzero".to_string()));
+        let rules = vec![r];
+        assert_eq!(apply_match_output("error TS1005", &rules, 0), None);
+        assert_eq!(
+            apply_match_output("error TS1005", &rules, 2),
+            Some("type errors".into())
+        );
+    }
+
+    #[test]
+    fn invalid_regex_never_matches() {
+        let rules = vec![regex_rule("(", Some("x")), rule("ok", Some("fallback"))];
+        assert_eq!(apply_match_output("ok", &rules, 0), Some("fallback".into()));
+    }
 }
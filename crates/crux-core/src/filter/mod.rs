@@ -3,123 +3,330 @@ pub mod cleanup;
 pub mod context;
 pub mod dedup;
 pub mod extract;
+pub mod json;
 #[cfg(feature = "lua")]
 pub mod lua;
 pub mod match_output;
+pub mod paths;
 pub mod replace;
 pub mod section;
+mod setup;
 pub mod skip;
+pub mod stage;
+pub mod table;
 pub mod tee;
 pub mod template;
+pub mod trace;
+pub mod truncate;
 pub mod universal;
 pub mod variant;
 
+use std::ops::ControlFlow;
+
 use crate::config::FilterConfig;
+use stage::Stage;
+use trace::{StageTrace, Tracer};
 
 /// Apply a full filter pipeline to command output.
 ///
+/// The `[profile.<name>]` block for the active profile (`CRUX_PROFILE`) and
+/// the `[on_success]`/`[on_failure]` block matching `exit_code` are merged
+/// into the config first, so their rules take part in the stages below. Filters
+/// named in `pipeline` then run in order, and their output is what the stages
+/// below see.
+///
 /// Pipeline order:
-///  0. `universal::pre_filter` — strip ANSI, remove progress bars (always)
-///  1. `match_output` — short-circuit if output contains substring
-///  2. Builtin — short-circuit if registered handler exists
+///  0. `universal::pre_filter` — strip ANSI (unless `strip_ansi = false`),
+///     remove progress bars (always)
+///  1. `match_output` — short-circuit if output contains substring / matches regex
+///  2. Builtin — short-circuit if registered handler exists (with `post_process`
+///     or `extends`, the handler's output continues through the stages below)
 ///  3. Lua — short-circuit if returns Some (feature-gated)
 ///  4. `strip_ansi` — remove ANSI escape codes
-///  5. `replace` — regex substitution
-///  6. `skip`/`keep` — line filtering
-///  7. `section` — collect sections into context
-///  8. `extract` — first regex match → template
-///  9. `dedup` — collapse consecutive duplicate lines
-/// 10. `template` — render with context vars/sections
-/// 11. `trim_trailing_whitespace`
-/// 12. `collapse_blank_lines`
-/// 13. `universal::post_filter` — collapse blanks, remove hints/notes (always)
+///  5. `json_paths` — parse JSON output, emit only the selected paths
+///  6. `relativize_paths` — rewrite absolute workspace paths to relative ones
+///  7. `replace` — regex substitution
+///  8. `skip`/`keep` — line filtering (`keep_context` keeps lines around matches)
+///  9. `table` — keep only the listed columns of fixed-width tables
+/// 10. `section` — collect sections into context
+/// 11. `extract` — first regex match → template
+/// 12. `dedup` — collapse consecutive duplicate lines
+/// 13. `collapse_repeats` — collapse repeated lines anywhere into `line (×N)`
+/// 14. `template` — render with context vars/sections
+/// 15. `trim_trailing_whitespace`
+/// 16. `collapse_blank_lines`
+/// 17. `max_line_width` — cut long lines, keeping ANSI styling balanced
+/// 18. `max_lines`/`head`/`tail` — cap output with an omission marker
+/// 19. `max_tokens` — drop lines from the middle to fit an estimated token budget
+/// 20. `universal::post_filter` — collapse blanks, remove hints/notes (always)
+///
+/// The text stages, `strip_ansi` through `max_tokens`, can be reordered per
+/// filter with `stage_order` (see [`stage::ordered`]). Finally, `max_bytes`
+/// caps the result however it was produced (including short-circuits).
 pub fn apply_filter(config: &FilterConfig, output: &str, exit_code: i32) -> String {
-    // 0. Universal pre-filter (ANSI strip, progress bar removal)
-    let output = universal::pre_filter(output);
+    apply_filter_nested(config, output, exit_code, 0, &mut Tracer::off())
+}
 
-    // 1. match_output — short-circuit on substring match
-    if !config.match_output.is_empty() {
-        if let Some(result) = match_output::apply_match_output(&output, &config.match_output) {
-            return universal::post_filter(&result);
+/// Like [`apply_filter`], also returning a trace of every step that ran:
+/// lines and bytes before and after, and which of its rules matched.
+pub fn explain_filter(
+    config: &FilterConfig,
+    output: &str,
+    exit_code: i32,
+) -> (String, Vec<StageTrace>) {
+    let mut tracer = Tracer::on();
+    let result = apply_filter_nested(config, output, exit_code, 0, &mut tracer);
+    (result, tracer.finish())
+}
+
+/// Like [`apply_filter`], also returning how many bytes each step removed,
+/// in pipeline order. Steps that removed nothing are left out; a step that
+/// grew the output reports a negative count.
+pub fn apply_filter_with_savings(
+    config: &FilterConfig,
+    output: &str,
+    exit_code: i32,
+) -> (String, Vec<(String, i64)>) {
+    let mut tracer = Tracer::bytes_only();
+    let result = apply_filter_nested(config, output, exit_code, 0, &mut tracer);
+    let savings = tracer
+        .finish()
+        .into_iter()
+        .map(|step| {
+            let removed = step.bytes_before as i64 - step.bytes_after as i64;
+            (step.stage, removed)
+        })
+        .filter(|(_, removed)| *removed != 0)
+        .collect();
+    (result, savings)
+}
+
+fn apply_filter_nested(
+    config: &FilterConfig,
+    output: &str,
+    exit_code: i32,
+    depth: usize,
+    tracer: &mut Tracer,
+) -> String {
+    let config = setup::merge_blocks(config, output, exit_code, tracer);
+    let result = run_stages(&config, output, exit_code, depth, tracer);
+
+    // Final safety cap, applied whichever stage produced the output. It reads
+    // the merged config, so profile and exit-code blocks can set it too.
+    match config.max_bytes {
+        Some(max_bytes) => {
+            let capped = truncate::apply_max_bytes(&result, max_bytes);
+            tracer.record("max_bytes", &result, &capped, Vec::new);
+            capped
         }
+        None => result,
     }
+}
 
-    // 2. Builtin — short-circuit if registered (unless disabled)
-    if config.builtin != Some(false) {
-        if let Some(builtin_fn) = builtin::registry().get(config.command.as_str()) {
-            return universal::post_filter(&builtin_fn(&output, exit_code));
-        }
+fn run_stages(
+    config: &FilterConfig,
+    output: &str,
+    exit_code: i32,
+    depth: usize,
+    tracer: &mut Tracer,
+) -> String {
+    // Chained filters run first; their output feeds this filter's stages
+    let output = setup::chain_pipeline(config, output, exit_code, depth, tracer);
+
+    // 0. Universal pre-filter (ANSI strip, progress bar removal)
+    let output = setup::pre_filter(config, &output, tracer);
+
+    // 1. match_output — short-circuit on substring/regex match
+    if let Some(result) = setup::try_match_output(config, &output, exit_code, tracer) {
+        return post_filter(&result, tracer);
     }
 
+    // 2. Builtin — short-circuit if registered (unless disabled). With
+    // `post_process`, or a filter that `extends` a builtin, the builtin's
+    // output continues through the stages below.
+    let output = match setup::dispatch_builtin(config, output, exit_code, tracer) {
+        ControlFlow::Break(result) => return post_filter(&result, tracer),
+        ControlFlow::Continue(output) => output,
+    };
+
     // 3. Lua escape hatch — short-circuit if returns Some
     #[cfg(feature = "lua")]
-    {
-        if let Some(ref lua_config) = config.lua {
-            let lua_result = if let Some(ref source) = lua_config.source {
-                lua::apply_lua(source, &output, exit_code, &[])
-            } else if let Some(ref file) = lua_config.file {
-                lua::apply_lua_file(file, &output, exit_code, &[])
-            } else {
-                None
-            };
-            if let Some(result) = lua_result {
-                return universal::post_filter(&result);
-            }
-        }
+    if let Some(result) = setup::try_lua(config, &output, exit_code, tracer) {
+        return post_filter(&result, tracer);
     }
 
+    let result = run_text_stages(config, output, exit_code, tracer);
+
+    // 20. Universal post-filter (collapse blanks, remove hints/notes)
+    post_filter(&result, tracer)
+}
+
+/// Stages 4-19, in the filter's `stage_order`.
+fn run_text_stages(
+    config: &FilterConfig,
+    output: String,
+    exit_code: i32,
+    tracer: &mut Tracer,
+) -> String {
     let mut result = output;
     let mut ctx = context::FilterContext::new(exit_code);
 
-    // 4. Strip ANSI escape codes
-    if config.strip_ansi == Some(true) {
-        result = cleanup::strip_ansi(&result);
+    for stage in stage::ordered(&config.stage_order) {
+        let before = (tracer.is_on() && stage.is_configured(config)).then(|| result.clone());
+        result = apply_stage(stage, config, result, &mut ctx);
+        if let Some(before) = before {
+            tracer.record(stage.name(), &before, &result, || {
+                trace::stage_rules(config, stage, &before)
+            });
+        }
     }
+    result
+}
 
-    // 5. Regex replacement
-    if !config.replace.is_empty() {
-        result = replace::apply_replace(&result, &config.replace);
-    }
+/// Run one text stage, or return `result` unchanged if the filter doesn't
+/// configure it.
+fn apply_stage(
+    stage: Stage,
+    config: &FilterConfig,
+    result: String,
+    ctx: &mut context::FilterContext,
+) -> String {
+    match stage {
+        // 4. Strip ANSI escape codes
+        Stage::StripAnsi if config.strip_ansi == Some(true) => cleanup::strip_ansi(&result),
 
-    // 6. Skip/keep line filtering
-    if !config.skip.is_empty() || !config.keep.is_empty() {
-        result = skip::apply_skip_keep(&result, &config.skip, &config.keep);
-    }
+        // 5. JSON path selection
+        Stage::JsonPaths if !config.json_paths.is_empty() => {
+            json::apply_json_paths(&result, &config.json_paths)
+        }
 
-    // 7. Section extraction
-    if !config.section.is_empty() {
-        result = section::apply_sections(&result, &config.section, &mut ctx);
-    }
+        // 6. Relativize absolute workspace paths
+        Stage::RelativizePaths if config.relativize_paths == Some(true) => {
+            match std::env::current_dir() {
+                Ok(cwd) => paths::apply_relativize_paths(&result, &cwd),
+                Err(_) => result,
+            }
+        }
 
-    // 8. Extract — first regex match → template (short-circuits remaining text stages)
-    if !config.extract.is_empty() {
-        if let Some(extracted) = extract::apply_extract(&result, &config.extract) {
-            result = extracted;
+        // 7. Regex replacement
+        Stage::Replace if !config.replace.is_empty() => {
+            replace::apply_replace(&result, &config.replace)
         }
-    }
 
-    // 9. Dedup consecutive identical lines
-    if config.dedup == Some(true) {
-        result = dedup::apply_dedup(&result);
-    }
+        // 8. Skip/keep line filtering
+        Stage::SkipKeep if !config.skip.is_empty() || !config.keep.is_empty() => {
+            match config.keep_context {
+                Some(context) => {
+                    skip::apply_keep_context(&result, &config.skip, &config.keep, context)
+                }
+                None => skip::apply_skip_keep(&result, &config.skip, &config.keep),
+            }
+        }
+
+        // 9. Table column selection
+        Stage::Table => match config.table {
+            Some(ref table) if !table.keep_columns.is_empty() => {
+                table::apply_keep_columns(&result, &table.keep_columns)
+            }
+            _ => result,
+        },
+
+        // 10. Section extraction
+        Stage::Section if !config.section.is_empty() => {
+            section::apply_sections(&result, &config.section, ctx)
+        }
 
-    // 10. Template interpolation
-    if let Some(ref tmpl) = config.template {
-        result = template::apply_template(tmpl, &ctx);
+        // 11. Extract — first regex match → template
+        Stage::Extract if !config.extract.is_empty() => {
+            extract::apply_extract(&result, &config.extract).unwrap_or(result)
+        }
+
+        _ => apply_shaping_stage(stage, config, result, ctx),
     }
+}
+
+/// The stages from `dedup` on, which shape and cap the selected lines.
+fn apply_shaping_stage(
+    stage: Stage,
+    config: &FilterConfig,
+    result: String,
+    ctx: &context::FilterContext,
+) -> String {
+    match stage {
+        // 12. Dedup consecutive identical lines
+        Stage::Dedup if config.dedup == Some(true) => dedup::apply_dedup(&result),
+
+        // 13. Collapse repeated lines across the whole output
+        Stage::CollapseRepeats if config.collapse_repeats == Some(true) => {
+            dedup::apply_collapse_repeats(&result)
+        }
+
+        // 14. Template interpolation
+        Stage::Template => match config.template {
+            Some(ref tmpl) => template::apply_template(tmpl, ctx),
+            None => result,
+        },
+
+        // 15. Trim trailing whitespace
+        Stage::TrimTrailingWhitespace if config.trim_trailing_whitespace == Some(true) => {
+            cleanup::trim_trailing_whitespace(&result)
+        }
+
+        // 16. Collapse blank lines
+        Stage::CollapseBlankLines if config.collapse_blank_lines == Some(true) => {
+            cleanup::collapse_blank_lines(&result)
+        }
 
-    // 11. Trim trailing whitespace
-    if config.trim_trailing_whitespace == Some(true) {
-        result = cleanup::trim_trailing_whitespace(&result);
+        // 17. Long-line truncation
+        Stage::MaxLineWidth => match config.max_line_width {
+            Some(width) => truncate::apply_max_line_width(&result, width),
+            None => result,
+        },
+
+        // 18. Head/tail truncation
+        Stage::Truncate
+            if config.max_lines.is_some() || config.head.is_some() || config.tail.is_some() =>
+        {
+            truncate::apply_truncate(&result, config.max_lines, config.head, config.tail)
+        }
+
+        // 19. Token budget
+        Stage::MaxTokens => match config.max_tokens {
+            Some(max_tokens) => truncate::apply_max_tokens(&result, max_tokens),
+            None => result,
+        },
+
+        _ => result,
     }
+}
 
-    // 12. Collapse blank lines
-    if config.collapse_blank_lines == Some(true) {
-        result = cleanup::collapse_blank_lines(&result);
+fn post_filter(output: &str, tracer: &mut Tracer) -> String {
+    let result = universal::post_filter(output);
+    tracer.record("post_filter", output, &result, Vec::new);
+    result
+}
+
+/// True unless the filter sets `min_savings_pct` and `filtered` saves less
+/// than that share of `raw` (output larger than the input never qualifies).
+///
+/// Callers show the raw output instead when this returns false.
+pub fn meets_min_savings(config: &FilterConfig, raw: &str, filtered: &str) -> bool {
+    let Some(min_pct) = config.min_savings_pct else {
+        return true;
+    };
+    if raw.is_empty() {
+        return true;
     }
+    let saved = raw.len() as f64 - filtered.len() as f64;
+    saved >= 0.0 && saved / raw.len() as f64 * 100.0 >= min_pct
+}
 
-    // 13. Universal post-filter (collapse blanks, remove hints/notes)
-    universal::post_filter(&result)
+/// Fallback for commands with no matching filter.
+///
+/// Compacts JVM/Node stack traces found anywhere in the output; everything
+/// else passes through unchanged.
+pub fn apply_fallback(output: &str, exit_code: i32) -> String {
+    builtin::stacktrace::filter_stack_traces(output, exit_code)
 }
 
 #[cfg(test)]
@@ -144,18 +351,6 @@ mod tests {
         assert_eq!(result, "error: bad\nwarning: ok");
     }
 
-    #[test]
-    fn apply_filter_strip_ansi_and_collapse() {
-        let config = FilterConfig {
-            strip_ansi: Some(true),
-            collapse_blank_lines: Some(true),
-            ..Default::default()
-        };
-        let input = "\x1b[31merror\x1b[0m\n\n\n\nok";
-        let result = apply_filter(&config, input, 0);
-        assert_eq!(result, "error\n\nok");
-    }
-
     #[test]
     fn apply_filter_full_pipeline() {
         let config = FilterConfig {
@@ -170,31 +365,6 @@ mod tests {
         assert_eq!(result, "error\n\nwarning");
     }
 
-    #[test]
-    fn apply_filter_builtin_git_status() {
-        let config = FilterConfig {
-            command: "git status".to_string(),
-            ..Default::default()
-        };
-        let output = "On branch main\n\nChanges:\n  (use hint)\n\tM  src/lib.rs";
-        let result = apply_filter(&config, output, 0);
-        assert!(result.contains("On branch main"));
-        assert!(result.contains("M  src/lib.rs"));
-    }
-
-    #[test]
-    fn apply_filter_builtin_disabled() {
-        let config = FilterConfig {
-            command: "git status".to_string(),
-            builtin: Some(false),
-            ..Default::default()
-        };
-        let output = "On branch main\nsome hint line";
-        let result = apply_filter(&config, output, 0);
-        // Builtin disabled, no TOML pipeline configured, so passthrough
-        assert_eq!(result, output);
-    }
-
     #[test]
     fn apply_filter_toml_pipeline_with_all_stages() {
         let config = FilterConfig {
@@ -234,23 +404,6 @@ mod tests {
         assert_eq!(result, "important line");
     }
 
-    #[test]
-    fn apply_filter_match_output_short_circuits() {
-        use crate::config::types::MatchOutputRule;
-        let config = FilterConfig {
-            command: "custom".to_string(),
-            match_output: vec![MatchOutputRule {
-                contains: "FATAL".to_string(),
-                template: Some("Build crashed!".to_string()),
-            }],
-            skip: vec!["^".to_string()], // Would remove everything, but match_output fires first
-            ..Default::default()
-        };
-        let output = "line1\nFATAL error\nline3";
-        let result = apply_filter(&config, output, 1);
-        assert_eq!(result, "Build crashed!");
-    }
-
     #[test]
     fn apply_filter_replace_stage() {
         use crate::config::types::ReplaceRule;
@@ -287,6 +440,8 @@ mod tests {
             extract: vec![ExtractRule {
                 pattern: r"result: (\w+)".to_string(),
                 template: Some("Status: {1}".to_string()),
+                all: None,
+                max: None,
             }],
             ..Default::default()
         };
@@ -315,4 +470,177 @@ mod tests {
         let result = apply_filter(&config, output, 0);
         assert_eq!(result, "timestamp=X msg\n\nok");
     }
+
+    #[test]
+    fn apply_filter_truncates_after_cleanup() {
+        let config = FilterConfig {
+            skip: vec!["^noise".to_string()],
+            collapse_blank_lines: Some(true),
+            head: Some(2),
+            tail: Some(1),
+            ..Default::default()
+        };
+        let output = "a\nnoise\n\n\nb\nc\nd\ne";
+        let result = apply_filter(&config, output, 0);
+        assert_eq!(result, "a\n\n… 3 lines omitted\ne");
+    }
+
+    #[test]
+    fn apply_filter_max_tokens() {
+        let config = FilterConfig {
+            max_tokens: Some(4),
+            ..Default::default()
+        };
+        let result = apply_filter(&config, "aaaa\nbbbb\ncccc\ndddd", 0);
+        assert_eq!(result, "aaaa\n… ~3 tokens omitted\ndddd");
+    }
+
+    #[test]
+    fn apply_filter_json_paths_then_keep() {
+        let config = FilterConfig {
+            json_paths: vec![".items[].name".to_string()],
+            skip: vec!["^test-".to_string()],
+            ..Default::default()
+        };
+        let output = r#"{"items": [{"name": "api"}, {"name": "test-db"}, {"name": "web"}]}"#;
+        assert_eq!(apply_filter(&config, output, 0), "api\nweb");
+    }
+
+    #[test]
+    fn apply_filter_named_section_summary_template() {
+        use crate::config::types::SectionRule;
+        let config = FilterConfig {
+            section: vec![SectionRule {
+                name: Some("suites".to_string()),
+                start: "^Failed suites:".to_string(),
+                end: Some("^$".to_string()),
+                keep: None,
+            }],
+            template: Some(r#"{suites.count} failing suites: {suites|join:", "}"#.to_string()),
+            ..Default::default()
+        };
+        let output = "Ran 10 suites\nFailed suites:\n  auth\n  cart\n  search\n\nDone";
+        assert_eq!(
+            apply_filter(&config, output, 1),
+            "3 failing suites: auth, cart, search"
+        );
+    }
+
+    #[test]
+    fn apply_filter_stage_order_replace_after_section() {
+        // By default replace rewrites the start marker before section matching
+        // sees it; with replace moved after section, the section is dropped.
+        let mut config = FilterConfig {
+            section: vec![crate::config::types::SectionRule {
+                name: None,
+                start: "^BEGIN$".to_string(),
+                end: Some("^END$".to_string()),
+                keep: None,
+            }],
+            replace: vec![crate::config::types::ReplaceRule {
+                pattern: "BEGIN".to_string(),
+                replacement: "start".to_string(),
+            }],
+            ..Default::default()
+        };
+        let output = "before\nBEGIN\ninner\nEND\nafter";
+        assert!(apply_filter(&config, output, 0).contains("inner"));
+
+        config.stage_order = vec!["section".to_string(), "replace".to_string()];
+        assert_eq!(apply_filter(&config, output, 0), "before\nafter");
+    }
+
+    #[test]
+    fn apply_filter_max_bytes_caps_short_circuit_output() {
+        use crate::config::types::MatchOutputRule;
+        let config = FilterConfig {
+            match_output: vec![MatchOutputRule {
+                contains: Some("FATAL".to_string()),
+                regex: None,
+                exit_code: None,
+                template: None,
+            }],
+            max_bytes: Some(64),
+            ..Default::default()
+        };
+        let output = format!("FATAL\n{}", "x\n".repeat(500));
+        assert_eq!(apply_filter(&config, &output, 1), "FATAL");
+
+        let config = FilterConfig {
+            max_bytes: Some(64),
+            ..Default::default()
+        };
+        let result = apply_filter(&config, &output, 1);
+        assert!(result.len() <= 64 + 32);
+        assert!(result.contains("bytes omitted"));
+    }
+
+    #[test]
+    fn meets_min_savings_threshold() {
+        let mut config = FilterConfig::default();
+        let raw = "x".repeat(100);
+        assert!(meets_min_savings(&config, &raw, &"x".repeat(150)));
+
+        config.min_savings_pct = Some(20.0);
+        assert!(meets_min_savings(&config, &raw, &"x".repeat(80)));
+        assert!(!meets_min_savings(&config, &raw, &"x".repeat(81)));
+
+        config.min_savings_pct = Some(0.0);
+        assert!(meets_min_savings(&config, &raw, &raw));
+        assert!(!meets_min_savings(&config, &raw, &"x".repeat(101)));
+        assert!(meets_min_savings(&config, "", "framing"));
+    }
+
+    #[test]
+    fn apply_fallback_compacts_stack_traces() {
+        let output = "Error: boom\n    at main (/app/index.js:3:9)\n    at Module._compile (node:internal/modules/cjs/loader:1256:14)";
+        let result = apply_fallback(output, 1);
+        assert_eq!(
+            result,
+            "Error: boom\n    at main (/app/index.js:3:9)\n    … 1 framework frame"
+        );
+    }
+
+    #[test]
+    fn apply_fallback_passthrough_without_traces() {
+        assert_eq!(apply_fallback("hello\nworld\n", 0), "hello\nworld\n");
+    }
+
+    #[test]
+    fn explain_filter_traces_configured_stages() {
+        let config = FilterConfig {
+            skip: vec!["^noise".to_string()],
+            tail: Some(1),
+            ..Default::default()
+        };
+        let input = "noise 1\nnoise 2\nerror: a\nerror: b";
+        let (result, steps) = explain_filter(&config, input, 0);
+        assert_eq!(result, apply_filter(&config, input, 0));
+
+        let names: Vec<&str> = steps.iter().map(|s| s.stage.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "pre_filter",
+                "skip/keep",
+                "max_lines/head/tail",
+                "post_filter"
+            ]
+        );
+        let skip = &steps[1];
+        assert_eq!((skip.lines_before, skip.lines_after), (4, 2));
+        assert_eq!(skip.rules, vec!["skip \"^noise\": 2 lines"]);
+    }
+
+    #[test]
+    fn apply_filter_with_savings_reports_bytes_removed_per_stage() {
+        let config = FilterConfig {
+            skip: vec!["^noise".to_string()],
+            ..Default::default()
+        };
+        let input = "noise 1\nnoise 2\nerror: a";
+        let (result, savings) = apply_filter_with_savings(&config, input, 0);
+        assert_eq!(result, apply_filter(&config, input, 0));
+        assert_eq!(savings, vec![("skip/keep".to_string(), 16)]);
+    }
 }
@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+
+use regex::{Captures, Regex};
+
+/// Rewrite absolute paths under the working directory (and its git root) to
+/// relative ones.
+///
+/// `/repo/src/main.rs` becomes `src/main.rs` when run from `/repo`, and
+/// `../src/main.rs` when run from `/repo/docs`. A bare `/repo` becomes `.`.
+/// Paths that merely share a prefix (`/repo2/...`) are left alone.
+pub fn apply_relativize_paths(input: &str, cwd: &Path) -> String {
+    let mut result = input.to_string();
+    for (root, relative) in workspace_roots(cwd) {
+        result = relativize(&result, &root, &relative);
+    }
+    result
+}
+
+/// Roots to relativize, longest first: the working directory itself, then
+/// the enclosing git root (as `../` steps) when it differs.
+fn workspace_roots(cwd: &Path) -> Vec<(String, String)> {
+    let mut roots = vec![(cwd.to_string_lossy().into_owned(), String::new())];
+    if let Some(git_root) = find_git_root(cwd) {
+        if let Ok(below) = cwd.strip_prefix(&git_root) {
+            let depth = below.components().count();
+            if depth > 0 {
+                roots.push((git_root.to_string_lossy().into_owned(), "../".repeat(depth)));
+            }
+        }
+    }
+    roots.retain(|(root, _)| root.len() > 1);
+    roots
+}
+
+/// Nearest ancestor of `dir` (inclusive) containing a `.git` entry.
+fn find_git_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Replace `root/` with `relative` and a bare `root` with `relative` (or `.`).
+fn relativize(input: &str, root: &str, relative: &str) -> String {
+    let root = root.trim_end_matches('/');
+    if !input.contains(root) {
+        return input.to_string();
+    }
+    let Ok(re) = Regex::new(&format!(r"{}(?:(/)|([^\w.\-/]|$))", regex::escape(root))) else {
+        return input.to_string();
+    };
+    re.replace_all(input, |caps: &Captures| {
+        if caps.get(1).is_some() {
+            return relative.to_string();
+        }
+        let bare = match relative.trim_end_matches('/') {
+            "" => ".",
+            rel => rel,
+        };
+        format!("{bare}{}", caps.get(2).map_or("", |m| m.as_str()))
+    })
+    .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_under_root_become_relative() {
+        let input = "error: /work/proj/src/main.rs:4:5: boom\n  --> /work/proj/src/lib.rs:1:1";
+        assert_eq!(
+            relativize(input, "/work/proj", ""),
+            "error: src/main.rs:4:5: boom\n  --> src/lib.rs:1:1"
+        );
+    }
+
+    #[test]
+    fn bare_root_becomes_dot() {
+        assert_eq!(
+            relativize("Compiling app v0.1.0 (/work/proj)", "/work/proj", ""),
+            "Compiling app v0.1.0 (.)"
+        );
+        assert_eq!(relativize("cd /work/proj", "/work/proj", ""), "cd .");
+    }
+
+    #[test]
+    fn sibling_prefix_untouched() {
+        let input = "/work/proj2/a.rs /work/proj.bak/b.rs";
+        assert_eq!(relativize(input, "/work/proj", ""), input);
+    }
+
+    #[test]
+    fn git_root_above_cwd_uses_parent_steps() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir(repo.path().join(".git")).unwrap();
+        let sub = repo.path().join("crates/app");
+        std::fs::create_dir_all(&sub).unwrap();
+
+        let root = repo.path().to_string_lossy();
+        let input = format!("{root}/crates/app/src/a.rs\n{root}/README.md");
+        assert_eq!(
+            apply_relativize_paths(&input, &sub),
+            "src/a.rs\n../../README.md"
+        );
+    }
+}
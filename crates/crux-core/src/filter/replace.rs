@@ -4,13 +4,17 @@ use crate::config::types::ReplaceRule;
 
 /// Apply regex replacement rules sequentially to each line of input.
 /// Invalid regex patterns are silently skipped.
+///
+/// Replacements may reference groups as `$1`/`${name}` (regex syntax) or as
+/// `{1}`/`{name}` (the template syntax used by extract rules).
 pub fn apply_replace(input: &str, rules: &[ReplaceRule]) -> String {
-    let compiled: Vec<(Regex, &str)> = rules
+    let compiled: Vec<(Regex, String)> = rules
         .iter()
         .filter_map(|r| {
-            Regex::new(&r.pattern)
-                .ok()
-                .map(|re| (re, r.replacement.as_str()))
+            Regex::new(&r.pattern).ok().map(|re| {
+                let replacement = expand_braces(&r.replacement, &re);
+                (re, replacement)
+            })
         })
         .collect();
 
@@ -19,7 +23,7 @@ pub fn apply_replace(input: &str, rules: &[ReplaceRule]) -> String {
         .map(|line| {
             let mut result = line.to_string();
             for (re, replacement) in &compiled {
-                result = re.replace_all(&result, *replacement).into_owned();
+                result = re.replace_all(&result, replacement.as_str()).into_owned();
             }
             result
         })
@@ -27,6 +31,20 @@ pub fn apply_replace(input: &str, rules: &[ReplaceRule]) -> String {
         .join("\n")
 }
 
+/// Rewrite `{N}`/`{name}` references to groups of `re` into `${N}`/`${name}`.
+/// Braces that don't name a group are left alone.
+fn expand_braces(replacement: &str, re: &Regex) -> String {
+    let mut result = replacement.to_string();
+    for name in re.capture_names().flatten() {
+        result = result.replace(&format!("{{{name}}}"), &format!("${{{name}}}"));
+    }
+    // Reverse order so `{10}` is rewritten before `{1}`.
+    for i in (0..re.captures_len()).rev() {
+        result = result.replace(&format!("{{{i}}}"), &format!("${{{i}}}"));
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,4 +92,25 @@ mod tests {
         let result = apply_replace(input, &[rule(r"(\d{4})-(\d{2})-(\d{2})", "$2/$3/$1")]);
         assert_eq!(result, "01/15/2024 event happened");
     }
+
+    #[test]
+    fn named_captures_in_replacement() {
+        let input = "2024-01-15 event happened";
+        let rule = rule(r"(?P<y>\d{4})-(?P<m>\d{2})-(?P<d>\d{2})", "{m}/{d}/{y}");
+        assert_eq!(apply_replace(input, &[rule]), "01/15/2024 event happened");
+    }
+
+    #[test]
+    fn brace_positional_in_replacement() {
+        let input = "took 42ms";
+        let result = apply_replace(input, &[rule(r"(\d+)ms", "{1} ms")]);
+        assert_eq!(result, "took 42 ms");
+    }
+
+    #[test]
+    fn unknown_braces_left_literal() {
+        let input = "value=1";
+        let result = apply_replace(input, &[rule(r"value=(\d)", "{1} {other} {}")]);
+        assert_eq!(result, "1 {other} {}");
+    }
 }
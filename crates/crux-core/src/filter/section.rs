@@ -7,8 +7,10 @@ use super::context::FilterContext;
 /// Extract or keep sections delimited by start/end regex patterns.
 ///
 /// For each rule, lines between the start and end markers are collected
-/// into `ctx.sections` keyed by `"section_N"`. If `rule.keep == Some(true)`,
-/// the section lines remain in the output; otherwise they are removed.
+/// into `ctx.sections` keyed by the rule's `name` (or `"section_N"`), and
+/// their non-blank inner lines into `ctx.section_items`. If
+/// `rule.keep == Some(true)`, the section lines remain in the output;
+/// otherwise they are removed.
 pub fn apply_sections(input: &str, rules: &[SectionRule], ctx: &mut FilterContext) -> String {
     if rules.is_empty() {
         return input.to_string();
@@ -26,6 +28,16 @@ pub fn apply_sections(input: &str, rules: &[SectionRule], ctx: &mut FilterContex
         })
         .collect();
 
+    let key = |idx: usize| -> String {
+        rules[idx]
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("section_{idx}"))
+    };
+    for &(idx, ..) in &compiled {
+        ctx.section_items.entry(key(idx)).or_default();
+    }
+
     let mut output_lines: Vec<String> = Vec::new();
     let mut active: Option<(usize, bool)> = None; // (rule_idx, keep)
     let mut section_buf: Vec<String> = Vec::new();
@@ -37,9 +49,9 @@ pub fn apply_sections(input: &str, rules: &[SectionRule], ctx: &mut FilterContex
             let end_matched = end_re.as_ref().is_some_and(|re| re.is_match(line));
 
             if end_matched {
+                record_items(ctx, key(rule_idx), &section_buf[1..]);
                 section_buf.push(line.to_string());
-                let key = format!("section_{}", rule_idx);
-                ctx.sections.insert(key, section_buf.clone());
+                ctx.sections.insert(key(rule_idx), section_buf.clone());
                 if keep {
                     output_lines.append(&mut section_buf);
                 } else {
@@ -67,8 +79,8 @@ pub fn apply_sections(input: &str, rules: &[SectionRule], ctx: &mut FilterContex
 
     // Handle open section at EOF (no end marker matched).
     if let Some((rule_idx, keep)) = active {
-        let key = format!("section_{}", rule_idx);
-        ctx.sections.insert(key, section_buf.clone());
+        record_items(ctx, key(rule_idx), &section_buf[1..]);
+        ctx.sections.insert(key(rule_idx), section_buf.clone());
         if keep {
             output_lines.extend(section_buf);
         }
@@ -77,12 +89,24 @@ pub fn apply_sections(input: &str, rules: &[SectionRule], ctx: &mut FilterContex
     output_lines.join("\n")
 }
 
+/// Append the non-blank inner lines of a section occurrence to its items.
+fn record_items(ctx: &mut FilterContext, key: String, inner: &[String]) {
+    ctx.section_items.entry(key).or_default().extend(
+        inner
+            .iter()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .map(String::from),
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn rule(start: &str, end: Option<&str>, keep: Option<bool>) -> SectionRule {
         SectionRule {
+            name: None,
             start: start.to_string(),
             end: end.map(|s| s.to_string()),
             keep,
@@ -145,4 +169,27 @@ mod tests {
         assert_eq!(out, input);
         assert!(ctx.sections.is_empty());
     }
+
+    #[test]
+    fn named_section_items_exclude_markers_and_accumulate() {
+        let input = "FAIL suites:\n  a\n\n  b\nEND\nok\nFAIL suites:\n  c\nEND";
+        let rules = vec![SectionRule {
+            name: Some("failing".to_string()),
+            ..rule("^FAIL suites:", Some("^END$"), None)
+        }];
+        let mut ctx = FilterContext::new(1);
+        let out = apply_sections(input, &rules, &mut ctx);
+        assert_eq!(out, "ok");
+        assert_eq!(ctx.section_items["failing"], vec!["a", "b", "c"]);
+        assert_eq!(ctx.sections["failing"], vec!["FAIL suites:", "  c", "END"]);
+    }
+
+    #[test]
+    fn unmatched_rule_has_empty_items() {
+        let rules = vec![rule("^NOMATCH$", None, None)];
+        let mut ctx = FilterContext::new(0);
+        apply_sections("x", &rules, &mut ctx);
+        assert!(ctx.section_items["section_0"].is_empty());
+        assert!(!ctx.sections.contains_key("section_0"));
+    }
 }
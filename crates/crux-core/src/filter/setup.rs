@@ -0,0 +1,387 @@
+//! The steps that run before a filter's text stages: merging profile and
+//! exit-code blocks, chained `pipeline` filters, the universal pre-filter,
+//! and the short-circuits (`match_output`, builtin handlers, Lua).
+
+use std::borrow::Cow;
+use std::ops::ControlFlow;
+
+use super::trace::{self, Tracer};
+use super::{apply_filter_nested, builtin, cleanup, match_output, universal};
+use crate::config::FilterConfig;
+
+/// Maximum nesting of `pipeline` references (guards against cycles).
+const MAX_PIPELINE_DEPTH: usize = 8;
+
+/// Merge the `[profile.<name>]` block for the active profile, then the
+/// `[on_success]`/`[on_failure]` block for this exit code.
+pub(super) fn merge_blocks<'a>(
+    config: &'a FilterConfig,
+    output: &str,
+    exit_code: i32,
+    tracer: &mut Tracer,
+) -> Cow<'a, FilterConfig> {
+    let profile = crate::config::profile::active_profile();
+    let config = config.for_profile(profile.as_deref());
+    if matches!(config, Cow::Owned(_)) {
+        let name = format!("[profile.{}]", profile.as_deref().unwrap_or_default());
+        tracer.record(name, output, output, || vec!["rules merged".to_string()]);
+    }
+    let block = if exit_code == 0 {
+        config.on_success.is_some().then_some("[on_success]")
+    } else {
+        config.on_failure.is_some().then_some("[on_failure]")
+    };
+    let Some(name) = block else {
+        return config;
+    };
+    tracer.record(name, output, output, || vec!["rules merged".to_string()]);
+    Cow::Owned(config.for_exit_code(exit_code).into_owned())
+}
+
+/// Run each named filter in `pipeline` in order, feeding each one's output
+/// into the next. Names that don't resolve to a filter are skipped.
+///
+/// Each chained filter shows up in a trace as one step.
+pub(super) fn chain_pipeline<'a>(
+    config: &FilterConfig,
+    output: &'a str,
+    exit_code: i32,
+    depth: usize,
+    tracer: &mut Tracer,
+) -> Cow<'a, str> {
+    if config.pipeline.is_empty() || depth >= MAX_PIPELINE_DEPTH {
+        return Cow::Borrowed(output);
+    }
+    let mut result = output.to_string();
+    for name in &config.pipeline {
+        if let Some(step) = crate::config::find_filter_by_name(name) {
+            let next =
+                apply_filter_nested(&step, &result, exit_code, depth + 1, &mut Tracer::off());
+            tracer.record(format!("pipeline {name}"), &result, &next, Vec::new);
+            result = next;
+        }
+    }
+    Cow::Owned(result)
+}
+
+/// Strip ANSI (unless `strip_ansi = false`) and remove progress bars.
+pub(super) fn pre_filter(config: &FilterConfig, output: &str, tracer: &mut Tracer) -> String {
+    let pre_filtered = if config.strip_ansi == Some(false) {
+        universal::pre_filter_keep_ansi(output)
+    } else {
+        universal::pre_filter(output)
+    };
+    tracer.record("pre_filter", output, &pre_filtered, Vec::new);
+    pre_filtered
+}
+
+/// The `match_output` message, if one of its rules matches `output`.
+pub(super) fn try_match_output(
+    config: &FilterConfig,
+    output: &str,
+    exit_code: i32,
+    tracer: &mut Tracer,
+) -> Option<String> {
+    if config.match_output.is_empty() {
+        return None;
+    }
+    let result = match_output::apply_match_output(output, &config.match_output, exit_code)?;
+    tracer.record("match_output", output, &result, || {
+        trace::match_output_rule(config, output, exit_code)
+    });
+    Some(result)
+}
+
+/// Run the registered builtin handler for the filter's command (or the one
+/// it `extends`), unless `builtin = false`.
+///
+/// Handlers parse plain text, so with `strip_ansi = false` they still get
+/// the output without escape codes; the styling only survives when no
+/// handler runs. Breaks with the handler's output; with `post_process`, or
+/// for a filter that `extends` a builtin, continues with it instead so the
+/// text stages run on it. Continues with `output` when there is no handler.
+pub(super) fn dispatch_builtin(
+    config: &FilterConfig,
+    output: String,
+    exit_code: i32,
+    tracer: &mut Tracer,
+) -> ControlFlow<String, String> {
+    if config.builtin == Some(false) {
+        return ControlFlow::Continue(output);
+    }
+    let registry = builtin::registry();
+    let handler = registry.get(config.command.as_str()).or_else(|| {
+        config
+            .extends
+            .as_deref()
+            .and_then(|base| registry.get(base))
+    });
+    let Some(handler) = handler else {
+        return ControlFlow::Continue(output);
+    };
+    let output = if config.strip_ansi == Some(false) {
+        cleanup::strip_ansi(&output)
+    } else {
+        output
+    };
+    let result = handler(&output, exit_code);
+    if tracer.is_on() {
+        let name = config.extends.as_deref().unwrap_or(&config.command);
+        tracer.record(format!("builtin {name}"), &output, &result, Vec::new);
+    }
+    if config.post_process != Some(true) && config.extends.is_none() {
+        ControlFlow::Break(result)
+    } else {
+        ControlFlow::Continue(result)
+    }
+}
+
+/// The Lua escape hatch's output, if its script returns one.
+#[cfg(feature = "lua")]
+pub(super) fn try_lua(
+    config: &FilterConfig,
+    output: &str,
+    exit_code: i32,
+    tracer: &mut Tracer,
+) -> Option<String> {
+    let lua_config = config.lua.as_ref()?;
+    let result = if let Some(ref source) = lua_config.source {
+        super::lua::apply_lua(source, output, exit_code, &[])
+    } else if let Some(ref file) = lua_config.file {
+        super::lua::apply_lua_file(file, output, exit_code, &[])
+    } else {
+        None
+    }?;
+    tracer.record("lua", output, &result, Vec::new);
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::FilterConfig;
+    use crate::filter::apply_filter;
+
+    #[test]
+    fn apply_filter_strip_ansi_and_collapse() {
+        let config = FilterConfig {
+            strip_ansi: Some(true),
+            collapse_blank_lines: Some(true),
+            ..Default::default()
+        };
+        let input = "\x1b[31merror\x1b[0m\n\n\n\nok";
+        let result = apply_filter(&config, input, 0);
+        assert_eq!(result, "error\n\nok");
+    }
+
+    #[test]
+    fn apply_filter_max_line_width_closes_kept_ansi() {
+        let config = FilterConfig {
+            strip_ansi: Some(false),
+            max_line_width: Some(11),
+            ..Default::default()
+        };
+        let input = "\x1b[31merror: \x1b[1mboom boom boom\x1b[0m\n\
+                     see \x1b]8;;https://example.com\x07the docs here\x1b]8;;\x07\n\
+                     [=====>    ] 50%";
+        assert_eq!(
+            apply_filter(&config, input, 0),
+            "\x1b[31merror: \x1b[1mboom\x1b[0m… 10 chars omitted\n\
+             see \x1b]8;;https://example.com\x07the doc\x1b]8;;\x07… 6 chars omitted"
+        );
+
+        // By default the codes are gone before any stage runs
+        let config = FilterConfig {
+            max_line_width: Some(11),
+            ..Default::default()
+        };
+        assert_eq!(
+            apply_filter(&config, input, 0),
+            "error: boom… 10 chars omitted\nsee the doc… 6 chars omitted"
+        );
+    }
+
+    #[test]
+    fn apply_filter_builtin_git_status() {
+        let config = FilterConfig {
+            command: "git status".to_string(),
+            ..Default::default()
+        };
+        let output = "On branch main\n\nChanges:\n  (use hint)\n\tM  src/lib.rs";
+        let result = apply_filter(&config, output, 0);
+        assert!(result.contains("On branch main"));
+        assert!(result.contains("M  src/lib.rs"));
+    }
+
+    #[test]
+    fn apply_filter_builtin_sees_plain_text_with_kept_ansi() {
+        let config = FilterConfig {
+            command: "git status".to_string(),
+            strip_ansi: Some(false),
+            ..Default::default()
+        };
+        let colored = "On branch main\n\nChanges to be committed:\n\
+                       \t\x1b[32mmodified:   src/lib.rs\x1b[m";
+        assert_eq!(
+            apply_filter(&config, colored, 0),
+            "On branch main\nM  src/lib.rs"
+        );
+    }
+
+    #[test]
+    fn apply_filter_builtin_disabled() {
+        let config = FilterConfig {
+            command: "git status".to_string(),
+            builtin: Some(false),
+            ..Default::default()
+        };
+        let output = "On branch main\nsome hint line";
+        let result = apply_filter(&config, output, 0);
+        // Builtin disabled, no TOML pipeline configured, so passthrough
+        assert_eq!(result, output);
+    }
+
+    #[test]
+    fn apply_filter_match_output_short_circuits() {
+        use crate::config::types::MatchOutputRule;
+        let config = FilterConfig {
+            command: "custom".to_string(),
+            match_output: vec![MatchOutputRule {
+                contains: Some("FATAL".to_string()),
+                regex: None,
+                exit_code: None,
+                template: Some("Build crashed!".to_string()),
+            }],
+            skip: vec!["^".to_string()], // Would remove everything, but match_output fires first
+            ..Default::default()
+        };
+        let output = "line1\nFATAL error\nline3";
+        let result = apply_filter(&config, output, 1);
+        assert_eq!(result, "Build crashed!");
+    }
+
+    #[test]
+    fn apply_filter_exit_code_blocks() {
+        use crate::config::rule_block::RuleBlock;
+        let config = FilterConfig {
+            on_success: Some(RuleBlock {
+                template: Some("all good".to_string()),
+                ..Default::default()
+            }),
+            on_failure: Some(RuleBlock {
+                keep: vec!["^error".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let output = "compiling\nerror: boom\ndone";
+        assert_eq!(apply_filter(&config, output, 0), "all good");
+        assert_eq!(apply_filter(&config, output, 1), "error: boom");
+    }
+
+    #[test]
+    fn apply_filter_pipeline_chains_named_filters() {
+        // "make" is an embedded stdlib filter that skips Entering/Leaving lines.
+        let config = FilterConfig {
+            pipeline: vec!["make".to_string(), "no-such-filter".to_string()],
+            keep: vec!["error".to_string()],
+            ..Default::default()
+        };
+        let output = "make[1]: Entering directory '/src/error-lib'\ncc -c a.c\na.c:1: error: boom";
+        assert_eq!(apply_filter(&config, output, 2), "a.c:1: error: boom");
+    }
+
+    #[test]
+    fn apply_filter_post_process_builtin_output() {
+        use crate::config::types::ReplaceRule;
+        let config = FilterConfig {
+            command: "git status".to_string(),
+            post_process: Some(true),
+            replace: vec![ReplaceRule {
+                pattern: r"src/\S+".to_string(),
+                replacement: "<path>".to_string(),
+            }],
+            ..Default::default()
+        };
+        let output =
+            "On branch main\nChanges not staged for commit:\n\tmodified:   src/secret/main.rs\n";
+        let result = apply_filter(&config, output, 0);
+        assert!(result.contains("<path>"));
+        assert!(!result.contains("src/secret"));
+
+        let short_circuit = FilterConfig {
+            post_process: None,
+            ..config
+        };
+        assert!(apply_filter(&short_circuit, output, 0).contains("src/secret"));
+    }
+
+    #[test]
+    fn apply_filter_extends_builtin_runs_own_stages() {
+        let config = FilterConfig {
+            command: "git status".to_string(),
+            extends: Some("git status".to_string()),
+            skip: vec!["^On branch".to_string()],
+            ..Default::default()
+        };
+        let output = "On branch main\nChanges not staged for commit:\n\tmodified:   src/main.rs\n";
+        let result = apply_filter(&config, output, 0);
+        assert!(!result.contains("On branch"));
+        assert!(result.contains("src/main.rs"));
+    }
+
+    #[test]
+    fn apply_filter_max_bytes_from_exit_code_blocks() {
+        use crate::config::rule_block::RuleBlock;
+        let config = FilterConfig {
+            on_success: Some(RuleBlock {
+                max_bytes: Some(64),
+                ..Default::default()
+            }),
+            on_failure: Some(RuleBlock {
+                max_bytes: Some(128),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let output = "x\n".repeat(500);
+        let success = apply_filter(&config, &output, 0);
+        assert!(success.len() <= 64 + 32, "{} bytes", success.len());
+        assert!(success.contains("bytes omitted"));
+        let failure = apply_filter(&config, &output, 1);
+        assert!(failure.len() > 64 + 32 && failure.len() <= 128 + 32);
+        assert!(failure.contains("bytes omitted"));
+    }
+
+    #[test]
+    fn apply_filter_max_bytes_from_profile_block() {
+        use crate::config::rule_block::RuleBlock;
+        let config = FilterConfig {
+            profile: [(
+                "crux-test-max-bytes".to_string(),
+                RuleBlock {
+                    max_bytes: Some(64),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let output = "x\n".repeat(500);
+        // No other test filter has a block for this profile
+        std::env::set_var(crate::config::profile::PROFILE_ENV, "crux-test-max-bytes");
+        let result = apply_filter(&config, &output, 0);
+        std::env::remove_var(crate::config::profile::PROFILE_ENV);
+        assert!(result.len() <= 64 + 32, "{} bytes", result.len());
+        assert!(result.contains("bytes omitted"));
+    }
+
+    #[test]
+    fn apply_filter_pipeline_self_reference_terminates() {
+        let config = FilterConfig {
+            command: "make".to_string(),
+            pipeline: vec!["make".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(apply_filter(&config, "hello", 0), "hello");
+    }
+}
@@ -26,6 +26,46 @@ pub fn apply_skip_keep(input: &str, skip: &[String], keep: &[String]) -> String
     filtered.join("\n")
 }
 
+/// Like [`apply_skip_keep`], but each `keep` match also retains up to
+/// `context` lines before and after it (`grep -C` style).
+///
+/// Non-adjacent groups are separated by a `--` line. Skip patterns still
+/// remove lines, including context lines. With no keep patterns this is
+/// plain skip filtering.
+pub fn apply_keep_context(input: &str, skip: &[String], keep: &[String], context: usize) -> String {
+    let keep_regexes: Vec<Regex> = keep.iter().filter_map(|p| Regex::new(p).ok()).collect();
+    if keep_regexes.is_empty() || context == 0 {
+        return apply_skip_keep(input, skip, keep);
+    }
+    let skip_regexes: Vec<Regex> = skip.iter().filter_map(|p| Regex::new(p).ok()).collect();
+
+    let lines: Vec<&str> = input.lines().collect();
+    let mut selected = vec![false; lines.len()];
+    for (i, line) in lines.iter().enumerate() {
+        if keep_regexes.iter().any(|r| r.is_match(line)) {
+            let end = (i + context).min(lines.len() - 1);
+            selected[i.saturating_sub(context)..=end].fill(true);
+        }
+    }
+
+    let mut result: Vec<&str> = Vec::new();
+    let mut last_selected: Option<usize> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if !selected[i] {
+            continue;
+        }
+        if last_selected.is_some_and(|prev| i > prev + 1) {
+            result.push("--");
+        }
+        last_selected = Some(i);
+        if !skip_regexes.iter().any(|r| r.is_match(line)) {
+            result.push(line);
+        }
+    }
+
+    result.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,4 +113,38 @@ mod tests {
         let result = apply_skip_keep(input, &["[invalid".to_string()], &[]);
         assert_eq!(result, "hello\nworld");
     }
+
+    #[test]
+    fn keep_context_retains_surrounding_lines() {
+        let input = "a\nb\nerror[E0308]: mismatched types\n  --> src/main.rs:4:5\nc\nd\ne";
+        let result = apply_keep_context(input, &[], &["^error".to_string()], 1);
+        assert_eq!(
+            result,
+            "b\nerror[E0308]: mismatched types\n  --> src/main.rs:4:5"
+        );
+    }
+
+    #[test]
+    fn keep_context_separates_groups_and_merges_overlaps() {
+        let input = "1\nERR a\n3\n4\n5\n6\nERR b\n8\nERR c\n10";
+        let result = apply_keep_context(input, &[], &["^ERR".to_string()], 1);
+        assert_eq!(result, "1\nERR a\n3\n--\n6\nERR b\n8\nERR c\n10");
+    }
+
+    #[test]
+    fn keep_context_still_applies_skip() {
+        let input = "noise\nERR x\ndetail";
+        let result = apply_keep_context(input, &["^noise".to_string()], &["^ERR".to_string()], 2);
+        assert_eq!(result, "ERR x\ndetail");
+    }
+
+    #[test]
+    fn keep_context_zero_is_plain_keep() {
+        let input = "a\nERR\nb";
+        let keep = ["ERR".to_string()];
+        assert_eq!(
+            apply_keep_context(input, &[], &keep, 0),
+            apply_skip_keep(input, &[], &keep)
+        );
+    }
 }
@@ -0,0 +1,179 @@
+use crate::config::FilterConfig;
+
+/// A reorderable text stage of the filter pipeline (`strip_ansi` through `max_tokens`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    StripAnsi,
+    JsonPaths,
+    RelativizePaths,
+    Replace,
+    SkipKeep,
+    Table,
+    Section,
+    Extract,
+    Dedup,
+    CollapseRepeats,
+    Template,
+    TrimTrailingWhitespace,
+    CollapseBlankLines,
+    MaxLineWidth,
+    Truncate,
+    MaxTokens,
+}
+
+/// The order stages run in when `stage_order` is not set.
+pub const DEFAULT_ORDER: &[Stage] = &[
+    Stage::StripAnsi,
+    Stage::JsonPaths,
+    Stage::RelativizePaths,
+    Stage::Replace,
+    Stage::SkipKeep,
+    Stage::Table,
+    Stage::Section,
+    Stage::Extract,
+    Stage::Dedup,
+    Stage::CollapseRepeats,
+    Stage::Template,
+    Stage::TrimTrailingWhitespace,
+    Stage::CollapseBlankLines,
+    Stage::MaxLineWidth,
+    Stage::Truncate,
+    Stage::MaxTokens,
+];
+
+impl Stage {
+    /// Parse a `stage_order` entry. Names follow the config field names;
+    /// `skip` and `keep` both name the skip/keep stage, and `max_lines`,
+    /// `head`, `tail` and `truncate` all name the truncation stage.
+    pub fn parse(name: &str) -> Option<Stage> {
+        Some(match name.trim() {
+            "strip_ansi" => Stage::StripAnsi,
+            "json_paths" => Stage::JsonPaths,
+            "relativize_paths" => Stage::RelativizePaths,
+            "replace" => Stage::Replace,
+            "skip" | "keep" => Stage::SkipKeep,
+            "table" => Stage::Table,
+            "section" => Stage::Section,
+            "extract" => Stage::Extract,
+            "dedup" => Stage::Dedup,
+            "collapse_repeats" => Stage::CollapseRepeats,
+            "template" => Stage::Template,
+            "trim_trailing_whitespace" => Stage::TrimTrailingWhitespace,
+            "collapse_blank_lines" => Stage::CollapseBlankLines,
+            "max_line_width" => Stage::MaxLineWidth,
+            "truncate" | "max_lines" | "head" | "tail" => Stage::Truncate,
+            "max_tokens" => Stage::MaxTokens,
+            _ => return None,
+        })
+    }
+
+    /// The config key naming this stage, as shown in traces.
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::StripAnsi => "strip_ansi",
+            Stage::JsonPaths => "json_paths",
+            Stage::RelativizePaths => "relativize_paths",
+            Stage::Replace => "replace",
+            Stage::SkipKeep => "skip/keep",
+            Stage::Table => "table",
+            Stage::Section => "section",
+            Stage::Extract => "extract",
+            Stage::Dedup => "dedup",
+            Stage::CollapseRepeats => "collapse_repeats",
+            Stage::Template => "template",
+            Stage::TrimTrailingWhitespace => "trim_trailing_whitespace",
+            Stage::CollapseBlankLines => "collapse_blank_lines",
+            Stage::MaxLineWidth => "max_line_width",
+            Stage::Truncate => "max_lines/head/tail",
+            Stage::MaxTokens => "max_tokens",
+        }
+    }
+
+    /// Whether `config` sets anything that makes this stage run.
+    pub fn is_configured(self, config: &FilterConfig) -> bool {
+        match self {
+            Stage::StripAnsi => config.strip_ansi == Some(true),
+            Stage::JsonPaths => !config.json_paths.is_empty(),
+            Stage::RelativizePaths => config.relativize_paths == Some(true),
+            Stage::Replace => !config.replace.is_empty(),
+            Stage::SkipKeep => !config.skip.is_empty() || !config.keep.is_empty(),
+            Stage::Table => config
+                .table
+                .as_ref()
+                .is_some_and(|table| !table.keep_columns.is_empty()),
+            Stage::Section => !config.section.is_empty(),
+            Stage::Extract => !config.extract.is_empty(),
+            Stage::Dedup => config.dedup == Some(true),
+            Stage::CollapseRepeats => config.collapse_repeats == Some(true),
+            Stage::Template => config.template.is_some(),
+            Stage::TrimTrailingWhitespace => config.trim_trailing_whitespace == Some(true),
+            Stage::CollapseBlankLines => config.collapse_blank_lines == Some(true),
+            Stage::MaxLineWidth => config.max_line_width.is_some(),
+            Stage::Truncate => {
+                config.max_lines.is_some() || config.head.is_some() || config.tail.is_some()
+            }
+            Stage::MaxTokens => config.max_tokens.is_some(),
+        }
+    }
+}
+
+/// Resolve a `stage_order` list into the full run order.
+///
+/// Listed stages run first, in the given order; stages not listed follow in
+/// their default order. Unknown names and repeats are ignored.
+pub fn ordered(stage_order: &[String]) -> Vec<Stage> {
+    let mut order: Vec<Stage> = Vec::with_capacity(DEFAULT_ORDER.len());
+    let listed = stage_order.iter().filter_map(|name| Stage::parse(name));
+    for stage in listed.chain(DEFAULT_ORDER.iter().copied()) {
+        if !order.contains(&stage) {
+            order.push(stage);
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn empty_is_default_order() {
+        assert_eq!(ordered(&[]), DEFAULT_ORDER);
+    }
+
+    #[test]
+    fn listed_stages_first_then_rest() {
+        let order = ordered(&names(&["section", "replace"]));
+        assert_eq!(
+            &order[..3],
+            &[Stage::Section, Stage::Replace, Stage::StripAnsi]
+        );
+        assert_eq!(order.len(), DEFAULT_ORDER.len());
+    }
+
+    #[test]
+    fn aliases_unknown_and_repeats_ignored() {
+        let order = ordered(&names(&["keep", "bogus", "skip", "head"]));
+        assert_eq!(&order[..2], &[Stage::SkipKeep, Stage::Truncate]);
+        assert_eq!(order.len(), DEFAULT_ORDER.len());
+    }
+
+    #[test]
+    fn configured_follows_config_keys() {
+        let config = FilterConfig {
+            skip: names(&["^noise"]),
+            tail: Some(10),
+            ..Default::default()
+        };
+        let active: Vec<Stage> = DEFAULT_ORDER
+            .iter()
+            .copied()
+            .filter(|stage| stage.is_configured(&config))
+            .collect();
+        assert_eq!(active, vec![Stage::SkipKeep, Stage::Truncate]);
+    }
+}
@@ -0,0 +1,92 @@
+use super::builtin::docker::{parse_column_positions, strip_columns};
+
+/// Keep only the named columns of fixed-width table output
+/// (`kubectl get`, `lsof`, `docker ps`-style tables).
+///
+/// The first non-blank line is taken as the header; columns are separated by
+/// 2+ spaces there, and names may contain single spaces (`CONTAINER ID`).
+/// Column names are compared case-insensitively and the table keeps its
+/// original column order. If no listed column is in the header, the input is
+/// returned unchanged.
+pub fn apply_keep_columns(input: &str, keep_columns: &[String]) -> String {
+    let Some(header_idx) = input.lines().position(|l| !l.trim().is_empty()) else {
+        return input.to_string();
+    };
+    let lines: Vec<&str> = input.lines().collect();
+    let cols = parse_column_positions(lines[header_idx]);
+
+    let is_kept = |name: &str| keep_columns.iter().any(|k| k.eq_ignore_ascii_case(name));
+    if !cols.iter().any(|c| is_kept(&c.name)) {
+        return input.to_string();
+    }
+    let strip: Vec<usize> = cols
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| !is_kept(&c.name))
+        .map(|(i, _)| i)
+        .collect();
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i < header_idx || line.trim().is_empty() {
+                line.to_string()
+            } else {
+                strip_columns(line, &cols, &strip)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cols(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    const PODS: &str = "\
+NAME                     READY   STATUS             RESTARTS   AGE
+web-7d4b9c8f5-abcde      1/1     Running            0          3d
+worker-5f6c7d8e9-fghij   0/1     CrashLoopBackOff   12         3d";
+
+    #[test]
+    fn keeps_selected_columns_in_order() {
+        let result = apply_keep_columns(PODS, &cols(&["STATUS", "NAME"]));
+        let lines: Vec<&str> = result.lines().collect();
+        assert!(lines[0].starts_with("NAME"));
+        assert!(lines[0].contains("STATUS"));
+        assert!(!lines[0].contains("READY"));
+        assert!(!lines[0].contains("AGE"));
+        assert!(lines[2].contains("worker-5f6c7d8e9-fghij"));
+        assert!(lines[2].contains("CrashLoopBackOff"));
+        assert!(!lines[2].contains("0/1"));
+    }
+
+    #[test]
+    fn case_insensitive_and_multi_word_names() {
+        let input = "\
+CONTAINER ID   IMAGE          STATUS
+abc123def456   nginx:latest   Up 2 hours";
+        let result = apply_keep_columns(input, &cols(&["image", "status"]));
+        assert!(!result.contains("abc123def456"));
+        assert!(result.contains("nginx:latest"));
+        assert!(result.contains("Up 2 hours"));
+    }
+
+    #[test]
+    fn preamble_before_header_is_kept() {
+        let input = format!("\n{PODS}");
+        let result = apply_keep_columns(&input, &cols(&["NAME"]));
+        assert!(result.starts_with("\nNAME"));
+        assert!(!result.contains("Running"));
+    }
+
+    #[test]
+    fn unknown_columns_leave_input_unchanged() {
+        assert_eq!(apply_keep_columns(PODS, &cols(&["NODE"])), PODS);
+    }
+}
@@ -2,10 +2,17 @@ use crate::config::types::TeeMode;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Save raw output to a tee file based on the tee mode setting.
+/// Default directory for raw output files, relative to the working directory.
+pub const DEFAULT_TEE_DIR: &str = ".crux/raw";
+
+/// Raw output files kept per tee directory; older ones are removed.
+const MAX_TEE_FILES: usize = 50;
+
+/// Save raw output to a timestamped file in `dir` based on the tee mode setting.
 /// Returns the path where the file was saved, or None if not saved.
 pub fn maybe_save_tee(
     tee_mode: &TeeMode,
+    dir: &Path,
     command_slug: &str,
     raw_output: &str,
     exit_code: i32,
@@ -18,26 +25,28 @@ pub fn maybe_save_tee(
     if !should_save {
         return None;
     }
-    let dir = tee_dir()?;
-    save_tee(&dir, command_slug, raw_output, 50)
+    save_tee(dir, command_slug, raw_output, MAX_TEE_FILES)
+}
+
+/// The tee directory: `configured` if set, else [`DEFAULT_TEE_DIR`].
+pub fn tee_dir(configured: Option<&str>) -> PathBuf {
+    PathBuf::from(configured.unwrap_or(DEFAULT_TEE_DIR))
 }
 
 fn save_tee(dir: &Path, command_slug: &str, raw_output: &str, max_files: usize) -> Option<PathBuf> {
     std::fs::create_dir_all(dir).ok()?;
     let slug = sanitize_slug(command_slug);
-    let ts = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
-    let path = dir.join(format!("{slug}-{ts}.log"));
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_millis();
+    // Timestamp first so rotation (by file name) drops the oldest files.
+    let path = dir.join(format!("{ts}-{slug}.log"));
     std::fs::write(&path, raw_output).ok()?;
     rotate_tee_dir(dir, max_files);
     Some(path)
 }
 
-fn tee_dir() -> Option<PathBuf> {
-    std::env::var("HOME")
-        .ok()
-        .map(|h| PathBuf::from(h).join(".local/share/crux/tee"))
-}
-
 fn sanitize_slug(s: &str) -> String {
     let sanitized: String = s
         .chars()
@@ -69,7 +78,9 @@ mod tests {
 
     #[test]
     fn never_mode_returns_none() {
-        assert!(maybe_save_tee(&TeeMode::Never, "cmd", "out", 1).is_none());
+        let dir = std::env::temp_dir().join("crux-tee-test-never");
+        assert!(maybe_save_tee(&TeeMode::Never, &dir, "cmd", "out", 1).is_none());
+        assert!(!dir.exists());
     }
 
     #[test]
@@ -86,7 +97,24 @@ mod tests {
 
     #[test]
     fn failures_mode_skips_on_zero() {
-        assert!(maybe_save_tee(&TeeMode::Failures, "cmd", "ok", 0).is_none());
+        let dir = std::env::temp_dir().join("crux-tee-test-zero");
+        assert!(maybe_save_tee(&TeeMode::Failures, &dir, "cmd", "ok", 0).is_none());
+    }
+
+    #[test]
+    fn always_mode_writes_timestamped_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = maybe_save_tee(&TeeMode::Always, dir.path(), "git status", "raw", 0).unwrap();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.ends_with("-git-status.log"), "{name}");
+        assert!(name.split('-').next().unwrap().parse::<u128>().is_ok());
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "raw");
+    }
+
+    #[test]
+    fn tee_dir_defaults_to_project_raw_dir() {
+        assert_eq!(tee_dir(None), PathBuf::from(".crux/raw"));
+        assert_eq!(tee_dir(Some("/tmp/raw")), PathBuf::from("/tmp/raw"));
     }
 
     #[test]
@@ -1,24 +1,55 @@
+use std::sync::LazyLock;
+
 use regex::Regex;
 
 use super::context::FilterContext;
 
+/// `{name}`, `{name.count}`, `{name.first}`, `{name.last}` or `{name|join:"sep"}`.
+static PLACEHOLDER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\{([a-zA-Z_][a-zA-Z0-9_]*)(?:\.(count|first|last)|\|join:"([^"]*)")?\}"#)
+        .expect("valid regex")
+});
+
 /// Interpolate `{var_name}` placeholders from context vars and sections.
 ///
 /// Lookup order: `ctx.vars` first, then `ctx.sections` (joined with newlines).
+/// Section items can also be summarized: `{name.count}`, `{name.first}`,
+/// `{name.last}` and `{name|join:", "}`.
 /// Unknown variables are left as-is.
 pub fn apply_template(template: &str, ctx: &FilterContext) -> String {
-    let re = Regex::new(r"\{([a-zA-Z_][a-zA-Z0-9_]*)\}").expect("valid regex");
-    re.replace_all(template, |caps: &regex::Captures| {
-        let name = &caps[1];
-        if let Some(val) = ctx.vars.get(name) {
-            val.clone()
-        } else if let Some(lines) = ctx.sections.get(name) {
-            lines.join("\n")
-        } else {
-            caps[0].to_string()
-        }
+    PLACEHOLDER_RE
+        .replace_all(template, |caps: &regex::Captures| {
+            let name = &caps[1];
+            if let Some(accessor) = caps.get(2) {
+                return section_accessor(ctx, name, accessor.as_str())
+                    .unwrap_or_else(|| caps[0].to_string());
+            }
+            if let Some(sep) = caps.get(3) {
+                return ctx
+                    .section_items
+                    .get(name)
+                    .map(|items| items.join(sep.as_str()))
+                    .unwrap_or_else(|| caps[0].to_string());
+            }
+            if let Some(val) = ctx.vars.get(name) {
+                val.clone()
+            } else if let Some(lines) = ctx.sections.get(name) {
+                lines.join("\n")
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Resolve `.count`/`.first`/`.last` against a section's items.
+fn section_accessor(ctx: &FilterContext, name: &str, accessor: &str) -> Option<String> {
+    let items = ctx.section_items.get(name)?;
+    Some(match accessor {
+        "count" => items.len().to_string(),
+        "first" => items.first().cloned().unwrap_or_default(),
+        _ => items.last().cloned().unwrap_or_default(),
     })
-    .into_owned()
 }
 
 #[cfg(test)]
@@ -55,4 +86,41 @@ mod tests {
             .insert("c".into(), vec!["x".into(), "y".into()]);
         assert_eq!(apply_template("{a}+{b}={c}", &ctx), "1+2=x\ny");
     }
+
+    fn ctx_with_items(name: &str, items: &[&str]) -> FilterContext {
+        let mut ctx = FilterContext::new(1);
+        ctx.section_items
+            .insert(name.into(), items.iter().map(|s| s.to_string()).collect());
+        ctx
+    }
+
+    #[test]
+    fn section_count_and_join() {
+        let ctx = ctx_with_items("suites", &["a", "b", "c"]);
+        assert_eq!(
+            apply_template(r#"{suites.count} failing suites: {suites|join:", "}"#, &ctx),
+            "3 failing suites: a, b, c"
+        );
+    }
+
+    #[test]
+    fn section_first_and_last() {
+        let ctx = ctx_with_items("errs", &["e1", "e2"]);
+        assert_eq!(apply_template("{errs.first}..{errs.last}", &ctx), "e1..e2");
+    }
+
+    #[test]
+    fn empty_section_count_is_zero() {
+        let ctx = ctx_with_items("errs", &[]);
+        assert_eq!(
+            apply_template("{errs.count} errors{errs.first}", &ctx),
+            "0 errors"
+        );
+    }
+
+    #[test]
+    fn unknown_section_accessor_left_as_is() {
+        let ctx = FilterContext::new(0);
+        assert_eq!(apply_template("{nope.count}", &ctx), "{nope.count}");
+    }
 }
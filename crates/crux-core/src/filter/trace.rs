@@ -0,0 +1,180 @@
+use regex::Regex;
+
+use super::match_output::apply_match_output;
+use super::stage::Stage;
+use crate::config::FilterConfig;
+
+/// What one pipeline step did to the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageTrace {
+    /// Stage name (`skip/keep`, `builtin git status`, `pre_filter`, ...).
+    pub stage: String,
+    pub lines_before: usize,
+    pub lines_after: usize,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+    /// Rules of the stage that matched, e.g. `skip "^Compiling": 12 lines`.
+    pub rules: Vec<String>,
+}
+
+/// Collects [`StageTrace`]s while the pipeline runs; does nothing when off.
+pub(crate) struct Tracer {
+    steps: Option<Vec<StageTrace>>,
+    /// Count lines and evaluate rules; off when only byte sizes are wanted.
+    detailed: bool,
+}
+
+impl Tracer {
+    pub(crate) fn off() -> Self {
+        Tracer {
+            steps: None,
+            detailed: false,
+        }
+    }
+
+    pub(crate) fn on() -> Self {
+        Tracer {
+            steps: Some(Vec::new()),
+            detailed: true,
+        }
+    }
+
+    /// Record only byte sizes: cheap enough to run on every filtered command.
+    pub(crate) fn bytes_only() -> Self {
+        Tracer {
+            steps: Some(Vec::new()),
+            detailed: false,
+        }
+    }
+
+    pub(crate) fn is_on(&self) -> bool {
+        self.steps.is_some()
+    }
+
+    /// Record a step turning `before` into `after`. `rules` only runs when
+    /// tracing in detail, so matching rules are never re-evaluated on the
+    /// normal path.
+    pub(crate) fn record(
+        &mut self,
+        stage: impl Into<String>,
+        before: &str,
+        after: &str,
+        rules: impl FnOnce() -> Vec<String>,
+    ) {
+        let Some(ref mut steps) = self.steps else {
+            return;
+        };
+        let (lines_before, lines_after, rules) = if self.detailed {
+            (before.lines().count(), after.lines().count(), rules())
+        } else {
+            (0, 0, Vec::new())
+        };
+        steps.push(StageTrace {
+            stage: stage.into(),
+            lines_before,
+            lines_after,
+            bytes_before: before.len(),
+            bytes_after: after.len(),
+            rules,
+        });
+    }
+
+    pub(crate) fn finish(self) -> Vec<StageTrace> {
+        self.steps.unwrap_or_default()
+    }
+}
+
+/// Describe the rules of `stage` that match `input`.
+pub(crate) fn stage_rules(config: &FilterConfig, stage: Stage, input: &str) -> Vec<String> {
+    match stage {
+        Stage::SkipKeep => {
+            let keep = config.keep.iter().map(|p| ("keep", p));
+            let skip = config.skip.iter().map(|p| ("skip", p));
+            keep.chain(skip)
+                .map(|(kind, pattern)| format!("{kind} {}", line_hits(pattern, input)))
+                .collect()
+        }
+        Stage::Replace => config
+            .replace
+            .iter()
+            .map(|rule| format!("replace {}", line_hits(&rule.pattern, input)))
+            .collect(),
+        Stage::Extract => config
+            .extract
+            .iter()
+            .map(|rule| format!("extract {}", line_hits(&rule.pattern, input)))
+            .collect(),
+        Stage::Section => config
+            .section
+            .iter()
+            .map(|rule| format!("section start {}", line_hits(&rule.start, input)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Which `match_output` rule (1-based) short-circuited on `input`.
+pub(crate) fn match_output_rule(config: &FilterConfig, input: &str, exit_code: i32) -> Vec<String> {
+    config
+        .match_output
+        .iter()
+        .position(|rule| apply_match_output(input, std::slice::from_ref(rule), exit_code).is_some())
+        .map(|i| vec![format!("rule #{} matched", i + 1)])
+        .unwrap_or_default()
+}
+
+/// `"pattern": N lines`, or a note that the pattern doesn't compile.
+fn line_hits(pattern: &str, input: &str) -> String {
+    match Regex::new(pattern) {
+        Ok(re) => {
+            let hits = input.lines().filter(|line| re.is_match(line)).count();
+            let noun = if hits == 1 { "line" } else { "lines" };
+            format!("{pattern:?}: {hits} {noun}")
+        }
+        Err(_) => format!("{pattern:?}: invalid regex, ignored"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn off_tracer_records_nothing() {
+        let mut tracer = Tracer::off();
+        tracer.record("skip", "a\nb", "a", || panic!("rules evaluated while off"));
+        assert!(tracer.finish().is_empty());
+    }
+
+    #[test]
+    fn bytes_only_tracer_skips_rules() {
+        let mut tracer = Tracer::bytes_only();
+        tracer.record("skip", "a\nb", "a", || panic!("rules evaluated"));
+        let steps = tracer.finish();
+        assert_eq!(steps.len(), 1);
+        assert_eq!((steps[0].bytes_before, steps[0].bytes_after), (3, 1));
+        assert!(steps[0].rules.is_empty());
+    }
+
+    #[test]
+    fn skip_keep_rules_count_matching_lines() {
+        let config = FilterConfig {
+            skip: vec!["^Compiling".to_string(), "(".to_string()],
+            keep: vec!["error".to_string()],
+            ..Default::default()
+        };
+        let rules = stage_rules(
+            &config,
+            Stage::SkipKeep,
+            "Compiling a\nCompiling b\nerror: x",
+        );
+        assert_eq!(
+            rules,
+            vec![
+                "keep \"error\": 1 line",
+                "skip \"^Compiling\": 2 lines",
+                "skip \"(\": invalid regex, ignored",
+            ]
+        );
+    }
+}
@@ -0,0 +1,393 @@
+use crate::tokens::estimate_tokens;
+
+/// Cap output to a maximum number of lines, keeping the head and/or tail.
+///
+/// - `max_lines` is the threshold: output at or under it is left untouched.
+///   Without `max_lines`, the threshold is `head + tail`.
+/// - `head`/`tail` choose how the kept lines are split. With only
+///   `max_lines`, the first and last halves are kept; with only one of
+///   `head`/`tail`, the other side gets the remainder of `max_lines`.
+///
+/// Dropped lines are replaced by a single `… N lines omitted` marker.
+pub fn apply_truncate(
+    input: &str,
+    max_lines: Option<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+) -> String {
+    let lines: Vec<&str> = input.lines().collect();
+    let total = lines.len();
+    let limit = max_lines.unwrap_or(head.unwrap_or(0) + tail.unwrap_or(0));
+    if total <= limit {
+        return input.to_string();
+    }
+
+    let (head, tail) = match (head, tail) {
+        (Some(h), Some(t)) => (h, t),
+        (Some(h), None) => (h, limit.saturating_sub(h)),
+        (None, Some(t)) => (limit.saturating_sub(t), t),
+        (None, None) => (limit - limit / 2, limit / 2),
+    };
+    if head + tail >= total {
+        return input.to_string();
+    }
+
+    let omitted = total - head - tail;
+    let plural = if omitted == 1 { "" } else { "s" };
+    let mut result: Vec<String> = lines[..head].iter().map(|l| l.to_string()).collect();
+    result.push(format!("… {omitted} line{plural} omitted"));
+    result.extend(lines[total - tail..].iter().map(|l| l.to_string()));
+    result.join("\n")
+}
+
+/// Cap output to an estimated token budget by dropping lines from the middle.
+///
+/// Lines are taken alternately from the start and the end (whichever side has
+/// used fewer tokens) until the next line would exceed `max_tokens`; the gap is
+/// replaced by a `… ~N tokens omitted` marker. Output within budget is untouched.
+pub fn apply_max_tokens(input: &str, max_tokens: usize) -> String {
+    let total = estimate_tokens(input);
+    if total <= max_tokens {
+        return input.to_string();
+    }
+
+    let lines: Vec<&str> = input.lines().collect();
+    let cost = |line: &str| estimate_tokens(line) + 1;
+    let (mut head, mut tail) = (0, lines.len());
+    let (mut head_tokens, mut tail_tokens) = (0, 0);
+
+    while head < tail {
+        let from_head = head_tokens <= tail_tokens;
+        let line = if from_head {
+            lines[head]
+        } else {
+            lines[tail - 1]
+        };
+        if head_tokens + tail_tokens + cost(line) > max_tokens {
+            break;
+        }
+        if from_head {
+            head_tokens += cost(line);
+            head += 1;
+        } else {
+            tail_tokens += cost(line);
+            tail -= 1;
+        }
+    }
+
+    let omitted = estimate_tokens(&lines[head..tail].join("\n"));
+    let mut result: Vec<String> = lines[..head].iter().map(|l| l.to_string()).collect();
+    result.push(format!("… ~{omitted} tokens omitted"));
+    result.extend(lines[tail..].iter().map(|l| l.to_string()));
+    result.join("\n")
+}
+
+/// Cap output to `max_bytes`, keeping the start and end.
+///
+/// The budget is split evenly between head and tail, and each side is
+/// shortened to a line boundary when one falls inside it; the gap becomes a
+/// `… N bytes omitted` marker. Output within the cap is untouched.
+pub fn apply_max_bytes(input: &str, max_bytes: usize) -> String {
+    if input.len() <= max_bytes {
+        return input.to_string();
+    }
+
+    let head_budget = max_bytes / 2;
+    let mut head_end = floor_char_boundary(input, head_budget);
+    if !input[head_end..].starts_with('\n') {
+        if let Some(nl) = input[..head_end].rfind('\n') {
+            head_end = nl;
+        }
+    }
+
+    let mut tail_start = ceil_char_boundary(input, input.len() - (max_bytes - head_budget));
+    if !input[..tail_start].ends_with('\n') {
+        if let Some(nl) = input[tail_start..].find('\n') {
+            if tail_start + nl + 1 < input.len() {
+                tail_start += nl + 1;
+            }
+        }
+    }
+
+    let omitted = tail_start - head_end;
+    let head = input[..head_end].trim_end_matches('\n');
+    let tail = &input[tail_start..];
+    let mut result = String::with_capacity(max_bytes + 32);
+    if !head.is_empty() {
+        result.push_str(head);
+        result.push('\n');
+    }
+    result.push_str(&format!("… {omitted} bytes omitted"));
+    if !tail.is_empty() {
+        result.push('\n');
+        result.push_str(tail);
+    }
+    result
+}
+
+/// Cut lines longer than `width` characters, ending them with a
+/// `… N chars omitted` marker.
+///
+/// ANSI escape sequences are copied through without counting toward the
+/// width. If a line is cut while SGR styling or an OSC 8 hyperlink is still
+/// open, it is closed before the marker so the styling doesn't leak.
+pub fn apply_max_line_width(input: &str, width: usize) -> String {
+    input
+        .lines()
+        .map(|line| truncate_line(line, width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn truncate_line(line: &str, width: usize) -> String {
+    if line.len() <= width {
+        return line.to_string();
+    }
+    let total = visible_width(line);
+    if total <= width {
+        return line.to_string();
+    }
+
+    let mut result = String::with_capacity(width + 32);
+    let (mut visible, mut styled, mut link_open) = (0, false, false);
+    let mut rest = line;
+    while visible < width {
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+        let len = if c == '\x1b' {
+            let len = escape_len(rest);
+            let seq = &rest[..len];
+            if let Some(params) = seq.strip_prefix("\x1b[").and_then(|p| p.strip_suffix('m')) {
+                styled = !params.split(';').all(|p| p.is_empty() || p == "0");
+            } else if let Some(body) = seq.strip_prefix("\x1b]8;") {
+                let body = body
+                    .trim_end_matches(['\x07', '\\'])
+                    .trim_end_matches('\x1b');
+                link_open = body.split_once(';').is_some_and(|(_, uri)| !uri.is_empty());
+            }
+            len
+        } else {
+            visible += 1;
+            c.len_utf8()
+        };
+        result.push_str(&rest[..len]);
+        rest = &rest[len..];
+    }
+
+    if link_open {
+        result.push_str("\x1b]8;;\x07");
+    }
+    if styled {
+        result.push_str("\x1b[0m");
+    }
+    result.push_str(&format!("… {} chars omitted", total - width));
+    result
+}
+
+/// Number of characters in `line`, not counting ANSI escape sequences.
+fn visible_width(line: &str) -> usize {
+    let mut count = 0;
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '\x1b' {
+            escape_len(rest)
+        } else {
+            count += 1;
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    count
+}
+
+/// Byte length of the escape sequence at the start of `s` (which starts with
+/// ESC): CSI (`ESC [ … final`), OSC (`ESC ] … BEL` or `ESC ] … ESC \`),
+/// charset selection (`ESC ( B`), or a two-character escape.
+fn escape_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let next_char_end = |start: usize| {
+        s[start..]
+            .chars()
+            .next()
+            .map_or(s.len(), |c| start + c.len_utf8())
+    };
+    match bytes.get(1) {
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(s.len(), |i| i + 3),
+        Some(b']') => {
+            let bel = s.find('\x07').map(|i| i + 1);
+            let st = s[2..].find("\x1b\\").map(|i| i + 4);
+            match (bel, st) {
+                (Some(a), Some(b)) => a.min(b),
+                (a, b) => a.or(b).unwrap_or(s.len()),
+            }
+        }
+        Some(b'(' | b')') if s.len() > 2 => next_char_end(2),
+        Some(_) => next_char_end(1),
+        None => 1,
+    }
+}
+
+/// Largest char boundary at or below `index`.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    (0..=index.min(s.len()))
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+/// Smallest char boundary at or above `index`.
+fn ceil_char_boundary(s: &str, index: usize) -> usize {
+    (index..s.len())
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(s.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(n: usize) -> String {
+        (1..=n)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn under_limit_unchanged() {
+        let input = numbered(5);
+        assert_eq!(apply_truncate(&input, Some(5), None, None), input);
+    }
+
+    #[test]
+    fn max_lines_keeps_both_halves() {
+        let result = apply_truncate(&numbered(10), Some(4), None, None);
+        assert_eq!(result, "line 1\nline 2\n… 6 lines omitted\nline 9\nline 10");
+    }
+
+    #[test]
+    fn head_only() {
+        let result = apply_truncate(&numbered(10), None, Some(3), None);
+        assert_eq!(result, "line 1\nline 2\nline 3\n… 7 lines omitted");
+    }
+
+    #[test]
+    fn tail_only() {
+        let result = apply_truncate(&numbered(10), None, None, Some(2));
+        assert_eq!(result, "… 8 lines omitted\nline 9\nline 10");
+    }
+
+    #[test]
+    fn head_and_tail() {
+        let result = apply_truncate(&numbered(50), None, Some(2), Some(1));
+        assert_eq!(result, "line 1\nline 2\n… 47 lines omitted\nline 50");
+    }
+
+    #[test]
+    fn max_lines_with_head_gives_remainder_to_tail() {
+        let result = apply_truncate(&numbered(10), Some(3), Some(1), None);
+        assert_eq!(result, "line 1\n… 7 lines omitted\nline 9\nline 10");
+    }
+
+    #[test]
+    fn max_lines_threshold_with_explicit_split() {
+        // 8 lines is under max_lines, so head/tail do not apply.
+        let input = numbered(8);
+        assert_eq!(apply_truncate(&input, Some(10), Some(2), Some(2)), input);
+    }
+
+    #[test]
+    fn single_omitted_line_singular() {
+        let result = apply_truncate(&numbered(3), Some(2), None, None);
+        assert_eq!(result, "line 1\n… 1 line omitted\nline 3");
+    }
+
+    #[test]
+    fn max_tokens_under_budget_unchanged() {
+        let input = numbered(3);
+        assert_eq!(apply_max_tokens(&input, 100), input);
+    }
+
+    #[test]
+    fn max_tokens_keeps_start_and_end() {
+        // Each "line N" is 2 tokens + 1 for the newline.
+        let result = apply_max_tokens(&numbered(20), 12);
+        assert_eq!(
+            result,
+            "line 1\nline 2\n… ~30 tokens omitted\nline 19\nline 20"
+        );
+    }
+
+    #[test]
+    fn max_tokens_result_fits_budget_plus_marker() {
+        let input = numbered(500);
+        let result = apply_max_tokens(&input, 200);
+        let marker = result.lines().find(|l| l.starts_with("… ~")).unwrap();
+        assert!(estimate_tokens(&result) <= 200 + estimate_tokens(marker));
+        assert!(result.starts_with("line 1\n"));
+        assert!(result.ends_with("line 500"));
+    }
+
+    #[test]
+    fn max_bytes_under_cap_unchanged() {
+        let input = numbered(3);
+        assert_eq!(apply_max_bytes(&input, 1000), input);
+    }
+
+    #[test]
+    fn max_bytes_keeps_whole_lines_at_both_ends() {
+        // 20 lines of "line N", 150 bytes in total.
+        let result = apply_max_bytes(&numbered(20), 40);
+        assert_eq!(
+            result,
+            "line 1\nline 2\nline 3\n… 115 bytes omitted\nline 19\nline 20"
+        );
+    }
+
+    #[test]
+    fn max_bytes_cuts_single_long_line_on_char_boundary() {
+        let input = "é".repeat(100);
+        let result = apply_max_bytes(&input, 11);
+        assert_eq!(result, "éé\n… 190 bytes omitted\nééé");
+    }
+
+    #[test]
+    fn max_line_width_cuts_only_long_lines() {
+        let input = format!("short\n{}\nend", "x".repeat(30));
+        assert_eq!(
+            apply_max_line_width(&input, 10),
+            "short\nxxxxxxxxxx… 20 chars omitted\nend"
+        );
+        assert_eq!(apply_max_line_width("héllo wörld", 11), "héllo wörld");
+        assert_eq!(
+            apply_max_line_width("héllo wörld", 5),
+            "héllo… 6 chars omitted"
+        );
+    }
+
+    #[test]
+    fn max_line_width_ignores_and_closes_ansi() {
+        // Styling doesn't count toward the width and is reset after the cut.
+        let input = "\x1b[31merror: \x1b[1mboom boom boom\x1b[0m";
+        assert_eq!(
+            apply_max_line_width(input, 11),
+            "\x1b[31merror: \x1b[1mboom\x1b[0m… 10 chars omitted"
+        );
+        // A line whose visible text fits is untouched despite its byte length.
+        let colored = "\x1b[32mok\x1b[0m";
+        assert_eq!(apply_max_line_width(colored, 2), colored);
+    }
+
+    #[test]
+    fn max_line_width_closes_open_hyperlink() {
+        let input = "see \x1b]8;;https://example.com\x07the docs here\x1b]8;;\x07 now";
+        assert_eq!(
+            apply_max_line_width(input, 7),
+            "see \x1b]8;;https://example.com\x07the\x1b]8;;\x07… 14 chars omitted"
+        );
+    }
+}
@@ -71,6 +71,17 @@ pub fn pre_filter(output: &str) -> String {
         .join("\n")
 }
 
+/// [`pre_filter`] for filters that set `strip_ansi = false`: progress lines
+/// are still removed (detected on their text without escape codes), but
+/// the remaining lines keep their styling.
+pub fn pre_filter_keep_ansi(output: &str) -> String {
+    output
+        .lines()
+        .filter(|line| !is_progress_line(&ANSI_RE.replace_all(line, "")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Post-filter: runs AFTER builtin filtering.
 ///
 /// - Collapses 3+ consecutive blank lines to 1 blank line
@@ -218,6 +229,12 @@ mod tests {
         assert_eq!(pre_filter(input), input);
     }
 
+    #[test]
+    fn pre_filter_keep_ansi_still_removes_progress() {
+        let input = "\x1b[32m⠋\x1b[0m building\n\x1b[31merror\x1b[0m: failed";
+        assert_eq!(pre_filter_keep_ansi(input), "\x1b[31merror\x1b[0m: failed");
+    }
+
     #[test]
     fn pre_filter_combined_ansi_and_progress() {
         let input = "\x1b[32mBuilding\x1b[0m\n\x1b[33m[====>  ]\x1b[0m\n\x1b[32mDone\x1b[0m";
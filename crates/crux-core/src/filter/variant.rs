@@ -1,5 +1,6 @@
 use regex::Regex;
 
+use crate::config::resolve::args_contain_flag;
 use crate::config::types::FilterConfig;
 
 /// Pre-execution variant detection: checks filesystem markers.
@@ -17,19 +18,55 @@ pub fn detect_variant_pre(config: &FilterConfig) -> Option<String> {
     None
 }
 
-/// Post-execution variant detection: matches output against regex patterns.
+/// Argument-based variant detection: checks the command's flags.
 ///
-/// Iterates variant rules and returns the filter name of the first rule
-/// whose `detect_output` regex matches anywhere in the given output.
-/// Invalid regex patterns are silently skipped.
-pub fn detect_variant_post(config: &FilterConfig, output: &str) -> Option<String> {
+/// Returns the filter name of the first rule with `when_args` whose flags all
+/// appear in `args`. A flag matches an argument equal to it or of the form
+/// `flag=value`.
+pub fn detect_variant_args(config: &FilterConfig, args: &[String]) -> Option<String> {
     for v in &config.variant {
-        if let Some(ref pattern) = v.detect_output {
-            if let Ok(re) = Regex::new(pattern) {
-                if re.is_match(output) {
-                    return v.filter.clone();
-                }
-            }
+        if !v.when_args.is_empty() && v.when_args.iter().all(|f| args_contain_flag(args, f)) {
+            return v.filter.clone();
+        }
+    }
+    None
+}
+
+/// Pick the variant filter to use before running the command.
+///
+/// Argument rules are checked first, then filesystem markers. The variant's
+/// `filter` names another filter by its `command`; `None` means keep `config`.
+pub fn select_variant_pre(config: &FilterConfig, args: &[String]) -> Option<FilterConfig> {
+    let name = detect_variant_args(config, args).or_else(|| detect_variant_pre(config))?;
+    crate::config::find_filter_by_name(&name)
+}
+
+/// Post-execution variant detection: checks the output and exit code.
+///
+/// Returns the filter name of the first rule with post-run conditions
+/// (`detect_output`, `when_exit`, `min_output_bytes`) that all hold:
+/// `detect_output` matches anywhere in the output, `when_exit` accepts the
+/// exit code, and the output is at least `min_output_bytes` long.
+/// Rules with an invalid `detect_output` regex are skipped.
+pub fn detect_variant_post(config: &FilterConfig, output: &str, exit_code: i32) -> Option<String> {
+    for v in &config.variant {
+        if v.detect_output.is_none() && v.when_exit.is_none() && v.min_output_bytes.is_none() {
+            continue;
+        }
+        let output_hit = match v.detect_output {
+            Some(ref pattern) => Regex::new(pattern).is_ok_and(|re| re.is_match(output)),
+            None => true,
+        };
+        let exit_hit = match v.when_exit {
+            Some(ref condition) => condition.matches(exit_code),
+            None => true,
+        };
+        let size_hit = match v.min_output_bytes {
+            Some(min) => output.len() >= min,
+            None => true,
+        };
+        if output_hit && exit_hit && size_hit {
+            return v.filter.clone();
         }
     }
     None
@@ -56,20 +93,75 @@ mod tests {
         VariantRule {
             name: name.to_string(),
             detect_file: Some(file.to_string()),
-            detect_output: None,
             filter: Some(filter.to_string()),
+            ..Default::default()
         }
     }
 
     fn variant_output(name: &str, pattern: &str, filter: &str) -> VariantRule {
         VariantRule {
             name: name.to_string(),
-            detect_file: None,
             detect_output: Some(pattern.to_string()),
             filter: Some(filter.to_string()),
+            ..Default::default()
         }
     }
 
+    fn variant_args(name: &str, flags: &[&str], filter: &str) -> VariantRule {
+        VariantRule {
+            name: name.to_string(),
+            when_args: flags.iter().map(|f| f.to_string()).collect(),
+            filter: Some(filter.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn args_detect_flag_present() {
+        let cfg = make_config(vec![
+            variant_args("stat", &["--stat"], "git log --stat"),
+            variant_args("oneline", &["--oneline"], "git log --oneline"),
+        ]);
+        assert_eq!(
+            detect_variant_args(&cfg, &args(&["git", "log", "--oneline", "-5"])),
+            Some("git log --oneline".to_string())
+        );
+        assert_eq!(
+            detect_variant_args(&cfg, &args(&["git", "log", "--stat=120"])),
+            Some("git log --stat".to_string())
+        );
+    }
+
+    #[test]
+    fn args_detect_requires_all_flags() {
+        let cfg = make_config(vec![variant_args("both", &["--stat", "-p"], "x")]);
+        assert_eq!(
+            detect_variant_args(&cfg, &args(&["git", "log", "--stat"])),
+            None
+        );
+        assert_eq!(
+            detect_variant_args(&cfg, &args(&["git", "log", "-p", "--stat"])),
+            Some("x".to_string())
+        );
+        // Prefix of a longer flag does not count.
+        assert_eq!(
+            detect_variant_args(&cfg, &args(&["--statistics", "-p"])),
+            None
+        );
+    }
+
+    #[test]
+    fn select_variant_pre_resolves_named_filter() {
+        let cfg = make_config(vec![variant_args("m", &["-k"], "make")]);
+        let selected = select_variant_pre(&cfg, &args(&["build", "-k"])).unwrap();
+        assert_eq!(selected.command, "make");
+        assert!(select_variant_pre(&cfg, &args(&["build"])).is_none());
+    }
+
     #[test]
     fn pre_detect_existing_file() {
         // Cargo.toml exists at workspace root (tests run from workspace root)
@@ -92,7 +184,7 @@ mod tests {
         let cfg = make_config(vec![variant_output("err", r"error\[E\d+\]", "cargo-error")]);
         let output = "error[E0308]: mismatched types";
         assert_eq!(
-            detect_variant_post(&cfg, output),
+            detect_variant_post(&cfg, output, 0),
             Some("cargo-error".to_string())
         );
     }
@@ -101,7 +193,54 @@ mod tests {
     fn post_detect_no_match_returns_none() {
         let cfg = make_config(vec![variant_output("err", r"FATAL", "fatal-filter")]);
         let output = "Compiling crux v0.1.0\n    Finished dev";
-        assert_eq!(detect_variant_post(&cfg, output), None);
+        assert_eq!(detect_variant_post(&cfg, output, 0), None);
+    }
+
+    #[test]
+    fn post_detect_exit_code_and_size() {
+        use crate::config::types::ExitCodeCondition;
+
+        let crash = VariantRule {
+            name: "crash".to_string(),
+            when_exit: Some(ExitCodeCondition::Named(">=128".to_string())),
+            filter: Some("crash-filter".to_string()),
+            ..Default::default()
+        };
+        let huge = VariantRule {
+            name: "huge".to_string(),
+            min_output_bytes: Some(100),
+            filter: Some("huge-filter".to_string()),
+            ..Default::default()
+        };
+        let cfg = make_config(vec![crash, huge]);
+        let big = "x".repeat(100);
+
+        assert_eq!(
+            detect_variant_post(&cfg, "killed", 137),
+            Some("crash-filter".to_string())
+        );
+        assert_eq!(
+            detect_variant_post(&cfg, &big, 1),
+            Some("huge-filter".to_string())
+        );
+        assert_eq!(detect_variant_post(&cfg, "small", 1), None);
+    }
+
+    #[test]
+    fn post_conditions_combine() {
+        let rule = VariantRule {
+            name: "big-failure".to_string(),
+            detect_output: Some("FAIL".to_string()),
+            when_exit: Some(crate::config::types::ExitCodeCondition::Named(
+                "nonzero".to_string(),
+            )),
+            filter: Some("x".to_string()),
+            ..Default::default()
+        };
+        let cfg = make_config(vec![rule, variant_file("f", "Cargo.toml", "file-only")]);
+        assert_eq!(detect_variant_post(&cfg, "FAIL", 1), Some("x".to_string()));
+        assert_eq!(detect_variant_post(&cfg, "FAIL", 0), None);
+        assert_eq!(detect_variant_post(&cfg, "ok", 1), None);
     }
 
     #[test]
@@ -117,7 +256,7 @@ mod tests {
             variant_output("b", r"hello", "filter-b"),
         ]);
         assert_eq!(
-            detect_variant_post(&cfg2, "hello world"),
+            detect_variant_post(&cfg2, "hello world", 0),
             Some("filter-a".to_string())
         );
     }
@@ -1,6 +1,7 @@
 pub mod config;
 pub mod filter;
 pub mod runner;
+pub mod tokens;
 pub mod verify;
 
 /// Core version
@@ -0,0 +1,96 @@
+//! Token estimation.
+//!
+//! Agents budget context in tokens, not bytes. Every token count in crux
+//! goes through [`estimate_tokens`] so the heuristic can be replaced by a
+//! real tokenizer in one place. Reports that only display counts can pick
+//! another estimate with [`Tokenizer`] (`tokenizer` in config.toml).
+
+use serde::{Deserialize, Serialize};
+
+/// Average number of characters per token for English text and code.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimate the number of tokens in `text` (chars / 4, rounded up).
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// How token counts shown to the user are estimated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tokenizer {
+    /// Characters / 4, see [`estimate_tokens`].
+    #[default]
+    Chars,
+    /// One token per run of letters/digits and per punctuation character,
+    /// closer to BPE tokenizers on code and logs.
+    Words,
+}
+
+impl Tokenizer {
+    /// Estimate the number of tokens in `text`.
+    pub fn count(self, text: &str) -> usize {
+        match self {
+            Tokenizer::Chars => estimate_tokens(text),
+            Tokenizer::Words => count_word_tokens(text),
+        }
+    }
+}
+
+fn count_word_tokens(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            if !in_word {
+                count += 1;
+                in_word = true;
+            }
+        } else {
+            in_word = false;
+            if !c.is_whitespace() {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_is_zero() {
+        assert_eq!(estimate_tokens(""), 0);
+    }
+
+    #[test]
+    fn rounds_up() {
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn counts_chars_not_bytes() {
+        assert_eq!(estimate_tokens("✔✔✔✔"), 1);
+    }
+
+    #[test]
+    fn words_counts_runs_and_punctuation() {
+        assert_eq!(Tokenizer::Words.count(""), 0);
+        assert_eq!(Tokenizer::Words.count("error: cannot find foo_bar"), 5);
+        assert_eq!(Tokenizer::Words.count("src/lib.rs:12"), 7);
+    }
+
+    #[test]
+    fn tokenizer_from_config_name() {
+        #[derive(Deserialize)]
+        struct Config {
+            tokenizer: Tokenizer,
+        }
+        let config: Config = toml::from_str("tokenizer = \"words\"").unwrap();
+        assert_eq!(config.tokenizer, Tokenizer::Words);
+        assert_eq!(Tokenizer::default().count("abcde"), 2);
+    }
+}
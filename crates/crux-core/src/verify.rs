@@ -4,6 +4,10 @@
 //! should contain pairs of files:
 //!   - `input.txt` / `expected.txt` (single test case)
 //!   - `<name>.input` / `<name>.expected` (named test cases)
+//!
+//! The same cases double as the benchmark corpus for `crux bench`.
+
+use std::path::Path;
 
 use include_dir::{include_dir, Dir};
 
@@ -12,6 +16,16 @@ use crate::filter::apply_filter;
 
 static STDLIB_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/filters");
 
+/// One input (and expected output) from a filter's `_test/` directory.
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    /// `<filter command>::<case>` (`default` for `input.txt`).
+    pub name: String,
+    pub config: FilterConfig,
+    pub input: String,
+    pub expected: String,
+}
+
 /// Result of a single test case.
 #[derive(Debug)]
 pub struct TestResult {
@@ -39,12 +53,29 @@ impl VerifyResult {
 
 /// Verify all embedded stdlib filter test suites.
 pub fn verify_embedded_stdlib() -> VerifyResult {
-    let mut results = Vec::new();
-    verify_embedded_dir(&STDLIB_DIR, &mut results);
+    let results = embedded_test_cases()
+        .into_iter()
+        .map(|case| {
+            let actual = apply_filter(&case.config, &case.input, 0);
+            TestResult {
+                passed: actual.trim() == case.expected.trim(),
+                name: case.name,
+                expected: case.expected,
+                actual,
+            }
+        })
+        .collect();
     VerifyResult { results }
 }
 
-fn verify_embedded_dir(dir: &Dir<'_>, results: &mut Vec<TestResult>) {
+/// Every test case of the embedded stdlib filters.
+pub fn embedded_test_cases() -> Vec<TestCase> {
+    let mut cases = Vec::new();
+    collect_embedded_dir(&STDLIB_DIR, &mut cases);
+    cases
+}
+
+fn collect_embedded_dir(dir: &Dir<'_>, cases: &mut Vec<TestCase>) {
     // Look for _test directories
     for subdir in dir.dirs() {
         let dir_name = subdir
@@ -60,38 +91,31 @@ fn verify_embedded_dir(dir: &Dir<'_>, results: &mut Vec<TestResult>) {
             if let Some(toml_file) = dir.get_file(dir.path().join(&toml_filename)) {
                 if let Some(toml_contents) = toml_file.contents_utf8() {
                     if let Ok(config) = toml::from_str::<FilterConfig>(toml_contents) {
-                        run_embedded_test_suite(&config, subdir, results);
+                        collect_embedded_suite(&config, subdir, cases);
                     }
                 }
             }
         } else {
             // Recurse into non-test subdirectories
-            verify_embedded_dir(subdir, results);
+            collect_embedded_dir(subdir, cases);
         }
     }
 }
 
-fn run_embedded_test_suite(
-    config: &FilterConfig,
-    test_dir: &Dir<'_>,
-    results: &mut Vec<TestResult>,
-) {
+fn collect_embedded_suite(config: &FilterConfig, test_dir: &Dir<'_>, cases: &mut Vec<TestCase>) {
+    let read = |name: &str| {
+        test_dir
+            .get_file(test_dir.path().join(name))
+            .and_then(|f| f.contents_utf8())
+    };
+
     // Check for input.txt / expected.txt pair (single test case)
-    let input_txt = test_dir
-        .get_file(test_dir.path().join("input.txt"))
-        .and_then(|f| f.contents_utf8());
-    let expected_txt = test_dir
-        .get_file(test_dir.path().join("expected.txt"))
-        .and_then(|f| f.contents_utf8());
-
-    if let (Some(input), Some(expected)) = (input_txt, expected_txt) {
-        let actual = apply_filter(config, input, 0);
-        let passed = actual.trim() == expected.trim();
-        results.push(TestResult {
+    if let (Some(input), Some(expected)) = (read("input.txt"), read("expected.txt")) {
+        cases.push(TestCase {
             name: format!("{}::default", config.command),
-            passed,
+            config: config.clone(),
+            input: input.to_string(),
             expected: expected.to_string(),
-            actual,
         });
     }
 
@@ -100,25 +124,90 @@ fn run_embedded_test_suite(
         let path = file.path();
         if path.extension().and_then(|e| e.to_str()) == Some("input") {
             let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-            let expected_path = test_dir.path().join(format!("{stem}.expected"));
-            if let Some(expected_file) = test_dir.get_file(&expected_path) {
-                if let (Some(input), Some(expected)) =
-                    (file.contents_utf8(), expected_file.contents_utf8())
-                {
-                    let actual = apply_filter(config, input, 0);
-                    let passed = actual.trim() == expected.trim();
-                    results.push(TestResult {
-                        name: format!("{}::{stem}", config.command),
-                        passed,
-                        expected: expected.to_string(),
-                        actual,
-                    });
-                }
+            if let (Some(input), Some(expected)) =
+                (file.contents_utf8(), read(&format!("{stem}.expected")))
+            {
+                cases.push(TestCase {
+                    name: format!("{}::{stem}", config.command),
+                    config: config.clone(),
+                    input: input.to_string(),
+                    expected: expected.to_string(),
+                });
             }
         }
     }
 }
 
+/// Test cases of the filters under `dir` (e.g. `.crux/filters`), searched
+/// recursively. Unreadable files and invalid filters are skipped.
+pub fn test_cases_in_dir(dir: &Path) -> Vec<TestCase> {
+    let mut cases = Vec::new();
+    collect_fs_dir(dir, &mut cases);
+    cases
+}
+
+fn collect_fs_dir(dir: &Path, cases: &mut Vec<TestCase>) {
+    let Ok(rd) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in rd.flatten().map(|entry| entry.path()) {
+        if !path.is_dir() {
+            continue;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let Some(base_name) = name.strip_suffix("_test") else {
+            collect_fs_dir(&path, cases);
+            continue;
+        };
+        let toml_path = dir.join(format!("{base_name}.toml"));
+        let Some(mut config) = std::fs::read_to_string(&toml_path)
+            .ok()
+            .and_then(|contents| toml::from_str::<FilterConfig>(&contents).ok())
+        else {
+            continue;
+        };
+        crate::config::env::interpolate_config(&mut config);
+        collect_fs_suite(&config, &path, cases);
+    }
+}
+
+fn collect_fs_suite(config: &FilterConfig, test_dir: &Path, cases: &mut Vec<TestCase>) {
+    let read = |path: &Path| std::fs::read_to_string(path).ok();
+
+    if let (Some(input), Some(expected)) = (
+        read(&test_dir.join("input.txt")),
+        read(&test_dir.join("expected.txt")),
+    ) {
+        cases.push(TestCase {
+            name: format!("{}::default", config.command),
+            config: config.clone(),
+            input,
+            expected,
+        });
+    }
+
+    let Ok(rd) = std::fs::read_dir(test_dir) else {
+        return;
+    };
+    for path in rd.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("input") {
+            continue;
+        }
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+        if let (Some(input), Some(expected)) = (
+            read(&path),
+            read(&test_dir.join(format!("{stem}.expected"))),
+        ) {
+            cases.push(TestCase {
+                name: format!("{}::{stem}", config.command),
+                config: config.clone(),
+                input,
+                expected,
+            });
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,4 +229,24 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_cases_in_dir_finds_nested_suites() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("team");
+        std::fs::create_dir_all(nested.join("deploy_test")).unwrap();
+        std::fs::write(nested.join("deploy.toml"), "command = \"deploy\"\n").unwrap();
+        std::fs::write(nested.join("deploy_test/input.txt"), "a").unwrap();
+        std::fs::write(nested.join("deploy_test/expected.txt"), "a").unwrap();
+        std::fs::write(nested.join("deploy_test/fail.input"), "b").unwrap();
+        std::fs::write(nested.join("deploy_test/fail.expected"), "b").unwrap();
+        std::fs::write(nested.join("deploy_test/orphan.input"), "c").unwrap();
+
+        let mut names: Vec<String> = test_cases_in_dir(dir.path())
+            .into_iter()
+            .map(|case| case.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["deploy::default", "deploy::fail"]);
+    }
 }
@@ -1,12 +1,16 @@
 use serde::{Deserialize, Serialize};
 
 /// Input from Claude Code's PreToolUse hook (stdin JSON).
-/// Extra fields like `session_id`, `hook_event_name` are ignored.
+/// Extra fields like `hook_event_name` are ignored.
 #[derive(Debug, Deserialize)]
 pub struct HookInput {
     pub tool_name: String,
     #[serde(default)]
     pub tool_input: serde_json::Value,
+    /// The agent conversation, passed on to `crux run` as `CRUX_SESSION_ID`
+    /// so savings can be grouped by session.
+    #[serde(default)]
+    pub session_id: Option<String>,
 }
 
 /// Output to Claude Code (stdout JSON) — only emitted when rewriting.
@@ -40,7 +44,7 @@ pub fn handle_hook(input: &HookInput) -> Option<HookOutput> {
         .and_then(|v| v.as_str())
         .unwrap_or("");
 
-    if let Some(rewritten) = rewrite_command(command) {
+    if let Some(rewritten) = rewrite_command(command, &runner(None, input.session_id.as_deref())) {
         let mut new_input = input.tool_input.clone();
         new_input["command"] = serde_json::Value::String(rewritten);
 
@@ -62,10 +66,10 @@ pub fn handle_hook(input: &HookInput) -> Option<HookOutput> {
 /// - Simple commands: `git status` → `crux run git status`
 /// - Compound commands: `cd /path && git status` → `cd /path && crux run git status`
 /// - Chained commands: `cd /p && cargo test && echo done` → rewrites each eligible part
-fn rewrite_command(command: &str) -> Option<String> {
+pub(crate) fn rewrite_command(command: &str, runner: &str) -> Option<String> {
     // Simple case: entire command is interceptable
     if should_intercept(command) {
-        return Some(format!("crux run {command}"));
+        return Some(format!("{runner} {command}"));
     }
 
     // Compound commands: split on && and ; operators, rewrite eligible parts
@@ -91,7 +95,7 @@ fn rewrite_command(command: &str) -> Option<String> {
 
         let trimmed = part.trim();
         if should_intercept(trimmed) {
-            result.push_str(&part.replace(trimmed, &format!("crux run {trimmed}")));
+            result.push_str(&part.replace(trimmed, &format!("{runner} {trimmed}")));
             changed = true;
         } else {
             result.push_str(part);
@@ -111,6 +115,30 @@ fn rewrite_command(command: &str) -> Option<String> {
     }
 }
 
+/// `crux run`, prefixed with `CRUX_SOURCE=<source>` for agents crux can't
+/// detect on its own and with the session id.
+pub(crate) fn runner(source: Option<&str>, session_id: Option<&str>) -> String {
+    let source = source.map(|source| format!("CRUX_SOURCE={source}"));
+    source
+        .into_iter()
+        .chain(session_env(session_id))
+        .chain(["crux run".to_string()])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `CRUX_SESSION_ID=<id>` for a session id that is safe to put in a shell
+/// command unquoted; anything else is dropped.
+fn session_env(session_id: Option<&str>) -> Option<String> {
+    let id = session_id?;
+    let safe = !id.is_empty()
+        && id.len() <= 128
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    safe.then(|| format!("CRUX_SESSION_ID={id}"))
+}
+
 /// Find the next `&&` or `;` separator in a command string.
 /// Returns the separator string and its position.
 fn find_next_separator(s: &str) -> (Option<&'static str>, Option<usize>) {
@@ -126,12 +154,24 @@ fn find_next_separator(s: &str) -> (Option<&'static str>, Option<usize>) {
     }
 }
 
+/// Long-running servers under otherwise intercepted tools. `crux run` only
+/// prints once the command exits, so wrapping these would hide their output.
+const LONG_RUNNING_PREFIXES: &[&str] =
+    &["wrangler dev", "firebase emulators:start", "firebase serve"];
+
 /// Check if a command should be intercepted by crux.
-fn should_intercept(command: &str) -> bool {
+pub(crate) fn should_intercept(command: &str) -> bool {
     // Don't intercept if already going through crux
     if command.starts_with("crux ") {
         return false;
     }
+    if LONG_RUNNING_PREFIXES.iter().any(|p| {
+        command
+            .strip_prefix(p)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    }) {
+        return false;
+    }
 
     let known_prefixes = [
         // Version control
@@ -174,12 +214,16 @@ fn should_intercept(command: &str) -> bool {
         // Infrastructure & ops
         "terraform ",
         "ansible ",
+        "ansible-playbook ",
         "ssh ",
         // Build systems
         "make ",
         // Cloud & deploy
         "firebase ",
         "supabase ",
+        "vercel ",
+        "netlify ",
+        "wrangler ",
         // Filesystem & utilities
         "ls ",
         "find ",
@@ -206,6 +250,7 @@ mod tests {
         HookInput {
             tool_name: tool_name.to_string(),
             tool_input: json!({ "command": command }),
+            session_id: None,
         }
     }
 
@@ -259,6 +304,7 @@ mod tests {
         let input = HookInput {
             tool_name: "Bash".to_string(),
             tool_input: json!({}),
+            session_id: None,
         };
         assert_passthrough(&input);
     }
@@ -271,6 +317,21 @@ mod tests {
         let input: HookInput = serde_json::from_str(raw).unwrap();
         assert_eq!(input.tool_name, "Bash");
         assert_eq!(input.tool_input["command"], "git status");
+        assert_eq!(input.session_id.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn session_id_passed_to_crux_run() {
+        let mut input = make_input("Bash", "cd /p && git status");
+        input.session_id = Some("0f3c-41a2_b.7".to_string());
+        assert_rewritten(
+            &input,
+            "cd /p && CRUX_SESSION_ID=0f3c-41a2_b.7 crux run git status",
+        );
+
+        // Ids that would need shell quoting are dropped
+        input.session_id = Some("x; rm -rf /".to_string());
+        assert_rewritten(&input, "cd /p && crux run git status");
     }
 
     // -- Rewrite cases --
@@ -387,12 +448,58 @@ mod tests {
         assert_rewritten(&input, "crux run ansible playbook.yml");
     }
 
+    #[test]
+    fn ansible_playbook_command_rewritten() {
+        let input = make_input("Bash", "ansible-playbook -i hosts site.yml");
+        assert_rewritten(&input, "crux run ansible-playbook -i hosts site.yml");
+    }
+
     #[test]
     fn ssh_command_rewritten() {
         let input = make_input("Bash", "ssh user@host ls");
         assert_rewritten(&input, "crux run ssh user@host ls");
     }
 
+    // -- Cloud & deploy --
+
+    #[test]
+    fn vercel_command_rewritten() {
+        let input = make_input("Bash", "vercel deploy --prod");
+        assert_rewritten(&input, "crux run vercel deploy --prod");
+    }
+
+    #[test]
+    fn netlify_command_rewritten() {
+        let input = make_input("Bash", "netlify deploy --build");
+        assert_rewritten(&input, "crux run netlify deploy --build");
+    }
+
+    #[test]
+    fn wrangler_command_rewritten() {
+        let input = make_input("Bash", "wrangler deploy");
+        assert_rewritten(&input, "crux run wrangler deploy");
+    }
+
+    #[test]
+    fn long_running_servers_pass_through() {
+        for command in [
+            "wrangler dev",
+            "wrangler dev --port 8787",
+            "firebase emulators:start --only functions",
+            "firebase serve",
+        ] {
+            assert_passthrough(&make_input("Bash", command));
+        }
+        assert_rewritten(
+            &make_input("Bash", "firebase emulators:exec 'npm test'"),
+            "crux run firebase emulators:exec 'npm test'",
+        );
+        assert_rewritten(
+            &make_input("Bash", "wrangler devices"),
+            "crux run wrangler devices",
+        );
+    }
+
     // -- Build systems --
 
     #[test]
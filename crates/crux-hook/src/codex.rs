@@ -1,12 +1,22 @@
-//! Codex hook integration for crux.
+//! Codex integration for crux.
 //!
-//! Codex uses a configuration-based approach. Since the exact hook format may
-//! vary, we create a wrapper script at `~/.local/bin/crux-codex-wrapper` that
-//! pipes commands through `crux run`, and print setup instructions for the user.
+//! Codex has no hook that runs before its shell tool, so `crux init --codex`
+//! works through what Codex does read:
+//!
+//! - a wrapper script at `~/.local/bin/crux-codex-wrapper` runs a command
+//!   through `crux run`, attributing it to Codex;
+//! - a marked block in `$CODEX_HOME/AGENTS.md` (`~/.codex/AGENTS.md` by
+//!   default), the instructions Codex loads for every session, asks the
+//!   agent to run commands through the wrapper.
 
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 
+use crate::rules::{has_rules, with_rules, without_rules};
+
+/// Codex instructions file, relative to the Codex home directory.
+const INSTRUCTIONS_FILE: &str = "AGENTS.md";
+
 /// The wrapper script content that intercepts commands and routes them through crux.
 const WRAPPER_SCRIPT: &str = r#"#!/usr/bin/env bash
 # crux-codex-wrapper — wraps shell commands through crux for token compression.
@@ -23,6 +33,8 @@ if ! command -v crux &>/dev/null; then
     exec "$@"
 fi
 
+# Recorded runs are attributed to Codex (see `crux gain --group-by source`)
+export CRUX_SOURCE="${CRUX_SOURCE:-codex}"
 exec crux run "$@"
 "#;
 
@@ -32,18 +44,75 @@ const WRAPPER_DIR: &str = ".local/bin";
 /// Filename for the wrapper script.
 const WRAPPER_NAME: &str = "crux-codex-wrapper";
 
-/// Install the Codex integration for crux.
-///
-/// This creates a wrapper script and prints configuration instructions
-/// for the user to wire it into their Codex setup.
-pub fn install_codex_skill() -> Result<()> {
-    let wrapper_path = install_wrapper_script()?;
+/// Install the Codex integration for crux: the wrapper script and the
+/// instructions that point Codex at it. With `print`, only show the
+/// instructions file that would be written.
+pub fn install_codex_skill(print: bool) -> Result<()> {
+    let path = instructions_path()?;
+    let instructions = with_rules(
+        &std::fs::read_to_string(&path).unwrap_or_default(),
+        &instructions_block(&wrapper_path()?),
+    );
 
-    print_setup_instructions(&wrapper_path);
+    if print {
+        println!("# {}", path.display());
+        println!("{instructions}");
+        return Ok(());
+    }
 
+    let wrapper_path = install_wrapper_script()?;
+    println!(
+        "crux: installed Codex wrapper script: {}",
+        wrapper_path.display()
+    );
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+    }
+    std::fs::write(&path, instructions)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    println!(
+        "crux: added crux instructions for Codex: {}",
+        path.display()
+    );
     Ok(())
 }
 
+/// The Codex home directory: `$CODEX_HOME`, or `~/.codex`.
+fn codex_home() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("CODEX_HOME").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    let home = home_dir().context("cannot determine home directory")?;
+    Ok(home.join(".codex"))
+}
+
+/// Path of the instructions file Codex loads for every session.
+pub fn instructions_path() -> Result<PathBuf> {
+    Ok(codex_home()?.join(INSTRUCTIONS_FILE))
+}
+
+/// The crux block for the Codex instructions, naming the wrapper at
+/// `wrapper_path`.
+fn instructions_block(wrapper_path: &Path) -> String {
+    let wrapper = wrapper_path.display();
+    format!(
+        "Run build, test, lint, git, package manager and container commands through
+{wrapper}, which filters their output down to what matters:
+`{wrapper} cargo test`, `cd app && {wrapper} npm test`. Add `--raw` after the
+wrapper to see the unfiltered output when it is needed.
+"
+    )
+}
+
+/// Whether the Codex instructions have the crux block.
+pub fn has_instructions() -> bool {
+    instructions_path()
+        .and_then(|path| Ok(std::fs::read_to_string(path)?))
+        .is_ok_and(|instructions| has_rules(&instructions))
+}
+
 /// Create the wrapper script at `~/.local/bin/crux-codex-wrapper`.
 ///
 /// Returns the absolute path to the installed script.
@@ -73,34 +142,6 @@ fn install_wrapper_script() -> Result<PathBuf> {
     Ok(wrapper_path)
 }
 
-/// Print human-readable setup instructions to stdout.
-fn print_setup_instructions(wrapper_path: &Path) {
-    println!(
-        "crux: installed Codex wrapper script: {}",
-        wrapper_path.display()
-    );
-    println!();
-    println!("To configure Codex to use crux, add the following to your");
-    println!("Codex config file (~/.codex/config.json or codex.json):");
-    println!();
-    println!("  {{");
-    println!("    \"shell\": \"{}\"", wrapper_path.display());
-    println!("  }}");
-    println!();
-    println!("Or, if Codex supports a command hook, set:");
-    println!();
-    println!("  {{");
-    println!("    \"hooks\": {{");
-    println!("      \"command_wrapper\": \"{}\"", wrapper_path.display());
-    println!("    }}");
-    println!("  }}");
-    println!();
-    println!(
-        "Make sure {} is in your PATH.",
-        wrapper_path.parent().unwrap().display()
-    );
-}
-
 /// Build the wrapper script content for a given crux binary path.
 ///
 /// This is used in testing to verify the script content without
@@ -115,6 +156,37 @@ pub fn wrapper_path() -> Result<PathBuf> {
     Ok(home.join(WRAPPER_DIR).join(WRAPPER_NAME))
 }
 
+/// Remove the crux block from the Codex instructions, deleting the file if
+/// nothing else is left. Returns false if there was none.
+pub fn uninstall_codex_instructions() -> Result<bool> {
+    let path = instructions_path()?;
+    let Ok(instructions) = std::fs::read_to_string(&path) else {
+        return Ok(false);
+    };
+    let Some(stripped) = without_rules(&instructions) else {
+        return Ok(false);
+    };
+    if stripped.trim().is_empty() {
+        std::fs::remove_file(&path)
+            .with_context(|| format!("failed to remove {}", path.display()))?;
+    } else {
+        std::fs::write(&path, stripped)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+    Ok(true)
+}
+
+/// Delete the wrapper script. Returns false if it was not installed.
+pub fn uninstall_codex_wrapper() -> Result<bool> {
+    let path = wrapper_path()?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path)
+        .with_context(|| format!("failed to remove wrapper script: {}", path.display()))?;
+    Ok(true)
+}
+
 fn home_dir() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
     {
@@ -130,11 +202,25 @@ fn home_dir() -> Option<PathBuf> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn instructions_block_names_the_wrapper() {
+        let block = instructions_block(Path::new("/home/u/.local/bin/crux-codex-wrapper"));
+        assert!(block.contains("`/home/u/.local/bin/crux-codex-wrapper cargo test`"));
+
+        let instructions = with_rules("Prefer small commits.\n", &block);
+        assert!(has_rules(&instructions));
+        assert_eq!(
+            without_rules(&instructions).as_deref(),
+            Some("Prefer small commits.\n")
+        );
+    }
+
     #[test]
     fn wrapper_script_is_valid_bash() {
         let script = build_wrapper_script();
         assert!(script.starts_with("#!/usr/bin/env bash"));
         assert!(script.contains("crux run"));
+        assert!(script.contains("CRUX_SOURCE=\"${CRUX_SOURCE:-codex}\""));
         assert!(script.contains("exec \"$@\""));
     }
 
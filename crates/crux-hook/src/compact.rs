@@ -0,0 +1,174 @@
+//! Compaction of earlier tool outputs at conversation checkpoints
+//! (`crux hook compact`, for Claude Code's PreCompact or Stop hooks).
+//!
+//! Each output is cleaned like unfiltered `crux run` output, then checked
+//! against everything before it: an output identical to an earlier one is
+//! replaced by a reference, and lines an earlier output already showed
+//! (the same diagnostics on every rebuild) are folded into a marker.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+
+/// Shorter lines (`}`, `ok`, blank) repeat naturally and are always kept.
+const MIN_SEEN_LINE_LEN: usize = 10;
+
+/// Tool outputs in the hook's stdin: a hook payload with `transcript_path`
+/// (its tool results are read), a JSON array of strings, or plain text
+/// taken as one output.
+pub fn outputs_from_input(input: &str) -> Result<Vec<String>> {
+    match serde_json::from_str::<serde_json::Value>(input) {
+        Ok(serde_json::Value::Object(payload)) if payload.contains_key("transcript_path") => {
+            let path = payload["transcript_path"]
+                .as_str()
+                .context("transcript_path is not a string")?;
+            let transcript = std::fs::read_to_string(path)
+                .with_context(|| format!("reading transcript {path}"))?;
+            Ok(tool_outputs_from_transcript(&transcript))
+        }
+        Ok(serde_json::Value::Array(items)) => Ok(items
+            .iter()
+            .filter_map(|item| item.as_str().map(String::from))
+            .collect()),
+        _ => Ok(vec![input.to_string()]),
+    }
+}
+
+/// Text of every tool result in a Claude Code transcript (JSON Lines), in
+/// order. Lines that aren't JSON are skipped.
+pub fn tool_outputs_from_transcript(transcript: &str) -> Vec<String> {
+    let mut outputs = Vec::new();
+    for line in transcript.lines() {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        let Some(content) = entry["message"]["content"].as_array() else {
+            continue;
+        };
+        for item in content {
+            if item["type"] != "tool_result" {
+                continue;
+            }
+            let text = match &item["content"] {
+                serde_json::Value::String(text) => text.clone(),
+                serde_json::Value::Array(parts) => parts
+                    .iter()
+                    .filter_map(|part| part["text"].as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                _ => continue,
+            };
+            if !text.trim().is_empty() {
+                outputs.push(text);
+            }
+        }
+    }
+    outputs
+}
+
+/// Compact `outputs` into one digest, each output under a `[n]` header.
+pub fn compact_outputs(outputs: &[String]) -> String {
+    let mut digest = String::new();
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+    let mut seen_lines: HashSet<String> = HashSet::new();
+
+    for (index, output) in outputs.iter().enumerate() {
+        let number = index + 1;
+        let cleaned =
+            crux_core::filter::dedup::apply_dedup(&crux_core::filter::apply_fallback(output, 0));
+        if let Some(earlier) = first_seen.get(&cleaned) {
+            digest.push_str(&format!("[{number}] (same output as [{earlier}])\n"));
+            continue;
+        }
+        first_seen.insert(cleaned.clone(), number);
+
+        digest.push_str(&format!("[{number}]\n"));
+        let mut folded = 0;
+        let mut new_lines = Vec::new();
+        for line in cleaned.lines() {
+            let key = line.trim();
+            if key.len() >= MIN_SEEN_LINE_LEN && seen_lines.contains(key) {
+                folded += 1;
+                continue;
+            }
+            flush_folded(&mut digest, &mut folded);
+            digest.push_str(line);
+            digest.push('\n');
+            if key.len() >= MIN_SEEN_LINE_LEN {
+                new_lines.push(key.to_string());
+            }
+        }
+        flush_folded(&mut digest, &mut folded);
+        // Lines count as seen only after their own output, so repeats
+        // within one output are left alone.
+        seen_lines.extend(new_lines);
+    }
+    digest
+}
+
+fn flush_folded(digest: &mut String, folded: &mut usize) {
+    match *folded {
+        0 => {}
+        1 => digest.push_str("… 1 line already shown above\n"),
+        n => digest.push_str(&format!("… {n} lines already shown above\n")),
+    }
+    *folded = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outputs(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn repeated_output_becomes_a_reference() {
+        let digest = compact_outputs(&outputs(&["On branch main", "ls output", "On branch main"]));
+        assert_eq!(
+            digest,
+            "[1]\nOn branch main\n[2]\nls output\n[3] (same output as [1])\n"
+        );
+    }
+
+    #[test]
+    fn diagnostics_seen_earlier_are_folded() {
+        let first = "warning: unused variable `x`\n  --> src/lib.rs:3:9\nerror: build failed";
+        let second = "warning: unused variable `x`\n  --> src/lib.rs:3:9\nerror[E0425]: cannot find `y`\nerror: build failed";
+        let digest = compact_outputs(&outputs(&[first, second]));
+        assert!(digest.ends_with(
+            "[2]\n… 2 lines already shown above\nerror[E0425]: cannot find `y`\n… 1 line already shown above\n"
+        ));
+    }
+
+    #[test]
+    fn repeats_within_one_output_and_short_lines_are_kept() {
+        let digest = compact_outputs(&outputs(&["error: boom\n}\nnext\nerror: boom", "}\nok"]));
+        assert_eq!(
+            digest,
+            "[1]\nerror: boom\n}\nnext\nerror: boom\n[2]\n}\nok\n"
+        );
+    }
+
+    #[test]
+    fn transcript_tool_results_are_extracted() {
+        let transcript = [
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"first"}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"thinking"}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":[{"type":"text","text":"second"}]}]}}"#,
+            "not json",
+        ]
+        .join("\n");
+        assert_eq!(
+            tool_outputs_from_transcript(&transcript),
+            vec!["first", "second"]
+        );
+    }
+
+    #[test]
+    fn plain_text_and_arrays_are_accepted() {
+        assert_eq!(outputs_from_input("raw text").unwrap(), vec!["raw text"]);
+        assert_eq!(outputs_from_input(r#"["a", "b"]"#).unwrap(), vec!["a", "b"]);
+    }
+}
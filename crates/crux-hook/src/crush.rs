@@ -0,0 +1,72 @@
+//! Crush hook integration for crux.
+//!
+//! Crush runs `crux hook handle --format crush` before a tool call with the
+//! call as JSON on stdin, and takes the `tool_input` of the answer:
+//!
+//! ```json
+//! {"event": "pre_tool_use", "session_id": "s1", "tool_name": "bash",
+//!  "tool_input": {"command": "git status"}}
+//! ```
+
+use serde_json::Value;
+
+use crate::claude::{rewrite_command, runner};
+
+/// Attributes runs to Crush (see `crux gain --group-by source`).
+const SOURCE: &str = "crush";
+
+/// Process a Crush `pre_tool_use` event.
+///
+/// Returns `{"tool_input": ...}` with the bash command routed through
+/// `crux run`, or `None` for passthrough (other events, tools and
+/// commands).
+pub fn handle_hook(event: &Value) -> Option<Value> {
+    let pre_tool_use = matches!(
+        event.get("event").and_then(Value::as_str),
+        None | Some("pre_tool_use")
+    );
+    if !pre_tool_use || event.get("tool_name").and_then(Value::as_str) != Some("bash") {
+        return None;
+    }
+    let input = event.get("tool_input")?;
+    let command = input.get("command").and_then(Value::as_str)?;
+    let session = event.get("session_id").and_then(Value::as_str);
+
+    let rewritten = rewrite_command(command, &runner(Some(SOURCE), session))?;
+    let mut input = input.clone();
+    input["command"] = Value::String(rewritten);
+    Some(serde_json::json!({ "tool_input": input }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bash_commands_are_rewritten() {
+        let event = json!({
+            "event": "pre_tool_use",
+            "session_id": "s1",
+            "tool_name": "bash",
+            "tool_input": {"command": "cd app && npm test", "timeout": 120}
+        });
+        assert_eq!(
+            handle_hook(&event),
+            Some(json!({"tool_input": {
+                "command": "cd app && CRUX_SOURCE=crush CRUX_SESSION_ID=s1 crux run npm test",
+                "timeout": 120
+            }}))
+        );
+    }
+
+    #[test]
+    fn other_events_tools_and_commands_pass_through() {
+        let post = json!({"event": "post_tool_use", "tool_name": "bash", "tool_input": {"command": "git status"}});
+        assert_eq!(handle_hook(&post), None);
+        let view = json!({"tool_name": "view", "tool_input": {"file_path": "x"}});
+        assert_eq!(handle_hook(&view), None);
+        let python = json!({"tool_name": "bash", "tool_input": {"command": "python x.py"}});
+        assert_eq!(handle_hook(&python), None);
+    }
+}
@@ -0,0 +1,311 @@
+//! Cursor hook integration for crux.
+//!
+//! `crux init --cursor` writes two files into the project:
+//!
+//! - `.cursor/rules/crux.mdc`, an always-applied rule asking the agent to
+//!   run commands crux has a filter for as `crux run <command>`;
+//! - `.cursor/hooks.json`, registering `crux hook handle --format cursor` for
+//!   `beforeShellExecution`. Cursor hooks cannot rewrite a command, so the
+//!   hook turns down commands that should have gone through crux and tells
+//!   the agent the command to run instead.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::claude::rewrite_command;
+
+/// Cursor hooks file, relative to the project.
+pub const HOOKS_FILE: &str = ".cursor/hooks.json";
+
+/// Cursor rule file, relative to the project.
+pub const RULE_FILE: &str = ".cursor/rules/crux.mdc";
+
+/// Hook registered for `beforeShellExecution`.
+pub const HOOK_COMMAND: &str = "crux hook handle --format cursor";
+
+const RULE: &str = r#"---
+description: Run commands through crux to keep their output short
+alwaysApply: true
+---
+
+Run build, test, lint, git, package manager and container commands through
+crux, which filters their output down to what matters:
+
+    crux run cargo test
+    crux run git status
+
+In compound commands, prefix each such command: `cd app && crux run npm test`.
+`crux run --raw <command>` shows the unfiltered output when it is needed.
+"#;
+
+/// Input from Cursor's `beforeShellExecution` hook (stdin JSON). Extra
+/// fields like `conversation_id` and `workspace_roots` are ignored.
+#[derive(Debug, Deserialize)]
+pub struct HookInput {
+    #[serde(default)]
+    pub hook_event_name: Option<String>,
+    #[serde(default)]
+    pub command: String,
+}
+
+/// Output to Cursor (stdout JSON), printed for every event.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HookOutput {
+    pub permission: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_message: Option<String>,
+}
+
+impl HookOutput {
+    pub fn allow() -> Self {
+        HookOutput {
+            permission: "allow".into(),
+            user_message: None,
+            agent_message: None,
+        }
+    }
+}
+
+/// Process a Cursor `beforeShellExecution` hook call.
+///
+/// Commands crux filters that don't already go through it are denied with
+/// the `crux run` form for the agent to run instead; everything else is
+/// allowed.
+pub fn handle_hook(input: &HookInput) -> HookOutput {
+    let event = input
+        .hook_event_name
+        .as_deref()
+        .unwrap_or("beforeShellExecution");
+    if event != "beforeShellExecution" {
+        return HookOutput::allow();
+    }
+    match rewrite_command(&input.command, "crux run") {
+        Some(rewritten) => HookOutput {
+            permission: "deny".into(),
+            user_message: Some("crux: asked the agent to rerun this through crux".into()),
+            agent_message: Some(format!(
+                "Run this command through crux to keep its output short: {rewritten}"
+            )),
+        },
+        None => HookOutput::allow(),
+    }
+}
+
+/// Install the Cursor integration in the project at `base_dir`. With
+/// `print`, only show the files that would be written.
+pub fn install_cursor(base_dir: &Path, print: bool) -> Result<()> {
+    let hooks_path = base_dir.join(HOOKS_FILE);
+    let rule_path = base_dir.join(RULE_FILE);
+    let mut hooks = read_hooks(&hooks_path)?;
+    add_hook(&mut hooks)?;
+    let json = serde_json::to_string_pretty(&hooks)?;
+
+    if print {
+        println!("# {}", rule_path.display());
+        println!("{RULE}");
+        println!("# {}", hooks_path.display());
+        println!("{json}");
+        return Ok(());
+    }
+
+    for path in [&hooks_path, &rule_path] {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+        }
+    }
+    std::fs::write(&rule_path, RULE)
+        .with_context(|| format!("failed to write {}", rule_path.display()))?;
+    std::fs::write(&hooks_path, json)
+        .with_context(|| format!("failed to write {}", hooks_path.display()))?;
+
+    println!("crux: wrote Cursor rule: {}", rule_path.display());
+    println!("crux: installed Cursor hook: {}", hooks_path.display());
+    Ok(())
+}
+
+/// Remove the crux hook and rule from the project at `base_dir`. Returns
+/// false if neither was installed.
+pub fn uninstall_cursor(base_dir: &Path) -> Result<bool> {
+    let hooks_path = base_dir.join(HOOKS_FILE);
+    let rule_path = base_dir.join(RULE_FILE);
+    let mut removed = false;
+
+    let mut hooks = read_hooks(&hooks_path)?;
+    if remove_hook(&mut hooks) {
+        std::fs::write(&hooks_path, serde_json::to_string_pretty(&hooks)?)
+            .with_context(|| format!("failed to write {}", hooks_path.display()))?;
+        removed = true;
+    }
+    if rule_path.exists() {
+        std::fs::remove_file(&rule_path)
+            .with_context(|| format!("failed to remove {}", rule_path.display()))?;
+        // Only removes the directory if nothing else lives there
+        if let Some(rules_dir) = rule_path.parent() {
+            let _ = std::fs::remove_dir(rules_dir);
+        }
+        removed = true;
+    }
+    Ok(removed)
+}
+
+/// Whether the rule at `base_dir` is the one this version writes.
+pub fn rule_is_current(base_dir: &Path) -> bool {
+    std::fs::read_to_string(base_dir.join(RULE_FILE)).is_ok_and(|rule| rule == RULE)
+}
+
+/// The hooks file at `path`, or an empty one if there is none.
+pub fn read_hooks(path: &Path) -> Result<Value> {
+    if !path.exists() {
+        return Ok(serde_json::json!({ "version": 1 }));
+    }
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Add the crux hook to `hooks.beforeShellExecution`, replacing older crux
+/// entries.
+pub fn add_hook(hooks: &mut Value) -> Result<()> {
+    let entries = before_shell_execution(hooks)?;
+    entries.retain(|entry| !is_crux_entry(entry));
+    entries.push(serde_json::json!({ "command": HOOK_COMMAND }));
+    Ok(())
+}
+
+/// Whether `hooks` runs the crux hook.
+pub fn has_hook(hooks: &Value) -> bool {
+    hooks
+        .pointer("/hooks/beforeShellExecution")
+        .and_then(Value::as_array)
+        .is_some_and(|entries| entries.iter().any(is_crux_entry))
+}
+
+/// Remove crux entries from `hooks.beforeShellExecution`. Empty
+/// `beforeShellExecution` / `hooks` objects are dropped. Returns false if
+/// there were none.
+pub fn remove_hook(hooks: &mut Value) -> bool {
+    if !has_hook(hooks) {
+        return false;
+    }
+    let Some(obj) = hooks.as_object_mut() else {
+        return false;
+    };
+    if let Some(events) = obj.get_mut("hooks").and_then(Value::as_object_mut) {
+        if let Some(entries) = events
+            .get_mut("beforeShellExecution")
+            .and_then(Value::as_array_mut)
+        {
+            entries.retain(|entry| !is_crux_entry(entry));
+            if entries.is_empty() {
+                events.remove("beforeShellExecution");
+            }
+        }
+        if events.is_empty() {
+            obj.remove("hooks");
+        }
+    }
+    true
+}
+
+/// The `hooks.beforeShellExecution` array of `hooks`, created if missing.
+fn before_shell_execution(hooks: &mut Value) -> Result<&mut Vec<Value>> {
+    let obj = hooks
+        .as_object_mut()
+        .context("hooks.json is not an object")?;
+    obj.entry("version").or_insert_with(|| Value::from(1));
+    obj.entry("hooks")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .context("hooks is not an object")?
+        .entry("beforeShellExecution")
+        .or_insert_with(|| serde_json::json!([]))
+        .as_array_mut()
+        .context("beforeShellExecution is not an array")
+}
+
+fn is_crux_entry(entry: &Value) -> bool {
+    entry
+        .get("command")
+        .and_then(Value::as_str)
+        .is_some_and(|command| command.starts_with("crux "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn handle(raw: &str) -> HookOutput {
+        handle_hook(&serde_json::from_str(raw).unwrap())
+    }
+
+    #[test]
+    fn filtered_commands_are_sent_back_through_crux() {
+        let output = handle(
+            r#"{"conversation_id":"c","hook_event_name":"beforeShellExecution","command":"cd app && cargo test","cwd":"/p","workspace_roots":["/p"]}"#,
+        );
+        assert_eq!(output.permission, "deny");
+        assert!(output
+            .agent_message
+            .unwrap()
+            .ends_with(": cd app && crux run cargo test"));
+    }
+
+    #[test]
+    fn other_commands_and_events_are_allowed() {
+        let allowed = HookOutput::allow();
+        assert_eq!(handle(r#"{"command":"python x.py"}"#), allowed);
+        assert_eq!(handle(r#"{"command":"crux run git status"}"#), allowed);
+        assert_eq!(
+            handle(r#"{"hook_event_name":"beforeReadFile","command":"git status"}"#),
+            allowed
+        );
+        assert_eq!(
+            serde_json::to_string(&allowed).unwrap(),
+            r#"{"permission":"allow"}"#
+        );
+    }
+
+    #[test]
+    fn hook_is_added_once_and_removed() {
+        let mut hooks =
+            json!({"version": 1, "hooks": {"beforeShellExecution": [{"command": "./audit.sh"}]}});
+        add_hook(&mut hooks).unwrap();
+        add_hook(&mut hooks).unwrap();
+        assert!(has_hook(&hooks));
+        assert_eq!(
+            hooks["hooks"]["beforeShellExecution"],
+            json!([{"command": "./audit.sh"}, {"command": HOOK_COMMAND}])
+        );
+
+        assert!(remove_hook(&mut hooks));
+        assert_eq!(
+            hooks,
+            json!({"version": 1, "hooks": {"beforeShellExecution": [{"command": "./audit.sh"}]}})
+        );
+        assert!(!remove_hook(&mut hooks));
+    }
+
+    #[test]
+    fn install_writes_rule_and_hook() {
+        let dir = std::env::temp_dir().join(format!("crux-cursor-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        install_cursor(&dir, false).unwrap();
+        assert!(rule_is_current(&dir));
+        assert!(has_hook(&read_hooks(&dir.join(HOOKS_FILE)).unwrap()));
+
+        assert!(uninstall_cursor(&dir).unwrap());
+        assert!(!dir.join(RULE_FILE).exists());
+        assert!(!has_hook(&read_hooks(&dir.join(HOOKS_FILE)).unwrap()));
+        assert!(!uninstall_cursor(&dir).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
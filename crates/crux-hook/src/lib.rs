@@ -1,2 +1,8 @@
 pub mod claude;
 pub mod codex;
+pub mod compact;
+pub mod crush;
+pub mod cursor;
+pub mod opencode;
+mod rules;
+pub mod zed;
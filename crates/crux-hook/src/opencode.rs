@@ -0,0 +1,68 @@
+//! OpenCode hook integration for crux.
+//!
+//! OpenCode hooks are plugins: a `tool.execute.before` handler sends
+//! `crux hook handle --format opencode` the call as JSON and copies the
+//! `args` of the answer back into the tool call (see the README):
+//!
+//! ```json
+//! {"tool": "bash", "sessionID": "ses_1", "args": {"command": "git status"}}
+//! ```
+
+use serde_json::Value;
+
+use crate::claude::{rewrite_command, runner};
+
+/// Attributes runs to OpenCode (see `crux gain --group-by source`).
+const SOURCE: &str = "opencode";
+
+/// Process an OpenCode `tool.execute.before` call.
+///
+/// Returns `{"args": ...}` with the bash command routed through `crux run`,
+/// or `None` for passthrough (other tools and commands).
+pub fn handle_hook(event: &Value) -> Option<Value> {
+    if event.get("tool").and_then(Value::as_str) != Some("bash") {
+        return None;
+    }
+    let args = event.get("args")?;
+    let command = args.get("command").and_then(Value::as_str)?;
+    let session = event.get("sessionID").and_then(Value::as_str);
+
+    let rewritten = rewrite_command(command, &runner(Some(SOURCE), session))?;
+    let mut args = args.clone();
+    args["command"] = Value::String(rewritten);
+    Some(serde_json::json!({ "args": args }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bash_commands_are_rewritten_keeping_other_args() {
+        let event = json!({
+            "tool": "bash",
+            "sessionID": "ses_1",
+            "callID": "call_1",
+            "args": {"command": "cargo test", "timeout": 60000, "description": "Run tests"}
+        });
+        assert_eq!(
+            handle_hook(&event),
+            Some(json!({"args": {
+                "command": "CRUX_SOURCE=opencode CRUX_SESSION_ID=ses_1 crux run cargo test",
+                "timeout": 60000,
+                "description": "Run tests"
+            }}))
+        );
+    }
+
+    #[test]
+    fn other_tools_and_commands_pass_through() {
+        let read = json!({"tool": "read", "args": {"filePath": "/p/x.rs"}});
+        assert_eq!(handle_hook(&read), None);
+        let python = json!({"tool": "bash", "args": {"command": "python x.py"}});
+        assert_eq!(handle_hook(&python), None);
+        let missing = json!({"tool": "bash"});
+        assert_eq!(handle_hook(&missing), None);
+    }
+}
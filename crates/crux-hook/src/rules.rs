@@ -0,0 +1,52 @@
+//! The crux block in agent instructions files (Zed rules, Codex
+//! `AGENTS.md`). It sits between `<!-- crux -->` markers, so the rest of
+//! the file is left alone.
+
+const RULES_START: &str = "<!-- crux -->";
+const RULES_END: &str = "<!-- /crux -->";
+
+/// Whether `rules` has a crux block.
+pub(crate) fn has_rules(rules: &str) -> bool {
+    rules.contains(RULES_START)
+}
+
+/// `rules` with a crux block holding `block` added, or replaced if already
+/// there.
+pub(crate) fn with_rules(rules: &str, block: &str) -> String {
+    let mut out = without_rules(rules).unwrap_or_else(|| rules.to_string());
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+    out.push_str(&format!("{RULES_START}\n{block}{RULES_END}\n"));
+    out
+}
+
+/// `rules` without the crux block, or `None` if it has none.
+pub(crate) fn without_rules(rules: &str) -> Option<String> {
+    let start = rules.find(RULES_START)?;
+    let end = rules[start..].find(RULES_END)? + start + RULES_END.len();
+    let before = rules[..start].trim_end_matches('\n');
+    let after = rules[end..].trim_start_matches('\n');
+    Some(match (before.is_empty(), after.is_empty()) {
+        (true, _) => after.to_string(),
+        (false, true) => format!("{before}\n"),
+        (false, false) => format!("{before}\n\n{after}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: &str = "Run commands through crux.\n";
+
+    #[test]
+    fn block_is_added_once_and_removed() {
+        let rules = with_rules("Use tabs.\n", BLOCK);
+        assert!(rules.starts_with("Use tabs.\n\n<!-- crux -->\n"));
+        assert_eq!(with_rules(&rules, BLOCK), rules);
+        assert_eq!(without_rules(&rules).as_deref(), Some("Use tabs.\n"));
+        assert_eq!(without_rules(&with_rules("", BLOCK)).as_deref(), Some(""));
+        assert_eq!(without_rules("Use tabs.\n"), None);
+    }
+}
@@ -0,0 +1,272 @@
+//! Zed integration for crux.
+//!
+//! Zed has no hook that runs before its agent's terminal tool, so
+//! `crux init --zed` works through the project instead:
+//!
+//! - tasks in `.zed/tasks.json` whose command crux has a filter for are
+//!   rewritten to `crux run <command>`;
+//! - a marked block is added to the rules file Zed's agent reads, asking it
+//!   to run such commands through crux.
+//!
+//! `crux hook handle --format zed` rewrites a terminal tool call
+//! (`{"command": "cargo test", "cd": "app"}`) for tools that sit in front of
+//! Zed's agent.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::claude::{rewrite_command, runner, should_intercept};
+use crate::rules::{has_rules, with_rules, without_rules};
+
+/// Zed tasks file, relative to the project.
+pub const TASKS_FILE: &str = ".zed/tasks.json";
+
+/// Rules files Zed's agent reads, in its order of preference; it uses only
+/// the first one that exists.
+const RULES_FILES: &[&str] = &[
+    ".rules",
+    ".cursorrules",
+    ".windsurfrules",
+    ".clinerules",
+    ".github/copilot-instructions.md",
+    "AGENT.md",
+    "AGENTS.md",
+    "CLAUDE.md",
+    "GEMINI.md",
+];
+
+const RULES: &str = "Run build, test, lint, git, package manager and container commands in the
+terminal through crux, which filters their output down to what matters:
+`crux run cargo test`, `cd app && crux run npm test`. `crux run --raw
+<command>` shows the unfiltered output when it is needed.
+";
+
+/// Attributes runs to Zed (see `crux gain --group-by source`).
+const SOURCE: &str = "zed";
+
+/// Process a Zed terminal tool call.
+///
+/// Returns the call with its command routed through `crux run`, or `None`
+/// for passthrough.
+pub fn handle_hook(event: &Value) -> Option<Value> {
+    let command = event.get("command").and_then(Value::as_str)?;
+    let rewritten = rewrite_command(command, &runner(Some(SOURCE), None))?;
+    let mut event = event.clone();
+    event["command"] = Value::String(rewritten);
+    Some(event)
+}
+
+/// Install the Zed integration in the project at `base_dir`. With
+/// `print`, only show what would be written.
+pub fn install_zed(base_dir: &Path, print: bool) -> Result<()> {
+    let tasks_path = base_dir.join(TASKS_FILE);
+    let mut tasks = read_tasks(&tasks_path)?;
+    let wrapped = tasks.as_array_mut().map_or(0, |tasks| {
+        tasks
+            .iter_mut()
+            .map(wrap_task)
+            .filter(|&wrapped| wrapped)
+            .count()
+    });
+    let rules_path = rules_path(base_dir);
+    let rules = with_rules(
+        &std::fs::read_to_string(&rules_path).unwrap_or_default(),
+        RULES,
+    );
+
+    if print {
+        println!("# {}", rules_path.display());
+        println!("{rules}");
+        if wrapped > 0 {
+            println!("# {}", tasks_path.display());
+            println!("{}", serde_json::to_string_pretty(&tasks)?);
+        }
+        return Ok(());
+    }
+
+    if let Some(parent) = rules_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+    }
+    std::fs::write(&rules_path, rules)
+        .with_context(|| format!("failed to write {}", rules_path.display()))?;
+    println!(
+        "crux: added crux rules for Zed's agent: {}",
+        rules_path.display()
+    );
+
+    if wrapped > 0 {
+        std::fs::write(&tasks_path, serde_json::to_string_pretty(&tasks)?)
+            .with_context(|| format!("failed to write {}", tasks_path.display()))?;
+        println!(
+            "crux: routed {wrapped} Zed task{} through crux: {}",
+            if wrapped == 1 { "" } else { "s" },
+            tasks_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Remove the crux rules and task rewrites from the project at `base_dir`.
+/// Returns false if there were none.
+pub fn uninstall_zed(base_dir: &Path) -> Result<bool> {
+    let mut removed = false;
+
+    let rules_path = rules_path(base_dir);
+    if let Ok(rules) = std::fs::read_to_string(&rules_path) {
+        if let Some(stripped) = without_rules(&rules) {
+            if stripped.trim().is_empty() {
+                std::fs::remove_file(&rules_path)
+                    .with_context(|| format!("failed to remove {}", rules_path.display()))?;
+            } else {
+                std::fs::write(&rules_path, stripped)
+                    .with_context(|| format!("failed to write {}", rules_path.display()))?;
+            }
+            removed = true;
+        }
+    }
+
+    let tasks_path = base_dir.join(TASKS_FILE);
+    let mut tasks = read_tasks(&tasks_path)?;
+    let unwrapped = tasks.as_array_mut().map_or(0, |tasks| {
+        tasks
+            .iter_mut()
+            .map(unwrap_task)
+            .filter(|&unwrapped| unwrapped)
+            .count()
+    });
+    if unwrapped > 0 {
+        std::fs::write(&tasks_path, serde_json::to_string_pretty(&tasks)?)
+            .with_context(|| format!("failed to write {}", tasks_path.display()))?;
+        removed = true;
+    }
+    Ok(removed)
+}
+
+/// Whether the crux rules are in the project's rules file.
+pub fn is_installed(base_dir: &Path) -> bool {
+    std::fs::read_to_string(rules_path(base_dir)).is_ok_and(|rules| has_rules(&rules))
+}
+
+/// The rules file Zed's agent reads in `base_dir`: the first one that
+/// exists, or `.rules`.
+pub fn rules_path(base_dir: &Path) -> PathBuf {
+    RULES_FILES
+        .iter()
+        .map(|name| base_dir.join(name))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| base_dir.join(RULES_FILES[0]))
+}
+
+/// The tasks in `path`, or none if there is no tasks file.
+fn read_tasks(path: &Path) -> Result<Value> {
+    if !path.exists() {
+        return Ok(Value::Array(Vec::new()));
+    }
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    match serde_json::from_str(&contents) {
+        Ok(tasks) => Ok(tasks),
+        Err(e) => bail!(
+            "cannot parse {} ({e}); comments are not supported, route tasks through `crux run` by hand",
+            path.display()
+        ),
+    }
+}
+
+/// Prefix a task's command with `crux run` if crux has a filter for it.
+fn wrap_task(task: &mut Value) -> bool {
+    let Some(command) = task.get("command").and_then(Value::as_str) else {
+        return false;
+    };
+    let args: Vec<&str> = task
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|args| args.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let full = std::iter::once(command)
+        .chain(args)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !should_intercept(&full) {
+        return false;
+    }
+    task["command"] = Value::String(format!("crux run {command}"));
+    true
+}
+
+/// Undo [`wrap_task`].
+fn unwrap_task(task: &mut Value) -> bool {
+    let Some(command) = task
+        .get("command")
+        .and_then(Value::as_str)
+        .and_then(|command| command.strip_prefix("crux run "))
+    else {
+        return false;
+    };
+    task["command"] = Value::String(command.to_string());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn terminal_tool_calls_are_rewritten() {
+        let call = json!({"command": "cargo test", "cd": "app"});
+        assert_eq!(
+            handle_hook(&call),
+            Some(json!({"command": "CRUX_SOURCE=zed crux run cargo test", "cd": "app"}))
+        );
+        assert_eq!(
+            handle_hook(&json!({"command": "python x.py", "cd": "."})),
+            None
+        );
+        assert_eq!(handle_hook(&json!({"path": "x"})), None);
+    }
+
+    #[test]
+    fn tasks_crux_filters_are_wrapped_and_unwrapped() {
+        let mut test = json!({"label": "test", "command": "cargo", "args": ["test"]});
+        let mut serve = json!({"label": "serve", "command": "python -m http.server"});
+        assert!(wrap_task(&mut test));
+        assert_eq!(test["command"], "crux run cargo");
+        assert!(!wrap_task(&mut serve));
+        assert!(!wrap_task(&mut test.clone()), "already wrapped");
+
+        assert!(unwrap_task(&mut test));
+        assert_eq!(test["command"], "cargo");
+        assert!(!unwrap_task(&mut serve));
+    }
+
+    #[test]
+    fn install_uses_the_rules_file_zed_reads() {
+        let dir = std::env::temp_dir().join(format!("crux-zed-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(".zed")).unwrap();
+        std::fs::write(dir.join("AGENTS.md"), "# Agents\n").unwrap();
+        std::fs::write(
+            dir.join(TASKS_FILE),
+            r#"[{"label": "check", "command": "cargo check"}]"#,
+        )
+        .unwrap();
+
+        install_zed(&dir, false).unwrap();
+        assert!(!dir.join(".rules").exists());
+        assert!(is_installed(&dir));
+        let tasks = std::fs::read_to_string(dir.join(TASKS_FILE)).unwrap();
+        assert!(tasks.contains("crux run cargo check"));
+
+        assert!(uninstall_zed(&dir).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("AGENTS.md")).unwrap(),
+            "# Agents\n"
+        );
+        assert!(!is_installed(&dir));
+        assert!(!uninstall_zed(&dir).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
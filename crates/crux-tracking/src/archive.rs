@@ -0,0 +1,236 @@
+use anyhow::{bail, Context, Result};
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use serde_json::{Map, Value as Json};
+
+use crate::export::{to_json, ExportTable};
+use crate::history::{compress, read_output};
+
+/// Archive format version written by [`dump`]; [`import`] rejects others.
+pub const ARCHIVE_VERSION: u64 = 1;
+
+const TABLES: [(&str, ExportTable); 2] = [
+    ("events", ExportTable::Events),
+    ("history", ExportTable::History),
+];
+
+/// Rows added and skipped by [`import`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportStats {
+    pub events: usize,
+    pub history: usize,
+    /// Rows already present (same timestamp and command).
+    pub skipped: usize,
+}
+
+/// Serialize the whole database to a portable JSON archive. Row ids are
+/// left out and history outputs are stored decompressed.
+pub fn dump(conn: &Connection) -> Result<Json> {
+    let mut archive = Map::new();
+    archive.insert("version".to_string(), ARCHIVE_VERSION.into());
+    for (key, table) in TABLES {
+        let columns = data_columns(table);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM {} ORDER BY timestamp, id",
+            columns.join(", "),
+            table.name()
+        ))?;
+        let mut rows = stmt.query([])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            let mut object = Map::new();
+            for (idx, column) in columns.iter().enumerate() {
+                let value = if column.ends_with("_output") {
+                    Json::String(read_output(row, idx)?)
+                } else {
+                    to_json(row.get_ref(idx)?)
+                };
+                object.insert(column.to_string(), value);
+            }
+            out.push(Json::Object(object));
+        }
+        archive.insert(key.to_string(), Json::Array(out));
+    }
+    Ok(Json::Object(archive))
+}
+
+/// Merge an archive written by [`dump`] into `conn`, skipping rows whose
+/// timestamp and command are already recorded. Runs in one transaction.
+pub fn import(conn: &mut Connection, archive: &Json) -> Result<ImportStats> {
+    match archive["version"].as_u64() {
+        Some(ARCHIVE_VERSION) => {}
+        Some(v) => bail!("unsupported archive version {v} (expected {ARCHIVE_VERSION})"),
+        None => bail!("not a crux tracking archive"),
+    }
+
+    let tx = conn.transaction()?;
+    let mut stats = ImportStats::default();
+    for (key, table) in TABLES {
+        let Some(rows) = archive[key].as_array() else {
+            continue;
+        };
+        let columns = data_columns(table);
+        let mut insert_columns = columns.clone();
+        if table == ExportTable::History {
+            insert_columns.push("raw_bytes");
+        }
+        let placeholders: Vec<String> = (1..=insert_columns.len())
+            .map(|i| format!("?{i}"))
+            .collect();
+        let mut exists = tx.prepare(&format!(
+            "SELECT 1 FROM {} WHERE timestamp = ?1 AND command = ?2",
+            table.name()
+        ))?;
+        let mut insert = tx.prepare(&format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table.name(),
+            insert_columns.join(", "),
+            placeholders.join(", ")
+        ))?;
+
+        for (n, row) in rows.iter().enumerate() {
+            let object = row
+                .as_object()
+                .with_context(|| format!("{key}[{n}] is not an object"))?;
+            let timestamp = object.get("timestamp").and_then(Json::as_str);
+            let command = object.get("command").and_then(Json::as_str);
+            let (Some(timestamp), Some(command)) = (timestamp, command) else {
+                bail!("{key}[{n}] has no timestamp or command");
+            };
+            if exists.exists(rusqlite::params![timestamp, command])? {
+                stats.skipped += 1;
+                continue;
+            }
+
+            let mut values = Vec::with_capacity(insert_columns.len());
+            for column in &columns {
+                let value = object.get(*column).unwrap_or(&Json::Null);
+                values.push(if column.ends_with("_output") {
+                    Value::Blob(compress(value.as_str().unwrap_or_default())?)
+                } else {
+                    from_json(value)
+                });
+            }
+            if table == ExportTable::History {
+                let raw = object.get("raw_output").and_then(Json::as_str);
+                values.push(Value::Integer(raw.unwrap_or_default().len() as i64));
+            }
+            insert.execute(rusqlite::params_from_iter(values))?;
+            match table {
+                ExportTable::Events => stats.events += 1,
+                ExportTable::History => stats.history += 1,
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(stats)
+}
+
+/// Exported columns minus the row id, which is local to each database.
+fn data_columns(table: ExportTable) -> Vec<&'static str> {
+    table
+        .columns()
+        .iter()
+        .copied()
+        .filter(|column| *column != "id")
+        .collect()
+}
+
+fn from_json(value: &Json) -> Value {
+    match value {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Integer(*b as i64),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        Json::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_memory_db;
+    use crate::events::{get_gain_summary, record_event, FilterEvent, TimeRange};
+    use crate::history::{get_recent_history, store_history};
+
+    fn event(command: &str) -> FilterEvent {
+        FilterEvent {
+            command: command.to_string(),
+            filter_name: Some("git status".to_string()),
+            input_bytes: 1000,
+            output_bytes: 100,
+            exit_code: 0,
+            duration_ms: Some(5),
+            filter_duration_us: Some(120),
+            input_tokens: None,
+            output_tokens: None,
+            raw_fallback: false,
+            session_id: Some("abc".to_string()),
+            stage_savings: Vec::new(),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_dump_and_import_round_trip() {
+        let source = open_memory_db().unwrap();
+        record_event(&source, &event("git status")).unwrap();
+        store_history(&source, "git status", "On branch main\n", "main", None, 0).unwrap();
+
+        let archive = dump(&source).unwrap();
+        assert_eq!(archive["version"], 1);
+        assert_eq!(archive["history"][0]["raw_output"], "On branch main\n");
+        assert!(archive["events"][0].get("id").is_none());
+
+        // Round-trip through text, as `crux tracking import` reads a file
+        let archive: Json = serde_json::from_str(&archive.to_string()).unwrap();
+        let mut target = open_memory_db().unwrap();
+        record_event(&target, &event("cargo build")).unwrap();
+        let stats = import(&mut target, &archive).unwrap();
+        assert_eq!(
+            stats,
+            ImportStats {
+                events: 1,
+                history: 1,
+                skipped: 0
+            }
+        );
+
+        let summary = get_gain_summary(&target, &TimeRange::default()).unwrap();
+        assert_eq!(summary.total_events, 2);
+        assert_eq!(summary.total_savings_bytes, 1800);
+        let history = get_recent_history(&target, 10).unwrap();
+        assert_eq!(history[0].raw_output, "On branch main\n");
+        assert_eq!(history[0].filtered_output, "main");
+    }
+
+    #[test]
+    fn test_import_skips_duplicates() {
+        let source = open_memory_db().unwrap();
+        record_event(&source, &event("git status")).unwrap();
+        store_history(&source, "git status", "a", "a", None, 0).unwrap();
+        let archive = dump(&source).unwrap();
+
+        let mut target = open_memory_db().unwrap();
+        import(&mut target, &archive).unwrap();
+        let stats = import(&mut target, &archive).unwrap();
+        assert_eq!(
+            stats,
+            ImportStats {
+                events: 0,
+                history: 0,
+                skipped: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_unknown_archives() {
+        let mut conn = open_memory_db().unwrap();
+        assert!(import(&mut conn, &serde_json::json!({"events": []})).is_err());
+        assert!(import(&mut conn, &serde_json::json!({"version": 2})).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! At-rest encryption of history outputs with a local key file
+//! (`encryption` feature).
+//!
+//! Encrypted outputs are `MAGIC || nonce || ciphertext` of the zstd frame,
+//! sealed with XChaCha20-Poly1305. Rows written without encryption stay
+//! readable, so it can be turned on (or off) at any time.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Result};
+
+/// Prefix of encrypted outputs; zstd frames start with a different magic.
+pub(crate) const MAGIC: &[u8] = b"CRUXENC1";
+
+#[cfg(feature = "encryption")]
+const NONCE_LEN: usize = 24;
+#[cfg(feature = "encryption")]
+const KEY_LEN: usize = 32;
+
+struct State {
+    #[cfg(feature = "encryption")]
+    key: Option<[u8; KEY_LEN]>,
+    encrypt: bool,
+}
+
+static STATE: OnceLock<State> = OnceLock::new();
+
+/// Set up encryption for this process: load the key in `key_file` so
+/// encrypted outputs can be read, and with `encrypt` seal new outputs,
+/// creating the key file (mode 0600) if needed. Only the first call counts.
+#[cfg(feature = "encryption")]
+pub fn init(key_file: &Path, encrypt: bool) -> Result<()> {
+    let key = if key_file.exists() {
+        Some(read_key(key_file)?)
+    } else if encrypt {
+        Some(create_key(key_file)?)
+    } else {
+        None
+    };
+    let _ = STATE.set(State { key, encrypt });
+    Ok(())
+}
+
+/// Without the `encryption` feature nothing can be sealed, so asking for
+/// it is an error rather than a silent fallback to plaintext.
+#[cfg(not(feature = "encryption"))]
+pub fn init(key_file: &Path, encrypt: bool) -> Result<()> {
+    let _ = key_file;
+    if encrypt {
+        bail!("history encryption needs crux built with the `encryption` feature");
+    }
+    let _ = STATE.set(State { encrypt });
+    Ok(())
+}
+
+/// Whether new outputs are sealed.
+pub fn is_encrypting() -> bool {
+    STATE.get().is_some_and(|state| state.encrypt)
+}
+
+/// Encrypt a compressed output if encryption is on.
+pub(crate) fn seal(compressed: Vec<u8>) -> Result<Vec<u8>> {
+    #[cfg(feature = "encryption")]
+    if let Some(State {
+        key: Some(key),
+        encrypt: true,
+    }) = STATE.get()
+    {
+        return seal_with(key, &compressed);
+    }
+    Ok(compressed)
+}
+
+/// Decrypt a stored output, passing unencrypted ones through.
+pub(crate) fn open(stored: &[u8]) -> Result<std::borrow::Cow<'_, [u8]>> {
+    let Some(sealed) = stored.strip_prefix(MAGIC) else {
+        return Ok(std::borrow::Cow::Borrowed(stored));
+    };
+    #[cfg(feature = "encryption")]
+    if let Some(State { key: Some(key), .. }) = STATE.get() {
+        return open_with(key, sealed).map(std::borrow::Cow::Owned);
+    }
+    let _ = sealed;
+    if cfg!(feature = "encryption") {
+        bail!("history output is encrypted and no key file was found");
+    }
+    bail!("history output is encrypted; this crux was built without the `encryption` feature")
+}
+
+#[cfg(feature = "encryption")]
+fn seal_with(key: &[u8; KEY_LEN], plain: &[u8]) -> Result<Vec<u8>> {
+    use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+    use chacha20poly1305::XChaCha20Poly1305;
+
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let sealed = cipher
+        .encrypt(&nonce, plain)
+        .map_err(|_| anyhow::anyhow!("encrypting history output"))?;
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + sealed.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+#[cfg(feature = "encryption")]
+fn open_with(key: &[u8; KEY_LEN], sealed: &[u8]) -> Result<Vec<u8>> {
+    use chacha20poly1305::aead::{Aead, KeyInit};
+    use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+    if sealed.len() < NONCE_LEN {
+        bail!("encrypted history output is truncated");
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("cannot decrypt history output (wrong key file?)"))
+}
+
+#[cfg(feature = "encryption")]
+fn read_key(path: &Path) -> Result<[u8; KEY_LEN]> {
+    use anyhow::Context;
+
+    let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("{} is not a {KEY_LEN}-byte key", path.display()))
+}
+
+#[cfg(feature = "encryption")]
+fn create_key(path: &Path) -> Result<[u8; KEY_LEN]> {
+    use anyhow::Context;
+    use chacha20poly1305::aead::{KeyInit, OsRng};
+    use std::io::Write;
+
+    let key: [u8; KEY_LEN] = chacha20poly1305::XChaCha20Poly1305::generate_key(&mut OsRng).into();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("creating {}", path.display()))?;
+    file.write_all(&key)?;
+    Ok(key)
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_output_round_trips_with_its_key_only() {
+        let key = [7u8; KEY_LEN];
+        let sealed = seal_with(&key, b"token=abc").unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert!(!sealed.windows(9).any(|w| w == b"token=abc"));
+        assert_eq!(
+            open_with(&key, &sealed[MAGIC.len()..]).unwrap(),
+            b"token=abc"
+        );
+        assert!(open_with(&[8u8; KEY_LEN], &sealed[MAGIC.len()..]).is_err());
+    }
+
+    #[test]
+    fn key_file_is_created_once() {
+        let dir = std::env::temp_dir().join(format!("crux-key-{}", std::process::id()));
+        let path = dir.join("history.key");
+        let created = create_key(&path).unwrap();
+        assert_eq!(read_key(&path).unwrap(), created);
+        assert!(create_key(&path).is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
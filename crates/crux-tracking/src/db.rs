@@ -1,15 +1,16 @@
-use anyhow::Result;
-use rusqlite::Connection;
+use anyhow::{Context, Result};
+use rusqlite::{Connection, TransactionBehavior};
 use std::path::PathBuf;
 
 /// Get the default database path (~/.local/share/crux/crux.db)
 pub fn default_db_path() -> Result<PathBuf> {
-    let data_dir = dirs_or_fallback();
+    let data_dir = data_dir();
     std::fs::create_dir_all(&data_dir)?;
     Ok(data_dir.join("crux.db"))
 }
 
-fn dirs_or_fallback() -> PathBuf {
+/// The directory holding the database, without creating it.
+pub fn data_dir() -> PathBuf {
     // Use XDG_DATA_HOME or fallback to ~/.local/share/crux
     std::env::var("XDG_DATA_HOME")
         .map(|d| PathBuf::from(d).join("crux"))
@@ -22,6 +23,8 @@ fn dirs_or_fallback() -> PathBuf {
 /// Open or create the database, run migrations
 pub fn open_db(path: &std::path::Path) -> Result<Connection> {
     let conn = Connection::open(path)?;
+    // Runs are recorded by background processes that may overlap
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
     migrate(&conn)?;
     Ok(conn)
 }
@@ -33,7 +36,71 @@ pub fn open_memory_db() -> Result<Connection> {
     Ok(conn)
 }
 
+/// A forward-only schema change, applied once per database.
+type Migration = fn(&Connection) -> Result<()>;
+
+/// Schema changes in the order they shipped; a database at version `n` has
+/// had the first `n` applied. Append new migrations, never edit or reorder
+/// released ones. Migrations up to `notes_and_tags` predate versioning and
+/// also run on old unversioned databases at any stage, so they must be
+/// idempotent.
+const MIGRATIONS: &[Migration] = &[
+    create_tables,
+    add_raw_fallback,
+    add_history_exit_code,
+    add_filter_duration,
+    add_token_counts,
+    add_session_id,
+    compress_history,
+    notes_and_tags,
+    add_stage_savings,
+    add_source,
+    add_history_truncated,
+];
+
+/// Schema version of a fully migrated database.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Bring the schema up to [`SCHEMA_VERSION`], one migration per
+/// transaction. A database from a newer crux is left as it is: schema
+/// changes only add tables and columns, which older code ignores.
 fn migrate(conn: &Connection) -> Result<()> {
+    conn.execute_batch("CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)")?;
+    // Every open lands here, so an up-to-date database must not wait on
+    // the write lock another crux process may be holding.
+    let current = schema_version(conn)?;
+    if current >= SCHEMA_VERSION {
+        return Ok(());
+    }
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let version = index as u32 + 1;
+        // IMMEDIATE takes the write lock up front, so concurrent crux
+        // processes apply each migration exactly once.
+        let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        if schema_version(&tx)? >= version {
+            continue;
+        }
+        migration(&tx).with_context(|| format!("migrating tracking database to v{version}"))?;
+        tx.execute("DELETE FROM schema_version", [])?;
+        tx.execute(
+            "INSERT INTO schema_version (version) VALUES (?1)",
+            [version],
+        )?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Number of migrations applied to `conn`'s database.
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    let version: Option<u32> =
+        conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+            row.get(0)
+        })?;
+    Ok(version.unwrap_or(0))
+}
+
+fn create_tables(conn: &Connection) -> Result<()> {
     conn.execute_batch(
         "
         CREATE TABLE IF NOT EXISTS filter_events (
@@ -61,11 +128,264 @@ fn migrate(conn: &Connection) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_events_timestamp ON filter_events(timestamp);
         CREATE INDEX IF NOT EXISTS idx_events_command ON filter_events(command);
         CREATE INDEX IF NOT EXISTS idx_history_timestamp ON history(timestamp);
+
+        CREATE TABLE IF NOT EXISTS meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
     ",
     )?;
     Ok(())
 }
 
+fn add_raw_fallback(conn: &Connection) -> Result<()> {
+    add_column_if_missing(
+        conn,
+        "filter_events",
+        "raw_fallback",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    Ok(())
+}
+
+fn add_history_exit_code(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "history", "exit_code", "INTEGER")?;
+    Ok(())
+}
+
+fn add_filter_duration(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "filter_events", "filter_duration_us", "INTEGER")?;
+    Ok(())
+}
+
+fn add_token_counts(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "filter_events", "input_tokens", "INTEGER")?;
+    add_column_if_missing(conn, "filter_events", "output_tokens", "INTEGER")?;
+    Ok(())
+}
+
+fn add_session_id(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "filter_events", "session_id", "TEXT")?;
+    Ok(())
+}
+
+fn compress_history(conn: &Connection) -> Result<()> {
+    if add_column_if_missing(conn, "history", "raw_bytes", "INTEGER")? {
+        crate::history::compress_legacy_rows(conn)?;
+    }
+    Ok(())
+}
+
+fn notes_and_tags(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "history", "notes", "TEXT")?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS history_tags (
+            history_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (history_id, tag)
+        );",
+    )?;
+    Ok(())
+}
+
+fn add_stage_savings(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE filter_events ADD COLUMN stage_savings TEXT")?;
+    Ok(())
+}
+
+fn add_source(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE filter_events ADD COLUMN source TEXT")?;
+    Ok(())
+}
+
+fn add_history_truncated(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE history ADD COLUMN truncated INTEGER DEFAULT 0")?;
+    Ok(())
+}
+
+/// Rows removed by [`prune`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PruneStats {
+    pub events: usize,
+    pub history: usize,
+}
+
+/// Delete events and history entries recorded before `before` (an SQLite
+/// datetime) and, per table, all but the newest `keep_last` rows.
+pub fn prune(
+    conn: &Connection,
+    before: Option<&str>,
+    keep_last: Option<usize>,
+) -> Result<PruneStats> {
+    let keep_last = keep_last.map(|n| n as i64);
+    let mut stats = PruneStats::default();
+    for (table, count) in [
+        ("filter_events", &mut stats.events),
+        ("history", &mut stats.history),
+    ] {
+        *count = conn.execute(
+            &format!(
+                "DELETE FROM {table}
+                 WHERE (?1 IS NOT NULL AND timestamp < ?1)
+                    OR (?2 IS NOT NULL AND id NOT IN (
+                        SELECT id FROM {table} ORDER BY timestamp DESC, id DESC LIMIT ?2))"
+            ),
+            rusqlite::params![before, keep_last],
+        )?;
+    }
+    conn.execute(
+        "DELETE FROM history_tags WHERE history_id NOT IN (SELECT id FROM history)",
+        [],
+    )?;
+    Ok(stats)
+}
+
+/// Limits on stored tracking data, from `[retention]` in config.toml.
+#[derive(Debug, Default, Clone)]
+pub struct Retention {
+    /// Drop rows older than this (`30d`, `12h`, `2w` or a date).
+    pub max_age: Option<String>,
+    /// Keep at most this many rows per table.
+    pub max_rows: Option<usize>,
+    /// Drop the oldest history entries while the data in the database
+    /// takes more than this many bytes. History is kept when the rest of
+    /// the data alone is over the limit.
+    pub max_db_bytes: Option<u64>,
+}
+
+impl Retention {
+    pub fn is_empty(&self) -> bool {
+        self.max_age.is_none() && self.max_rows.is_none() && self.max_db_bytes.is_none()
+    }
+}
+
+/// How long [`maybe_apply_retention`] waits between prunes.
+const RETENTION_INTERVAL: &str = "-1 hours";
+
+/// Apply `retention` unless that was done within the last hour, so the
+/// cost of pruning is paid rarely rather than on every recorded run.
+/// Returns `None` when skipped.
+pub fn maybe_apply_retention(
+    conn: &Connection,
+    retention: &Retention,
+) -> Result<Option<PruneStats>> {
+    if retention.is_empty() {
+        return Ok(None);
+    }
+    let recent: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM meta WHERE key = 'last_prune' AND value > datetime('now', ?1))",
+        [RETENTION_INTERVAL],
+        |row| row.get(0),
+    )?;
+    if recent {
+        return Ok(None);
+    }
+    let stats = apply_retention(conn, retention)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES ('last_prune', datetime('now'))",
+        [],
+    )?;
+    Ok(Some(stats))
+}
+
+/// Delete whatever `retention` does not allow to be kept.
+pub fn apply_retention(conn: &Connection, retention: &Retention) -> Result<PruneStats> {
+    let before = retention
+        .max_age
+        .as_deref()
+        .map(|age| crate::events::TimeRange::parse(conn, Some(age), None))
+        .transpose()?
+        .and_then(|range| range.since);
+    let mut stats = prune(conn, before.as_deref(), retention.max_rows)?;
+
+    if let Some(max_bytes) = retention.max_db_bytes {
+        stats.history += trim_history(conn, max_bytes)?;
+        conn.execute(
+            "DELETE FROM history_tags WHERE history_id NOT IN (SELECT id FROM history)",
+            [],
+        )?;
+    }
+    Ok(stats)
+}
+
+/// Drop the oldest history entries, a tenth per round, while the database
+/// takes more than `max_bytes`. Returns how many entries were deleted.
+///
+/// History holds the full outputs, so it is all that gets trimmed. When the
+/// rest of the database alone is over `max_bytes`, no amount of trimming
+/// gets under it and history is left alone; a round that frees no space
+/// also ends the trimming.
+fn trim_history(conn: &Connection, max_bytes: u64) -> Result<usize> {
+    let mut used = used_bytes(conn)?;
+    if used.saturating_sub(history_bytes(conn)?) >= max_bytes {
+        return Ok(0);
+    }
+    let mut deleted = 0;
+    while used > max_bytes {
+        let remaining: i64 =
+            conn.query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))?;
+        if remaining == 0 {
+            break;
+        }
+        let keep = remaining - (remaining / 10).max(1);
+        deleted += conn.execute(
+            "DELETE FROM history WHERE id NOT IN (
+                SELECT id FROM history ORDER BY timestamp DESC, id DESC LIMIT ?1)",
+            [keep],
+        )?;
+        let now = used_bytes(conn)?;
+        if now >= used {
+            break;
+        }
+        used = now;
+    }
+    Ok(deleted)
+}
+
+/// Bytes of output stored in history, the bulk of what its pages hold.
+fn history_bytes(conn: &Connection) -> Result<u64> {
+    let bytes: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(length(CAST(raw_output AS BLOB))
+                           + length(CAST(filtered_output AS BLOB))), 0)
+         FROM history",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(bytes as u64)
+}
+
+/// Bytes of the database file in use (excluding free pages left by deletes).
+fn used_bytes(conn: &Connection) -> Result<u64> {
+    let used: i64 = conn.query_row(
+        "SELECT (page_count - freelist_count) * page_size
+         FROM pragma_page_count(), pragma_freelist_count(), pragma_page_size()",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(used as u64)
+}
+
+/// Rebuild the database file to reclaim the space of deleted rows.
+pub fn vacuum(conn: &Connection) -> Result<()> {
+    conn.execute_batch("VACUUM")?;
+    Ok(())
+}
+
+/// Add a column to an existing table unless it is already there
+/// (databases created by older versions lack newer columns). Returns
+/// whether the column was added.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<bool> {
+    let exists = conn
+        .prepare(&format!(
+            "SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"
+        ))?
+        .exists([column])?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))?;
+    }
+    Ok(!exists)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,9 +413,280 @@ mod tests {
     }
 
     #[test]
-    fn test_dirs_or_fallback_default() {
+    fn test_migrate_records_schema_version() {
+        let conn = open_memory_db().unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[test]
+    fn test_migrate_resumes_from_recorded_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE schema_version (version INTEGER NOT NULL);
+             INSERT INTO schema_version (version) VALUES (1);",
+        )
+        .unwrap();
+        create_tables(&conn).unwrap();
+        migrate(&conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+
+        // A database from a newer crux is opened without touching it
+        conn.execute("UPDATE schema_version SET version = 999", [])
+            .unwrap();
+        migrate(&conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 999);
+    }
+
+    #[test]
+    fn test_migrate_skips_write_lock_when_up_to_date() {
+        let path = std::env::temp_dir().join(format!("crux-migrate-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        drop(open_db(&path).unwrap());
+
+        let writer = Connection::open(&path).unwrap();
+        writer.execute_batch("BEGIN IMMEDIATE").unwrap();
+        let reader = Connection::open(&path).unwrap();
+        reader.busy_timeout(std::time::Duration::ZERO).unwrap();
+        migrate(&reader).expect("an up-to-date database needs no write lock");
+        writer.execute_batch("ROLLBACK").unwrap();
+
+        drop((reader, writer));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_adds_missing_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE filter_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL DEFAULT (datetime('now')),
+                command TEXT NOT NULL,
+                filter_name TEXT,
+                input_bytes INTEGER NOT NULL,
+                output_bytes INTEGER NOT NULL,
+                savings_bytes INTEGER NOT NULL,
+                savings_pct REAL NOT NULL,
+                exit_code INTEGER NOT NULL DEFAULT 0,
+                duration_ms INTEGER
+            );
+            CREATE TABLE history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL DEFAULT (datetime('now')),
+                command TEXT NOT NULL,
+                raw_output TEXT NOT NULL,
+                filtered_output TEXT NOT NULL,
+                filter_name TEXT
+            );
+            INSERT INTO history (command, raw_output, filtered_output)
+            VALUES ('ls', 'a\nb\nc', 'a');",
+        )
+        .unwrap();
+        migrate(&conn).expect("migration should upgrade old schema");
+
+        let fallback: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('filter_events') WHERE name = 'raw_fallback'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(fallback, 1);
+
+        let exit_code: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('history') WHERE name = 'exit_code'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(exit_code, 1);
+
+        let (stored_type, raw_bytes): (String, i64) = conn
+            .query_row(
+                "SELECT typeof(raw_output), raw_bytes FROM history",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(stored_type, "blob");
+        assert_eq!(raw_bytes, 5);
+        let entry = crate::history::get_history_entry(&conn, 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.raw_output, "a\nb\nc");
+        assert_eq!(entry.filtered_output, "a");
+    }
+
+    #[test]
+    fn test_prune() {
+        let conn = open_memory_db().unwrap();
+        for (timestamp, command) in [
+            ("2026-01-01 00:00:00", "old"),
+            ("2026-06-01 00:00:00", "mid"),
+            ("2026-09-01 00:00:00", "new"),
+        ] {
+            conn.execute(
+                "INSERT INTO history (timestamp, command, raw_output, filtered_output)
+                 VALUES (?1, ?2, '', '')",
+                [timestamp, command],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO filter_events (timestamp, command, input_bytes, output_bytes, savings_bytes, savings_pct)
+                 VALUES (?1, ?2, 0, 0, 0, 0)",
+                [timestamp, command],
+            )
+            .unwrap();
+        }
+        let remaining = |table: &str| -> Vec<String> {
+            conn.prepare(&format!("SELECT command FROM {table} ORDER BY id"))
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .map(|r| r.unwrap())
+                .collect()
+        };
+
+        let stats = prune(&conn, Some("2026-03-01 00:00:00"), None).unwrap();
+        assert_eq!(
+            stats,
+            PruneStats {
+                events: 1,
+                history: 1
+            }
+        );
+        assert_eq!(remaining("history"), vec!["mid", "new"]);
+
+        let stats = prune(&conn, None, Some(1)).unwrap();
+        assert_eq!(
+            stats,
+            PruneStats {
+                events: 1,
+                history: 1
+            }
+        );
+        assert_eq!(remaining("history"), vec!["new"]);
+        assert_eq!(remaining("filter_events"), vec!["new"]);
+
+        assert_eq!(prune(&conn, None, None).unwrap(), PruneStats::default());
+        vacuum(&conn).unwrap();
+    }
+
+    #[test]
+    fn test_retention_by_size_and_throttling() {
+        let conn = open_memory_db().unwrap();
+        let big = "x".repeat(20_000);
+        for _ in 0..20 {
+            conn.execute(
+                "INSERT INTO history (command, raw_output, filtered_output) VALUES ('cat', ?1, '')",
+                [&big],
+            )
+            .unwrap();
+        }
+        for _ in 0..50 {
+            conn.execute(
+                "INSERT INTO filter_events (command, input_bytes, output_bytes, savings_bytes, savings_pct)
+                 VALUES ('cat', 1, 1, 0, 0.0)",
+                [],
+            )
+            .unwrap();
+        }
+        let retention = Retention {
+            max_db_bytes: Some(200_000),
+            ..Default::default()
+        };
+        let stats = maybe_apply_retention(&conn, &retention).unwrap().unwrap();
+        assert!(stats.history > 0);
+        assert!(used_bytes(&conn).unwrap() <= 200_000);
+        // Only history is trimmed for size; events are small and kept
+        assert_eq!(stats.events, 0);
+        let events: i64 = conn
+            .query_row("SELECT COUNT(*) FROM filter_events", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(events, 50);
+
+        // Ran just now, so the next call is skipped
+        assert!(maybe_apply_retention(&conn, &retention).unwrap().is_none());
+        assert!(maybe_apply_retention(&conn, &Retention::default())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_retention_by_size_keeps_history_when_events_exceed_limit() {
+        let conn = open_memory_db().unwrap();
+        for _ in 0..5 {
+            conn.execute(
+                "INSERT INTO history (command, raw_output, filtered_output) VALUES ('cat', ?1, '')",
+                ["x".repeat(500)],
+            )
+            .unwrap();
+        }
+        for i in 0..3000 {
+            conn.execute(
+                "INSERT INTO filter_events (command, input_bytes, output_bytes, savings_bytes, savings_pct)
+                 VALUES (?1, 1, 1, 0, 0.0)",
+                [format!("command number {i}")],
+            )
+            .unwrap();
+        }
+        let max_db_bytes = 50_000;
+        assert!(used_bytes(&conn).unwrap() - history_bytes(&conn).unwrap() > max_db_bytes);
+
+        let stats = apply_retention(
+            &conn,
+            &Retention {
+                max_db_bytes: Some(max_db_bytes),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.history, 0);
+        let history: i64 = conn
+            .query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(history, 5);
+    }
+
+    #[test]
+    fn test_retention_by_age_and_rows() {
+        let conn = open_memory_db().unwrap();
+        conn.execute_batch(
+            "INSERT INTO history (timestamp, command, raw_output, filtered_output)
+                 VALUES ('2020-01-01 00:00:00', 'old', '', '');
+             INSERT INTO history (command, raw_output, filtered_output) VALUES ('a', '', '');
+             INSERT INTO history (command, raw_output, filtered_output) VALUES ('b', '', '');",
+        )
+        .unwrap();
+        let stats = apply_retention(
+            &conn,
+            &Retention {
+                max_age: Some("30d".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.history, 1);
+        let stats = apply_retention(
+            &conn,
+            &Retention {
+                max_rows: Some(1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(stats.history, 1);
+    }
+
+    #[test]
+    fn test_data_dir_default() {
         // Just verify it returns a path without panicking
-        let path = dirs_or_fallback();
+        let path = data_dir();
         assert!(path.to_str().is_some());
     }
 }
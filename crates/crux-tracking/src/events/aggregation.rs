@@ -0,0 +1,269 @@
+//! Savings bucketed by time, filter, session or source.
+
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::range::RANGE_WHERE;
+use super::TimeRange;
+
+/// How [`get_grouped_summary`] buckets events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Calendar day (UTC), oldest first.
+    Day,
+    /// ISO-like week (`2026-W41`, weeks starting Monday), oldest first.
+    Week,
+    /// Filter that handled the command, most savings first.
+    Filter,
+    /// Agent session, most recently active first.
+    Session,
+    /// What invoked crux (hook, wrapper, CLI), most savings first.
+    Source,
+}
+
+/// Savings of one bucket of events.
+#[derive(Debug, Serialize)]
+pub struct GroupSummary {
+    pub group: String,
+    pub events: i64,
+    pub total_input_bytes: i64,
+    pub total_output_bytes: i64,
+    pub total_savings_bytes: i64,
+    pub avg_savings_pct: f64,
+}
+
+/// Get savings summary of events in `range`, bucketed by `group_by`.
+pub fn get_grouped_summary(
+    conn: &Connection,
+    range: &TimeRange,
+    group_by: GroupBy,
+) -> Result<Vec<GroupSummary>> {
+    let (key, order) = match group_by {
+        GroupBy::Day => ("date(timestamp)", "grp"),
+        GroupBy::Week => ("strftime('%Y-W%W', timestamp)", "grp"),
+        GroupBy::Filter => (
+            "COALESCE(filter_name, '(passthrough)')",
+            "SUM(savings_bytes) DESC",
+        ),
+        GroupBy::Session => ("COALESCE(session_id, '(none)')", "MAX(timestamp) DESC, grp"),
+        GroupBy::Source => ("COALESCE(source, '(unknown)')", "SUM(savings_bytes) DESC"),
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT
+            {key} AS grp,
+            COUNT(*),
+            COALESCE(SUM(input_bytes), 0),
+            COALESCE(SUM(output_bytes), 0),
+            COALESCE(SUM(savings_bytes), 0),
+            COALESCE(AVG(savings_pct), 0.0)
+         FROM filter_events
+         {RANGE_WHERE}
+         GROUP BY grp
+         ORDER BY {order}"
+    ))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![range.since, range.until], |row| {
+            Ok(GroupSummary {
+                group: row.get(0)?,
+                events: row.get(1)?,
+                total_input_bytes: row.get(2)?,
+                total_output_bytes: row.get(3)?,
+                total_savings_bytes: row.get(4)?,
+                avg_savings_pct: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows)
+}
+
+/// Bucket size for [`get_gain_by_period`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// Calendar day (UTC).
+    Day,
+    /// Week starting Monday, keyed by its first day.
+    Week,
+}
+
+/// Savings in one period of a timeline.
+#[derive(Debug, Serialize)]
+pub struct PeriodSummary {
+    /// First day of the period (`2026-10-05`).
+    pub period_start: String,
+    pub events: i64,
+    pub total_input_bytes: i64,
+    pub total_savings_bytes: i64,
+}
+
+/// Get savings per period of events in `range`, oldest first. Periods
+/// between the first and last recorded ones are included even when no
+/// command ran, so the series can be charted as is.
+pub fn get_gain_by_period(
+    conn: &Connection,
+    range: &TimeRange,
+    period: Period,
+) -> Result<Vec<PeriodSummary>> {
+    let (key, step) = match period {
+        Period::Day => ("date(timestamp)", "+1 day"),
+        Period::Week => ("date(timestamp, '-6 days', 'weekday 1')", "+7 days"),
+    };
+    let mut stmt = conn.prepare(&format!(
+        "WITH buckets AS (
+            SELECT
+                {key} AS start,
+                COUNT(*) AS events,
+                SUM(input_bytes) AS input_bytes,
+                SUM(savings_bytes) AS savings_bytes
+            FROM filter_events
+            {RANGE_WHERE}
+            GROUP BY start
+         ),
+         periods(start) AS (
+            SELECT MIN(start) FROM buckets
+            UNION ALL
+            SELECT date(start, '{step}') FROM periods
+            WHERE start < (SELECT MAX(start) FROM buckets)
+         )
+         SELECT
+            p.start,
+            COALESCE(b.events, 0),
+            COALESCE(b.input_bytes, 0),
+            COALESCE(b.savings_bytes, 0)
+         FROM periods p
+         LEFT JOIN buckets b ON b.start = p.start
+         WHERE p.start IS NOT NULL
+         ORDER BY p.start"
+    ))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![range.since, range.until], |row| {
+            Ok(PeriodSummary {
+                period_start: row.get(0)?,
+                events: row.get(1)?,
+                total_input_bytes: row.get(2)?,
+                total_savings_bytes: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_memory_db;
+    use crate::events::tests::event;
+    use crate::events::{get_gain_summary, record_event};
+
+    #[test]
+    fn test_range_and_grouping() {
+        let conn = open_memory_db().unwrap();
+        record_event(&conn, &event("cargo test", Some("cargo test"), 1000, 100)).unwrap();
+        record_event(&conn, &event("git status", Some("git status"), 500, 400)).unwrap();
+        record_event(&conn, &event("ls", None, 200, 200)).unwrap();
+        conn.execute_batch(
+            "UPDATE filter_events SET timestamp = '2026-10-01 09:00:00' WHERE id = 1;
+             UPDATE filter_events SET timestamp = '2026-10-01 18:00:00' WHERE id = 2;
+             UPDATE filter_events SET timestamp = '2026-10-09 12:00:00' WHERE id = 3;",
+        )
+        .unwrap();
+
+        let first_week = TimeRange::parse(&conn, Some("2026-10-01"), Some("2026-10-07")).unwrap();
+        let summary = get_gain_summary(&conn, &first_week).unwrap();
+        assert_eq!(summary.total_events, 2);
+        assert_eq!(summary.total_savings_bytes, 1000);
+
+        let all = TimeRange::default();
+        let days = get_grouped_summary(&conn, &all, GroupBy::Day).unwrap();
+        let day_keys: Vec<&str> = days.iter().map(|g| g.group.as_str()).collect();
+        assert_eq!(day_keys, vec!["2026-10-01", "2026-10-09"]);
+        assert_eq!(days[0].events, 2);
+
+        let weeks = get_grouped_summary(&conn, &all, GroupBy::Week).unwrap();
+        assert_eq!(weeks.len(), 2);
+
+        let filters = get_grouped_summary(&conn, &all, GroupBy::Filter).unwrap();
+        assert_eq!(filters[0].group, "cargo test");
+        assert!(filters.iter().any(|g| g.group == "(passthrough)"));
+    }
+
+    #[test]
+    fn test_gain_by_period_fills_gaps() {
+        let conn = open_memory_db().unwrap();
+        for input in [1000, 500, 200] {
+            record_event(&conn, &event("cargo test", Some("cargo test"), input, 100)).unwrap();
+        }
+        // Thursday, the following Sunday, and Monday a week later
+        conn.execute_batch(
+            "UPDATE filter_events SET timestamp = '2026-10-01 09:00:00' WHERE id = 1;
+             UPDATE filter_events SET timestamp = '2026-10-04 18:00:00' WHERE id = 2;
+             UPDATE filter_events SET timestamp = '2026-10-12 12:00:00' WHERE id = 3;",
+        )
+        .unwrap();
+
+        let all = TimeRange::default();
+        let days = get_gain_by_period(&conn, &all, Period::Day).unwrap();
+        assert_eq!(days.len(), 12);
+        assert_eq!(days[0].period_start, "2026-10-01");
+        assert_eq!(days[0].total_savings_bytes, 900);
+        assert_eq!(days[1].events, 0);
+        assert_eq!(days[11].period_start, "2026-10-12");
+
+        let weeks = get_gain_by_period(&conn, &all, Period::Week).unwrap();
+        let starts: Vec<&str> = weeks.iter().map(|w| w.period_start.as_str()).collect();
+        assert_eq!(starts, vec!["2026-09-28", "2026-10-05", "2026-10-12"]);
+        assert_eq!(weeks[0].events, 2);
+        assert_eq!(weeks[0].total_input_bytes, 1500);
+        assert_eq!(weeks[1].events, 0);
+
+        let empty = TimeRange::parse(&conn, Some("2027-01-01"), None).unwrap();
+        assert!(get_gain_by_period(&conn, &empty, Period::Day)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_group_by_session() {
+        let conn = open_memory_db().unwrap();
+        for (session, input) in [
+            (Some("a"), 100),
+            (Some("b"), 300),
+            (Some("a"), 100),
+            (None, 10),
+        ] {
+            let mut e = event("cargo test", Some("cargo test"), input, input / 2);
+            e.session_id = session.map(String::from);
+            record_event(&conn, &e).unwrap();
+        }
+        conn.execute_batch(
+            "UPDATE filter_events SET timestamp = '2026-10-01 09:00:00' WHERE id = 2;
+             UPDATE filter_events SET timestamp = '2026-10-02 09:00:00' WHERE id IN (1, 3);
+             UPDATE filter_events SET timestamp = '2026-09-01 09:00:00' WHERE id = 4;",
+        )
+        .unwrap();
+
+        let sessions = get_grouped_summary(&conn, &TimeRange::default(), GroupBy::Session).unwrap();
+        let keys: Vec<&str> = sessions.iter().map(|g| g.group.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b", "(none)"]);
+        assert_eq!(sessions[0].events, 2);
+        assert_eq!(sessions[0].total_savings_bytes, 100);
+    }
+
+    #[test]
+    fn test_group_by_source() {
+        let conn = open_memory_db().unwrap();
+        for (source, input) in [(Some("cli"), 100), (Some("claude-hook"), 400), (None, 10)] {
+            let mut e = event("cargo test", Some("cargo test"), input, input / 2);
+            e.source = source.map(String::from);
+            record_event(&conn, &e).unwrap();
+        }
+        let sources = get_grouped_summary(&conn, &TimeRange::default(), GroupBy::Source).unwrap();
+        let keys: Vec<&str> = sources.iter().map(|g| g.group.as_str()).collect();
+        assert_eq!(keys, vec!["claude-hook", "cli", "(unknown)"]);
+        assert_eq!(sources[0].total_savings_bytes, 200);
+    }
+}
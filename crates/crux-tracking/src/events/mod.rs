@@ -0,0 +1,260 @@
+//! Recorded runs (`filter_events`) and the reports built from them.
+
+mod aggregation;
+mod queries;
+mod range;
+mod reports;
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+pub use aggregation::{
+    get_gain_by_period, get_grouped_summary, GroupBy, GroupSummary, Period, PeriodSummary,
+};
+pub use queries::{
+    get_failure_summary, get_gain_summary, get_per_command_summary, get_per_filter_summary,
+    get_stage_savings, CommandSummary, FailureSummary, FilterSummary, GainSummary,
+    StageSavingsSummary,
+};
+pub use range::TimeRange;
+pub use reports::{
+    command_key, get_filter_stats, get_regressions, get_waste_report, FilterStats,
+    RegressionSummary, WasteSummary,
+};
+
+/// A filter event to record in the database.
+pub struct FilterEvent {
+    pub command: String,
+    pub filter_name: Option<String>,
+    pub input_bytes: usize,
+    pub output_bytes: usize,
+    pub exit_code: i32,
+    pub duration_ms: Option<u64>,
+    /// Time spent in the filter pipeline alone, excluding the command.
+    pub filter_duration_us: Option<u64>,
+    /// Exact token counts of the raw and shown output, recorded with the
+    /// `tokenizer` feature (see [`crate::tokens`]).
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    /// The filter saved less than its `min_savings_pct`, so raw output was shown.
+    pub raw_fallback: bool,
+    /// Agent conversation the run belongs to (`CRUX_SESSION_ID`).
+    pub session_id: Option<String>,
+    /// What invoked crux: `claude-hook`, `codex`, `cli`, ... (`CRUX_SOURCE`).
+    pub source: Option<String>,
+    /// Bytes each pipeline stage removed, in pipeline order (see
+    /// `crux_core::filter::apply_filter_with_savings`).
+    pub stage_savings: Vec<(String, i64)>,
+}
+
+/// Record a filter event (input/output sizes, savings, etc.)
+pub fn record_event(conn: &Connection, event: &FilterEvent) -> Result<()> {
+    let savings = event.input_bytes as i64 - event.output_bytes as i64;
+    let pct = if event.input_bytes > 0 {
+        (savings as f64 / event.input_bytes as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    // Stored as a JSON object of stage -> bytes; a stage that ran more
+    // than once (e.g. in nested pipelines) is summed.
+    let stage_savings = (!event.stage_savings.is_empty()).then(|| {
+        let mut stages = serde_json::Map::new();
+        for (stage, removed) in &event.stage_savings {
+            let total = stages.get(stage).and_then(|v| v.as_i64()).unwrap_or(0);
+            stages.insert(stage.clone(), (total + removed).into());
+        }
+        serde_json::Value::Object(stages).to_string()
+    });
+
+    conn.execute(
+        "INSERT INTO filter_events (command, filter_name, input_bytes, output_bytes, savings_bytes, savings_pct, exit_code, duration_ms, raw_fallback, filter_duration_us, input_tokens, output_tokens, session_id, stage_savings, source)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        rusqlite::params![
+            event.command,
+            event.filter_name,
+            event.input_bytes as i64,
+            event.output_bytes as i64,
+            savings,
+            pct,
+            event.exit_code,
+            event.duration_ms.map(|d| d as i64),
+            event.raw_fallback,
+            event.filter_duration_us.map(|d| d as i64),
+            event.input_tokens.map(|t| t as i64),
+            event.output_tokens.map(|t| t as i64),
+            event.session_id,
+            stage_savings,
+            event.source,
+        ],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_memory_db;
+
+    pub(super) fn event(
+        command: &str,
+        filter_name: Option<&str>,
+        input: usize,
+        output: usize,
+    ) -> FilterEvent {
+        FilterEvent {
+            command: command.to_string(),
+            filter_name: filter_name.map(String::from),
+            input_bytes: input,
+            output_bytes: output,
+            exit_code: 0,
+            duration_ms: None,
+            filter_duration_us: None,
+            input_tokens: None,
+            output_tokens: None,
+            session_id: None,
+            stage_savings: Vec::new(),
+            source: None,
+            raw_fallback: false,
+        }
+    }
+
+    #[test]
+    fn test_record_event() {
+        let conn = open_memory_db().unwrap();
+        let event = FilterEvent {
+            command: "cargo test".to_string(),
+            filter_name: Some("cargo-test".to_string()),
+            input_bytes: 1000,
+            output_bytes: 300,
+            exit_code: 0,
+            duration_ms: Some(150),
+            filter_duration_us: None,
+            input_tokens: None,
+            output_tokens: None,
+            session_id: None,
+            stage_savings: Vec::new(),
+            source: None,
+            raw_fallback: false,
+        };
+        record_event(&conn, &event).expect("should record event");
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM filter_events", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_record_event_no_filter_name() {
+        let conn = open_memory_db().unwrap();
+        let event = FilterEvent {
+            command: "ls -la".to_string(),
+            filter_name: None,
+            input_bytes: 500,
+            output_bytes: 500,
+            exit_code: 0,
+            duration_ms: None,
+            filter_duration_us: None,
+            input_tokens: None,
+            output_tokens: None,
+            session_id: None,
+            stage_savings: Vec::new(),
+            source: None,
+            raw_fallback: false,
+        };
+        record_event(&conn, &event).expect("should record event without filter name");
+    }
+
+    #[test]
+    fn test_savings_calculation() {
+        let conn = open_memory_db().unwrap();
+        let event = FilterEvent {
+            command: "cargo test".to_string(),
+            filter_name: Some("cargo-test".to_string()),
+            input_bytes: 1000,
+            output_bytes: 300,
+            exit_code: 0,
+            duration_ms: None,
+            filter_duration_us: None,
+            input_tokens: None,
+            output_tokens: None,
+            session_id: None,
+            stage_savings: Vec::new(),
+            source: None,
+            raw_fallback: false,
+        };
+        record_event(&conn, &event).unwrap();
+
+        let row: (i64, f64) = conn
+            .query_row(
+                "SELECT savings_bytes, savings_pct FROM filter_events WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+
+        assert_eq!(row.0, 700); // 1000 - 300
+        assert!((row.1 - 70.0).abs() < 0.01); // 70%
+    }
+
+    #[test]
+    fn test_zero_input_bytes() {
+        let conn = open_memory_db().unwrap();
+        let event = FilterEvent {
+            command: "echo".to_string(),
+            filter_name: None,
+            input_bytes: 0,
+            output_bytes: 0,
+            exit_code: 0,
+            duration_ms: None,
+            filter_duration_us: None,
+            input_tokens: None,
+            output_tokens: None,
+            session_id: None,
+            stage_savings: Vec::new(),
+            source: None,
+            raw_fallback: false,
+        };
+        record_event(&conn, &event).unwrap();
+
+        let pct: f64 = conn
+            .query_row(
+                "SELECT savings_pct FROM filter_events WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!((pct - 0.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_record_raw_fallback() {
+        let conn = open_memory_db().unwrap();
+        let event = FilterEvent {
+            command: "ls".to_string(),
+            filter_name: Some("ls".to_string()),
+            input_bytes: 40,
+            output_bytes: 40,
+            exit_code: 0,
+            duration_ms: None,
+            filter_duration_us: None,
+            input_tokens: None,
+            output_tokens: None,
+            session_id: None,
+            stage_savings: Vec::new(),
+            source: None,
+            raw_fallback: true,
+        };
+        record_event(&conn, &event).unwrap();
+
+        let fallback: bool = conn
+            .query_row(
+                "SELECT raw_fallback FROM filter_events WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(fallback);
+    }
+}
@@ -0,0 +1,494 @@
+//! Savings totals, per command, per filter, per stage and per outcome.
+
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::range::RANGE_WHERE;
+use super::TimeRange;
+
+/// Aggregate savings summary across all recorded events.
+#[derive(Debug, Serialize)]
+pub struct GainSummary {
+    pub total_input_bytes: i64,
+    pub total_output_bytes: i64,
+    pub total_savings_bytes: i64,
+    pub avg_savings_pct: f64,
+    pub total_events: i64,
+    /// Events with exact token counts, and their token totals.
+    pub tokenized_events: i64,
+    pub total_input_tokens: i64,
+    pub total_output_tokens: i64,
+}
+
+/// Get total savings summary across filter events in `range`.
+pub fn get_gain_summary(conn: &Connection, range: &TimeRange) -> Result<GainSummary> {
+    let summary = conn.query_row(
+        &format!(
+            "SELECT
+                COALESCE(SUM(input_bytes), 0),
+                COALESCE(SUM(output_bytes), 0),
+                COALESCE(SUM(savings_bytes), 0),
+                COALESCE(AVG(savings_pct), 0.0),
+                COUNT(*),
+                COUNT(input_tokens),
+                COALESCE(SUM(input_tokens), 0),
+                COALESCE(SUM(output_tokens), 0)
+             FROM filter_events
+             {RANGE_WHERE}"
+        ),
+        rusqlite::params![range.since, range.until],
+        |row| {
+            Ok(GainSummary {
+                total_input_bytes: row.get(0)?,
+                total_output_bytes: row.get(1)?,
+                total_savings_bytes: row.get(2)?,
+                avg_savings_pct: row.get(3)?,
+                total_events: row.get(4)?,
+                tokenized_events: row.get(5)?,
+                total_input_tokens: row.get(6)?,
+                total_output_tokens: row.get(7)?,
+            })
+        },
+    )?;
+    Ok(summary)
+}
+
+/// Per-command savings breakdown.
+#[derive(Debug, Serialize)]
+pub struct CommandSummary {
+    pub command: String,
+    pub events: i64,
+    pub total_input_bytes: i64,
+    pub total_output_bytes: i64,
+    pub total_savings_bytes: i64,
+    pub avg_savings_pct: f64,
+}
+
+/// Get savings summary of events in `range` grouped by command, ordered by
+/// total savings descending.
+pub fn get_per_command_summary(
+    conn: &Connection,
+    range: &TimeRange,
+) -> Result<Vec<CommandSummary>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT
+            command,
+            COUNT(*) as events,
+            COALESCE(SUM(input_bytes), 0),
+            COALESCE(SUM(output_bytes), 0),
+            COALESCE(SUM(savings_bytes), 0),
+            COALESCE(AVG(savings_pct), 0.0)
+         FROM filter_events
+         {RANGE_WHERE}
+         GROUP BY command
+         ORDER BY SUM(savings_bytes) DESC"
+    ))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![range.since, range.until], |row| {
+            Ok(CommandSummary {
+                command: row.get(0)?,
+                events: row.get(1)?,
+                total_input_bytes: row.get(2)?,
+                total_output_bytes: row.get(3)?,
+                total_savings_bytes: row.get(4)?,
+                avg_savings_pct: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows)
+}
+
+/// Per-filter savings breakdown.
+#[derive(Debug, Serialize)]
+pub struct FilterSummary {
+    pub filter_name: String,
+    pub events: i64,
+    /// Distinct command lines the filter handled.
+    pub commands: i64,
+    pub total_input_bytes: i64,
+    pub total_output_bytes: i64,
+    pub total_savings_bytes: i64,
+    pub avg_savings_pct: f64,
+    /// Runs where the filter saved too little and raw output was shown.
+    pub raw_fallbacks: i64,
+}
+
+/// Get savings summary of filtered events in `range` grouped by filter,
+/// ordered by total savings descending. Unfiltered runs are left out.
+pub fn get_per_filter_summary(conn: &Connection, range: &TimeRange) -> Result<Vec<FilterSummary>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT
+            filter_name,
+            COUNT(*),
+            COUNT(DISTINCT command),
+            COALESCE(SUM(input_bytes), 0),
+            COALESCE(SUM(output_bytes), 0),
+            COALESCE(SUM(savings_bytes), 0),
+            COALESCE(AVG(savings_pct), 0.0),
+            COALESCE(SUM(raw_fallback), 0)
+         FROM filter_events
+         {RANGE_WHERE} AND filter_name IS NOT NULL
+         GROUP BY filter_name
+         ORDER BY SUM(savings_bytes) DESC, filter_name"
+    ))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![range.since, range.until], |row| {
+            Ok(FilterSummary {
+                filter_name: row.get(0)?,
+                events: row.get(1)?,
+                commands: row.get(2)?,
+                total_input_bytes: row.get(3)?,
+                total_output_bytes: row.get(4)?,
+                total_savings_bytes: row.get(5)?,
+                avg_savings_pct: row.get(6)?,
+                raw_fallbacks: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows)
+}
+
+/// Bytes one pipeline stage removed across a filter's runs.
+#[derive(Debug, Serialize)]
+pub struct StageSavingsSummary {
+    pub filter_name: String,
+    pub stage: String,
+    /// Runs in which the stage changed the output size.
+    pub events: i64,
+    /// Negative when the stage grew the output (e.g. omission markers).
+    pub total_bytes_removed: i64,
+}
+
+/// Get how many bytes each pipeline stage removed in `range`, per filter,
+/// ordered by filter and then bytes removed descending. Only runs recorded
+/// with a stage breakdown are counted.
+pub fn get_stage_savings(conn: &Connection, range: &TimeRange) -> Result<Vec<StageSavingsSummary>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT
+            filter_name,
+            stage.key,
+            COUNT(*),
+            COALESCE(SUM(stage.value), 0)
+         FROM filter_events, json_each(filter_events.stage_savings) AS stage
+         {RANGE_WHERE} AND filter_name IS NOT NULL
+         GROUP BY filter_name, stage.key
+         ORDER BY filter_name, SUM(stage.value) DESC, stage.key"
+    ))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![range.since, range.until], |row| {
+            Ok(StageSavingsSummary {
+                filter_name: row.get(0)?,
+                stage: row.get(1)?,
+                events: row.get(2)?,
+                total_bytes_removed: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows)
+}
+
+/// How often one command line failed (non-zero exit code).
+#[derive(Debug, Serialize)]
+pub struct FailureSummary {
+    pub command: String,
+    pub events: i64,
+    pub failures: i64,
+    pub failure_pct: f64,
+    /// Average shown output size of failed and of successful runs; `None`
+    /// when the command never failed (or never succeeded).
+    pub avg_output_bytes_failed: Option<f64>,
+    pub avg_output_bytes_succeeded: Option<f64>,
+}
+
+/// Get run counts and failure rates per command in `range`, the most
+/// failures first.
+pub fn get_failure_summary(conn: &Connection, range: &TimeRange) -> Result<Vec<FailureSummary>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT
+            command,
+            COUNT(*),
+            COALESCE(SUM(exit_code != 0), 0),
+            AVG(CASE WHEN exit_code != 0 THEN output_bytes END),
+            AVG(CASE WHEN exit_code = 0 THEN output_bytes END)
+         FROM filter_events
+         {RANGE_WHERE}
+         GROUP BY command
+         ORDER BY SUM(exit_code != 0) DESC, 1.0 * SUM(exit_code != 0) / COUNT(*) DESC, command"
+    ))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![range.since, range.until], |row| {
+            let events: i64 = row.get(1)?;
+            let failures: i64 = row.get(2)?;
+            Ok(FailureSummary {
+                command: row.get(0)?,
+                events,
+                failures,
+                failure_pct: failures as f64 / events as f64 * 100.0,
+                avg_output_bytes_failed: row.get(3)?,
+                avg_output_bytes_succeeded: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_memory_db;
+    use crate::events::tests::event;
+    use crate::events::{record_event, FilterEvent};
+
+    #[test]
+    fn test_gain_summary_empty() {
+        let conn = open_memory_db().unwrap();
+        let summary = get_gain_summary(&conn, &TimeRange::default()).unwrap();
+        assert_eq!(summary.total_events, 0);
+        assert_eq!(summary.total_input_bytes, 0);
+        assert_eq!(summary.total_output_bytes, 0);
+        assert_eq!(summary.total_savings_bytes, 0);
+        assert!((summary.avg_savings_pct - 0.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_per_command_summary_empty() {
+        let conn = open_memory_db().unwrap();
+        let summaries = get_per_command_summary(&conn, &TimeRange::default()).unwrap();
+        assert!(summaries.is_empty());
+    }
+
+    #[test]
+    fn test_per_command_summary_groups_by_command() {
+        let conn = open_memory_db().unwrap();
+
+        let events = vec![
+            FilterEvent {
+                command: "cargo test".to_string(),
+                filter_name: Some("cargo-test".to_string()),
+                input_bytes: 1000,
+                output_bytes: 300,
+                exit_code: 0,
+                duration_ms: None,
+                filter_duration_us: None,
+                input_tokens: None,
+                output_tokens: None,
+                session_id: None,
+                stage_savings: Vec::new(),
+                source: None,
+                raw_fallback: false,
+            },
+            FilterEvent {
+                command: "cargo test".to_string(),
+                filter_name: Some("cargo-test".to_string()),
+                input_bytes: 2000,
+                output_bytes: 600,
+                exit_code: 0,
+                duration_ms: None,
+                filter_duration_us: None,
+                input_tokens: None,
+                output_tokens: None,
+                session_id: None,
+                stage_savings: Vec::new(),
+                source: None,
+                raw_fallback: false,
+            },
+            FilterEvent {
+                command: "git status".to_string(),
+                filter_name: Some("git-status".to_string()),
+                input_bytes: 500,
+                output_bytes: 100,
+                exit_code: 0,
+                duration_ms: None,
+                filter_duration_us: None,
+                input_tokens: None,
+                output_tokens: None,
+                session_id: None,
+                stage_savings: Vec::new(),
+                source: None,
+                raw_fallback: false,
+            },
+        ];
+
+        for e in &events {
+            record_event(&conn, e).unwrap();
+        }
+
+        let summaries = get_per_command_summary(&conn, &TimeRange::default()).unwrap();
+        assert_eq!(summaries.len(), 2);
+
+        // Ordered by total savings DESC: cargo test saved 2100, git status saved 400
+        assert_eq!(summaries[0].command, "cargo test");
+        assert_eq!(summaries[0].events, 2);
+        assert_eq!(summaries[0].total_input_bytes, 3000);
+        assert_eq!(summaries[0].total_output_bytes, 900);
+        assert_eq!(summaries[0].total_savings_bytes, 2100);
+
+        assert_eq!(summaries[1].command, "git status");
+        assert_eq!(summaries[1].events, 1);
+        assert_eq!(summaries[1].total_savings_bytes, 400);
+    }
+
+    #[test]
+    fn test_gain_summary_multiple_events() {
+        let conn = open_memory_db().unwrap();
+
+        let events = vec![
+            FilterEvent {
+                command: "cargo test".to_string(),
+                filter_name: Some("cargo-test".to_string()),
+                input_bytes: 1000,
+                output_bytes: 300,
+                exit_code: 0,
+                duration_ms: Some(100),
+                filter_duration_us: None,
+                input_tokens: None,
+                output_tokens: None,
+                session_id: None,
+                stage_savings: Vec::new(),
+                source: None,
+                raw_fallback: false,
+            },
+            FilterEvent {
+                command: "cargo build".to_string(),
+                filter_name: Some("cargo-build".to_string()),
+                input_bytes: 2000,
+                output_bytes: 500,
+                exit_code: 0,
+                duration_ms: Some(200),
+                filter_duration_us: None,
+                input_tokens: None,
+                output_tokens: None,
+                session_id: None,
+                stage_savings: Vec::new(),
+                source: None,
+                raw_fallback: false,
+            },
+        ];
+
+        for e in &events {
+            record_event(&conn, e).unwrap();
+        }
+
+        let summary = get_gain_summary(&conn, &TimeRange::default()).unwrap();
+        assert_eq!(summary.total_events, 2);
+        assert_eq!(summary.total_input_bytes, 3000);
+        assert_eq!(summary.total_output_bytes, 800);
+        assert_eq!(summary.total_savings_bytes, 2200);
+        // Event 1: 70%, Event 2: 75%, avg = 72.5%
+        assert!((summary.avg_savings_pct - 72.5).abs() < 0.01);
+        assert_eq!(summary.tokenized_events, 0);
+    }
+
+    #[test]
+    fn test_gain_summary_token_totals() {
+        let conn = open_memory_db().unwrap();
+        let mut counted = event("cargo test", Some("cargo test"), 1000, 100);
+        counted.input_tokens = Some(250);
+        counted.output_tokens = Some(30);
+        record_event(&conn, &counted).unwrap();
+        record_event(&conn, &event("ls", Some("ls"), 100, 50)).unwrap();
+
+        let summary = get_gain_summary(&conn, &TimeRange::default()).unwrap();
+        assert_eq!(summary.total_events, 2);
+        assert_eq!(summary.tokenized_events, 1);
+        assert_eq!(summary.total_input_tokens, 250);
+        assert_eq!(summary.total_output_tokens, 30);
+    }
+
+    #[test]
+    fn test_failure_summary() {
+        let conn = open_memory_db().unwrap();
+        for (command, exit_code, output) in [
+            ("cargo test", 101, 400),
+            ("cargo test", 0, 100),
+            ("cargo test", 101, 600),
+            ("make", 2, 50),
+            ("make", 0, 10),
+            ("make", 0, 20),
+            ("ls", 0, 5),
+        ] {
+            let mut e = event(command, None, 1000, output);
+            e.exit_code = exit_code;
+            record_event(&conn, &e).unwrap();
+        }
+
+        let rows = get_failure_summary(&conn, &TimeRange::default()).unwrap();
+        let commands: Vec<&str> = rows.iter().map(|r| r.command.as_str()).collect();
+        assert_eq!(commands, vec!["cargo test", "make", "ls"]);
+        assert_eq!((rows[0].events, rows[0].failures), (3, 2));
+        assert!((rows[0].failure_pct - 66.7).abs() < 0.1);
+        assert_eq!(rows[0].avg_output_bytes_failed, Some(500.0));
+        assert_eq!(rows[0].avg_output_bytes_succeeded, Some(100.0));
+        assert_eq!(rows[1].avg_output_bytes_succeeded, Some(15.0));
+        assert_eq!(rows[2].failures, 0);
+        assert_eq!(rows[2].avg_output_bytes_failed, None);
+    }
+
+    #[test]
+    fn test_stage_savings() {
+        let conn = open_memory_db().unwrap();
+        let stages = |pairs: &[(&str, i64)]| -> Vec<(String, i64)> {
+            pairs.iter().map(|(s, n)| (s.to_string(), *n)).collect()
+        };
+        let mut first = event("cargo build", Some("cargo build"), 1000, 100);
+        first.stage_savings = stages(&[("skip/keep", 800), ("dedup", 100)]);
+        let mut second = event("cargo build", Some("cargo build"), 500, 100);
+        second.stage_savings = stages(&[("skip/keep", 300), ("lua", 50), ("lua", 50)]);
+        for e in [first, second, event("ls", Some("ls"), 100, 50)] {
+            record_event(&conn, &e).unwrap();
+        }
+
+        let rows = get_stage_savings(&conn, &TimeRange::default()).unwrap();
+        let got: Vec<(&str, &str, i64, i64)> = rows
+            .iter()
+            .map(|r| {
+                let (f, s) = (r.filter_name.as_str(), r.stage.as_str());
+                (f, s, r.events, r.total_bytes_removed)
+            })
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("cargo build", "skip/keep", 2, 1100),
+                ("cargo build", "dedup", 1, 100),
+                ("cargo build", "lua", 1, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_per_filter_summary() {
+        let conn = open_memory_db().unwrap();
+        record_event(&conn, &event("cargo test", Some("cargo test"), 1000, 100)).unwrap();
+        record_event(
+            &conn,
+            &event("cargo test -p a", Some("cargo test"), 1000, 100),
+        )
+        .unwrap();
+        record_event(&conn, &event("git log", Some("git log"), 100, 95)).unwrap();
+        let mut fallback = event("git log -p", Some("git log"), 100, 100);
+        fallback.raw_fallback = true;
+        record_event(&conn, &fallback).unwrap();
+        record_event(&conn, &event("whoami", None, 10, 10)).unwrap();
+
+        let filters = get_per_filter_summary(&conn, &TimeRange::default()).unwrap();
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters[0].filter_name, "cargo test");
+        assert_eq!(filters[0].events, 2);
+        assert_eq!(filters[0].commands, 2);
+        assert_eq!(filters[0].total_savings_bytes, 1800);
+        assert_eq!(filters[0].raw_fallbacks, 0);
+        assert_eq!(filters[1].filter_name, "git log");
+        assert_eq!(filters[1].raw_fallbacks, 1);
+        assert!((filters[1].avg_savings_pct - 2.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,84 @@
+//! Time bounds for event queries.
+
+use anyhow::{bail, Result};
+use rusqlite::Connection;
+
+/// Bounds on event timestamps, as SQLite UTC datetimes
+/// (`YYYY-MM-DD HH:MM:SS`). `since` is inclusive, `until` exclusive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+impl TimeRange {
+    /// Build a range from user-facing bounds: a relative age (`30m`, `12h`,
+    /// `7d`, `2w`), a date (`2026-10-01`, which as `until` includes that
+    /// whole day) or a datetime (`2026-10-01 14:00`).
+    pub fn parse(conn: &Connection, since: Option<&str>, until: Option<&str>) -> Result<Self> {
+        Ok(TimeRange {
+            since: since
+                .map(|spec| resolve_bound(conn, spec, false))
+                .transpose()?,
+            until: until
+                .map(|spec| resolve_bound(conn, spec, true))
+                .transpose()?,
+        })
+    }
+}
+
+/// Turn a bound spec into a datetime, letting SQLite do the date math.
+fn resolve_bound(conn: &Connection, spec: &str, is_until: bool) -> Result<String> {
+    let spec = spec.trim();
+    let (sql, arg) = match relative_modifier(spec) {
+        Some(modifier) => ("SELECT datetime('now', ?1)", modifier),
+        None if is_until && spec.len() == 10 => ("SELECT datetime(?1, '+1 day')", spec.to_string()),
+        None => ("SELECT datetime(?1)", spec.to_string()),
+    };
+    let resolved: Option<String> = conn.query_row(sql, [arg], |row| row.get(0))?;
+    match resolved {
+        Some(datetime) => Ok(datetime),
+        None => bail!("invalid time '{spec}' (expected e.g. 7d, 12h, 2026-10-01)"),
+    }
+}
+
+/// `7d` → `-7 days`, for SQLite's `datetime('now', ...)`.
+fn relative_modifier(spec: &str) -> Option<String> {
+    let unit_start = spec.find(|c: char| !c.is_ascii_digit())?;
+    let count: u64 = spec[..unit_start].parse().ok()?;
+    let unit = match &spec[unit_start..] {
+        "m" | "min" => "minutes",
+        "h" => "hours",
+        "d" => "days",
+        "w" => return Some(format!("-{} days", count * 7)),
+        _ => return None,
+    };
+    Some(format!("-{count} {unit}"))
+}
+
+/// `WHERE` clause selecting events in a [`TimeRange`] bound to `?1`/`?2`.
+pub(super) const RANGE_WHERE: &str =
+    "WHERE (?1 IS NULL OR timestamp >= ?1) AND (?2 IS NULL OR timestamp < ?2)";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_memory_db;
+
+    #[test]
+    fn test_time_range_parse() {
+        let conn = open_memory_db().unwrap();
+        let range = TimeRange::parse(&conn, Some("2026-10-01"), Some("2026-10-07")).unwrap();
+        assert_eq!(range.since.as_deref(), Some("2026-10-01 00:00:00"));
+        assert_eq!(range.until.as_deref(), Some("2026-10-08 00:00:00"));
+
+        let week = TimeRange::parse(&conn, Some("2w"), None).unwrap();
+        let expected: String = conn
+            .query_row("SELECT datetime('now', '-14 days')", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(week.since, Some(expected));
+
+        assert!(TimeRange::parse(&conn, Some("yesterday"), None).is_err());
+        assert!(TimeRange::parse(&conn, Some("7y"), None).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Rankings that point at filters worth fixing or writing: regressions,
+//! unsaved output and per-filter performance.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::range::RANGE_WHERE;
+use super::TimeRange;
+
+/// A filter whose output was at least as large as its input.
+#[derive(Debug, Serialize)]
+pub struct RegressionSummary {
+    pub filter_name: String,
+    /// Runs where the filter saved nothing or expanded the output.
+    pub events: i64,
+    /// Bytes added on top of the raw output, summed over those runs.
+    pub total_extra_bytes: i64,
+    /// History entries of the most recent such runs, newest first.
+    pub history_ids: Vec<i64>,
+}
+
+/// How many example history ids [`get_regressions`] returns per filter.
+const REGRESSION_EXAMPLES: usize = 3;
+
+/// Get filters that produced output no smaller than their input in
+/// `range`, worst first. Runs that fell back to raw output on purpose and
+/// runs with empty input are not counted.
+pub fn get_regressions(conn: &Connection, range: &TimeRange) -> Result<Vec<RegressionSummary>> {
+    const REGRESSED: &str = "filter_name IS NOT NULL AND raw_fallback = 0
+           AND input_bytes > 0 AND output_bytes >= input_bytes";
+    let mut stmt = conn.prepare(&format!(
+        "SELECT filter_name, COUNT(*), SUM(output_bytes - input_bytes)
+         FROM filter_events
+         {RANGE_WHERE} AND {REGRESSED}
+         GROUP BY filter_name
+         ORDER BY SUM(output_bytes - input_bytes) DESC, COUNT(*) DESC, filter_name"
+    ))?;
+    let mut regressions = stmt
+        .query_map(rusqlite::params![range.since, range.until], |row| {
+            Ok(RegressionSummary {
+                filter_name: row.get(0)?,
+                events: row.get(1)?,
+                total_extra_bytes: row.get(2)?,
+                history_ids: Vec::new(),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Events and history rows of a run are written one after the other with
+    // the same command and filter, so match them on those and the timestamp.
+    let mut examples = conn.prepare(&format!(
+        "SELECT (
+            SELECT MIN(h.id) FROM history h
+            WHERE h.command = e.command AND h.filter_name = e.filter_name
+              AND h.timestamp BETWEEN e.timestamp AND datetime(e.timestamp, '+1 second')
+         ) AS history_id
+         FROM filter_events e
+         WHERE (?1 IS NULL OR e.timestamp >= ?1) AND (?2 IS NULL OR e.timestamp < ?2)
+           AND e.filter_name = ?3 AND {REGRESSED}
+           AND history_id IS NOT NULL
+         ORDER BY e.timestamp DESC, e.id DESC
+         LIMIT ?4"
+    ))?;
+    for regression in &mut regressions {
+        regression.history_ids = examples
+            .query_map(
+                rusqlite::params![
+                    range.since,
+                    range.until,
+                    regression.filter_name,
+                    REGRESSION_EXAMPLES as i64
+                ],
+                |row| row.get(0),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+
+    Ok(regressions)
+}
+
+/// Output an agent still received from one kind of command.
+#[derive(Debug, Serialize)]
+pub struct WasteSummary {
+    /// Program and subcommand (`cargo test`, `cat`), see [`command_key`].
+    pub command: String,
+    pub events: i64,
+    /// Runs that went through a filter (the rest passed through unfiltered).
+    pub filtered_events: i64,
+    pub total_input_bytes: i64,
+    /// Bytes not saved: what the agent was shown.
+    pub total_output_bytes: i64,
+}
+
+/// Commands in `range` ranked by bytes not saved, most first.
+pub fn get_waste_report(conn: &Connection, range: &TimeRange) -> Result<Vec<WasteSummary>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT
+            command,
+            COUNT(*),
+            SUM(filter_name IS NOT NULL AND raw_fallback = 0),
+            COALESCE(SUM(input_bytes), 0),
+            COALESCE(SUM(output_bytes), 0)
+         FROM filter_events
+         {RANGE_WHERE}
+         GROUP BY command"
+    ))?;
+    let rows = stmt
+        .query_map(rusqlite::params![range.since, range.until], |row| {
+            Ok(WasteSummary {
+                command: row.get(0)?,
+                events: row.get(1)?,
+                filtered_events: row.get(2)?,
+                total_input_bytes: row.get(3)?,
+                total_output_bytes: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut by_key: BTreeMap<String, WasteSummary> = BTreeMap::new();
+    for row in rows {
+        let key = command_key(&row.command);
+        let entry = by_key.entry(key.clone()).or_insert(WasteSummary {
+            command: key,
+            events: 0,
+            filtered_events: 0,
+            total_input_bytes: 0,
+            total_output_bytes: 0,
+        });
+        entry.events += row.events;
+        entry.filtered_events += row.filtered_events;
+        entry.total_input_bytes += row.total_input_bytes;
+        entry.total_output_bytes += row.total_output_bytes;
+    }
+    let mut report: Vec<WasteSummary> = by_key.into_values().collect();
+    report.sort_by_key(|w| std::cmp::Reverse(w.total_output_bytes));
+    Ok(report)
+}
+
+/// Per-filter performance: how often it ran, how fast and how well.
+#[derive(Debug, Serialize)]
+pub struct FilterStats {
+    pub filter_name: String,
+    pub invocations: i64,
+    /// Mean filter pipeline time; `None` if no run in range recorded one.
+    pub avg_filter_us: Option<f64>,
+    pub avg_savings_pct: f64,
+    pub total_savings_bytes: i64,
+}
+
+/// Filters that ran in `range`, most invoked first.
+pub fn get_filter_stats(conn: &Connection, range: &TimeRange) -> Result<Vec<FilterStats>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT
+            filter_name,
+            COUNT(*),
+            AVG(filter_duration_us),
+            COALESCE(AVG(savings_pct), 0.0),
+            COALESCE(SUM(savings_bytes), 0)
+         FROM filter_events
+         {RANGE_WHERE} AND filter_name IS NOT NULL
+         GROUP BY filter_name
+         ORDER BY COUNT(*) DESC, filter_name"
+    ))?;
+    let stats = stmt
+        .query_map(rusqlite::params![range.since, range.until], |row| {
+            Ok(FilterStats {
+                filter_name: row.get(0)?,
+                invocations: row.get(1)?,
+                avg_filter_us: row.get(2)?,
+                avg_savings_pct: row.get(3)?,
+                total_savings_bytes: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(stats)
+}
+
+/// The program (without its directory) plus its subcommand, if the second
+/// word looks like one: `cargo test --all` → `cargo test`,
+/// `cat src/lib.rs` → `cat`, `/usr/bin/ls -la` → `ls`.
+pub fn command_key(command: &str) -> String {
+    let mut words = command.split_whitespace();
+    let Some(program) = words.next() else {
+        return String::new();
+    };
+    let program = program.rsplit('/').next().unwrap_or(program);
+    let is_subcommand = |word: &str| {
+        word.starts_with(|c: char| c.is_ascii_lowercase())
+            && word.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | ':')
+            })
+    };
+    match words.next() {
+        Some(sub) if is_subcommand(sub) => format!("{program} {sub}"),
+        _ => program.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_memory_db;
+    use crate::events::record_event;
+    use crate::events::tests::event;
+
+    #[test]
+    fn test_regressions() {
+        let conn = open_memory_db().unwrap();
+        let runs = [
+            ("cargo test", "cargo test", 1000, 100),
+            ("make", "make", 100, 150),
+            ("make all", "make", 100, 100),
+            ("git log", "git log", 100, 120),
+        ];
+        for (command, filter, input, output) in runs {
+            record_event(&conn, &event(command, Some(filter), input, output)).unwrap();
+            crate::history::store_history(&conn, command, "raw", "out", Some(filter), 0).unwrap();
+        }
+        let mut fallback = event("git diff", Some("git diff"), 100, 100);
+        fallback.raw_fallback = true;
+        record_event(&conn, &fallback).unwrap();
+        record_event(&conn, &event("empty", Some("empty"), 0, 10)).unwrap();
+
+        let regressions = get_regressions(&conn, &TimeRange::default()).unwrap();
+        let names: Vec<&str> = regressions.iter().map(|r| r.filter_name.as_str()).collect();
+        assert_eq!(names, vec!["make", "git log"]);
+        assert_eq!(regressions[0].events, 2);
+        assert_eq!(regressions[0].total_extra_bytes, 50);
+        assert_eq!(regressions[0].history_ids, vec![3, 2]);
+        assert_eq!(regressions[1].history_ids, vec![4]);
+    }
+
+    #[test]
+    fn test_filter_stats() {
+        let conn = open_memory_db().unwrap();
+        for (filter_us, output) in [(Some(100), 50), (Some(300), 0), (None, 100)] {
+            let mut e = event("cargo test", Some("cargo test"), 100, output);
+            e.filter_duration_us = filter_us;
+            record_event(&conn, &e).unwrap();
+        }
+        record_event(&conn, &event("ls", Some("ls"), 10, 10)).unwrap();
+        record_event(&conn, &event("whoami", None, 10, 10)).unwrap();
+
+        let stats = get_filter_stats(&conn, &TimeRange::default()).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].filter_name, "cargo test");
+        assert_eq!(stats[0].invocations, 3);
+        assert_eq!(stats[0].avg_filter_us, Some(200.0));
+        assert!((stats[0].avg_savings_pct - 50.0).abs() < 1e-9);
+        assert_eq!(stats[0].total_savings_bytes, 150);
+        assert_eq!(stats[1].filter_name, "ls");
+        assert_eq!(stats[1].avg_filter_us, None);
+    }
+
+    #[test]
+    fn test_command_key() {
+        assert_eq!(command_key("cargo test --all"), "cargo test");
+        assert_eq!(command_key("cat src/lib.rs"), "cat");
+        assert_eq!(command_key("/usr/bin/ls -la"), "ls");
+        assert_eq!(command_key("python script.py"), "python");
+        assert_eq!(command_key("php artisan migrate:status"), "php artisan");
+        assert_eq!(command_key(""), "");
+    }
+
+    #[test]
+    fn test_waste_report_ranks_unsaved_bytes() {
+        let conn = open_memory_db().unwrap();
+        record_event(&conn, &event("cat a.log", None, 4000, 4000)).unwrap();
+        record_event(&conn, &event("cat b.log", None, 3000, 3000)).unwrap();
+        record_event(&conn, &event("cargo test", Some("cargo test"), 9000, 500)).unwrap();
+        let mut fallback = event("cargo test --all", Some("cargo test"), 600, 600);
+        fallback.raw_fallback = true;
+        record_event(&conn, &fallback).unwrap();
+
+        let report = get_waste_report(&conn, &TimeRange::default()).unwrap();
+        assert_eq!(report[0].command, "cat");
+        assert_eq!(report[0].events, 2);
+        assert_eq!(report[0].filtered_events, 0);
+        assert_eq!(report[0].total_output_bytes, 7000);
+        assert_eq!(report[1].command, "cargo test");
+        assert_eq!(report[1].events, 2);
+        assert_eq!(report[1].filtered_events, 1);
+        assert_eq!(report[1].total_output_bytes, 1100);
+    }
+}
@@ -0,0 +1,250 @@
+use std::io::Write;
+
+use anyhow::Result;
+use rusqlite::types::ValueRef;
+use rusqlite::Connection;
+
+use crate::events::TimeRange;
+use crate::history::read_output;
+
+/// Output format for [`export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// One JSON object per line.
+    Jsonl,
+}
+
+/// Which table [`export`] reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportTable {
+    /// Recorded filter runs and their savings.
+    Events,
+    /// Stored command outputs, decompressed.
+    History,
+}
+
+impl ExportTable {
+    pub(crate) fn columns(self) -> &'static [&'static str] {
+        match self {
+            ExportTable::Events => &[
+                "id",
+                "timestamp",
+                "command",
+                "filter_name",
+                "input_bytes",
+                "output_bytes",
+                "savings_bytes",
+                "savings_pct",
+                "exit_code",
+                "duration_ms",
+                "filter_duration_us",
+                "input_tokens",
+                "output_tokens",
+                "raw_fallback",
+                "session_id",
+                "stage_savings",
+                "source",
+            ],
+            ExportTable::History => &[
+                "id",
+                "timestamp",
+                "command",
+                "filter_name",
+                "exit_code",
+                "notes",
+                "truncated",
+                "raw_output",
+                "filtered_output",
+            ],
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ExportTable::Events => "filter_events",
+            ExportTable::History => "history",
+        }
+    }
+}
+
+/// Write every row of `table` in `range` to `out`, oldest first. Returns
+/// the number of rows written.
+pub fn export(
+    conn: &Connection,
+    table: ExportTable,
+    format: ExportFormat,
+    range: &TimeRange,
+    out: &mut dyn Write,
+) -> Result<usize> {
+    let columns = table.columns();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM {}
+         WHERE (?1 IS NULL OR timestamp >= ?1) AND (?2 IS NULL OR timestamp < ?2)
+         ORDER BY timestamp, id",
+        columns.join(", "),
+        table.name()
+    ))?;
+
+    if format == ExportFormat::Csv {
+        writeln!(out, "{}", columns.join(","))?;
+    }
+    let mut rows = stmt.query(rusqlite::params![range.since, range.until])?;
+    let mut count = 0;
+    while let Some(row) = rows.next()? {
+        let mut values = Vec::with_capacity(columns.len());
+        for (idx, column) in columns.iter().enumerate() {
+            let value = if column.ends_with("_output") {
+                serde_json::Value::String(read_output(row, idx)?)
+            } else {
+                to_json(row.get_ref(idx)?)
+            };
+            values.push(value);
+        }
+        match format {
+            ExportFormat::Csv => write_csv_row(out, &values)?,
+            ExportFormat::Jsonl => write_json_row(out, columns, &values)?,
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+pub(crate) fn to_json(value: ValueRef) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(n) => n.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(text) | ValueRef::Blob(text) => {
+            String::from_utf8_lossy(text).into_owned().into()
+        }
+    }
+}
+
+fn write_csv_row(out: &mut dyn Write, values: &[serde_json::Value]) -> Result<()> {
+    let fields: Vec<String> = values
+        .iter()
+        .map(|value| match value {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(s) => csv_field(s),
+            other => other.to_string(),
+        })
+        .collect();
+    writeln!(out, "{}", fields.join(","))?;
+    Ok(())
+}
+
+/// Quote a field if it contains a separator, quote or line break (RFC 4180).
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Write an object with keys in column order.
+fn write_json_row(
+    out: &mut dyn Write,
+    columns: &[&str],
+    values: &[serde_json::Value],
+) -> Result<()> {
+    let fields: Vec<String> = columns
+        .iter()
+        .zip(values)
+        .map(|(column, value)| format!("\"{column}\":{value}"))
+        .collect();
+    writeln!(out, "{{{}}}", fields.join(","))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_memory_db;
+    use crate::events::{record_event, FilterEvent};
+    use crate::history::store_history;
+
+    fn export_string(conn: &Connection, table: ExportTable, format: ExportFormat) -> String {
+        let mut out = Vec::new();
+        export(conn, table, format, &TimeRange::default(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_export_events_csv() {
+        let conn = open_memory_db().unwrap();
+        record_event(
+            &conn,
+            &FilterEvent {
+                command: "cargo test --features \"a,b\"".to_string(),
+                filter_name: Some("cargo test".to_string()),
+                input_bytes: 1000,
+                output_bytes: 250,
+                exit_code: 0,
+                duration_ms: Some(42),
+                filter_duration_us: None,
+                input_tokens: None,
+                output_tokens: None,
+                raw_fallback: false,
+                session_id: None,
+                stage_savings: Vec::new(),
+                source: None,
+            },
+        )
+        .unwrap();
+
+        let csv = export_string(&conn, ExportTable::Events, ExportFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("id,timestamp,command,filter_name,input_bytes"));
+        assert!(lines[1].contains(
+            ",\"cargo test --features \"\"a,b\"\"\",cargo test,1000,250,750,75.0,0,42,,,,0,"
+        ));
+    }
+
+    #[test]
+    fn test_export_history_jsonl() {
+        let conn = open_memory_db().unwrap();
+        store_history(&conn, "ls", "a\nb", "a", None, 0).unwrap();
+        store_history(&conn, "false", "", "", None, 1).unwrap();
+
+        let jsonl = export_string(&conn, ExportTable::History, ExportFormat::Jsonl);
+        let rows: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["command"], "ls");
+        assert_eq!(rows[0]["raw_output"], "a\nb");
+        assert_eq!(rows[0]["filter_name"], serde_json::Value::Null);
+        assert_eq!(rows[1]["exit_code"], 1);
+        assert!(jsonl.starts_with("{\"id\":1,\"timestamp\":"));
+    }
+
+    #[test]
+    fn test_export_respects_range() {
+        let conn = open_memory_db().unwrap();
+        conn.execute(
+            "INSERT INTO history (timestamp, command, raw_output, filtered_output)
+             VALUES ('2026-01-01 00:00:00', 'old', '', '')",
+            [],
+        )
+        .unwrap();
+        store_history(&conn, "new", "", "", None, 0).unwrap();
+
+        let range = TimeRange::parse(&conn, Some("1d"), None).unwrap();
+        let mut out = Vec::new();
+        let count = export(
+            &conn,
+            ExportTable::History,
+            ExportFormat::Csv,
+            &range,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(count, 1);
+        assert!(String::from_utf8(out).unwrap().contains(",new,"));
+    }
+}
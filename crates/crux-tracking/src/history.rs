@@ -1,5 +1,16 @@
-use anyhow::Result;
-use rusqlite::Connection;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use rusqlite::types::{Type, ValueRef};
+use rusqlite::{Connection, Row};
+use serde::Serialize;
+
+/// zstd level for stored outputs: fast, and most of the gain on logs.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Default cap on each stored output (see [`store_history_with_limit`]).
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 256 * 1024;
 
 /// A single history entry representing a filtered command output.
 pub struct HistoryEntry {
@@ -9,49 +20,355 @@ pub struct HistoryEntry {
     pub raw_output: String,
     pub filtered_output: String,
     pub filter_name: Option<String>,
+    /// `None` for entries recorded before exit codes were stored.
+    pub exit_code: Option<i32>,
+    /// Labels added with [`tag_history`], sorted.
+    pub tags: Vec<String>,
+    /// Free-form note set with [`set_history_notes`].
+    pub notes: Option<String>,
+    /// An output was longer than the size cap and only its head and tail
+    /// were stored.
+    pub truncated: bool,
 }
 
-/// Store a command's raw and filtered output in history.
+/// Store a command's raw and filtered output in history. Both outputs are
+/// stored zstd-compressed; `raw_bytes` keeps the uncompressed size.
 pub fn store_history(
     conn: &Connection,
     command: &str,
     raw: &str,
     filtered: &str,
     filter_name: Option<&str>,
+    exit_code: i32,
 ) -> Result<()> {
+    store_history_with_limit(
+        conn,
+        command,
+        raw,
+        filtered,
+        filter_name,
+        exit_code,
+        DEFAULT_MAX_OUTPUT_BYTES,
+    )
+}
+
+/// Like [`store_history`], keeping at most about `max_bytes` of each
+/// output: its head and tail around a marker. The row is flagged
+/// `truncated`, and `raw_bytes` still records the full size.
+pub fn store_history_with_limit(
+    conn: &Connection,
+    command: &str,
+    raw: &str,
+    filtered: &str,
+    filter_name: Option<&str>,
+    exit_code: i32,
+    max_bytes: usize,
+) -> Result<()> {
+    let (stored_raw, raw_cut) = head_and_tail(raw, max_bytes);
+    let (stored_filtered, filtered_cut) = head_and_tail(filtered, max_bytes);
     conn.execute(
-        "INSERT INTO history (command, raw_output, filtered_output, filter_name)
-         VALUES (?1, ?2, ?3, ?4)",
-        rusqlite::params![command, raw, filtered, filter_name],
+        "INSERT INTO history (command, raw_output, filtered_output, filter_name, exit_code, raw_bytes, truncated)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            command,
+            compress(&stored_raw)?,
+            compress(&stored_filtered)?,
+            filter_name,
+            exit_code,
+            raw.len() as i64,
+            raw_cut || filtered_cut
+        ],
     )?;
     Ok(())
 }
 
+/// `text` if it fits in `max_bytes`, else its first and last halves (cut
+/// at line breaks where possible) around an omission marker.
+fn head_and_tail(text: &str, max_bytes: usize) -> (Cow<'_, str>, bool) {
+    if text.len() <= max_bytes {
+        return (Cow::Borrowed(text), false);
+    }
+    let half = max_bytes / 2;
+    let mut head_end = half;
+    while !text.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+    if let Some(newline) = text[..head_end].rfind('\n') {
+        head_end = newline + 1;
+    }
+    let mut tail_start = text.len() - half;
+    while !text.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+    if let Some(newline) = text[tail_start..].find('\n') {
+        if tail_start + newline + 1 < text.len() {
+            tail_start += newline + 1;
+        }
+    }
+    let omitted = tail_start - head_end;
+    let marker = format!("... [{omitted} bytes not stored] ...\n");
+    let head = &text[..head_end];
+    let separator = if head.is_empty() || head.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    (
+        Cow::Owned(format!("{head}{separator}{marker}{}", &text[tail_start..])),
+        true,
+    )
+}
+
+/// Compress an output for storage, encrypting it when
+/// [`crate::crypto::init`] turned that on.
+pub(crate) fn compress(text: &str) -> Result<Vec<u8>> {
+    crate::crypto::seal(zstd::encode_all(text.as_bytes(), COMPRESSION_LEVEL)?)
+}
+
+/// Compress outputs of rows written before history was compressed.
+pub(crate) fn compress_legacy_rows(conn: &Connection) -> Result<()> {
+    let mut select = conn.prepare(
+        "SELECT id, raw_output, filtered_output FROM history
+         WHERE typeof(raw_output) = 'text' OR typeof(filtered_output) = 'text'",
+    )?;
+    let rows = select
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                read_output(row, 1)?,
+                read_output(row, 2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut update = conn.prepare(
+        "UPDATE history SET raw_output = ?2, filtered_output = ?3, raw_bytes = ?4 WHERE id = ?1",
+    )?;
+    for (id, raw, filtered) in rows {
+        update.execute(rusqlite::params![
+            id,
+            compress(&raw)?,
+            compress(&filtered)?,
+            raw.len() as i64
+        ])?;
+    }
+    Ok(())
+}
+
+/// Read a stored output: a zstd BLOB (possibly encrypted), or TEXT in rows
+/// written before outputs were compressed.
+pub(crate) fn read_output(row: &Row, idx: usize) -> rusqlite::Result<String> {
+    let failed = |e: Box<dyn std::error::Error + Send + Sync>| {
+        rusqlite::Error::FromSqlConversionFailure(idx, Type::Blob, e)
+    };
+    match row.get_ref(idx)? {
+        ValueRef::Blob(bytes) => {
+            let compressed = crate::crypto::open(bytes).map_err(|e| failed(e.into()))?;
+            zstd::decode_all(&compressed[..])
+                .map(|raw| String::from_utf8_lossy(&raw).into_owned())
+                .map_err(|e| failed(Box::new(e)))
+        }
+        ValueRef::Text(text) => Ok(String::from_utf8_lossy(text).into_owned()),
+        other => Err(rusqlite::Error::InvalidColumnType(
+            idx,
+            "output".to_string(),
+            other.data_type(),
+        )),
+    }
+}
+
+const ENTRY_COLUMNS: &str = "id, timestamp, command, raw_output, filtered_output, filter_name,
+    exit_code, notes,
+    (SELECT group_concat(tag, ' ') FROM (
+        SELECT tag FROM history_tags WHERE history_id = history.id ORDER BY tag)),
+    truncated";
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        timestamp: row.get(1)?,
+        command: row.get(2)?,
+        raw_output: read_output(row, 3)?,
+        filtered_output: read_output(row, 4)?,
+        filter_name: row.get(5)?,
+        exit_code: row.get(6)?,
+        notes: row.get(7)?,
+        tags: row
+            .get::<_, Option<String>>(8)?
+            .map(|tags| tags.split(' ').map(String::from).collect())
+            .unwrap_or_default(),
+        // NULL in rows imported from archives that predate the column
+        truncated: row.get::<_, Option<bool>>(9)?.unwrap_or(false),
+    })
+}
+
+/// Conditions for [`search_history`]; the default matches every entry.
+#[derive(Debug, Default)]
+pub struct HistoryQuery {
+    /// Substring of the command line, ignoring case.
+    pub command: Option<String>,
+    /// Exact filter name.
+    pub filter: Option<String>,
+    /// Only runs with a non-zero exit code.
+    pub failed_only: bool,
+    /// Minimum raw output size in bytes.
+    pub min_size: Option<usize>,
+    /// Only entries carrying this tag.
+    pub tag: Option<String>,
+}
+
 /// Get the most recent history entries, ordered newest first.
 pub fn get_recent_history(conn: &Connection, limit: usize) -> Result<Vec<HistoryEntry>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, timestamp, command, raw_output, filtered_output, filter_name
+    search_history(conn, &HistoryQuery::default(), limit)
+}
+
+/// Get the most recent history entries matching `query`, newest first.
+pub fn search_history(
+    conn: &Connection,
+    query: &HistoryQuery,
+    limit: usize,
+) -> Result<Vec<HistoryEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ENTRY_COLUMNS}
          FROM history
-         ORDER BY timestamp DESC
-         LIMIT ?1",
-    )?;
+         WHERE (?1 IS NULL OR instr(lower(command), lower(?1)) > 0)
+           AND (?2 IS NULL OR filter_name = ?2)
+           AND (?3 = 0 OR exit_code != 0)
+           AND (?4 IS NULL OR COALESCE(raw_bytes, length(CAST(raw_output AS BLOB))) >= ?4)
+           AND (?6 IS NULL OR id IN (SELECT history_id FROM history_tags WHERE tag = ?6))
+         ORDER BY timestamp DESC, id DESC
+         LIMIT ?5"
+    ))?;
 
+    let params = rusqlite::params![
+        query.command,
+        query.filter,
+        query.failed_only,
+        query.min_size.map(|n| n as i64),
+        limit as i64,
+        query.tag
+    ];
     let entries = stmt
-        .query_map(rusqlite::params![limit as i64], |row| {
-            Ok(HistoryEntry {
-                id: row.get(0)?,
-                timestamp: row.get(1)?,
-                command: row.get(2)?,
-                raw_output: row.get(3)?,
-                filtered_output: row.get(4)?,
-                filter_name: row.get(5)?,
-            })
-        })?
+        .query_map(params, entry_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(entries)
 }
 
+/// Get a single history entry by id.
+pub fn get_history_entry(conn: &Connection, id: i64) -> Result<Option<HistoryEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ENTRY_COLUMNS}
+         FROM history
+         WHERE id = ?1"
+    ))?;
+    let mut rows = stmt.query_map(rusqlite::params![id], entry_from_row)?;
+    Ok(rows.next().transpose()?)
+}
+
+/// Label a history entry. Tags are single words so they can be listed
+/// and matched exactly; adding one twice is a no-op. Returns `false` if
+/// there is no entry with that id.
+pub fn tag_history(conn: &Connection, id: i64, tag: &str) -> Result<bool> {
+    if tag.is_empty() || tag.chars().any(char::is_whitespace) {
+        bail!("invalid tag '{tag}' (tags are single words, e.g. repro or baseline)");
+    }
+    if !history_exists(conn, id)? {
+        return Ok(false);
+    }
+    conn.execute(
+        "INSERT OR IGNORE INTO history_tags (history_id, tag) VALUES (?1, ?2)",
+        rusqlite::params![id, tag],
+    )?;
+    Ok(true)
+}
+
+/// Remove a tag from a history entry. Returns whether it was there.
+pub fn untag_history(conn: &Connection, id: i64, tag: &str) -> Result<bool> {
+    let removed = conn.execute(
+        "DELETE FROM history_tags WHERE history_id = ?1 AND tag = ?2",
+        rusqlite::params![id, tag],
+    )?;
+    Ok(removed > 0)
+}
+
+/// Set or, with `None`, clear the note of a history entry. Returns
+/// `false` if there is no entry with that id.
+pub fn set_history_notes(conn: &Connection, id: i64, notes: Option<&str>) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE history SET notes = ?2 WHERE id = ?1",
+        rusqlite::params![id, notes],
+    )?;
+    Ok(updated > 0)
+}
+
+/// How the filtered output of one run differs from another's.
+#[derive(Debug, Serialize)]
+pub struct HistoryComparison {
+    pub id_a: i64,
+    pub id_b: i64,
+    pub command_a: String,
+    pub command_b: String,
+    pub exit_code_a: Option<i32>,
+    pub exit_code_b: Option<i32>,
+    pub unchanged_lines: usize,
+    /// Lines only in `a`, in its order.
+    pub removed: Vec<String>,
+    /// Lines only in `b`, in its order.
+    pub added: Vec<String>,
+}
+
+/// Compare the filtered outputs of entries `id_a` and `id_b`, usually two
+/// runs of the same command. Outputs are compared as multisets of lines,
+/// so lines that only moved (parallel test output) don't count as changes.
+pub fn compare(conn: &Connection, id_a: i64, id_b: i64) -> Result<HistoryComparison> {
+    let load = |id| match get_history_entry(conn, id)? {
+        Some(entry) => Ok(entry),
+        None => bail!("no history entry with id {id}"),
+    };
+    let (a, b) = (load(id_a)?, load(id_b)?);
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line in a.filtered_output.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    let mut unchanged_lines = 0;
+    let mut added = Vec::new();
+    for line in b.filtered_output.lines() {
+        match counts.get_mut(line) {
+            Some(n) if *n > 0 => {
+                *n -= 1;
+                unchanged_lines += 1;
+            }
+            _ => added.push(line.to_string()),
+        }
+    }
+    let mut removed = Vec::new();
+    for line in a.filtered_output.lines() {
+        if let Some(n) = counts.get_mut(line).filter(|n| **n > 0) {
+            *n -= 1;
+            removed.push(line.to_string());
+        }
+    }
+
+    Ok(HistoryComparison {
+        id_a,
+        id_b,
+        command_a: a.command,
+        command_b: b.command,
+        exit_code_a: a.exit_code,
+        exit_code_b: b.exit_code,
+        unchanged_lines,
+        removed,
+        added,
+    })
+}
+
+fn history_exists(conn: &Connection, id: i64) -> Result<bool> {
+    Ok(conn
+        .prepare("SELECT 1 FROM history WHERE id = ?1")?
+        .exists([id])?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,6 +383,7 @@ mod tests {
             "raw output here",
             "filtered output here",
             Some("cargo-test"),
+            101,
         )
         .expect("should store history");
 
@@ -78,7 +396,7 @@ mod tests {
     #[test]
     fn test_store_history_no_filter() {
         let conn = open_memory_db().unwrap();
-        store_history(&conn, "ls -la", "file list", "file list", None)
+        store_history(&conn, "ls -la", "file list", "file list", None, 0)
             .expect("should store history without filter name");
 
         let entry: (String, Option<String>) = conn
@@ -104,9 +422,9 @@ mod tests {
         let conn = open_memory_db().unwrap();
 
         // Insert multiple entries
-        store_history(&conn, "cmd1", "raw1", "filtered1", Some("f1")).unwrap();
-        store_history(&conn, "cmd2", "raw2", "filtered2", Some("f2")).unwrap();
-        store_history(&conn, "cmd3", "raw3", "filtered3", None).unwrap();
+        store_history(&conn, "cmd1", "raw1", "filtered1", Some("f1"), 0).unwrap();
+        store_history(&conn, "cmd2", "raw2", "filtered2", Some("f2"), 0).unwrap();
+        store_history(&conn, "cmd3", "raw3", "filtered3", None, 0).unwrap();
 
         let entries = get_recent_history(&conn, 10).unwrap();
         assert_eq!(entries.len(), 3);
@@ -127,6 +445,7 @@ mod tests {
                 &format!("raw{i}"),
                 &format!("filtered{i}"),
                 None,
+                0,
             )
             .unwrap();
         }
@@ -144,6 +463,7 @@ mod tests {
             "compiling...\nfinished",
             "finished",
             Some("cargo-build"),
+            0,
         )
         .unwrap();
 
@@ -158,4 +478,211 @@ mod tests {
         assert!(!entry.timestamp.is_empty());
         assert!(entry.id > 0);
     }
+
+    #[test]
+    fn test_search_history_filters() {
+        let conn = open_memory_db().unwrap();
+        store_history(
+            &conn,
+            "cargo test",
+            "x".repeat(500).as_str(),
+            "ok",
+            Some("cargo test"),
+            101,
+        )
+        .unwrap();
+        store_history(
+            &conn,
+            "Cargo build",
+            "small",
+            "small",
+            Some("cargo build"),
+            0,
+        )
+        .unwrap();
+        store_history(&conn, "ls -la", "files", "files", None, 0).unwrap();
+
+        let search = |query: HistoryQuery| -> Vec<String> {
+            search_history(&conn, &query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.command)
+                .collect()
+        };
+        assert_eq!(
+            search(HistoryQuery {
+                command: Some("CARGO".to_string()),
+                ..Default::default()
+            }),
+            vec!["Cargo build", "cargo test"]
+        );
+        assert_eq!(
+            search(HistoryQuery {
+                filter: Some("cargo build".to_string()),
+                ..Default::default()
+            }),
+            vec!["Cargo build"]
+        );
+        assert_eq!(
+            search(HistoryQuery {
+                failed_only: true,
+                ..Default::default()
+            }),
+            vec!["cargo test"]
+        );
+        assert_eq!(
+            search(HistoryQuery {
+                min_size: Some(100),
+                ..Default::default()
+            }),
+            vec!["cargo test"]
+        );
+    }
+
+    #[test]
+    fn test_compare_filtered_outputs() {
+        let conn = open_memory_db().unwrap();
+        let before = "test a ... ok\ntest b ... FAILED\ntest c ... ok\n";
+        let after = "test c ... ok\ntest a ... ok\ntest b ... ok\ntest d ... ok\n";
+        store_history(&conn, "cargo test", "raw", before, None, 101).unwrap();
+        store_history(&conn, "cargo test", "raw", after, None, 0).unwrap();
+
+        let diff = compare(&conn, 1, 2).unwrap();
+        assert_eq!(diff.unchanged_lines, 2);
+        assert_eq!(diff.removed, vec!["test b ... FAILED"]);
+        assert_eq!(diff.added, vec!["test b ... ok", "test d ... ok"]);
+        assert_eq!((diff.exit_code_a, diff.exit_code_b), (Some(101), Some(0)));
+        assert!(compare(&conn, 1, 9).is_err());
+    }
+
+    #[test]
+    fn test_tags_and_notes() {
+        let conn = open_memory_db().unwrap();
+        store_history(&conn, "cargo test", "raw", "out", None, 101).unwrap();
+        store_history(&conn, "cargo bench", "raw", "out", None, 0).unwrap();
+
+        assert!(tag_history(&conn, 1, "repro").unwrap());
+        assert!(tag_history(&conn, 1, "flaky").unwrap());
+        assert!(tag_history(&conn, 1, "repro").unwrap());
+        assert!(tag_history(&conn, 2, "baseline").unwrap());
+        assert!(!tag_history(&conn, 99, "repro").unwrap());
+        assert!(tag_history(&conn, 1, "two words").is_err());
+        assert!(set_history_notes(&conn, 1, Some("fails on CI only")).unwrap());
+
+        let entry = get_history_entry(&conn, 1).unwrap().unwrap();
+        assert_eq!(entry.tags, vec!["flaky", "repro"]);
+        assert_eq!(entry.notes.as_deref(), Some("fails on CI only"));
+
+        let tagged = HistoryQuery {
+            tag: Some("baseline".to_string()),
+            ..Default::default()
+        };
+        let found = search_history(&conn, &tagged, 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].command, "cargo bench");
+
+        assert!(untag_history(&conn, 1, "flaky").unwrap());
+        assert!(!untag_history(&conn, 1, "flaky").unwrap());
+        assert!(set_history_notes(&conn, 1, None).unwrap());
+        let entry = get_history_entry(&conn, 1).unwrap().unwrap();
+        assert_eq!(entry.tags, vec!["repro"]);
+        assert_eq!(entry.notes, None);
+
+        crate::db::prune(&conn, None, Some(1)).unwrap();
+        let orphans: i64 = conn
+            .query_row("SELECT COUNT(*) FROM history_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(orphans, 1);
+    }
+
+    #[test]
+    fn test_outputs_stored_compressed() {
+        let conn = open_memory_db().unwrap();
+        let raw = "Compiling crux v0.3.3\n".repeat(1000);
+        store_history(&conn, "cargo build", &raw, "ok", Some("cargo build"), 0).unwrap();
+
+        let (stored_type, stored_len, raw_bytes): (String, i64, i64) = conn
+            .query_row(
+                "SELECT typeof(raw_output), length(raw_output), raw_bytes FROM history",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(stored_type, "blob");
+        assert!(stored_len < raw.len() as i64 / 10);
+        assert_eq!(raw_bytes, raw.len() as i64);
+
+        let entry = get_history_entry(&conn, 1).unwrap().unwrap();
+        assert_eq!(entry.raw_output, raw);
+        assert_eq!(entry.filtered_output, "ok");
+        let big = HistoryQuery {
+            min_size: Some(raw.len()),
+            ..Default::default()
+        };
+        assert_eq!(search_history(&conn, &big, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_long_outputs_keep_head_and_tail() {
+        let conn = open_memory_db().unwrap();
+        let raw: String = (0..1000).map(|i| format!("line {i}\n")).collect();
+        store_history_with_limit(&conn, "make", &raw, "ok", None, 0, 100).unwrap();
+
+        let entry = get_history_entry(&conn, 1).unwrap().unwrap();
+        assert!(entry.truncated);
+        assert!(entry.raw_output.starts_with("line 0\nline 1\n"));
+        assert!(entry.raw_output.ends_with("line 998\nline 999\n"));
+        assert!(entry.raw_output.contains(" bytes not stored] ...\n"));
+        assert!(entry.raw_output.len() < 200);
+        assert_eq!(entry.filtered_output, "ok");
+        let big = HistoryQuery {
+            min_size: Some(raw.len()),
+            ..Default::default()
+        };
+        assert_eq!(search_history(&conn, &big, 10).unwrap().len(), 1);
+
+        store_history_with_limit(&conn, "make", "short", "ok", None, 0, 100).unwrap();
+        assert!(!get_history_entry(&conn, 2).unwrap().unwrap().truncated);
+    }
+
+    #[test]
+    fn head_and_tail_respects_char_boundaries() {
+        let text = "é".repeat(100);
+        let (kept, cut) = head_and_tail(&text, 51);
+        assert!(cut);
+        assert!(kept.starts_with('é') && kept.ends_with('é'));
+    }
+
+    #[test]
+    fn test_reads_uncompressed_legacy_rows() {
+        let conn = open_memory_db().unwrap();
+        conn.execute(
+            "INSERT INTO history (command, raw_output, filtered_output) VALUES ('ls', 'a\nb', 'a')",
+            [],
+        )
+        .unwrap();
+        let entry = get_history_entry(&conn, 1).unwrap().unwrap();
+        assert_eq!(entry.raw_output, "a\nb");
+        assert_eq!(entry.filtered_output, "a");
+        let small = HistoryQuery {
+            min_size: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(search_history(&conn, &small, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_get_history_entry_by_id() {
+        let conn = open_memory_db().unwrap();
+        store_history(&conn, "cmd1", "raw1", "filtered1", Some("f1"), 0).unwrap();
+        store_history(&conn, "cmd2", "raw2", "filtered2", None, 0).unwrap();
+
+        let entry = get_history_entry(&conn, 2)
+            .unwrap()
+            .expect("entry 2 exists");
+        assert_eq!(entry.command, "cmd2");
+        assert_eq!(entry.raw_output, "raw2");
+        assert_eq!(entry.exit_code, Some(0));
+        assert!(get_history_entry(&conn, 99).unwrap().is_none());
+    }
 }
@@ -1,11 +1,28 @@
+pub mod archive;
+pub mod crypto;
 pub mod db;
 pub mod events;
+pub mod export;
 pub mod history;
+pub mod sync;
+pub mod tokens;
 
 // Re-export key types for convenience
-pub use db::{default_db_path, open_db, open_memory_db};
+pub use archive::{dump, import, ImportStats};
+pub use db::{
+    apply_retention, default_db_path, maybe_apply_retention, open_db, open_memory_db, prune,
+    schema_version, vacuum, PruneStats, Retention, SCHEMA_VERSION,
+};
 pub use events::{
-    get_gain_summary, get_per_command_summary, record_event, CommandSummary, FilterEvent,
-    GainSummary,
+    get_failure_summary, get_filter_stats, get_gain_by_period, get_gain_summary,
+    get_grouped_summary, get_per_command_summary, get_per_filter_summary, get_regressions,
+    get_stage_savings, get_waste_report, record_event, CommandSummary, FailureSummary, FilterEvent,
+    FilterStats, FilterSummary, GainSummary, GroupBy, GroupSummary, Period, PeriodSummary,
+    RegressionSummary, StageSavingsSummary, TimeRange, WasteSummary,
+};
+pub use export::{export, ExportFormat, ExportTable};
+pub use history::{
+    compare, get_history_entry, get_recent_history, search_history, set_history_notes,
+    store_history, store_history_with_limit, tag_history, untag_history, HistoryComparison,
+    HistoryEntry, HistoryQuery, DEFAULT_MAX_OUTPUT_BYTES,
 };
-pub use history::{get_recent_history, store_history, HistoryEntry};
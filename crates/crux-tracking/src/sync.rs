@@ -0,0 +1,173 @@
+//! Aggregated savings for `crux sync`: per-day totals of runs recorded
+//! since the last push to an endpoint. Outputs never leave the machine, and
+//! command lines only when asked for.
+
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+
+/// Savings of one day, filter and source, summed over new runs.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SyncRow {
+    pub day: String,
+    pub filter_name: Option<String>,
+    pub source: Option<String>,
+    /// Only with `include_commands`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    pub events: i64,
+    pub input_bytes: i64,
+    pub output_bytes: i64,
+    pub savings_bytes: i64,
+    pub raw_fallbacks: i64,
+    pub failures: i64,
+}
+
+/// Rows not yet pushed to an endpoint, and the cursor to store once they
+/// are.
+#[derive(Debug)]
+pub struct PendingSync {
+    pub rows: Vec<SyncRow>,
+    /// Id of the newest run included; `None` when there is nothing new.
+    pub last_event_id: Option<i64>,
+}
+
+fn cursor_key(endpoint: &str) -> String {
+    format!("sync_cursor:{endpoint}")
+}
+
+/// Id of the last run pushed to `endpoint`, 0 if it never was.
+pub fn last_synced_id(conn: &Connection, endpoint: &str) -> Result<i64> {
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = ?1",
+            [cursor_key(endpoint)],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(value.and_then(|v| v.parse().ok()).unwrap_or(0))
+}
+
+/// Remember that runs up to `event_id` were pushed to `endpoint`.
+pub fn mark_synced(conn: &Connection, endpoint: &str, event_id: i64) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+        rusqlite::params![cursor_key(endpoint), event_id.to_string()],
+    )?;
+    Ok(())
+}
+
+/// Aggregate runs with an id above `after_id`, by day, filter and source
+/// (and command line with `include_commands`).
+pub fn pending_stats(
+    conn: &Connection,
+    after_id: i64,
+    include_commands: bool,
+) -> Result<PendingSync> {
+    let last_event_id: Option<i64> = conn.query_row(
+        "SELECT MAX(id) FROM filter_events WHERE id > ?1",
+        [after_id],
+        |row| row.get(0),
+    )?;
+    let Some(last) = last_event_id else {
+        return Ok(PendingSync {
+            rows: Vec::new(),
+            last_event_id: None,
+        });
+    };
+
+    let command = if include_commands { "command" } else { "NULL" };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT
+            date(timestamp),
+            filter_name,
+            source,
+            {command},
+            COUNT(*),
+            COALESCE(SUM(input_bytes), 0),
+            COALESCE(SUM(output_bytes), 0),
+            COALESCE(SUM(savings_bytes), 0),
+            COALESCE(SUM(raw_fallback), 0),
+            COALESCE(SUM(exit_code != 0), 0)
+         FROM filter_events
+         WHERE id > ?1 AND id <= ?2
+         GROUP BY 1, 2, 3, 4
+         ORDER BY 1, 2, 3, 4"
+    ))?;
+    let rows = stmt
+        .query_map([after_id, last], |row| {
+            Ok(SyncRow {
+                day: row.get(0)?,
+                filter_name: row.get(1)?,
+                source: row.get(2)?,
+                command: row.get(3)?,
+                events: row.get(4)?,
+                input_bytes: row.get(5)?,
+                output_bytes: row.get(6)?,
+                savings_bytes: row.get(7)?,
+                raw_fallbacks: row.get(8)?,
+                failures: row.get(9)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(PendingSync {
+        rows,
+        last_event_id: Some(last),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_memory_db;
+    use crate::events::{record_event, FilterEvent};
+
+    fn record(conn: &Connection, command: &str, filter_name: Option<&str>, exit_code: i32) {
+        let event = FilterEvent {
+            command: command.to_string(),
+            filter_name: filter_name.map(String::from),
+            input_bytes: 100,
+            output_bytes: 40,
+            exit_code,
+            duration_ms: None,
+            filter_duration_us: None,
+            input_tokens: None,
+            output_tokens: None,
+            raw_fallback: false,
+            session_id: None,
+            stage_savings: Vec::new(),
+            source: Some("cli".to_string()),
+        };
+        record_event(conn, &event).unwrap();
+    }
+
+    #[test]
+    fn pending_stats_aggregate_new_runs_only() {
+        let conn = open_memory_db().unwrap();
+        record(&conn, "cargo test", Some("cargo test"), 0);
+        record(&conn, "cargo test --lib", Some("cargo test"), 101);
+        record(&conn, "ls", None, 0);
+
+        let pending = pending_stats(&conn, 0, false).unwrap();
+        assert_eq!(pending.last_event_id, Some(3));
+        assert_eq!(pending.rows.len(), 2);
+        let cargo = &pending.rows[1];
+        assert_eq!(cargo.filter_name.as_deref(), Some("cargo test"));
+        assert_eq!(cargo.command, None);
+        assert_eq!(
+            (cargo.events, cargo.savings_bytes, cargo.failures),
+            (2, 120, 1)
+        );
+
+        let with_commands = pending_stats(&conn, 0, true).unwrap();
+        assert_eq!(with_commands.rows.len(), 3);
+
+        mark_synced(&conn, "https://example.com", 3).unwrap();
+        assert_eq!(last_synced_id(&conn, "https://example.com").unwrap(), 3);
+        assert_eq!(last_synced_id(&conn, "https://other.example").unwrap(), 0);
+        let pending = pending_stats(&conn, 3, false).unwrap();
+        assert!(pending.rows.is_empty());
+        assert_eq!(pending.last_event_id, None);
+    }
+}
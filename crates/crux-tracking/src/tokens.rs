@@ -0,0 +1,56 @@
+//! Exact token counts for recorded runs.
+//!
+//! With the `tokenizer` feature, raw and filtered output are encoded with
+//! tiktoken so `crux gain` can report real token savings. Without it every
+//! count is `None` and only byte totals are recorded.
+
+/// Encoding used when none is configured.
+pub const DEFAULT_ENCODING: &str = "cl100k_base";
+
+/// Number of tokens in `text` under `encoding` (`cl100k_base`,
+/// `o200k_base`, `p50k_base`, `r50k_base`), or `None` if the feature is off
+/// or the encoding is unknown.
+#[cfg(feature = "tokenizer")]
+pub fn count_tokens(text: &str, encoding: &str) -> Option<usize> {
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
+
+    use tiktoken_rs::CoreBPE;
+
+    // Loading an encoding parses its whole vocabulary, so keep each one
+    static ENCODINGS: OnceLock<Mutex<HashMap<String, Option<&'static CoreBPE>>>> = OnceLock::new();
+    let bpe = *ENCODINGS
+        .get_or_init(Default::default)
+        .lock()
+        .ok()?
+        .entry(encoding.to_string())
+        .or_insert_with(|| {
+            let bpe = match encoding {
+                "cl100k_base" => tiktoken_rs::cl100k_base(),
+                "o200k_base" => tiktoken_rs::o200k_base(),
+                "p50k_base" => tiktoken_rs::p50k_base(),
+                "r50k_base" => tiktoken_rs::r50k_base(),
+                _ => return None,
+            };
+            bpe.ok().map(|bpe| &*Box::leak(Box::new(bpe)))
+        });
+    Some(bpe?.encode_ordinary(text).len())
+}
+
+#[cfg(not(feature = "tokenizer"))]
+pub fn count_tokens(_text: &str, _encoding: &str) -> Option<usize> {
+    None
+}
+
+#[cfg(all(test, feature = "tokenizer"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_with_known_encodings() {
+        assert_eq!(count_tokens("", DEFAULT_ENCODING), Some(0));
+        assert_eq!(count_tokens("hello world", DEFAULT_ENCODING), Some(2));
+        assert!(count_tokens("hello world", "o200k_base").is_some());
+        assert_eq!(count_tokens("hello world", "nope"), None);
+    }
+}